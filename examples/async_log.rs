@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use sanguine::{
+    error::*,
+    event::{Event, UserEvent},
+    widgets::{Border, LogView},
+    App, Config,
+};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let log = LogView::new();
+    let buffer = log.buffer();
+
+    let mut app = App::<(), String>::new(Config::default())?
+        .with_layout(|layout, widgets| {
+            let log = widgets.register(log);
+            Some(layout.add_leaf(widgets, Border::new("Async log", log)))
+        })
+        // Lines sent by the background task arrive as user events
+        .with_handler(move |_, event, _| match event {
+            Event::User(UserEvent::User(line)) => {
+                write_or_err(&buffer)?.push_back(line.clone());
+                Ok(true)
+            }
+            _ => Ok(false),
+        });
+
+    let tx = app.event_sender_async();
+    tokio::spawn(async move {
+        for i in 0.. {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            if tx.send(format!("INFO tick {i}")).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    // Press Ctrl+Q to exit
+    app.exec_async().await
+}
@@ -1,27 +1,79 @@
-use std::sync::{mpsc::Sender, Arc, RwLock};
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{Arc, RwLock},
+};
 
 use sanguine::{
     error::*,
-    event::{Event, UserEvent},
-    layout::{Axis, Constraint, Direction, NodeId, Rect, WidgetId},
-    widgets::{Border, Menu, TextBox},
-    App, Config, Layout, WidgetStore,
+    event::{Event, EventSender},
+    hsplit,
+    layout::{floating, leaf, Constraint, Direction, NodeId, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    vsplit,
+    widgets::{Border, CommandPalette, Menu, TextBox},
+    Action, App, Config, ExitDecision, Keymap, Layout, RenderCtx, Widget, WidgetStore,
 };
 use termwiz::input::{KeyCode, KeyEvent, Modifiers};
 
+/// A simple widget that draws a circle filling its bounds, to show off aspect ratio constraints
+struct Canvas;
+
+impl<U, S> Widget<U, S> for Canvas {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        for y in 0..height {
+            let line = (0..width)
+                .map(|x| {
+                    // Distance from the center, normalized so the edges of the surface are at 1.0
+                    let dx = (x as f32 + 0.5) / width as f32 * 2. - 1.;
+                    let dy = (y as f32 + 0.5) / height as f32 * 2. - 1.;
+                    if dx * dx + dy * dy <= 1. {
+                        '█'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect::<String>();
+            surface.add_changes(vec![
+                Change::CursorPosition {
+                    x: Position::Absolute(0),
+                    y: Position::Absolute(y),
+                },
+                Change::Text(line),
+            ]);
+        }
+        Ok(vec![])
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
 fn menu(buf: Arc<RwLock<Vec<String>>>, widgets: &mut WidgetStore<(), ()>) -> WidgetId {
     // create a menu widget, and add some items to it
     let menu_id = widgets.register({
         let mut menu = Menu::new("Demo menu");
         menu.add_item("Quit", "", move |_, _, event_tx| {
             // exit button using the event sender
-            event_tx.send(UserEvent::Exit).ok();
+            event_tx.quit().ok();
         });
         menu.add_item("Delete", "", {
             // use a shared copy of the textbox buffer, and delete the last character of the buffer
             let buf = buf.clone();
             move |_, _, _| {
-                let mut w = buf.write().unwrap();
+                let Ok(mut w) = buf.write() else {
+                    return;
+                };
                 let len = w.len();
                 let last = w.last_mut().unwrap();
                 if last.is_empty() && len > 1 {
@@ -36,7 +88,10 @@ fn menu(buf: Arc<RwLock<Vec<String>>>, widgets: &mut WidgetStore<(), ()>) -> Wid
             let buf = buf.clone();
             move |this, menu, _| {
                 // count buffer lines, and update the menu item
-                menu.update_tag(this, |_| buf.read().unwrap().len().to_string())
+                menu.update_tag(this, |old| {
+                    buf.read()
+                        .map_or(old.to_owned(), |buf| buf.len().to_string())
+                })
             }
         });
         menu
@@ -54,81 +109,114 @@ fn app(layout: &mut Layout, widgets: &mut WidgetStore<(), ()>) -> Option<NodeId>
     // Get a copy of the textbox buffer
     let buffer = textbox.buffer();
 
+    // Register the editor, so the same widget can be shown in two windows. Naming it lets other
+    // widgets and handlers find it with `id_of("editor")` or `resolve_named` instead of capturing
+    // its id.
+    let textbox = widgets.register_named("editor", textbox).ok()?;
+    let editor = widgets.register(Border::new("Shared TextBox", textbox));
     // Add the menu widget
     let menu = menu(Arc::clone(&buffer), widgets);
-    let menu_id = layout.add_leaf(menu);
-
-    // Add the first editor to the layout
-    let textbox = widgets.register(textbox);
-    let editor = widgets.register(Border::new("Shared TextBox", textbox));
-    let left = layout.add_leaf(editor);
-
-    // Add a floating window
-    let textbox = widgets.register(TextBox::new());
-    let editor_2 = widgets.register(Border::new("Floating", textbox));
-    layout.add_floating(
-        // The window will contain a text box
-        editor_2,
-        Rect {
-            x: 10.,
-            y: 10.,
-            width: 25.,
-            height: 5.,
-        },
-    );
-
-    // Clone the first editor to add it to the layout again
-    // This widget will be *shared* between the two windows, meaning that changes to the underlying
-    // buffer will be shown in both windows and focusing on either window will allow you to edit
-    // the same buffer.
-    let bot_right = layout.clone_leaf(left);
-
-    // Create a container to hold the two right hand side editors
-    let right = layout.add_with_children(
-        // The container will be a vertical layout
-        Axis::Vertical,
-        // The container will take up all available space
-        Some(Constraint::fill()),
-        // The container will contain the cloned first editor, and the second editor
-        [menu_id, bot_right],
-    );
-
-    // Get the root node of the layout
-    let root = layout.root();
-    // Ensure that the root container is laid out horizontally
-    layout.set_direction(root, Axis::Horizontal);
+    let canvas = widgets.register(Canvas);
+    let scratch = widgets.register(TextBox::new());
 
-    // Add the left window (leaf) and the right container to the root
-    layout.add_child(root, left);
-    layout.add_child(root, right);
+    // Describe the whole layout at once. Panes can show a registered widget by id, or a widget
+    // that is registered when the layout is built.
+    let panes = layout
+        .build(
+            widgets,
+            hsplit![
+                // The first editor, on the left
+                leaf(editor).named("left"),
+                // A column taking up the rest of the space
+                vsplit![
+                    hsplit![
+                        leaf(menu).named("menu"),
+                        // A canvas next to the menu. Terminal cells are about twice as tall as
+                        // they are wide, so a width / height ratio of 2 keeps the canvas square as
+                        // the terminal is resized.
+                        leaf(Border::new("Canvas", canvas)).constraint(Constraint::Aspect(2.)),
+                    ],
+                    // The first editor again. This widget will be *shared* between the two
+                    // windows, meaning that changes to the underlying buffer will be shown in
+                    // both windows and focusing on either window will allow you to edit the same
+                    // buffer.
+                    leaf(editor),
+                ]
+                .constraint(Constraint::fill()),
+                // A floating window containing another text box
+                floating(
+                    Border::new("Floating", scratch),
+                    Rect {
+                        x: 10.,
+                        y: 10.,
+                        width: 25.,
+                        height: 5.,
+                    },
+                ),
+            ],
+        )
+        .ok()?;
 
     // return the left node to automatically focus it on app init (only works with
     // `App::with_layout`)
-    Some(left)
+    panes.get("left")
 }
 
-fn handle_event(state: &mut App, event: &Event<()>, _: Arc<Sender<UserEvent<()>>>) -> Result<bool> {
+/// Window management bindings. The keymap is consulted before the global event handler.
+fn keymap() -> Keymap<(), ()> {
+    let mut keymap = Keymap::with_defaults();
+    for (key, direction) in [
+        (KeyCode::UpArrow, Direction::Up),
+        (KeyCode::DownArrow, Direction::Down),
+        (KeyCode::LeftArrow, Direction::Left),
+        (KeyCode::RightArrow, Direction::Right),
+    ] {
+        keymap.bind(
+            Modifiers::CTRL,
+            key,
+            Action::Custom(Box::new(move |app| app.resize_focused(direction, 2))),
+        );
+        // Ctrl+W followed by an arrow also moves focus, like in Vim
+        keymap.bind_sequence(
+            [
+                (Modifiers::CTRL, KeyCode::Char('w')),
+                (Modifiers::NONE, key),
+            ],
+            Action::FocusDirection(direction),
+        );
+    }
+    keymap.bind(
+        Modifiers::CTRL,
+        KeyCode::Char('e'),
+        Action::Custom(Box::new(|app| {
+            app.equalize();
+            Ok(())
+        })),
+    );
+    // Ctrl+F zooms the focused window to fill the terminal, and zooms back out, like tmux's zoom
+    keymap.bind(
+        Modifiers::CTRL,
+        KeyCode::Char('f'),
+        Action::Custom(Box::new(|app| app.toggle_zoom().map(|_| ()))),
+    );
+    keymap
+}
+
+fn handle_event(state: &mut App, event: &Event<()>, _: EventSender<()>) -> Result<bool> {
     match event {
         Event::Key(KeyEvent {
-            key: KeyCode::Tab,
-            modifiers: Modifiers::SHIFT,
-        }) => {
-            state.cycle_focus()?;
-            Ok(true)
-        }
-        Event::Key(KeyEvent {
-            key:
-                k @ (KeyCode::UpArrow | KeyCode::DownArrow | KeyCode::LeftArrow | KeyCode::RightArrow),
-            modifiers: Modifiers::SHIFT,
+            key: KeyCode::Char('p'),
+            modifiers: Modifiers::CTRL,
         }) => {
-            let dir = match k {
-                KeyCode::UpArrow => Direction::Up,
-                KeyCode::DownArrow => Direction::Down,
-                KeyCode::LeftArrow => Direction::Left,
-                KeyCode::RightArrow => Direction::Right,
-                _ => unreachable!(),
-            };
-            state.focus_direction(dir)?;
+            // Open a command palette in a centered floating window. The palette closes itself
+            // when a command is run or escape is pressed, and focus returns to this window.
+            let palette = CommandPalette::new()
+                .with_command("Quit", Some("Ctrl+Q"), |event_tx| {
+                    event_tx.quit().ok();
+                })
+                .with_command("Cancel", Some("Esc"), |_| {});
+            let float = state.open_float(palette, 40, 10);
+            state.update_layout(|layout, _| layout.set_shadow(float, true));
             Ok(true)
         }
         // If the event wasn't matched, return false to allow it to propagate
@@ -137,10 +225,8 @@ fn handle_event(state: &mut App, event: &Event<()>, _: Arc<Sender<UserEvent<()>>
 }
 
 pub fn main() -> Result<()> {
-    // Create the sanguine app, providing a handler for *global* input events.
-    // In this case, we only handle occurrences of Shift+Tab, which we use to cycle focus.
-    // If Shift+Tab is pressed, we return true to signal that the event should not be
-    // propagated.
+    // Create the sanguine app, providing a keymap for window management and a handler for other
+    // *global* input events. If the handler returns true, the event is not propagated.
     let mut demo = App::new(
         // The default config is fine for this example
         Config::default(),
@@ -148,12 +234,56 @@ pub fn main() -> Result<()> {
     // The with_layout function can be used to setup the layout and set the initially focused
     // window at the same time
     .with_layout(app)
+    .with_keymap(keymap())
     // Setup the handler for global input events
     .with_handler(handle_event);
 
+    // Ctrl+B hides and shows the menu, like a sidebar. Hidden windows keep their place in the
+    // layout, but take up no space until they're shown again.
+    let menu = demo.inspect_layout(|layout, _| {
+        let mut leaves = layout.leaves().into_iter();
+        leaves.find(|node| layout.tag(*node) == Some("menu"))
+    });
+    if let Some(menu) = menu {
+        demo.keymap_mut().bind(
+            Modifiers::CTRL,
+            KeyCode::Char('b'),
+            Action::Custom(Box::new(move |app| app.toggle_node(menu).map(|_| ()))),
+        );
+    }
+
+    // Ask before quitting. The exit hook can't open windows itself, so it records that the user
+    // should be asked and the render loop opens a confirmation float.
+    let confirmed = Rc::new(Cell::new(false));
+    let ask = Rc::new(Cell::new(false));
+    demo.on_exit({
+        let confirmed = Rc::clone(&confirmed);
+        let ask = Rc::clone(&ask);
+        move |_| {
+            if confirmed.get() {
+                ExitDecision::Exit(0)
+            } else {
+                ask.set(true);
+                ExitDecision::Cancel
+            }
+        }
+    });
+
     // The main render loop, which will run until the user closes the application (defaults to
     // Ctrl-q).
     while demo.handle_events()? {
+        if ask.replace(false) {
+            let confirmed = Rc::clone(&confirmed);
+            let dialog = CommandPalette::new()
+                .with_title("Really quit?")
+                .with_command("Quit", None, move |event_tx| {
+                    confirmed.set(true);
+                    event_tx.quit().ok();
+                })
+                .with_command("Cancel", Some("Esc"), |_| {});
+            let float = demo.open_float(dialog, 30, 8);
+            demo.update_layout(|layout, _| layout.set_shadow(float, true));
+        }
         demo.render()?;
     }
 
@@ -0,0 +1,52 @@
+use std::{
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+};
+
+use sanguine::{
+    error::*,
+    widgets::{AnsiView, Border},
+    App, Config,
+};
+
+pub fn main() -> Result<()> {
+    let view = AnsiView::new();
+    let buffer = view.buffer();
+
+    // Stream the colored output of `git log` into the view as it's read
+    let mut git = Command::new("git")
+        .args([
+            "log",
+            "--color=always",
+            "--graph",
+            "--oneline",
+            "--decorate",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(Error::external)?;
+    let stdout = git.stdout.take().ok_or(Error::external("no stdout"))?;
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout)
+            .lines()
+            .map_while(std::io::Result::ok)
+        {
+            if write_or_err(&buffer)
+                .map(|mut buf| buf.push_str(&(line + "\n")))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let app = App::<(), ()>::new(Config::default())?.with_layout(|layout, widgets| {
+        let view = widgets.register(view);
+        let border = layout.add_leaf(widgets, Border::new("git log", view));
+        layout.add_child(layout.root(), border).ok()?;
+        Some(border)
+    });
+
+    // Scroll with the arrow keys or the mouse wheel, and press Ctrl+Q to exit
+    app.exec()
+}
@@ -0,0 +1,37 @@
+use std::{cell::Cell, rc::Rc};
+
+use sanguine::{error::*, widgets::Menu, App, Config};
+
+/// Show a menu below the shell prompt, and return the chosen item or `None` if the picker was
+/// closed with Ctrl+Q
+fn pick(title: &str, items: &[&str]) -> Result<Option<String>> {
+    let choice = Rc::new(Cell::new(None));
+    let mut menu = Menu::new(title);
+    for (i, item) in items.iter().enumerate() {
+        let choice = Rc::clone(&choice);
+        menu.add_item(*item, "", move |_, _, event_tx| {
+            choice.set(Some(i));
+            event_tx.quit().ok();
+        });
+    }
+
+    // The menu's title and the blank line below it take two rows
+    let app = App::<(), ()>::inline(items.len() + 2, Config::default())?;
+    let app = app.with_layout(|layout, widgets| {
+        let picker = layout.add_leaf(widgets, menu);
+        layout.add_child(layout.root(), picker).ok()?;
+        Some(picker)
+    });
+    // The picker's rows are cleared once the app exits, leaving the cursor below the prompt
+    app.exec()?;
+
+    Ok(choice.get().map(|i| items[i].to_owned()))
+}
+
+pub fn main() -> Result<()> {
+    match pick("Pick a fruit", &["Apple", "Banana", "Cherry", "Durian"])? {
+        Some(fruit) => println!("You picked {fruit}"),
+        None => println!("You didn't pick anything"),
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Compares the cost of recomputing a whole layout against recomputing only the part of it that
+//! changed.
+//!
+//! Run with `cargo run --release --example layout_bench`.
+
+use std::time::{Duration, Instant};
+
+use sanguine::{
+    layout::{Axis, Constraint, NodeId, Rect, WidgetId},
+    Layout,
+};
+
+const COLUMNS: usize = 20;
+const ROWS: usize = 15;
+const ITERATIONS: u32 = 1000;
+
+/// Builds a layout of `COLUMNS` columns with `ROWS` leaves each, returning the leaves
+fn build(layout: &mut Layout) -> Vec<NodeId> {
+    let root = layout.root();
+    layout.set_direction(root, Axis::Horizontal);
+    let mut leaves = vec![];
+    for _ in 0..COLUMNS {
+        let column = layout.add_container(Axis::Vertical, None);
+        layout.add_child(root, column).unwrap();
+        for _ in 0..ROWS {
+            // The layout doesn't need real widgets to be computed
+            let leaf = layout.add_leaf_id(WidgetId::default());
+            layout.add_child(column, leaf).unwrap();
+            leaves.push(leaf);
+        }
+    }
+    leaves
+}
+
+fn bench(name: &str, mut f: impl FnMut(u32)) -> Duration {
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(i);
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{name}: {:?} total, {:?} per compute",
+        elapsed,
+        elapsed / ITERATIONS
+    );
+    elapsed
+}
+
+pub fn main() {
+    let bounds = Rect::new(0., 0., 400., 300.);
+    let mut layout = Layout::<(), ()>::new();
+    let leaves = build(&mut layout);
+    layout.compute(&bounds);
+    println!("{} leaves, {} iterations", leaves.len(), ITERATIONS);
+
+    // Resize one leaf each iteration, recomputing the whole tree
+    let full = bench("full", |i| {
+        let leaf = leaves[i as usize % leaves.len()];
+        layout
+            .set_size(leaf, Constraint::Fixed(5 + i as usize % 5))
+            .unwrap();
+        layout.mark_dirty(None);
+        layout.compute(&bounds);
+    });
+
+    // Resize one leaf each iteration, recomputing only its column
+    let incremental = bench("incremental", |i| {
+        let leaf = leaves[i as usize % leaves.len()];
+        layout
+            .set_size(leaf, Constraint::Fixed(5 + i as usize % 5))
+            .unwrap();
+        layout.compute(&bounds);
+    });
+
+    println!(
+        "incremental is {:.1}x faster",
+        full.as_secs_f64() / incremental.as_secs_f64()
+    );
+}
@@ -25,16 +25,16 @@ impl<U, S> Widget<U, S> for MarkdownPreview {
         &self,
         _: &'r RenderCtx<'r, U, S>,
         surface: &'r mut Surface,
-    ) -> Option<Vec<(Rect, WidgetId)>> {
+    ) -> Result<Vec<(Rect, WidgetId)>> {
         let skin = MadSkin::default_dark();
         let dims = surface.dimensions();
         let text = skin
-            .text(&self.buf.read().unwrap().join("\n"), Some(dims.0))
+            .text(&read_or_err(&self.buf)?.join("\n"), Some(dims.0))
             .to_string();
 
         sanguine::ansi::write_ansi(surface, text.as_str()).ok()?;
         surface.add_change(Change::AllAttributes(CellAttributes::default()));
-        None
+        Ok(vec![])
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -56,14 +56,12 @@ fn main() -> Result<()> {
             let buf = textbox.buffer();
 
             let textbox_id = widgets.register(textbox);
-            let textbox_widget = widgets.register(Border::new("Editor".to_owned(), textbox_id));
-            let editor = layout.add_leaf(textbox_widget);
-            layout.add_child(root, editor);
+            let editor = layout.add_leaf(widgets, Border::new("Editor".to_owned(), textbox_id));
+            layout.add_child(root, editor).ok()?;
 
             let preview = widgets.register(MarkdownPreview::new(buf));
-            let preview =
-                layout.add_leaf(widgets.register(Border::new("Preview".to_owned(), preview)));
-            layout.add_child(root, preview);
+            let preview = layout.add_leaf(widgets, Border::new("Preview".to_owned(), preview));
+            layout.add_child(root, preview).ok()?;
 
             Some(editor)
         })
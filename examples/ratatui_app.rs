@@ -0,0 +1,88 @@
+use std::sync::mpsc::channel;
+
+use ratatui::{
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListState},
+    Frame,
+};
+use sanguine::{
+    bridge::{
+        crossterm::event::{Event, KeyCode, MouseEventKind},
+        RatatuiWidget,
+    },
+    error::*,
+    layout::Axis,
+    widgets::{Border, TextBox},
+    App, Config, RenderCtx,
+};
+
+/// A plain ratatui application, written the same way it would be for a standalone terminal
+struct Planets {
+    items: Vec<&'static str>,
+    state: ListState,
+}
+
+impl Planets {
+    fn new() -> Self {
+        Self {
+            items: vec![
+                "Mercury", "Venus", "Earth", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune",
+            ],
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Key(key) => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+                _ => {}
+            },
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollUp => self.state.select_previous(),
+                MouseEventKind::ScrollDown => self.state.select_next(),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let list = List::new(self.items.iter().copied())
+            .block(Block::default().borders(Borders::BOTTOM).title("Planets"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, frame.area(), &mut self.state);
+    }
+}
+
+pub fn main() -> Result<()> {
+    let (tx, rx) = channel();
+    let mut planets = Planets::new();
+    let widget = RatatuiWidget::new(move |frame, _cx: &RenderCtx<(), ()>| {
+        for event in rx.try_iter() {
+            planets.handle(event);
+        }
+        planets.draw(frame);
+    })
+    .with_event_handler(move |event| {
+        tx.send(event).ok();
+    });
+
+    let app = App::<(), ()>::new(Config::default())?.with_layout(|layout, widgets| {
+        let planets = widgets.register(widget);
+        let notes = widgets.register(TextBox::new());
+        let left = layout.add_leaf(widgets, Border::new("ratatui", planets));
+        let right = layout.add_leaf(widgets, Border::new("Notes", notes));
+        let split = layout
+            .add_with_children(Axis::Horizontal, None, [left, right])
+            .ok()?;
+        layout.add_child(layout.root(), split).ok()?;
+        Some(left)
+    });
+
+    // Move through the list with the arrow keys, switch panes with the mouse, and press Ctrl+Q to
+    // exit
+    app.exec()
+}
@@ -0,0 +1,86 @@
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    symbols::Marker,
+    widgets::{Axis, Chart, Dataset, GraphType, List},
+};
+use sanguine::{
+    bridge::Bridge,
+    error::*,
+    layout::{Rect, WidgetId},
+    surface::Surface,
+    widgets::Border,
+    App, Config, RenderCtx, Widget,
+};
+
+/// Renders a ratatui chart of a sine wave next to a list of its samples
+struct SineWave {
+    points: Vec<(f64, f64)>,
+}
+
+impl SineWave {
+    fn new() -> Self {
+        let points = (0..=40)
+            .map(|i| {
+                let x = i as f64 / 4.;
+                (x, x.sin())
+            })
+            .collect();
+        Self { points }
+    }
+}
+
+impl Widget<(), ()> for SineWave {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, (), ()>,
+        mut surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let mut terminal = surface.ratatui();
+        terminal
+            .draw(|frame| {
+                let [chart_area, list_area] =
+                    Layout::horizontal([Constraint::Fill(3), Constraint::Fill(1)])
+                        .areas(frame.area());
+
+                let dataset = Dataset::default()
+                    .name("sin(x)")
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(Color::Cyan))
+                    .data(&self.points);
+                let chart = Chart::new(vec![dataset])
+                    .x_axis(Axis::default().bounds([0., 10.]).labels(["0", "5", "10"]))
+                    .y_axis(Axis::default().bounds([-1., 1.]).labels(["-1", "0", "1"]));
+                frame.render_widget(chart, chart_area);
+
+                let samples = self
+                    .points
+                    .iter()
+                    .map(|(x, y)| format!("{x:>5.2} {y:>6.3}"));
+                frame.render_widget(List::new(samples), list_area);
+            })
+            .map_err(Error::external)?;
+        Ok(vec![])
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+pub fn main() -> Result<()> {
+    let app = App::<(), ()>::new(Config::default())?.with_layout(|layout, widgets| {
+        let chart = widgets.register(SineWave::new());
+        let border = layout.add_leaf(widgets, Border::new("ratatui", chart));
+        layout.add_child(layout.root(), border).ok()?;
+        Some(border)
+    });
+
+    // Press Ctrl+Q to exit
+    app.exec()
+}
@@ -0,0 +1,118 @@
+//! Counts the heap allocations made by `App::render`, for frames where nothing changed and for
+//! frames where every window is drawn again.
+//!
+//! Run with `cargo run --release --example render_bench`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout as AllocLayout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use sanguine::{
+    backend::TestBackend,
+    error::Result,
+    layout::{Axis, Rect, WidgetId},
+    surface::{Change, Surface},
+    widgets::Border,
+    App, Config, RenderCtx, Widget,
+};
+
+const FRAMES: usize = 1000;
+
+/// Counts allocations made through the system allocator
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: AllocLayout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// A line of text that never changes, so it's only drawn again when asked to
+struct Label(String);
+
+impl Widget<(), ()> for Label {
+    fn render(
+        &self,
+        _: &RenderCtx<(), ()>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        surface.add_change(Change::Text(self.0.clone()));
+        Ok(vec![])
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+fn bench(name: &str, app: &mut App, mut f: impl FnMut(&mut App)) {
+    // Let pools and buffers grow to their steady-state size first
+    for _ in 0..10 {
+        f(app);
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        f(app);
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{name}: {:?} per frame, {:.1} allocations per frame",
+        elapsed / FRAMES as u32,
+        allocations as f64 / FRAMES as f64
+    );
+}
+
+pub fn main() {
+    let backend = TestBackend::new(160, 48);
+    let mut app = App::with_backend(Config::default(), (), backend)
+        .unwrap()
+        .with_layout(|layout, widgets| {
+            let root = layout.root();
+            layout.set_direction(root, Axis::Horizontal);
+            let mut first = None;
+            for column in 0..4 {
+                let container = layout.add_container(Axis::Vertical, None);
+                layout.add_child(root, container).ok()?;
+                for row in 0..3 {
+                    let title = format!("Window {}", column * 3 + row);
+                    let text = widgets.register(Label(format!("The text of {title}")));
+                    let leaf = layout.add_leaf(widgets, Border::new(title, text));
+                    layout.add_child(container, leaf).ok()?;
+                    first.get_or_insert(leaf);
+                }
+            }
+            let status = widgets.register(Label("Status".to_owned()));
+            layout.dock_bottom(status);
+            first
+        });
+    app.render().unwrap();
+
+    bench("unchanged", &mut app, |app| app.render().unwrap());
+    bench("redrawn", &mut app, |app| {
+        app.request_redraw();
+        app.render().unwrap();
+    });
+}
@@ -0,0 +1,50 @@
+use sanguine::{
+    error::*,
+    event::{Event, UserEvent},
+    layout::Axis,
+    widgets::{Border, CommandBuilder, Terminal},
+    App, Config,
+};
+
+pub fn main() -> Result<()> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_owned());
+    let mut app = App::<(), ()>::new(Config::default())?;
+
+    // Two shells side by side, like a split in tmux
+    let mut shells = vec![];
+    for _ in 0..2 {
+        let terminal = Terminal::spawn(CommandBuilder::new(&shell), &app.event_sender())?;
+        shells.push((terminal.pid(), terminal));
+    }
+    let pids = shells.iter().map(|(pid, _)| *pid).collect::<Vec<_>>();
+    let mut nodes = vec![];
+    app = app.with_layout(|layout, widgets| {
+        for (_, terminal) in shells {
+            let terminal = widgets.register(terminal);
+            nodes.push(layout.add_leaf(widgets, Border::new("Shell", terminal)));
+        }
+        let split = layout
+            .add_with_children(Axis::Horizontal, None, nodes.clone())
+            .ok()?;
+        layout.add_child(layout.root(), split).ok()?;
+        nodes.first().copied()
+    });
+
+    // Close each shell's window when it exits, and quit once the last one has
+    app = app.with_handler(move |app, event, tx| match event {
+        Event::User(UserEvent::ProcessExited(pid, _)) => {
+            let Some(index) = pids.iter().position(|p| p == pid) else {
+                return Ok(false);
+            };
+            match app.close_node(nodes[index], true) {
+                Err(Error::LastLeaf(_)) => tx.quit()?,
+                res => res?,
+            }
+            Ok(true)
+        }
+        _ => Ok(false),
+    });
+
+    // Press Ctrl+Q to exit
+    app.exec()
+}
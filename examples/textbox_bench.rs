@@ -0,0 +1,87 @@
+//! Compares keystroke latency in a `TextBox` backed by a `Vec<String>` and by a `Rope`, in 1MB
+//! and 50MB buffers. Each keystroke is handled and drawn like in the event loop.
+//!
+//! Run with `cargo run --release --features rope --example textbox_bench`.
+
+use std::time::{Duration, Instant};
+
+use sanguine::{
+    backend::TestBackend,
+    event::{KeyCode, KeyEvent, Modifiers},
+    widgets::TextBox,
+    App, Widget,
+};
+use termwiz::input::InputEvent;
+
+const KEYSTROKES: usize = 200;
+
+/// About `size` bytes of text, in lines of about 80 characters
+fn text(size: usize) -> String {
+    let mut text = String::with_capacity(size + 100);
+    let mut line = 0;
+    while text.len() < size {
+        text.push_str(&format!(
+            "{line:>8} the quick brown fox jumps over the lazy dog, again and again and again\n"
+        ));
+        line += 1;
+    }
+    text
+}
+
+fn key(key: KeyCode) -> InputEvent {
+    InputEvent::Key(KeyEvent {
+        key,
+        modifiers: Modifiers::NONE,
+    })
+}
+
+fn app(textbox: impl Widget<(), ()> + 'static) -> App {
+    let mut app = App::<(), ()>::test(100, 30).with_layout(|layout, widgets| {
+        let leaf = layout.add_leaf(widgets, textbox);
+        layout.add_child(layout.root(), leaf).ok()?;
+        Some(leaf)
+    });
+    app.render().unwrap();
+    app
+}
+
+/// How long it takes to handle and draw an input event
+fn input(app: &mut App, event: InputEvent) -> Duration {
+    let start = Instant::now();
+    app.backend_mut::<TestBackend>().unwrap().push_input(event);
+    app.handle_events().unwrap();
+    app.render().unwrap();
+    start.elapsed()
+}
+
+/// The average time taken to handle and draw each of the keystrokes
+fn keystrokes(textbox: impl Widget<(), ()> + 'static, keys: &[KeyCode]) -> Duration {
+    let mut app = app(textbox);
+    let keys = keys.iter().cycle().take(KEYSTROKES);
+    keys.map(|k| input(&mut app, key(*k))).sum::<Duration>() / KEYSTROKES as u32
+}
+
+pub fn main() {
+    for (name, size) in [("1MB", 1 << 20), ("50MB", 50 << 20)] {
+        let text = text(size);
+        for (keys, label) in [
+            (&[KeyCode::Char('x')][..], "typing"),
+            (
+                &[KeyCode::Enter, KeyCode::Backspace][..],
+                "enter and backspace",
+            ),
+        ] {
+            let simple = keystrokes(TextBox::from_str(text.as_str()), keys);
+            let rope = keystrokes(TextBox::from_reader(text.as_bytes()).unwrap(), keys);
+            println!("{name} {label}: Vec<String> {simple:?}, Rope {rope:?} per keystroke");
+        }
+        // Pasting a thousand lines at the top of the buffer
+        let paste = InputEvent::Paste(self::text(80_000));
+        let simple = input(&mut app(TextBox::from_str(text.as_str())), paste.clone());
+        let rope = input(
+            &mut app(TextBox::from_reader(text.as_bytes()).unwrap()),
+            paste,
+        );
+        println!("{name} paste: Vec<String> {simple:?}, Rope {rope:?}");
+    }
+}
@@ -1,39 +1,93 @@
 #![cfg(feature = "ansi")]
 //! Utility function for parsing ansi escape sequences and writing the result to a [`Surface`]
+//!
+//! ```
+//! use sanguine::{
+//!     ansi::{write_ansi, write_ansi_at},
+//!     style::{AnsiColor, ColorAttribute, Intensity, Underline},
+//!     surface::Surface,
+//! };
+//!
+//! let mut surface = Surface::new(12, 3);
+//! write_ansi(&mut surface, "\x1b[1;31mbold\x1b[0m \x1b[3;4mitalic\x1b[0m").unwrap();
+//! let cells = surface.screen_cells();
+//! let bold = cells[0][0].attrs();
+//! assert_eq!(bold.intensity(), Intensity::Bold);
+//! assert_eq!(bold.foreground(), ColorAttribute::from(AnsiColor::Maroon));
+//! let italic = cells[0][5].attrs();
+//! assert!(italic.italic());
+//! assert_eq!(italic.underline(), Underline::Single);
+//! assert_eq!(cells[0][4].attrs().intensity(), Intensity::Normal);
+//!
+//! // Every line starts at the given column, and is cut off at the edge of the surface
+//! let mut surface = Surface::new(8, 3);
+//! write_ansi_at(&mut surface, 2, 1, "first line\n\x1b[32msecond\x1b[0m").unwrap();
+//! assert_eq!(surface.screen_chars_to_string(), "        \n  first \n  second\n");
+//! ```
 
 use ansi_to_tui::IntoText;
-use termwiz::{
-    cell::CellAttributes,
-    surface::{Change, Position, Surface},
-};
 
 use crate::{
     bridge::TuiStyle,
     error::{Error, Result},
+    layout::Rect,
+    surface::Surface,
+    text::{render_styled, Alignment, Line, Span, StyledText},
 };
 
 /// Parse ansi text from the provided string using [`ansi_to_tui`], and write the result onto the
-/// specified surface
+/// specified surface from its top left corner. See [`write_ansi_at`].
 pub fn write_ansi(screen: &mut Surface, bytes: &str) -> Result<()> {
-    let text = bytes.into_text().map_err(Error::external)?;
-    text.lines.into_iter().for_each(|l| {
-        l.0.into_iter().for_each(|span| {
-            let content = span.content;
-            let style = span.style;
-            let style: CellAttributes = TuiStyle(style).into();
-            screen.add_changes(vec![
-                Change::AllAttributes(style),
-                Change::Text(content.to_string()),
-                Change::AllAttributes(CellAttributes::default()),
-            ]);
-        });
-        screen.add_changes(vec![
-            Change::CursorPosition {
-                x: Position::Absolute(0),
-                y: Position::Relative(1),
-            },
-            Change::AllAttributes(CellAttributes::default()),
-        ]);
-    });
+    write_ansi_at(screen, 0, 0, bytes)
+}
+
+/// Parse ansi text from the provided string using [`ansi_to_tui`], and write the result onto the
+/// specified surface with its top left corner at `x`, `y`. Each line starts at column `x`, and
+/// lines that don't fit are cut off at the edges of the surface instead of wrapping.
+pub fn write_ansi_at(screen: &mut Surface, x: usize, y: usize, bytes: &str) -> Result<()> {
+    let text = parse_ansi(bytes)?;
+    let (width, height) = screen.dimensions();
+    let rect = Rect::new(
+        x as f32,
+        y as f32,
+        width.saturating_sub(x) as f32,
+        height.saturating_sub(y) as f32,
+    );
+    render_styled(screen, &text, rect, false, Alignment::Left);
     Ok(())
 }
+
+/// Parse ansi text into styled lines. Styles carry over from one line to the next, like they do
+/// in a terminal.
+pub(crate) fn parse_ansi(bytes: &str) -> Result<StyledText> {
+    let text = bytes.into_text().map_err(Error::external)?;
+    let lines = text
+        .lines
+        .into_iter()
+        .map(|line| {
+            Line(
+                line.spans
+                    .into_iter()
+                    .map(|span| Span::new(span.content, TuiStyle(span.style).into()))
+                    .collect(),
+            )
+        })
+        .collect();
+    Ok(StyledText(lines))
+}
+
+/// The index where an escape sequence at the end of `text` starts, if it's cut off before its
+/// final character, such as when output is read in chunks
+pub(crate) fn incomplete_escape(text: &str) -> Option<usize> {
+    let start = text.rfind('\x1b')?;
+    let mut rest = text[start + 1..].chars();
+    let complete = match rest.next() {
+        None => false,
+        // CSI sequences end with a character in `@`..=`~`, after any parameters
+        Some('[') => rest.any(|c| ('@'..='~').contains(&c)),
+        // OSC sequences end with BEL, or with ST (ESC \) whose escape would be found instead
+        Some(']') => rest.any(|c| c == '\x07'),
+        Some(_) => true,
+    };
+    (!complete).then_some(start)
+}
@@ -1,17 +1,47 @@
 use std::{
-    sync::{atomic::AtomicBool, mpsc::Sender, Arc},
-    time::Duration,
+    cell::RefCell,
+    collections::HashMap,
+    path::PathBuf,
+    rc::Rc,
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
 };
 
 pub use crate::widget::{RenderCtx, UpdateCtx};
 
+mod dialog;
+mod input;
+mod journal;
+mod logs;
+mod metrics;
+mod notify;
+mod panic;
+mod picker;
+mod runtime;
+mod tooltip;
+mod workspace;
+
+use input::InputQueue;
+pub use journal::{JournalEntry, RecordedEvent};
+pub use metrics::{FrameMetrics, FrameTiming};
+pub use notify::{Notification, NotifyLevel};
+pub(crate) use panic::{install_panic_hook, ActiveApp};
+use picker::WindowPicker;
+use tooltip::Tooltip;
+pub(crate) use tooltip::TooltipRequest;
+use workspace::Workspace;
+pub use workspace::WorkspaceId;
+
 use slotmap::{SecondaryMap, SlotMap};
 
 use crate::{
+    backend::{Backend, Screen, TerminalBackend, TestBackend},
     error::{Error, Result},
     event::*,
     layout::*,
-    surface::{term::*, *},
+    style::{AnsiColor, CellAttributes, ColorAttribute, Intensity},
+    surface::*,
+    theme::Theme,
     Widget,
 };
 
@@ -21,8 +51,72 @@ pub struct Config {
     ///
     /// Set to false if you implement your own exit handling.
     pub ctrl_q_quit: bool,
+    /// Whether or not to suspend the app on <kbd>ctrl</kbd>+<kbd>z</kbd>, like a shell job
+    /// `default: true`
+    pub enable_suspend: bool,
     /// Whether or not to focus a window when the mouse hovers over it `default: false`
     pub focus_follows_hover: bool,
+    /// The color drawn behind windows, visible through gaps and padding between them
+    /// `default: ColorAttribute::Default`
+    pub background: ColorAttribute,
+    /// Whether or not [`App::cycle_focus`] includes docked windows `default: false`
+    pub cycle_docked: bool,
+    /// Whether or not focusing a floating window raises it above other floats `default: true`
+    pub raise_float_on_focus: bool,
+    /// How much of a floating window's top left corner is kept on screen when the terminal is
+    /// resized, as `(columns, rows)`, or `None` to keep the whole window on screen `default: None`
+    pub float_margin: Option<(usize, usize)>,
+    /// Whether or not to dim the windows behind floating windows while any are visible
+    /// `default: false`
+    pub dim_background_for_floats: bool,
+    /// The longest time between two clicks that counts as a double click `default: 500ms`
+    pub double_click_interval: Duration,
+    /// How long the pointer has to rest before a tooltip set with
+    /// [`UpdateCtx::set_tooltip`] is shown `default: 500ms`
+    pub tooltip_delay: Duration,
+    /// How long the cursor is shown and then hidden for when blinking, or `None` to leave
+    /// blinking to the terminal. The cursor is shown again whenever a key is pressed.
+    /// `default: None`
+    pub cursor_blink: Option<Duration>,
+    /// The most frames drawn per second, or `None` to draw a frame whenever something changed.
+    /// Changes that arrive faster are drawn together in a later frame. `default: None`
+    pub max_fps: Option<u32>,
+    /// Which mouse events to request from the terminal and handle `default: MouseMode::Full`
+    ///
+    /// Read when the app is created. Use [`App::set_mouse_mode`] to change it at runtime.
+    pub mouse: MouseMode,
+    /// Whether or not to draw the app on the alternate screen `default: true`
+    ///
+    /// When false, the app is drawn over the normal screen and left there when it exits, with the
+    /// cursor moved below it, which suits REPL-style apps. Only read when the app is created.
+    pub alternate_screen: bool,
+    /// How long [`App::handle_events`] waits for input before returning `default: 15ms`
+    pub poll_timeout: Duration,
+    /// How long [`App::handle_events`] spends processing input before returning, so that a frame
+    /// can be drawn during floods of input. The rest is processed by the next call
+    /// `default: 8ms`
+    pub input_budget: Duration,
+    /// Whether or not errors returned from a widget's render or update are contained to that
+    /// widget instead of stopping the app `default: false`
+    ///
+    /// A widget that fails is drawn as an error panel until [`App::clear_widget_error`] is
+    /// called, and [`UserEvent::WidgetError`] is sent to the global event handlers.
+    pub contain_widget_errors: bool,
+    /// The corner notifications from [`App::notify`] are stacked in `default: Anchor::TopRight`
+    pub notify_anchor: Anchor,
+    /// A file to record the input the app receives to, which can be played back with
+    /// [`App::replay`] `default: None`
+    ///
+    /// The file is replaced when the app is created. See [`JournalEntry`] for the format.
+    pub record_events: Option<PathBuf>,
+    /// The key that opens and closes a floating window showing the records kept by the logger
+    /// from [`crate::log::init`], or `None` to not bind it `default: F12`
+    pub log_view_key: Option<KeyEvent>,
+    /// Whether or not to show the timings of the last frame in the bottom right corner
+    /// `default: false`
+    ///
+    /// See [`App::metrics`].
+    pub show_metrics: bool,
 }
 
 impl Config {
@@ -37,111 +131,900 @@ impl Config {
         self
     }
 
+    /// Set whether or not to suspend the app on <kbd>ctrl</kbd>+<kbd>z</kbd>
+    pub fn enable_suspend(mut self, enable_suspend: bool) -> Self {
+        self.enable_suspend = enable_suspend;
+        self
+    }
+
     /// Set whether or not to focus a window when the mouse hovers over it
     pub fn focus_follows_hover(mut self, focus_follows_hover: bool) -> Self {
         self.focus_follows_hover = focus_follows_hover;
         self
     }
+
+    /// Set whether or not cycling focus includes docked windows
+    pub fn cycle_docked(mut self, cycle_docked: bool) -> Self {
+        self.cycle_docked = cycle_docked;
+        self
+    }
+
+    /// Set whether or not focusing a floating window raises it above other floats
+    pub fn raise_float_on_focus(mut self, raise_float_on_focus: bool) -> Self {
+        self.raise_float_on_focus = raise_float_on_focus;
+        self
+    }
+
+    /// Set how much of a floating window is kept on screen when the terminal is resized
+    pub fn float_margin(mut self, float_margin: Option<(usize, usize)>) -> Self {
+        self.float_margin = float_margin;
+        self
+    }
+
+    /// Set whether or not to dim the windows behind floating windows while any are visible
+    pub fn dim_background_for_floats(mut self, dim_background_for_floats: bool) -> Self {
+        self.dim_background_for_floats = dim_background_for_floats;
+        self
+    }
+
+    /// Set the longest time between two clicks that counts as a double click
+    pub fn double_click_interval(mut self, double_click_interval: Duration) -> Self {
+        self.double_click_interval = double_click_interval;
+        self
+    }
+
+    /// Set how long the pointer has to rest before a tooltip is shown
+    pub fn tooltip_delay(mut self, tooltip_delay: Duration) -> Self {
+        self.tooltip_delay = tooltip_delay;
+        self
+    }
+
+    /// Set how long the cursor is shown and hidden for when blinking, or `None` to not blink it
+    pub fn cursor_blink(mut self, cursor_blink: Option<Duration>) -> Self {
+        self.cursor_blink = cursor_blink;
+        self
+    }
+
+    /// Set the most frames drawn per second
+    pub fn max_fps(mut self, max_fps: Option<u32>) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// Set which mouse events to request and handle
+    pub fn mouse(mut self, mouse: MouseMode) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    /// Set whether or not to draw the app on the alternate screen
+    pub fn alternate_screen(mut self, alternate_screen: bool) -> Self {
+        self.alternate_screen = alternate_screen;
+        self
+    }
+
+    /// Set how long [`App::handle_events`] waits for input
+    pub fn poll_timeout(mut self, poll_timeout: Duration) -> Self {
+        self.poll_timeout = poll_timeout;
+        self
+    }
+
+    /// Set how long [`App::handle_events`] spends processing input before returning
+    pub fn input_budget(mut self, input_budget: Duration) -> Self {
+        self.input_budget = input_budget;
+        self
+    }
+
+    /// Set whether or not widget errors are contained to the widget that failed
+    pub fn contain_widget_errors(mut self, contain_widget_errors: bool) -> Self {
+        self.contain_widget_errors = contain_widget_errors;
+        self
+    }
+
+    /// Set the corner notifications are stacked in
+    pub fn notify_anchor(mut self, notify_anchor: Anchor) -> Self {
+        self.notify_anchor = notify_anchor;
+        self
+    }
+
+    /// Record the input the app receives to a file
+    pub fn record_events(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_events = Some(path.into());
+        self
+    }
+
+    /// Set the key that toggles the log view
+    pub fn log_view_key(mut self, log_view_key: Option<KeyEvent>) -> Self {
+        self.log_view_key = log_view_key;
+        self
+    }
+
+    /// Set whether or not to show the timings of the last frame
+    pub fn show_metrics(mut self, show_metrics: bool) -> Self {
+        self.show_metrics = show_metrics;
+        self
+    }
+
+    /// Set the color drawn behind windows
+    pub fn background(mut self, background: impl Into<ColorAttribute>) -> Self {
+        self.background = background.into();
+        self
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             ctrl_q_quit: true,
+            enable_suspend: true,
             focus_follows_hover: false,
+            background: ColorAttribute::Default,
+            cycle_docked: false,
+            raise_float_on_focus: true,
+            float_margin: None,
+            dim_background_for_floats: false,
+            double_click_interval: Duration::from_millis(500),
+            tooltip_delay: Duration::from_millis(500),
+            cursor_blink: None,
+            max_fps: None,
+            mouse: MouseMode::Full,
+            alternate_screen: true,
+            poll_timeout: Duration::from_millis(15),
+            input_budget: Duration::from_millis(8),
+            contain_widget_errors: false,
+            notify_anchor: Anchor::TopRight,
+            record_events: None,
+            log_view_key: Some(KeyEvent {
+                key: KeyCode::Function(12),
+                modifiers: Modifiers::NONE,
+            }),
+            show_metrics: false,
         }
     }
 }
 
-pub type GlobalHandler<S, U> =
-    dyn Fn(&mut App<S, U>, &Event<U>, Arc<Sender<UserEvent<U>>>) -> Result<bool>;
+pub type GlobalHandler<S, U> = dyn Fn(&mut App<S, U>, &Event<U>, EventSender<U>) -> Result<bool>;
+
+/// Identifies a global event handler added with [`App::add_handler`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+/// A global event handler and the priority it runs at
+struct Handler<S, U> {
+    id: HandlerId,
+    priority: i32,
+    /// Shared so the handler can be called while the app is borrowed mutably, and so handlers
+    /// added or removed during dispatch don't affect it
+    handler: Rc<GlobalHandler<S, U>>,
+}
+
+/// How [`UserEvent::User`] events received from the event channel are delivered to widgets
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UserEventDelivery {
+    /// Only the focused widget receives the event
+    #[default]
+    Focused,
+    /// Every widget shown in a window receives the event. See [`App::broadcast`].
+    Broadcast,
+}
+
+/// Which mouse events the terminal is asked to report, see [`Config::mouse`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseMode {
+    /// No mouse events, which leaves the mouse to the terminal for selecting text
+    Off,
+    /// Button presses and releases, and the wheel
+    Clicks,
+    /// Clicks, and motion while a button is held
+    Drag,
+    /// Clicks and all motion, which hovering and tooltips need
+    #[default]
+    Full,
+}
+
+/// Something to do when a key binding in a [`Keymap`] is pressed
+pub enum Action<S, U> {
+    /// See [`App::cycle_focus`]
+    CycleFocus,
+    /// See [`App::focus_direction`]
+    FocusDirection(Direction),
+    /// Close the focused window and remove its widget. See [`App::close_focused`].
+    CloseFocused,
+    /// See [`App::next_workspace`]
+    NextWorkspace,
+    /// See [`App::prev_workspace`]
+    PrevWorkspace,
+    /// See [`App::pick_window`]
+    PickWindow,
+    /// Exit the app
+    Quit,
+    Custom(Box<ActionHandler<S, U>>),
+}
+
+pub type ActionHandler<S, U> = dyn Fn(&mut App<S, U>) -> Result<()>;
+
+/// What an exit hook set with [`App::on_exit`] decides to do when the app is asked to exit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitDecision {
+    /// Exit with the given exit code, returned by [`App::exec_with_code`]
+    Exit(i32),
+    /// Keep running
+    Cancel,
+}
+
+pub type ExitHook<S> = dyn FnMut(&mut S) -> ExitDecision;
+
+/// A sequence of keys and the action they're bound to
+type Binding<S, U> = (Vec<KeyEvent>, Rc<Action<S, U>>);
+
+/// Key bindings consulted by [`App`] before the global event handler. Bindings can be single
+/// keys, or sequences of keys pressed one after another like tmux's prefix key.
+pub struct Keymap<S, U> {
+    bindings: Vec<Binding<S, U>>,
+    /// How long to wait for the next key of a sequence
+    timeout: Duration,
+    /// The keys of a sequence pressed so far
+    pending: Vec<KeyEvent>,
+    /// When the last key of the pending sequence was pressed
+    last_key: Option<Instant>,
+}
+
+impl<S, U> Default for Keymap<S, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, U> Keymap<S, U> {
+    /// Create an empty keymap
+    pub fn new() -> Self {
+        Self {
+            bindings: vec![],
+            timeout: Duration::from_secs(1),
+            pending: vec![],
+            last_key: None,
+        }
+    }
+
+    /// Create a keymap with the usual window management bindings: <kbd>Shift</kbd> +
+    /// <kbd>Tab</kbd> to cycle focus, and <kbd>Shift</kbd> + arrows to focus by direction.
+    pub fn with_defaults() -> Self {
+        let mut keymap = Self::new();
+        keymap.bind(Modifiers::SHIFT, KeyCode::Tab, Action::CycleFocus);
+        for (key, direction) in [
+            (KeyCode::UpArrow, Direction::Up),
+            (KeyCode::DownArrow, Direction::Down),
+            (KeyCode::LeftArrow, Direction::Left),
+            (KeyCode::RightArrow, Direction::Right),
+        ] {
+            keymap.bind(Modifiers::SHIFT, key, Action::FocusDirection(direction));
+        }
+        keymap
+    }
+
+    /// Set how long to wait for the next key of a sequence before starting over `default: 1s`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Bind a single key, replacing any existing binding for it
+    pub fn bind(&mut self, modifiers: Modifiers, key: KeyCode, action: Action<S, U>) -> &mut Self {
+        self.bind_sequence([(modifiers, key)], action)
+    }
+
+    /// Bind a sequence of keys, replacing any existing binding for the same sequence. A binding
+    /// that is the start of a longer sequence takes precedence over it.
+    pub fn bind_sequence(
+        &mut self,
+        keys: impl IntoIterator<Item = (Modifiers, KeyCode)>,
+        action: Action<S, U>,
+    ) -> &mut Self {
+        let keys = Self::sequence(keys);
+        if keys.is_empty() {
+            return self;
+        }
+        self.bindings.retain(|(bound, _)| *bound != keys);
+        self.bindings.push((keys, Rc::new(action)));
+        self
+    }
+
+    /// Remove the binding for a single key, returning whether it was bound
+    pub fn unbind(&mut self, modifiers: Modifiers, key: KeyCode) -> bool {
+        self.unbind_sequence([(modifiers, key)])
+    }
+
+    /// Remove the binding for a sequence of keys, returning whether it was bound
+    pub fn unbind_sequence(
+        &mut self,
+        keys: impl IntoIterator<Item = (Modifiers, KeyCode)>,
+    ) -> bool {
+        let keys = Self::sequence(keys);
+        let len = self.bindings.len();
+        self.bindings.retain(|(bound, _)| *bound != keys);
+        self.reset();
+        self.bindings.len() != len
+    }
+
+    /// Whether a sequence has been started but not finished
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn sequence(keys: impl IntoIterator<Item = (Modifiers, KeyCode)>) -> Vec<KeyEvent> {
+        keys.into_iter()
+            .map(|(modifiers, key)| KeyEvent { key, modifiers })
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        self.pending.clear();
+        self.last_key = None;
+    }
+
+    /// Feeds a key press to the keymap. Returns whether the key was consumed, along with the
+    /// action to run if it completed a binding.
+    fn press(&mut self, key: &KeyEvent) -> (bool, Option<Rc<Action<S, U>>>) {
+        let now = Instant::now();
+        if self
+            .last_key
+            .is_some_and(|last| now.duration_since(last) > self.timeout)
+        {
+            self.reset();
+        }
+
+        self.pending.push(key.clone());
+        if let Some((_, action)) = self.bindings.iter().find(|(keys, _)| *keys == self.pending) {
+            let action = action.clone();
+            self.reset();
+            return (true, Some(action));
+        }
+        if self
+            .bindings
+            .iter()
+            .any(|(keys, _)| keys.starts_with(&self.pending))
+        {
+            self.last_key = Some(now);
+            return (true, None);
+        }
+
+        // The key doesn't continue the sequence, so start over from this key
+        let restart = self.pending.len() > 1;
+        self.reset();
+        if restart {
+            self.press(key)
+        } else {
+            (false, None)
+        }
+    }
+}
 
 pub struct WidgetStore<U, S> {
-    widgets: SlotMap<WidgetId, Box<dyn Widget<U, S>>>,
+    /// The registered widgets. A widget is taken out of its slot while it's being updated.
+    widgets: SlotMap<WidgetId, Option<Box<dyn Widget<U, S>>>>,
+    /// Widgets that may have changed since they were last rendered
+    dirty: RefCell<SecondaryMap<WidgetId, ()>>,
+    /// Widgets registered with [`WidgetStore::register_named`], by name
+    names: HashMap<String, WidgetId>,
+}
+
+impl<U, S> Default for WidgetStore<U, S> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<U, S> WidgetStore<U, S> {
     pub fn new() -> Self {
         Self {
             widgets: SlotMap::with_key(),
+            dirty: RefCell::new(SecondaryMap::new()),
+            names: HashMap::new(),
         }
     }
 
+    /// Get a reference to a widget. Widgets can't be reached while they're being updated.
     pub fn get(&self, id: WidgetId) -> Option<&dyn Widget<U, S>> {
-        self.widgets.get(id).map(|v| v.as_ref())
+        self.widgets.get(id)?.as_deref()
     }
 
-    pub fn get_mut<'a>(&mut self, id: WidgetId) -> Option<&'a mut dyn Widget<U, S>> {
-        self.widgets
-            .get_mut(id)
-            // Safety: The pointer will be valid for as long as the WidgetStore is alive, and the
-            // WidgetStore lives for the whole lifetime of the app. This is just a bit of a hack
-            // to shut the borrow checker up.
-            .map(|v| unsafe { (v.as_mut() as *mut dyn Widget<U, S>).as_mut() })
-            .flatten()
+    /// Get a mutable reference to a widget. The widget is assumed to change, and will be redrawn
+    /// on the next render.
+    pub fn get_mut(&mut self, id: WidgetId) -> Option<&mut dyn Widget<U, S>> {
+        self.mark_dirty(id);
+        let widget = self.widgets.get_mut(id)?.as_deref_mut()?;
+        Some(widget)
+    }
+
+    /// Take a widget out of the store and run `f` with it and the store, putting the widget back
+    /// afterwards unless it was removed. This lets a widget's update borrow the store mutably.
+    pub(crate) fn with_widget<R>(
+        &mut self,
+        id: WidgetId,
+        f: impl FnOnce(&mut dyn Widget<U, S>, &mut Self) -> R,
+    ) -> Option<R> {
+        let mut widget = self.widgets.get_mut(id)?.take()?;
+        self.mark_dirty(id);
+        let res = f(widget.as_mut(), self);
+        if let Some(slot) = self.widgets.get_mut(id) {
+            *slot = Some(widget);
+        }
+        Some(res)
     }
 
     pub fn resolve<W>(&self, id: WidgetId) -> Option<&W>
     where
         W: Widget<U, S> + 'static,
     {
-        self.widgets
-            .get(id)
-            .map(|b| (*b).as_ref().as_any().downcast_ref::<W>())
-            .flatten()
+        self.get(id)?.as_any().downcast_ref::<W>()
     }
 
+    /// Get a mutable reference to a widget as a concrete type. The widget is assumed to change,
+    /// and will be redrawn on the next render.
     pub fn resolve_mut<W>(&mut self, id: WidgetId) -> Option<&mut W>
     where
         W: Widget<U, S> + 'static,
     {
-        self.widgets
-            .get_mut(id)
-            .map(|b| (*b).as_mut().as_any_mut().downcast_mut::<W>())
-            .flatten()
+        self.get_mut(id)?.as_any_mut().downcast_mut::<W>()
     }
 
     pub fn register(&mut self, widget: impl Widget<U, S> + 'static) -> WidgetId {
-        self.widgets.insert(Box::new(widget))
+        self.register_boxed(Box::new(widget))
     }
 
+    /// Register a boxed widget. Every registration gets a new id, even for a widget that was
+    /// registered and removed before.
     pub fn register_boxed(&mut self, widget: Box<dyn Widget<U, S>>) -> WidgetId {
-        self.widgets.insert(widget)
+        let id = self.widgets.insert(Some(widget));
+        self.mark_dirty(id);
+        id
+    }
+
+    /// Register a widget under a name, so that it can be found with [`WidgetStore::id_of`]
+    /// without passing its id around. Returns [`Error::DuplicateName`] if another registered
+    /// widget already has the name.
+    ///
+    /// ```
+    /// use sanguine::{widgets::TextBox, WidgetStore};
+    ///
+    /// let mut widgets = WidgetStore::<(), ()>::new();
+    /// let editor = widgets.register_named("editor", TextBox::new()).unwrap();
+    /// assert_eq!(widgets.id_of("editor"), Some(editor));
+    /// assert!(widgets.register_named("editor", TextBox::new()).is_err());
+    ///
+    /// // Removing the widget frees its name
+    /// widgets.remove(editor);
+    /// assert_eq!(widgets.id_of("editor"), None);
+    /// ```
+    pub fn register_named(
+        &mut self,
+        name: impl Into<String>,
+        widget: impl Widget<U, S> + 'static,
+    ) -> Result<WidgetId> {
+        self.register_boxed_named(name, Box::new(widget))
+    }
+
+    /// Register a boxed widget under a name. See [`WidgetStore::register_named`].
+    pub fn register_boxed_named(
+        &mut self,
+        name: impl Into<String>,
+        widget: Box<dyn Widget<U, S>>,
+    ) -> Result<WidgetId> {
+        let name = name.into();
+        if self.names.contains_key(&name) {
+            return Err(Error::DuplicateName(name));
+        }
+        let id = self.register_boxed(widget);
+        self.names.insert(name, id);
+        Ok(id)
+    }
+
+    /// The id of the widget registered under `name`
+    pub fn id_of(&self, name: &str) -> Option<WidgetId> {
+        self.names.get(name).copied()
+    }
+
+    /// The name a widget was registered under, if it has one
+    pub fn name_of(&self, id: WidgetId) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|(_, named)| **named == id)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Get a reference to the widget registered under `name`
+    pub fn get_named(&self, name: &str) -> Option<&dyn Widget<U, S>> {
+        self.get(self.id_of(name)?)
+    }
+
+    /// Get a mutable reference to the widget registered under `name`. The widget is assumed to
+    /// change, and will be redrawn on the next render.
+    pub fn get_named_mut(&mut self, name: &str) -> Option<&mut dyn Widget<U, S>> {
+        self.get_mut(self.id_of(name)?)
+    }
+
+    /// Get a reference to the widget registered under `name` as a concrete type
+    pub fn resolve_named<W>(&self, name: &str) -> Option<&W>
+    where
+        W: Widget<U, S> + 'static,
+    {
+        self.resolve(self.id_of(name)?)
+    }
+
+    /// Get a mutable reference to the widget registered under `name` as a concrete type. The
+    /// widget is assumed to change, and will be redrawn on the next render.
+    pub fn resolve_named_mut<W>(&mut self, name: &str) -> Option<&mut W>
+    where
+        W: Widget<U, S> + 'static,
+    {
+        self.resolve_mut(self.id_of(name)?)
     }
 
+    /// Remove a widget, returning it if it was registered. The id is invalidated, and won't refer
+    /// to any widget registered later, and the widget's name can be used again.
     pub fn remove(&mut self, id: WidgetId) -> Option<Box<dyn Widget<U, S>>> {
-        self.widgets.remove(id)
+        self.dirty.borrow_mut().remove(id);
+        self.names.retain(|_, named| *named != id);
+        self.widgets.remove(id).flatten()
+    }
+
+    /// Check whether a widget is registered
+    pub fn contains(&self, id: WidgetId) -> bool {
+        self.widgets.contains_key(id)
+    }
+
+    /// The number of registered widgets
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// The ids of all registered widgets
+    pub fn ids(&self) -> impl Iterator<Item = WidgetId> + '_ {
+        self.widgets.keys()
+    }
+
+    /// Iterate over all widgets, except one that is being updated
+    pub fn iter(&self) -> impl Iterator<Item = (WidgetId, &dyn Widget<U, S>)> {
+        self.widgets
+            .iter()
+            .filter_map(|(id, widget)| Some((id, widget.as_deref()?)))
+    }
+
+    /// Iterate mutably over all widgets, except one that is being updated. The widgets are
+    /// assumed to change, and will be redrawn on the next render.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (WidgetId, &mut dyn Widget<U, S>)> {
+        let dirty = self.dirty.get_mut();
+        self.widgets.iter_mut().filter_map(|(id, widget)| {
+            let widget: &mut dyn Widget<U, S> = widget.as_deref_mut()?;
+            dirty.insert(id, ());
+            Some((id, widget))
+        })
+    }
+
+    /// Iterate over the widgets of type `W`
+    pub fn iter_of<W>(&self) -> impl Iterator<Item = (WidgetId, &W)>
+    where
+        W: Widget<U, S> + 'static,
+    {
+        self.iter()
+            .filter_map(|(id, widget)| Some((id, widget.as_any().downcast_ref::<W>()?)))
+    }
+
+    /// Mark a widget as changed, so that it is redrawn on the next render
+    pub fn mark_dirty(&self, id: WidgetId) {
+        if self.widgets.contains_key(id) {
+            self.dirty.borrow_mut().insert(id, ());
+        }
+    }
+
+    /// Check whether a widget has changed since it was last rendered
+    pub fn is_dirty(&self, id: WidgetId) -> bool {
+        self.dirty.borrow().contains_key(id)
+    }
+
+    /// Moves the set of changed widgets into `dirty`, leaving the store's set empty. The sets are
+    /// swapped rather than replaced, so neither is allocated again every frame.
+    fn swap_dirty(&mut self, dirty: &mut SecondaryMap<WidgetId, ()>) {
+        dirty.clear();
+        std::mem::swap(self.dirty.get_mut(), dirty);
+    }
+}
+
+/// The surfaces a widget was rendered onto, kept between frames
+#[derive(Default)]
+struct PooledSurface {
+    /// The widget drawn on `current`, or `None` if it didn't render successfully, in which case
+    /// `current` isn't offered to the next widget as its previous surface
+    widget: Option<WidgetId>,
+    /// The surface drawn in the last frame, given to the widget as
+    /// [`RenderCtx::previous_surface`] while it draws on `spare`
+    current: Surface,
+    /// The surface drawn in the frame before, drawn on again in the next one
+    spare: Surface,
+}
+
+/// An in-progress mouse drag of the edge between two adjacent windows
+struct SplitDrag {
+    /// The node before the edge
+    first: NodeId,
+    /// The node after the edge
+    second: NodeId,
+    /// The axis of the container holding the two nodes
+    axis: Axis,
+    /// Distance from the mouse to the edge when the drag started
+    offset: f32,
+    /// Where the button was pressed, until the mouse moves with it held and the drag starts
+    pressed: Option<(u16, u16)>,
+}
+
+/// Repaints the cells of `screen` within `rect` with reduced intensity, and optionally a new
+/// background color. Text is left unchanged.
+fn shade(screen: &mut Surface, rect: &Rect, background: Option<ColorAttribute>) {
+    let (width, height) = screen.dimensions();
+    let Some(rect) = rect.intersection(&Rect::from_size((width, height))) else {
+        return;
+    };
+    let columns = rect.left() as usize..rect.right() as usize;
+    for line in screen
+        .screen_cells()
+        .into_iter()
+        .skip(rect.top() as usize)
+        .take(rect.height as usize)
+    {
+        for cell in line.iter_mut().take(columns.end).skip(columns.start) {
+            let attrs = cell.attrs_mut();
+            attrs.set_intensity(Intensity::Half);
+            if let Some(background) = background {
+                attrs.set_background(background);
+            }
+        }
+    }
+}
+
+/// Prepares a surface kept from the last frame to be drawn on again. Resizing empties its change
+/// log without freeing it, and only reallocates its cells when the size changed.
+fn recycle_surface(surface: &mut Surface, width: usize, height: usize, background: ColorAttribute) {
+    surface.resize(width, height);
+    surface.add_change(Change::ClearScreen(background));
+}
+
+/// Draws the panel shown in place of a widget that failed: a border titled with the widget's id,
+/// around the error message, drawn with the theme's `error` style
+fn error_panel(surface: &mut Surface, widget: WidgetId, message: &str, attrs: CellAttributes) {
+    let (width, height) = surface.dimensions();
+    surface.add_changes(vec![
+        Change::ClearScreen(ColorAttribute::Default),
+        Change::AllAttributes(attrs),
+    ]);
+    if width < 2 || height < 2 {
+        surface.add_change(Change::Text(message.chars().take(width).collect()));
+        return;
+    }
+    let inner = width - 2;
+    let title = format!("Error in {widget:?}")
+        .chars()
+        .take(inner)
+        .collect::<String>();
+    let fill = inner - title.chars().count();
+    let mut rows = vec![format!("┌{title}{}┐", "─".repeat(fill))];
+    let mut lines = message.lines().flat_map(|line| {
+        let chars = line.chars().collect::<Vec<_>>();
+        let chunks = chars
+            .chunks(inner.max(1))
+            .map(|chunk| chunk.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        if chunks.is_empty() {
+            vec![String::new()]
+        } else {
+            chunks
+        }
+    });
+    for _ in 1..height - 1 {
+        let line = lines.next().unwrap_or_default();
+        let fill = inner - line.chars().count();
+        rows.push(format!("│{line}{}│", " ".repeat(fill)));
+    }
+    rows.push(format!("└{}┘", "─".repeat(inner)));
+    for (y, row) in rows.into_iter().enumerate() {
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(y),
+            },
+            Change::Text(row),
+        ]);
     }
 }
 
+/// Writes the SGR escape sequence that switches to `attrs` from any other attributes
+fn write_sgr(out: &mut String, attrs: &CellAttributes) {
+    use std::fmt::Write;
+    use termwiz::{
+        cell::{Blink, Underline},
+        color::ColorSpec,
+        escape::{csi::Sgr, CSI},
+    };
+
+    let color = |color: ColorAttribute| match color {
+        ColorAttribute::TrueColorWithPaletteFallback(color, _)
+        | ColorAttribute::TrueColorWithDefaultFallback(color) => ColorSpec::TrueColor(color),
+        ColorAttribute::PaletteIndex(index) => ColorSpec::PaletteIndex(index),
+        ColorAttribute::Default => ColorSpec::Default,
+    };
+    let mut sgr = vec![Sgr::Reset];
+    if attrs.intensity() != Intensity::Normal {
+        sgr.push(Sgr::Intensity(attrs.intensity()));
+    }
+    if attrs.underline() != Underline::None {
+        sgr.push(Sgr::Underline(attrs.underline()));
+    }
+    if attrs.blink() != Blink::None {
+        sgr.push(Sgr::Blink(attrs.blink()));
+    }
+    for (on, sgr_on) in [
+        (attrs.italic(), Sgr::Italic(true)),
+        (attrs.reverse(), Sgr::Inverse(true)),
+        (attrs.strikethrough(), Sgr::StrikeThrough(true)),
+        (attrs.invisible(), Sgr::Invisible(true)),
+    ] {
+        if on {
+            sgr.push(sgr_on);
+        }
+    }
+    if attrs.foreground() != ColorAttribute::Default {
+        sgr.push(Sgr::Foreground(color(attrs.foreground())));
+    }
+    if attrs.background() != ColorAttribute::Default {
+        sgr.push(Sgr::Background(color(attrs.background())));
+    }
+    for sgr in sgr {
+        write!(out, "{}", CSI::Sgr(sgr)).ok();
+    }
+}
+
+/// A floating window being dragged with the mouse
+struct FloatDrag {
+    /// The floating window
+    node: NodeId,
+    /// Position of the mouse relative to the window when the drag started
+    offset: (i32, i32),
+    /// Position of the mouse
+    pointer: (i32, i32),
+}
+
 /// The main application struct, responsible for managing the layout tree,
 /// keeping track of focus, and rendering the widgets.
 ///
 /// The generic type U is the type of user events that can be sent to widgets. It can be used to
 /// define custom message-passing behavior between widgets.
 pub struct App<S = (), U = ()> {
-    /// The layout tree
+    /// The layout tree of the active workspace
     layout: Layout<U, S>,
+    /// Every workspace, including the active one whose layout and focus are kept in the app
+    workspaces: SlotMap<WorkspaceId, Workspace<U, S>>,
+    /// The order workspaces are cycled through and listed in
+    workspace_order: Vec<WorkspaceId>,
+    /// The workspace whose layout is drawn and receives events
+    active_workspace: WorkspaceId,
     /// The arena containing all widgets
     widgets: WidgetStore<U, S>,
-    /// The post-render widget rects for mouse events
-    rendered: SecondaryMap<NodeId, Vec<(Rect, WidgetId)>>,
+    /// The post-render widget rects for mouse events, in render order, with the index of the
+    /// inner widget that returned each one or `None` for the window's widget
+    rendered: SecondaryMap<NodeId, Vec<(Rect, WidgetId, Option<usize>)>>,
+    /// The inner widget with focus in each window that has several, as the widget that returned
+    /// it and its index in that widget's inner widgets. See [`App::focused_child`].
+    child_focus: SecondaryMap<NodeId, (WidgetId, usize)>,
+    /// The last rendered contents of each window, reused while its widgets are unchanged, along
+    /// with the part of the window that was visible
+    cache: SecondaryMap<NodeId, (Rect, Surface)>,
+    /// The errors of widgets that failed while [`Config::contain_widget_errors`] was set, shown
+    /// in their place until cleared with [`App::clear_widget_error`]
+    widget_errors: SecondaryMap<WidgetId, String>,
+    /// The styles widgets look up when they render
+    theme: Theme,
+    /// The windows drawn in the last frame, in drawing order
+    last_frame: Vec<NodeId>,
+    /// The buffer the windows of the next frame are listed into
+    frame_nodes: Vec<NodeId>,
+    /// The widgets that changed since the last frame, while a frame is drawn
+    frame_dirty: SecondaryMap<WidgetId, ()>,
+    /// Surfaces that widgets were rendered onto, kept for the next frame by window and by their
+    /// position in the window, with the window's widget first. The last two frames are kept, so
+    /// widgets can read what they drew last time while drawing the next frame.
+    surface_pool: SecondaryMap<NodeId, Vec<PooledSurface>>,
+    /// The focused node when the last frame was drawn
+    last_focus: Option<NodeId>,
+    /// The last frame drawn, before it was written to the terminal
+    frame: Surface,
+    /// When the cursor was last shown, which starts the blink cycle over
+    blink_start: Instant,
+    /// Whether the focused widget showed a cursor in the last frame, which is blinked if
+    /// [`Config::cursor_blink`] is set
+    has_cursor: bool,
+    /// Whether the cursor is currently visible, as of the last frame
+    cursor_shown: bool,
+    /// When the last frame was drawn, used to limit the frame rate
+    last_render: Option<Instant>,
+    /// Whether a frame was postponed to limit the frame rate, and still needs to be drawn
+    frame_postponed: bool,
     /// The actual terminal used for rendering
-    term: BufferedTerminal<UnixTerminal>,
+    term: Screen,
     /// The size of the terminal
     size: Rect,
+    /// Whether the next frame repaints the whole terminal instead of only the changes, set when
+    /// the terminal is resized
+    repaint: bool,
     /// The focused node in the tree, if any
     focus: Option<NodeId>,
+    /// The window and widget under the mouse, if any, along with the widget's bounds
+    hover: Option<(NodeId, WidgetId, Rect)>,
+    /// Nodes to return focus to when floating windows opened with [`App::open_float`] are closed
+    float_return: SecondaryMap<NodeId, NodeId>,
+    /// The split edge currently being dragged with the mouse, if any
+    drag: Option<SplitDrag>,
+    /// The floating window currently being dragged with the mouse, if any
+    float_drag: Option<FloatDrag>,
+    /// The window drawn at the size of the terminal in place of the layout, if any
+    zoomed: Option<NodeId>,
+    /// Recognizes double clicks and drags from mouse events
+    gestures: GestureTracker,
+    /// Input read from the terminal and not processed yet
+    input: InputQueue,
+    /// Messages shown above every window with [`App::notify`], oldest first
+    notifications: Vec<Notification>,
+    /// Whether notifications were shown or dismissed since the last frame
+    notifications_changed: bool,
+    /// Whether a click on a notification is in progress, whose release is consumed too
+    notification_press: bool,
+    /// The tooltip asked for by the widget under the pointer, if any
+    tooltip: Option<Tooltip>,
+    /// Where the widget being updated leaves its tooltip request, see [`UpdateCtx::set_tooltip`]
+    tooltip_request: Option<TooltipRequest>,
+    /// Whether the tooltip was shown in the last frame
+    tooltip_shown: bool,
+    /// The last position of the mouse pointer, if it has moved over the terminal
+    pointer: Option<(u16, u16)>,
+    /// The labels shown by [`App::pick_window`], while it waits for a key press
+    picker: Option<WindowPicker>,
+    /// The journal input is recorded to, if [`Config::record_events`] is set
+    journal: Option<journal::Recorder>,
+    /// The floating window opened with [`Config::log_view_key`], if it's open
+    log_view: Option<NodeId>,
+    /// Timings of the last frames drawn
+    metrics: FrameMetrics,
+    /// Timings of the frame being drawn, and of the events processed before it
+    frame_timing: FrameTiming,
     /// Sender for user events, given to widgets when `Widget::update` is called
-    event_tx: Arc<std::sync::mpsc::Sender<UserEvent<U>>>,
+    event_tx: EventSender<U>,
     /// Receiver for user events, only used internally
-    event_rx: std::sync::mpsc::Receiver<UserEvent<U>>,
+    event_rx: std::sync::mpsc::Receiver<Envelope<U>>,
     /// Used to signal the exit internally
     exit: Arc<AtomicBool>,
-    /// Global event handler, which intercepts events before they are propagated to the focused
-    /// widget. If the handler returns `Ok(true)`, the event is considered handled and is not
-    /// propagated to the widget that would otherwise receive it.
-    global_event_handler: Box<GlobalHandler<S, U>>,
+    /// Called when the app is asked to exit, and can cancel the exit or choose the exit code
+    exit_hook: Option<Box<ExitHook<S>>>,
+    /// The exit code chosen by the exit hook
+    exit_code: i32,
+    /// Global event handlers, which intercept events before they are propagated to the focused
+    /// widget, sorted from the highest priority to the lowest. If a handler returns `Ok(true)`,
+    /// the event is considered handled and is not passed to the remaining handlers or propagated
+    /// to the widget that would otherwise receive it.
+    handlers: Vec<Handler<S, U>>,
+    /// The id given to the next handler that is added
+    next_handler: u64,
+    /// The handler set with [`App::handler`], which the next call replaces
+    main_handler: Option<HandlerId>,
+    /// Key bindings, consulted before the global event handler
+    keymap: Keymap<S, U>,
+    /// Channel for user events sent from async tasks, see [`App::event_sender_async`]
+    #[cfg(feature = "tokio")]
+    async_events: Option<runtime::AsyncChannel<U>>,
+    /// Clones user events so they can be broadcast, set when user events from the channel are
+    /// delivered with [`UserEventDelivery::Broadcast`]
+    clone_user: Option<fn(&U) -> U>,
     /// Configuration struct
     config: Config,
     /// User state
@@ -153,67 +1036,89 @@ impl<S, U> Drop for App<S, U> {
         // Restore cursor visibility and leave alternate screen when app exits
         self.term
             .add_change(Change::CursorVisibility(CursorVisibility::Visible));
-        self.term.terminal().exit_alternate_screen().unwrap();
+        // Writing can fail if the terminal has gone away, which shouldn't turn into a panic
+        let backend = self.term.backend_mut();
+        backend.set_mouse_mode(MouseMode::Off).ok();
+        backend.exit_alternate_screen().ok();
+    }
+}
+
+/// Turns off mouse reporting and bracketed paste, and shows the cursor. Termwiz only resets these
+/// when the terminal is dropped, but they need to be off while the app is suspended for the shell
+/// to work normally.
+pub(crate) fn reset_terminal_modes() -> std::io::Result<()> {
+    use std::io::Write;
+    use termwiz::escape::csi::{DecPrivateMode, DecPrivateModeCode, Mode, CSI};
+
+    let mut tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    let paste = DecPrivateMode::Code(DecPrivateModeCode::BracketedPaste);
+    write!(tty, "{}", CSI::Mode(Mode::ResetDecPrivateMode(paste)))?;
+    write_mouse_mode(&mut tty, MouseMode::Off)?;
+    let show_cursor = Mode::SetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::ShowCursor));
+    write!(tty, "{}", CSI::Mode(show_cursor))?;
+    tty.flush()
+}
+
+/// Asks the terminal to report the mouse events of `mode`, and no others
+pub(crate) fn request_mouse_mode(mode: MouseMode) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    write_mouse_mode(&mut tty, mode)?;
+    tty.flush()
+}
+
+fn write_mouse_mode(tty: &mut impl std::io::Write, mode: MouseMode) -> std::io::Result<()> {
+    use termwiz::escape::csi::{DecPrivateMode, DecPrivateModeCode, Mode, CSI};
+
+    let tracking = match mode {
+        MouseMode::Off => None,
+        MouseMode::Clicks => Some(DecPrivateModeCode::MouseTracking),
+        MouseMode::Drag => Some(DecPrivateModeCode::ButtonEventMouse),
+        MouseMode::Full => Some(DecPrivateModeCode::AnyEventMouse),
+    };
+    // Terminals keep a single tracking mode that resetting any of them turns off, so they're all
+    // reset before the new one is set
+    for code in [
+        DecPrivateModeCode::SGRMouse,
+        DecPrivateModeCode::MouseTracking,
+        DecPrivateModeCode::ButtonEventMouse,
+        DecPrivateModeCode::AnyEventMouse,
+    ] {
+        let mode = Mode::ResetDecPrivateMode(DecPrivateMode::Code(code));
+        write!(tty, "{}", CSI::Mode(mode))?;
     }
+    if let Some(tracking) = tracking {
+        // SGR encoding reports positions past column 223, and which button was released
+        for code in [tracking, DecPrivateModeCode::SGRMouse] {
+            let mode = Mode::SetDecPrivateMode(DecPrivateMode::Code(code));
+            write!(tty, "{}", CSI::Mode(mode))?;
+        }
+    }
+    Ok(())
 }
 
 impl<S: Default + 'static, U: 'static> Default for App<S, U> {
     fn default() -> Self {
-        let term = Capabilities::new_from_env()
-            .and_then(|caps| {
-                UnixTerminal::new(caps).and_then(|mut t| {
-                    t.set_raw_mode()?;
-                    t.enter_alternate_screen().ok();
-                    BufferedTerminal::new(t)
-                })
-            })
-            .unwrap();
-        let (event_tx, event_rx) = std::sync::mpsc::channel();
-        Self {
-            global_event_handler: Box::new(|_, _, _| Ok(false)),
-            size: Rect::from_size(term.dimensions()),
-            event_tx: Arc::new(event_tx),
-            exit: Arc::new(AtomicBool::new(false)),
-            rendered: SecondaryMap::new(),
-            layout: Layout::new(),
-            widgets: WidgetStore::new(),
-            focus: None,
-            term,
-            event_rx,
-            config: Default::default(),
-            state: Default::default(),
-        }
+        let backend = TerminalBackend::new().unwrap();
+        Self::with_backend(Default::default(), Default::default(), backend).unwrap()
     }
 }
 
 impl<S: Default + 'static, U: 'static> App<S, U> {
     /// Create a new Sanguine application with the provided layout and no global event handler.
     pub fn new(config: Config) -> Result<Self> {
-        let term = Capabilities::new_from_env()
-            .and_then(|caps| {
-                UnixTerminal::new(caps).and_then(|mut t| {
-                    t.set_raw_mode()?;
-                    t.enter_alternate_screen()?;
-                    BufferedTerminal::new(t)
-                })
-            })
-            .map_err(|_| Error::TerminalError)?;
-        let (event_tx, event_rx) = std::sync::mpsc::channel();
+        let backend = TerminalBackend::with_config(&config)?;
+        Self::with_backend(config, Default::default(), backend)
+    }
 
-        Ok(App {
-            global_event_handler: Box::new(|_, _, _| Ok(false)),
-            size: Rect::from_size(term.dimensions()),
-            event_tx: Arc::new(event_tx),
-            exit: Arc::new(AtomicBool::new(false)),
-            widgets: WidgetStore::new(),
-            rendered: SecondaryMap::new(),
-            layout: Layout::new(),
-            focus: None,
-            term,
-            event_rx,
-            config,
-            state: Default::default(),
-        })
+    /// Create an app drawn on `height` rows below the cursor instead of the whole alternate
+    /// screen, like a picker shown under the shell prompt. The app's height stays fixed when the
+    /// terminal is resized, and its rows are cleared when it's dropped, leaving the cursor where
+    /// it was when the app started.
+    pub fn inline(height: usize, config: Config) -> Result<Self> {
+        let backend = TerminalBackend::inline(height, &config)?;
+        Self::with_backend(config, Default::default(), backend)
     }
 
     /// Create a new Sanguine app with the provided global event handler. The global event handler
@@ -221,42 +1126,57 @@ impl<S: Default + 'static, U: 'static> App<S, U> {
     /// from propagating to widgets, or false to allow propagation.
     pub fn new_with_handler(
         config: Config,
-        handler: impl Fn(&mut App<S, U>, &Event<U>, Arc<Sender<UserEvent<U>>>) -> Result<bool> + 'static,
+        handler: impl Fn(&mut App<S, U>, &Event<U>, EventSender<U>) -> Result<bool> + 'static,
     ) -> Result<Self> {
-        let term = Capabilities::new_from_env()
-            .and_then(|caps| {
-                UnixTerminal::new(caps).and_then(|mut t| {
-                    t.set_raw_mode()?;
-                    t.enter_alternate_screen()?;
-                    BufferedTerminal::new(t)
-                })
-            })
-            .map_err(|_| Error::TerminalError)?;
-        let (event_tx, event_rx) = std::sync::mpsc::channel();
+        Ok(Self::new(config)?.with_handler(handler))
+    }
 
-        Ok(App {
-            global_event_handler: Box::new(handler),
-            widgets: WidgetStore::new(),
-            size: Rect::from_size(term.dimensions()),
-            event_tx: Arc::new(event_tx),
-            exit: Arc::new(AtomicBool::new(false)),
-            rendered: SecondaryMap::new(),
-            layout: Layout::new(),
-            focus: None,
-            term,
-            event_rx,
-            config,
-            state: Default::default(),
-        })
+    /// Create an app that draws to a [`TestBackend`] of the given size instead of the terminal,
+    /// for testing. Use [`App::backend_mut`] to queue input and inspect the screen.
+    pub fn test(width: usize, height: usize) -> Self {
+        let backend = TestBackend::new(width, height);
+        Self::with_backend(Config::default(), Default::default(), backend)
+            .expect("the test backend can't fail")
     }
 }
 
 impl<S: 'static, U: 'static> App<S, U> {
-    pub fn exec(mut self) -> Result<()> {
+    pub fn exec(self) -> Result<()> {
+        self.exec_with_code().map(|_| ())
+    }
+
+    /// Run the app until it exits, like [`App::exec`], and return the exit code chosen by the
+    /// exit hook. See [`App::on_exit`].
+    pub fn exec_with_code(mut self) -> Result<i32> {
+        self.check_size()?;
         while self.handle_events()? {
             self.render()?;
         }
-        Ok(())
+        Ok(self.exit_code)
+    }
+
+    /// Set a hook that is called with the app state whenever the app is asked to exit, whether by
+    /// [`UserEvent::Exit`], <kbd>ctrl</kbd>+<kbd>q</kbd> or [`Action::Quit`]. The hook can
+    /// cancel the exit, for example to ask about unsaved changes, or choose the exit code.
+    pub fn on_exit(&mut self, hook: impl FnMut(&mut S) -> ExitDecision + 'static) {
+        self.exit_hook = Some(Box::new(hook));
+    }
+
+    /// The exit code chosen by the exit hook, or 0 if there is no hook
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    /// Asks the exit hook whether to exit, and exits unless it cancels
+    fn request_exit(&mut self) {
+        let decision = match &mut self.exit_hook {
+            Some(hook) => hook(&mut self.state),
+            None => ExitDecision::Exit(0),
+        };
+        if let ExitDecision::Exit(code) = decision {
+            self.exit_code = code;
+            self.exit.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
     }
 
     pub fn register_widget(&mut self, widget: impl Widget<U, S> + 'static) -> WidgetId {
@@ -282,27 +1202,105 @@ impl<S: 'static, U: 'static> App<S, U> {
         self.widgets.resolve_mut(id)
     }
 
+    /// Register a widget under a name. See [`WidgetStore::register_named`].
+    pub fn register_named(
+        &mut self,
+        name: impl Into<String>,
+        widget: impl Widget<U, S> + 'static,
+    ) -> Result<WidgetId> {
+        self.widgets.register_named(name, widget)
+    }
+
+    /// The id of the widget registered under `name`
+    pub fn widget_id(&self, name: &str) -> Option<WidgetId> {
+        self.widgets.id_of(name)
+    }
+
+    pub fn resolve_named<W: Widget<U, S> + 'static>(&self, name: &str) -> Option<&W> {
+        self.widgets.resolve_named(name)
+    }
+
+    /// Get a mutable reference to the widget registered under `name` as a concrete type
+    pub fn resolve_named_mut<W: Widget<U, S> + 'static>(&mut self, name: &str) -> Option<&mut W> {
+        self.widgets.resolve_named_mut(name)
+    }
+
     pub fn new_with_state(config: Config, state: S) -> Result<Self> {
-        let term = Capabilities::new_from_env()
-            .and_then(|caps| {
-                UnixTerminal::new(caps).and_then(|mut t| {
-                    t.set_raw_mode()?;
-                    t.enter_alternate_screen()?;
-                    BufferedTerminal::new(t)
-                })
-            })
-            .map_err(|_| Error::TerminalError)?;
+        let backend = TerminalBackend::with_config(&config)?;
+        Self::with_backend(config, state, backend)
+    }
+
+    /// Create an app that draws to the given backend
+    pub fn with_backend(
+        config: Config,
+        state: S,
+        mut backend: impl Backend + 'static,
+    ) -> Result<Self> {
+        backend.set_mouse_mode(config.mouse)?;
+        let term = Screen::new(Box::new(backend))?;
         let (event_tx, event_rx) = std::sync::mpsc::channel();
+        let mut workspaces = SlotMap::with_key();
+        let active_workspace = workspaces.insert(Workspace::new("main", None));
+        let journal = match &config.record_events {
+            Some(path) => Some(journal::Recorder::create(path, term.dimensions())?),
+            None => None,
+        };
 
         Ok(App {
-            global_event_handler: Box::new(|_, _, _| Ok(false)),
+            handlers: vec![],
+            next_handler: 0,
+            main_handler: None,
             widgets: WidgetStore::new(),
             size: Rect::from_size(term.dimensions()),
-            event_tx: Arc::new(event_tx),
+            event_tx: event_tx.into(),
             exit: Arc::new(AtomicBool::new(false)),
             rendered: SecondaryMap::new(),
+            child_focus: SecondaryMap::new(),
             layout: Layout::new(),
+            workspaces,
+            workspace_order: vec![active_workspace],
+            active_workspace,
             focus: None,
+            hover: None,
+            float_return: SecondaryMap::new(),
+            cache: SecondaryMap::new(),
+            widget_errors: SecondaryMap::new(),
+            theme: Theme::default(),
+            last_frame: vec![],
+            frame_nodes: vec![],
+            frame_dirty: SecondaryMap::new(),
+            surface_pool: SecondaryMap::new(),
+            last_focus: None,
+            frame: Surface::new(term.dimensions().0, term.dimensions().1),
+            blink_start: Instant::now(),
+            has_cursor: false,
+            cursor_shown: false,
+            last_render: None,
+            frame_postponed: false,
+            drag: None,
+            float_drag: None,
+            zoomed: None,
+            gestures: GestureTracker::default(),
+            input: InputQueue::default(),
+            notifications: vec![],
+            notifications_changed: false,
+            notification_press: false,
+            tooltip: None,
+            tooltip_request: None,
+            tooltip_shown: false,
+            pointer: None,
+            picker: None,
+            journal,
+            log_view: None,
+            metrics: FrameMetrics::default(),
+            frame_timing: FrameTiming::default(),
+            keymap: Keymap::new(),
+            clone_user: None,
+            repaint: false,
+            exit_hook: None,
+            exit_code: 0,
+            #[cfg(feature = "tokio")]
+            async_events: None,
             term,
             event_rx,
             config,
@@ -310,55 +1308,232 @@ impl<S: 'static, U: 'static> App<S, U> {
         })
     }
 
+    /// Get the backend the app draws to, if it's a `B`
+    pub fn backend<B: Backend + 'static>(&self) -> Option<&B> {
+        self.term.backend().as_any().downcast_ref()
+    }
+
+    /// Get the backend the app draws to mutably, if it's a `B`
+    pub fn backend_mut<B: Backend + 'static>(&mut self) -> Option<&mut B> {
+        self.term.backend_mut().as_any_mut().downcast_mut()
+    }
+
     pub fn with_state(mut self, state: S) -> Self {
         self.state = state;
         self
     }
 
+    /// Use the given key bindings, which are consulted before the global event handler
+    pub fn with_keymap(mut self, keymap: Keymap<S, U>) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    pub fn keymap(&self) -> &Keymap<S, U> {
+        &self.keymap
+    }
+
+    /// Get a mutable reference to the key bindings, to add or remove bindings at runtime
+    pub fn keymap_mut(&mut self) -> &mut Keymap<S, U> {
+        &mut self.keymap
+    }
+
+    /// Load key bindings, theme styles and config flags from a config file, adding the bindings
+    /// to the current keymap and replacing the theme. See [`crate::config_file`] for the format.
+    ///
+    /// `mouse` and `alternate_screen` are read when the terminal is set up, so setting them here
+    /// has no effect on an app that has already been created. Use
+    /// [`AppConfigFile::apply_config`](crate::config_file::AppConfigFile::apply_config) on the
+    /// [`Config`] passed to [`App::new`] instead.
+    #[cfg(feature = "config-file")]
+    pub fn with_config_file(mut self, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let file = crate::config_file::AppConfigFile::load(path)?;
+        file.apply_keymap(&mut self.keymap)?;
+        self.config = file.apply_config(std::mem::take(&mut self.config));
+        self.set_theme(file.theme().clone());
+        Ok(self)
+    }
+
+    /// Use the given theme for the styles of widgets
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.set_theme(theme);
+        self
+    }
+
+    /// Replace the theme, redrawing every widget with it on the next frame
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.request_redraw();
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get a mutable reference to the config, to change the app's behavior at runtime
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    /// Choose how user events received from the event channel are delivered. By default only the
+    /// focused widget receives them.
+    pub fn with_user_event_delivery(mut self, delivery: UserEventDelivery) -> Self
+    where
+        U: Clone,
+    {
+        self.clone_user = match delivery {
+            UserEventDelivery::Focused => None,
+            UserEventDelivery::Broadcast => Some(U::clone),
+        };
+        self
+    }
+
+    /// Send [`UserEvent::Tick`] to the app every `interval`, from a background timer thread.
+    ///
+    /// Ticks are delivered to every visible widget rather than just the focused one, and can be
+    /// used to drive animated widgets such as [`crate::widgets::Spinner`]. The thread stops once
+    /// the app is dropped.
+    pub fn with_tick_interval(self, interval: Duration) -> Self
+    where
+        U: Send + 'static,
+    {
+        let tx = self.event_tx.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            if tx.send(UserEvent::Tick).is_err() {
+                break;
+            }
+        });
+        self
+    }
+
+    /// Set the global event handler, replacing the one set before. See [`App::handler`].
     pub fn with_handler(
         mut self,
-        handler: impl Fn(&mut App<S, U>, &Event<U>, Arc<Sender<UserEvent<U>>>) -> Result<bool> + 'static,
+        handler: impl Fn(&mut App<S, U>, &Event<U>, EventSender<U>) -> Result<bool> + 'static,
     ) -> Self {
-        self.global_event_handler = Box::new(handler);
+        self.handler(handler);
         self
     }
 
+    /// Set the global event handler, replacing the one set before with this method or
+    /// [`App::with_handler`]. It runs at priority 0 alongside handlers added with
+    /// [`App::add_handler`], which it leaves in place.
     pub fn handler(
         &mut self,
-        handler: impl Fn(&mut App<S, U>, &Event<U>, Arc<Sender<UserEvent<U>>>) -> Result<bool> + 'static,
+        handler: impl Fn(&mut App<S, U>, &Event<U>, EventSender<U>) -> Result<bool> + 'static,
     ) {
-        self.global_event_handler = Box::new(handler);
+        if let Some(id) = self.main_handler.take() {
+            self.remove_handler(id);
+        }
+        self.main_handler = Some(self.add_handler(0, handler));
+    }
+
+    /// Add a global event handler, which sees events before the focused widget does. Handlers
+    /// are called from the highest priority to the lowest, and in the order they were added
+    /// within a priority, until one returns `Ok(true)` to mark the event as handled.
+    pub fn add_handler(
+        &mut self,
+        priority: i32,
+        handler: impl Fn(&mut App<S, U>, &Event<U>, EventSender<U>) -> Result<bool> + 'static,
+    ) -> HandlerId {
+        let id = HandlerId(self.next_handler);
+        self.next_handler += 1;
+        let index = self
+            .handlers
+            .iter()
+            .position(|h| h.priority < priority)
+            .unwrap_or(self.handlers.len());
+        self.handlers.insert(
+            index,
+            Handler {
+                id,
+                priority,
+                handler: Rc::new(handler),
+            },
+        );
+        id
+    }
+
+    /// Remove a global event handler, returning whether it was found
+    pub fn remove_handler(&mut self, id: HandlerId) -> bool {
+        let len = self.handlers.len();
+        self.handlers.retain(|h| h.id != id);
+        self.handlers.len() != len
     }
 
     fn global_event(&mut self, event: &Event<U>) -> Result<bool> {
+        if self.config.enable_suspend {
+            if let Event::Key(KeyEvent {
+                key: KeyCode::Char('z'),
+                modifiers: Modifiers::CTRL,
+            }) = event
+            {
+                self.suspend()?;
+                return Ok(true);
+            }
+        }
         if self.config.ctrl_q_quit {
             if let Event::Key(KeyEvent {
                 key: KeyCode::Char('q'),
                 modifiers: Modifiers::CTRL,
             }) = event
             {
-                self.event_tx
-                    .send(UserEvent::Exit)
-                    .map_err(|_| Error::SignalSendFail)?
+                self.event_tx.quit()?
+            }
+        }
+        if let (Some(toggle), Event::Key(key)) = (&self.config.log_view_key, event) {
+            if toggle == key {
+                self.toggle_log_view()?;
+                return Ok(true);
             }
         }
 
-        // Safety: The function pointer is stored in self so the borrow checker doesn't like
-        // us calling it with a mutable reference to self. However, the function pointer won't be changed
-        // so it should be safe to call with a mutable reference to self.
-        let evt = &self.global_event_handler as *const GlobalHandler<S, U>;
-        unsafe { (*evt)(self, event, self.event_tx.clone()) }
+        // Handlers may add or remove handlers, so they're called from a snapshot of the list
+        let handlers = self
+            .handlers
+            .iter()
+            .map(|h| Rc::clone(&h.handler))
+            .collect::<Vec<_>>();
+        for handler in handlers {
+            if handler(self, event, self.event_tx.clone())? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
 
     fn process_event(&mut self, event: Event<U>) -> Result<()> {
+        self.frame_timing.events += 1;
+        // Replayed journals and backends that ignore the mode can still produce mouse events
+        if matches!(event, Event::Mouse(_)) && self.config.mouse == MouseMode::Off {
+            return Ok(());
+        }
+        match &event {
+            Event::Key(_) => self.dismiss_tooltip(),
+            Event::Mouse(m) => self.track_tooltip(m.x, m.y, m.mouse_buttons),
+            _ => {}
+        }
         match &event {
             Event::Resize { cols, rows } => {
                 self.size = Rect::from_size((*cols, *rows));
                 self.term.resize(*cols, *rows);
-                self.term.repaint().map_err(|_| Error::TerminalError)?;
-                self.term.flush().map_err(|_| Error::TerminalError)?;
-                self.layout.mark_dirty();
+                self.repaint = true;
+                self.layout.mark_dirty(None);
+                self.layout
+                    .reflow_floats(&self.size, self.config.float_margin);
+                self.cache.clear();
             }
+            // A click cancels the window picker, like any key that isn't a label
+            Event::Mouse(m) if self.picker.is_some() && m.mouse_buttons != MouseButtons::NONE => {
+                self.finish_pick(&KeyCode::Escape)?;
+            }
+            // Notifications are above every window, so clicks on them go no further
+            Event::Mouse(m) if self.click_notification(m.x, m.y, m.mouse_buttons) => {}
             Event::Mouse(MouseEvent {
                 x,
                 y,
@@ -366,78 +1541,82 @@ impl<S: 'static, U: 'static> App<S, U> {
                 modifiers,
             }) => {
                 if !self.global_event(&event)? {
-                    let Some(node) = self.layout.node_at_pos((*x, *y)) else {
+                    if mouse_buttons.contains(MouseButtons::VERT_WHEEL) {
+                        return self.scroll(*x, *y, *mouse_buttons);
+                    }
+                    // Mouse events during a float drag are handled as gestures
+                    if self.drag_split(*x, *y, *mouse_buttons) || self.float_drag.is_some() {
+                        return Ok(());
+                    }
+                    let node = self.node_at_pos((*x, *y));
+                    let target = node.and_then(|node| {
+                        let (widget, layout) = self.mouse_target(node, *x, *y)?;
+                        Some((node, widget, layout))
+                    });
+                    self.set_hover(target)?;
+                    let Some(node) = node else {
                         return Ok(());
                     };
-                    if let Some(focus) = self.focus {
-                        let focus = if focus != node {
-                            // Send hover events to the hovered node, but focus the window if the mouse is clicked
-                            if *mouse_buttons != MouseButtons::NONE {
-                                // If the node under the mouse is different from the focused node,
-                                // focus the new node and consume the event
-                                self.focus = Some(node);
-                                return Ok(());
-                            }
-                            node
-                        } else {
-                            focus
-                        };
-
-                        // check if there are inner widgets that the event should be sent to
-                        let children = self.rendered.get(focus).cloned().unwrap_or(vec![]);
-                        let child = children
-                            .iter()
-                            .filter(|(rect, _)| rect.contains(*x as f32, *y as f32))
-                            .next();
-
-                        // If the node under the mouse is the same as the focused node,
-                        // send the event to the focused node
-                        let mut widget = self.layout.node(focus).unwrap().widget().unwrap();
-                        let mut layout = self.layout.layout(focus).cloned().unwrap();
-
-                        if let Some((child_layout, child_widget)) = child {
-                            layout = Rect {
-                                x: child_layout.x + 1.,
-                                y: child_layout.y + 1.,
-                                width: child_layout.width,
-                                height: child_layout.height,
-                            };
-                            widget = *child_widget;
-                        } else if children.len() > 0 {
+                    if *mouse_buttons != MouseButtons::NONE {
+                        self.focus_child_at(node, *x, *y);
+                    }
+                    if self.focus != Some(node) {
+                        if *mouse_buttons != MouseButtons::NONE {
+                            // If the node under the mouse is different from the focused node,
+                            // focus the new node and consume the event
+                            self.set_focus(node)?;
                             return Ok(());
+                        } else if self.focus.is_none() && self.config.focus_follows_hover {
+                            // If there's no focus, focus the node under the mouse
+                            self.set_focus(node)?;
                         }
+                    }
 
-                        let offset_event = Event::Mouse(MouseEvent {
-                            x: x - layout.x as u16,
-                            y: y - layout.y as u16,
-                            mouse_buttons: *mouse_buttons,
-                            modifiers: *modifiers,
-                        });
-
-                        let mut cx = UpdateCtx::new(
-                            focus,
-                            layout,
-                            &mut self.widgets,
-                            &mut self.layout,
-                            self.event_tx.clone(),
-                            &mut self.state,
-                        );
-                        let widget = self
-                            .widgets
-                            .get_mut(widget)
-                            .ok_or(Error::WidgetNotFound(focus))?;
-                        widget.update(&mut cx, offset_event)?;
-                    } else if *mouse_buttons == MouseButtons::LEFT
-                        || self.config.focus_follows_hover
+                    // Send the event to the widget under the mouse. Motion events are sent to
+                    // the hovered widget without changing focus.
+                    let Some((node, widget, layout)) = target else {
+                        return Ok(());
+                    };
+                    let offset_event = Event::Mouse(MouseEvent {
+                        x: x.saturating_sub(layout.x as u16),
+                        y: y.saturating_sub(layout.y as u16),
+                        mouse_buttons: *mouse_buttons,
+                        modifiers: *modifiers,
+                    });
+                    if *mouse_buttons != MouseButtons::NONE
+                        && self.child_event(node, widget, &offset_event)?
                     {
-                        // If there's no focus, focus the node under the mouse
-                        self.focus = Some(node);
+                        return Ok(());
                     }
+                    self.update_widget(node, widget, layout, offset_event)?;
                 }
             }
             Event::User(UserEvent::Exit) => {
-                self.exit.store(true, std::sync::atomic::Ordering::SeqCst);
+                self.request_exit();
+            }
+            Event::User(UserEvent::Tick) => {
+                if !self.global_event(&event)? {
+                    self.broadcast_with(|| Event::User(UserEvent::Tick))?;
+                }
+            }
+            Event::User(UserEvent::User(user)) if self.clone_user.is_some() => {
+                if !self.global_event(&event)? {
+                    let clone = self.clone_user.unwrap();
+                    self.broadcast_with(|| Event::User(UserEvent::User(clone(user))))?;
+                }
+            }
+            Event::User(UserEvent::Close(node)) => {
+                // The window may have already been closed by an earlier event
+                if self.layout.node(*node).is_some() {
+                    self.close_float(*node)?;
+                }
+            }
+            Event::User(UserEvent::WidgetError(..) | UserEvent::ProcessExited(..)) => {
+                self.global_event(&event)?;
             }
+            Event::Key(key) if self.picker.is_some() => self.finish_pick(&key.key)?,
+            Event::Key(key) if self.cycle_child_focus(key) => {}
+            Event::Key(key) if self.keymap_event(key)? => {}
             // Anything that doesn't need special handling (keys, paste, user events)
             _ => {
                 // Handle global events
@@ -448,39 +1627,38 @@ impl<S: 'static, U: 'static> App<S, U> {
                             return Ok(());
                         };
                         self.set_focus(leaf)?;
-                        return Ok(())
+                        return Ok(());
                     };
                     // Retrieve widget trait object from node
-                    let Some(widget) = self
-                        .layout
-						.node(focus)
-						.unwrap()
-                        .widget() else {
-                            return Ok(());
-                        };
+                    let Some(node) = self.layout.node(focus) else {
+                        self.focus = None;
+                        return Ok(());
+                    };
+                    let Some(widget) = node.widget_id() else {
+                        return Ok(());
+                    };
 
                     // Retrieve computed layout for window
-                    let Some(layout) = self
-                        .layout
-                        .layout(focus)
-                        .cloned() else {
+                    let Some(layout) = self.window_rect(focus) else {
+                        return Ok(());
+                    };
+                    if matches!(event, Event::Key(_)) {
+                        // Keep the cursor visible while typing
+                        self.blink_start = Instant::now();
+                    }
+                    // Typing goes to the inner widget with focus, if the window has several
+                    let child = self
+                        .focused_stop(focus)
+                        .filter(|_| matches!(event, Event::Key(_) | Event::Paste(_)));
+                    if let (Some((_, child, _)), Event::Key(_)) = (child, &event) {
+                        if self.child_event(focus, child, &event)? {
                             return Ok(());
-                        };
-                    let tx = self.event_tx.clone();
-
-                    let mut cx = UpdateCtx::new(
-                        focus,
-                        layout,
-                        &mut self.widgets,
-                        &mut self.layout,
-                        tx,
-                        &mut self.state,
-                    );
-                    let w = self
-                        .widgets
-                        .get_mut(widget)
-                        .ok_or(Error::WidgetWriteLockError(focus))?;
-                    w.update(&mut cx, event)?;
+                        }
+                    }
+                    match child {
+                        Some((rect, child, _)) => self.update_widget(focus, child, rect, event)?,
+                        None => self.update_widget(focus, widget, layout, event)?,
+                    }
                 };
             }
         }
@@ -488,250 +1666,1723 @@ impl<S: 'static, U: 'static> App<S, U> {
         Ok(())
     }
 
-    fn handle_user_events(&mut self) -> Result<()> {
-        if let Ok(event) = self.event_rx.try_recv() {
-            self.process_event(Event::User(event))?;
+    /// Finds the widget in a window that should receive a mouse event at the given position,
+    /// checking inner widgets first, along with the rect the event's position is relative to.
+    /// Positions over a window with inner widgets but outside all of them have no target.
+    fn mouse_target(&self, node: NodeId, x: u16, y: u16) -> Option<(WidgetId, Rect)> {
+        let children = self.rendered.get(node).map(Vec::as_slice).unwrap_or(&[]);
+        let child = children
+            .iter()
+            .find(|(rect, _, _)| rect.contains(x as f32, y as f32));
+        match child {
+            Some((child_layout, child_widget, _)) => Some((*child_widget, *child_layout)),
+            None if !children.is_empty() => None,
+            None => Some((self.layout.widget(node)?, self.window_rect(node)?)),
         }
-        Ok(())
     }
 
-    fn handle_input_events(&mut self) -> Result<()> {
-        while let Some(event) = self
-            .term
-            .terminal()
-            .poll_input(Some(Duration::from_millis(15)))
-            .map_err(|_| Error::PollInputFailed)?
-        {
-            use termwiz::input::InputEvent;
-            let translated = match event {
-                InputEvent::Key(k) => Event::Key(k),
-                InputEvent::Mouse(m) => Event::Mouse(m),
-                InputEvent::Resized { rows, cols } => Event::Resize { rows, cols },
-                InputEvent::Paste(s) => Event::Paste(s),
-                _ => continue,
-            };
-            self.process_event(translated)?;
+    /// Moves the hover to the given window and widget, sending [`Event::MouseLeave`] to the
+    /// widget that was hovered and [`Event::MouseEnter`] to the new one if it changed
+    fn set_hover(&mut self, hover: Option<(NodeId, WidgetId, Rect)>) -> Result<()> {
+        let key = |hover: Option<(NodeId, WidgetId, Rect)>| hover.map(|(n, w, _)| (n, w));
+        if key(self.hover) == key(hover) {
+            self.hover = hover;
+            return Ok(());
+        }
+        // Tooltips belong to the widget under the pointer
+        self.forget_tooltip();
+        if let Some((node, widget, bounds)) = std::mem::replace(&mut self.hover, hover) {
+            // The window may have been closed since it was hovered
+            if self.layout.node(node).is_some() {
+                match self.update_widget(node, widget, bounds, Event::MouseLeave) {
+                    Err(Error::WidgetNotFound(_)) => {}
+                    res => res?,
+                }
+            }
+        }
+        if let Some((node, widget, bounds)) = hover {
+            self.update_widget(node, widget, bounds, Event::MouseEnter)?;
         }
         Ok(())
     }
 
-    /// Calls a closure, passing in a mutable reference to the layout and a function that registers
-    /// a new widget. Intended to be used at initialization only, use [`App::update_layout`] to modify
-    /// layout during application runtime.
-    pub fn with_layout<F>(mut self, f: F) -> Self
-    where
-        F: FnOnce(&mut Layout<U, S>, &mut WidgetStore<U, S>) -> Option<NodeId>,
-    {
-        f(&mut self.layout, &mut self.widgets).map(|target| self.set_focus(target).ok());
-        self
-    }
-
-    /// Calls a closure, passing in a mutable reference to the layout and a function that registers
-    /// a new widget.
-    pub fn update_layout<F, R>(&mut self, f: F) -> R
-    where
-        F: FnOnce(&mut Layout<U, S>, &mut WidgetStore<U, S>) -> R,
-        R: Sized,
-    {
-        f(&mut self.layout, &mut self.widgets)
+    /// Sends a mouse wheel event to the widget under the mouse as an [`Event::Scroll`], without
+    /// changing focus.
+    fn scroll(&mut self, x: u16, y: u16, mouse_buttons: MouseButtons) -> Result<()> {
+        let Some(node) = self.node_at_pos((x, y)) else {
+            return Ok(());
+        };
+        let Some((widget, layout)) = self.mouse_target(node, x, y) else {
+            return Ok(());
+        };
+        let delta = if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+            -1
+        } else {
+            1
+        };
+        let event = Event::Scroll {
+            delta,
+            x: x.saturating_sub(layout.x as u16),
+            y: y.saturating_sub(layout.y as u16),
+        };
+        self.update_widget(node, widget, layout, event)
     }
 
-    /// Calls a closure, passing in an immutable reference to the layout.
-    pub fn inspect_layout<F, R>(&self, f: F) -> R
-    where
-        F: FnOnce(&Layout<U, S>, &WidgetStore<U, S>) -> R,
-        R: Sized,
-    {
-        f(&self.layout, &self.widgets)
+    /// Passes a key press to the keymap, running the bound action if it completes a binding.
+    /// Returns whether the key was consumed.
+    fn keymap_event(&mut self, key: &KeyEvent) -> Result<bool> {
+        let (consumed, action) = self.keymap.press(key);
+        let Some(action) = action else {
+            return Ok(consumed);
+        };
+        let res = match &*action {
+            Action::CycleFocus => self.cycle_focus(),
+            Action::FocusDirection(direction) => self.focus_direction(*direction),
+            Action::CloseFocused => self.close_focused(true),
+            Action::NextWorkspace => self.next_workspace(),
+            Action::PrevWorkspace => self.prev_workspace(),
+            Action::PickWindow => {
+                self.pick_window();
+                Ok(())
+            }
+            Action::Quit => {
+                self.request_exit();
+                Ok(())
+            }
+            Action::Custom(f) => f(self),
+        };
+        match res {
+            // Window management bindings do nothing if there is nothing to act on
+            Err(Error::NoFocus | Error::LastLeaf(_)) => Ok(true),
+            res => res.map(|_| true),
+        }
     }
 
-    /// Handles and propagates events, returning whether or not the app should continue running.
-    ///
-    /// This should be used as the condition (or part of the condition) for an application's render loop.
-    pub fn handle_events(&mut self) -> Result<bool> {
-        self.handle_user_events()?;
-        self.handle_input_events()?;
-        Ok(!self.exit.load(std::sync::atomic::Ordering::SeqCst))
-    }
+    /// Starts, continues or ends a drag of the edge between two windows, returning whether the
+    /// mouse event was consumed. The drag starts once the mouse moves after pressing on an edge,
+    /// so a click on an edge without moving is passed on to the window under it.
+    fn drag_split(&mut self, x: u16, y: u16, mouse_buttons: MouseButtons) -> bool {
+        // Splits aren't shown while a window is zoomed
+        if self.zoomed.is_some() {
+            return false;
+        }
+        let pos = |axis: Axis| match axis {
+            Axis::Horizontal => x as f32,
+            Axis::Vertical => y as f32,
+        };
+        let start = |rect: &Rect, axis: Axis| match axis {
+            Axis::Horizontal => rect.x,
+            Axis::Vertical => rect.y,
+        };
 
-    /// Sets the focus to the given node.
-    pub fn set_focus(&mut self, node: NodeId) -> Result<()> {
-        if self.layout.is_container(node) {
-            return Err(Error::ExpectedLeaf(node));
+        if let Some(drag) = &mut self.drag {
+            if mouse_buttons != MouseButtons::LEFT {
+                // Releasing the button ends the drag
+                let dragged = drag.pressed.is_none();
+                self.drag = None;
+                return dragged;
+            }
+            if drag.pressed == Some((x, y)) {
+                return false;
+            }
+            drag.pressed = None;
+            let Some(first) = self.layout.layout(drag.first) else {
+                self.drag = None;
+                return true;
+            };
+            let size = pos(drag.axis) + drag.offset - start(first, drag.axis);
+            self.layout.resize_split(drag.first, drag.second, size);
+            return true;
         }
-        self.focus = Some(node);
-        Ok(())
-    }
 
-    /// Get the id of the currently focused node, if any
-    pub fn get_focus(&self) -> Option<NodeId> {
-        self.focus
+        if mouse_buttons != MouseButtons::LEFT {
+            return false;
+        }
+        let Some((first, second)) = self.layout.split_at((x, y)) else {
+            return false;
+        };
+        let (Some(axis), Some(edge)) = (
+            self.layout
+                .parent(first)
+                .and_then(|p| self.layout.direction(p)),
+            self.layout.layout(second),
+        ) else {
+            return false;
+        };
+        self.drag = Some(SplitDrag {
+            first,
+            second,
+            axis,
+            offset: start(edge, axis) - pos(axis),
+            pressed: Some((x, y)),
+        });
+        false
     }
 
-    /// Cycle focus to the next window
-    pub fn cycle_focus(&mut self) -> Result<()> {
-        let current = self.get_focus().ok_or(Error::NoFocus)?;
-        let next = self.inspect_layout(|l, _| {
-            l.leaves()
-                .into_iter()
-                .cycle()
-                .skip_while(|v| *v != current)
-                .nth(1)
-                .ok_or(Error::NoFocus)
-        })?;
-        self.set_focus(next)?;
-        Ok(())
+    /// Starts, continues or ends dragging the focused floating window by its top row. Returns
+    /// true if the gesture was consumed by the drag.
+    fn drag_float(&mut self, gesture: &Gesture) -> bool {
+        match (gesture, &mut self.float_drag) {
+            (Gesture::DragMove { dx, dy }, Some(drag)) => {
+                drag.pointer.0 += dx;
+                drag.pointer.1 += dy;
+                let (node, pointer, offset) = (drag.node, drag.pointer, drag.offset);
+                let Some(rect) = self.layout.layout(node) else {
+                    self.float_drag = None;
+                    return true;
+                };
+                let delta = (
+                    pointer.0 - offset.0 - rect.x as i32,
+                    pointer.1 - offset.1 - rect.y as i32,
+                );
+                self.layout.move_float(node, delta);
+                true
+            }
+            (Gesture::DragEnd, Some(_)) => {
+                self.float_drag = None;
+                true
+            }
+            (
+                Gesture::DragStart {
+                    x,
+                    y,
+                    button: MouseButtons::LEFT,
+                },
+                None,
+            ) => {
+                let (x, y) = (*x, *y);
+                let Some(focus) = self
+                    .focus
+                    .filter(|f| self.layout.node(*f).is_some_and(|n| n.is_floating()))
+                else {
+                    return false;
+                };
+                if self.layout.node_at_pos((x, y)) != Some(focus) {
+                    return false;
+                }
+                let Some(rect) = self.layout.layout(focus) else {
+                    return false;
+                };
+                if y as f32 != rect.y {
+                    return false;
+                }
+                self.float_drag = Some(FloatDrag {
+                    node: focus,
+                    offset: (x as i32 - rect.x as i32, 0),
+                    pointer: (x as i32, y as i32),
+                });
+                self.layout.raise_float(focus);
+                true
+            }
+            _ => false,
+        }
     }
 
-    /// Focus the window in the given direction from the currently focused one
-    pub fn focus_direction(&mut self, direction: Direction) -> Result<()> {
-        let current = self.get_focus().ok_or(Error::NoFocus)?;
-        let available = self.inspect_layout(|l, _| l.adjacent_on_side(current, direction));
-        let Some(next) = available.iter().next() else {
+    /// Sends a gesture to the widget under the position where it started, after giving the global
+    /// handler a chance to intercept it.
+    fn process_gesture(&mut self, gesture: Gesture, origin: (u16, u16)) -> Result<()> {
+        if self.global_event(&Event::MouseGesture(gesture))? || self.drag_float(&gesture) {
+            return Ok(());
+        }
+        let Some(node) = self.node_at_pos(origin) else {
             return Ok(());
         };
-        self.set_focus(*next)?;
-        Ok(())
+        let Some((widget, layout)) = self.mouse_target(node, origin.0, origin.1) else {
+            return Ok(());
+        };
+        let (left, top) = (layout.x as u16, layout.y as u16);
+        let gesture = match gesture {
+            Gesture::DoubleClick { x, y, button } => Gesture::DoubleClick {
+                x: x.saturating_sub(left),
+                y: y.saturating_sub(top),
+                button,
+            },
+            Gesture::DragStart { x, y, button } => Gesture::DragStart {
+                x: x.saturating_sub(left),
+                y: y.saturating_sub(top),
+                button,
+            },
+            gesture => gesture,
+        };
+        self.update_widget(node, widget, layout, Event::MouseGesture(gesture))
     }
 
-    fn render_recursive(
+    /// Calls [`Widget::update`] on a widget, with `owner` and `bounds` as the window it's shown in
+    fn update_widget(
         &mut self,
         owner: NodeId,
-        inner_widget: Option<WidgetId>,
-        inner_layout: Option<Rect>,
-        mut screen: &mut Surface,
-    ) {
-        let layout = match inner_layout {
-            Some(layout) => layout,
-            None => {
-                if let Some(layout) = self.layout.layout(owner) {
-                    layout.clone()
-                } else {
-                    return;
-                }
+        widget: WidgetId,
+        bounds: Rect,
+        event: Event<U>,
+    ) -> Result<()> {
+        let Self {
+            widgets,
+            layout,
+            event_tx,
+            state,
+            theme,
+            tooltip_request,
+            ..
+        } = self;
+        let updated = widgets
+            .with_widget(widget, |widget, widgets| {
+                let mut cx =
+                    UpdateCtx::new(owner, bounds, widgets, layout, event_tx.clone(), state)
+                        .with_theme(theme);
+                cx.tooltip = Some(tooltip_request);
+                widget.update(&mut cx, event)
+            })
+            .ok_or(Error::WidgetNotFound(owner))?;
+        self.take_tooltip_request(widget);
+        match updated {
+            Err(err) if self.config.contain_widget_errors => {
+                self.contain_error(widget, err);
+                Ok(())
             }
+            updated => updated,
+        }
+    }
+
+    /// Offers an event for one of a window's inner widgets to the widget that returned it,
+    /// returning whether it was consumed. See [`Widget::child_event`].
+    fn child_event(&mut self, owner: NodeId, widget: WidgetId, event: &Event<U>) -> Result<bool> {
+        let Some(rendered) = self.rendered.get(owner) else {
+            return Ok(false);
         };
-        let widget = match inner_widget.clone() {
-            Some(widget) => widget,
-            None => {
-                if let Some(widget) = self.layout.node(owner).unwrap().widget() {
-                    widget.clone()
-                } else {
-                    return;
-                }
-            }
+        let Some(index) = rendered.iter().position(|(_, w, _)| *w == widget) else {
+            return Ok(false);
+        };
+        let parent_index = rendered[index].2;
+        let siblings = self.inner_widgets(owner, parent_index);
+        let Some(child) = siblings.iter().position(|i| *i == index) else {
+            return Ok(false);
         };
+        let (parent, bounds) = match parent_index {
+            Some(parent) => (rendered[parent].1, rendered[parent].0),
+            None => match (self.layout.widget(owner), self.window_rect(owner)) {
+                (Some(parent), Some(bounds)) => (parent, bounds),
+                _ => return Ok(false),
+            },
+        };
+        let Self {
+            widgets,
+            layout,
+            event_tx,
+            state,
+            theme,
+            ..
+        } = self;
+        let consumed = widgets
+            .with_widget(parent, |widget, widgets| {
+                let mut cx =
+                    UpdateCtx::new(owner, bounds, widgets, layout, event_tx.clone(), state)
+                        .with_theme(theme);
+                widget.child_event(&mut cx, child, event)
+            })
+            .ok_or(Error::WidgetNotFound(owner))?;
+        match consumed {
+            Err(err) if self.config.contain_widget_errors => {
+                self.contain_error(parent, err);
+                Ok(true)
+            }
+            consumed => consumed,
+        }
+    }
 
-        // Draw onto widget screen for composition
-        let mut widget_screen = Surface::new(layout.width as usize, layout.height as usize);
+    /// Records the error of a widget that failed, so that it's drawn as an error panel, and
+    /// notifies the global event handlers with [`UserEvent::WidgetError`]
+    fn contain_error(&mut self, widget: WidgetId, err: Error) {
+        let message = match err {
+            Error::WidgetRenderFailed(_, message) => message,
+            err => err.to_string(),
+        };
+        self.widget_errors.insert(widget, message.clone());
+        self.widgets.mark_dirty(widget);
+        self.event_tx
+            .send(UserEvent::WidgetError(widget, message))
+            .ok();
+    }
+
+    /// The error of a widget that failed while [`Config::contain_widget_errors`] was set, if it
+    /// hasn't been cleared
+    pub fn widget_error(&self, widget: WidgetId) -> Option<&str> {
+        self.widget_errors.get(widget).map(String::as_str)
+    }
+
+    /// Clear the error of a widget that failed while [`Config::contain_widget_errors`] was set,
+    /// so that it's rendered again on the next frame instead of the error panel. Returns whether
+    /// the widget had an error.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     error::{Error, Result},
+    ///     layout::{Rect, WidgetId},
+    ///     surface::Surface,
+    ///     App, RenderCtx, Widget,
+    /// };
+    ///
+    /// struct Broken;
+    ///
+    /// impl Widget<(), ()> for Broken {
+    ///     fn render(&self, _: &RenderCtx<(), ()>, _: &mut Surface) -> Result<Vec<(Rect, WidgetId)>> {
+    ///         Err(Error::external("could not parse preview"))
+    ///     }
+    ///
+    ///     fn as_any(&self) -> &dyn std::any::Any {
+    ///         self
+    ///     }
+    ///
+    ///     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+    ///         self
+    ///     }
+    /// }
+    ///
+    /// let mut app = App::<(), ()>::test(40, 5);
+    /// app.config_mut().contain_widget_errors = true;
+    /// let broken = app.update_layout(|layout, widgets| {
+    ///     let broken = widgets.register(Broken);
+    ///     let node = layout.add_leaf_id(broken);
+    ///     layout.add_child(layout.root(), node).unwrap();
+    ///     broken
+    /// });
+    ///
+    /// // The app keeps running, with an error panel in place of the widget
+    /// app.render().unwrap();
+    /// assert!(app.screenshot()[1].contains("could not parse preview"));
+    ///
+    /// assert!(app.clear_widget_error(broken));
+    /// assert!(app.widget_error(broken).is_none());
+    /// ```
+    pub fn clear_widget_error(&mut self, widget: WidgetId) -> bool {
+        self.widgets.mark_dirty(widget);
+        self.widget_errors.remove(widget).is_some()
+    }
+
+    /// Sends an event to the widget of every window, in render order: leaves, then docked
+    /// windows, then floats from the bottom up. Widgets shown in more than one window only
+    /// receive the event once.
+    ///
+    /// The windows are collected before any events are sent, so windows opened by a widget during
+    /// the broadcast don't receive the event, and windows closed during it are skipped.
+    fn broadcast_with(&mut self, mut event: impl FnMut() -> Event<U>) -> Result<()> {
+        let mut sent = vec![];
+        for node in self
+            .layout
+            .leaves()
+            .into_iter()
+            .chain(self.layout.docked())
+            .chain(self.layout.floats())
+        {
+            let Some(widget) = self.layout.widget(node) else {
+                continue;
+            };
+            let Some(layout) = self.window_rect(node) else {
+                continue;
+            };
+            if sent.contains(&widget) || self.widgets.get(widget).is_none() {
+                continue;
+            }
+            sent.push(widget);
+            self.update_widget(node, widget, layout, event())?;
+        }
+        Ok(())
+    }
+
+    /// Send a user event to the widget of every window, bypassing the global event handler. See
+    /// [`UserEventDelivery::Broadcast`] for the order of delivery.
+    pub fn broadcast(&mut self, event: U) -> Result<()>
+    where
+        U: Clone,
+    {
+        self.broadcast_with(|| Event::User(UserEvent::User(event.clone())))
+    }
+
+    /// Get a sender for user events, which can be cloned and moved to other threads
+    pub fn event_sender(&self) -> EventSender<U> {
+        self.event_tx.clone()
+    }
+
+    /// Run `f` on a worker thread, and send its result to the app as a user event once it's done.
+    /// Use this for blocking work like I/O, which would otherwise freeze the render loop.
+    pub fn spawn<R>(
+        &self,
+        f: impl FnOnce() -> R + Send + 'static,
+        map: impl FnOnce(R) -> U + Send + 'static,
+    ) -> TaskHandle
+    where
+        U: Send + 'static,
+    {
+        self.event_tx.spawn(f, map)
+    }
+
+    /// Send a user event to a single widget, or the widget shown in a window, bypassing the
+    /// global event handler. Widgets that are only shown inside another widget, like the
+    /// contents of a [`crate::widgets::Border`], can be targeted once they have been rendered.
+    pub fn send_to(&mut self, target: impl Into<Target>, event: U) -> Result<()> {
+        let (owner, widget, bounds) = match target.into() {
+            Target::Node(node) => {
+                let widget = self
+                    .layout
+                    .widget(node)
+                    .ok_or(Error::WidgetNotFound(node))?;
+                let bounds = self.window_rect(node).unwrap_or_default();
+                (node, widget, bounds)
+            }
+            Target::Widget(widget) => self.locate(widget).ok_or(Error::WidgetNotShown(widget))?,
+        };
+        self.update_widget(owner, widget, bounds, Event::User(UserEvent::User(event)))
+    }
+
+    /// Finds the window a widget is shown in and its bounds, checking windows in render order
+    /// and then the inner widgets of the last render
+    fn locate(&self, widget: WidgetId) -> Option<(NodeId, WidgetId, Rect)> {
+        self.layout
+            .leaves()
+            .into_iter()
+            .chain(self.layout.docked())
+            .chain(self.layout.floats())
+            .find(|node| self.layout.widget(*node) == Some(widget))
+            .and_then(|node| Some((node, widget, self.window_rect(node)?)))
+            .or_else(|| {
+                self.rendered.iter().find_map(|(node, inner)| {
+                    let (rect, _, _) = inner.iter().find(|(_, w, _)| *w == widget)?;
+                    Some((node, widget, *rect))
+                })
+            })
+    }
+
+    /// Processes the user events that were pending when called. Events sent while they are being
+    /// processed wait for the next call.
+    fn handle_user_events(&mut self) -> Result<()> {
+        let pending = self.event_rx.try_iter().collect::<Vec<_>>();
+        for envelope in pending {
+            self.process_envelope(envelope)?;
+        }
+        Ok(())
+    }
+
+    /// Routes an event received from the event channel to the widget it's addressed to, or
+    /// processes it like any other event
+    fn process_envelope(&mut self, envelope: Envelope<U>) -> Result<()> {
+        match envelope {
+            (Some(widget), UserEvent::User(event)) => match self.send_to(widget, event) {
+                Err(Error::WidgetNotShown(_) | Error::WidgetNotFound(_)) => Ok(()),
+                res => res,
+            },
+            (_, event) => self.process_event(Event::User(event)),
+        }
+    }
+
+    /// Processes an input event, recording it first if [`Config::record_events`] is set
+    fn process_input(&mut self, event: RecordedEvent) -> Result<()> {
+        if let Some(journal) = &mut self.journal {
+            journal.record(&event)?;
+        }
+        let RecordedEvent::Mouse(m) = event else {
+            return self.process_event(event.into());
+        };
+        if self.config.mouse == MouseMode::Off {
+            return Ok(());
+        }
+        let gestures = self.gestures.track(&m, self.config.double_click_interval);
+        let pressed = self.notification_press;
+        self.process_event(Event::Mouse(m))?;
+        // Clicks on notifications aren't gestures for the windows below them
+        if pressed || self.notification_press {
+            return Ok(());
+        }
+        for (gesture, origin) in gestures {
+            self.process_gesture(gesture, origin)?;
+        }
+        Ok(())
+    }
+
+    /// Query the size of the terminal, and resize the app if it changed without a resize event
+    /// being received. Returns whether the app was resized.
+    fn check_size(&mut self) -> Result<bool> {
+        let (cols, rows) = self.term.backend_mut().size()?;
+        if (cols, rows) == self.size() {
+            return Ok(false);
+        }
+        self.process_event(Event::Resize { cols, rows })?;
+        Ok(true)
+    }
+
+    /// The size of the terminal, as `(columns, rows)`
+    pub fn size(&self) -> (usize, usize) {
+        self.size.size_usize()
+    }
+
+    /// Calls a closure, passing in a mutable reference to the layout and a function that registers
+    /// a new widget. Intended to be used at initialization only, use [`App::update_layout`] to modify
+    /// layout during application runtime.
+    pub fn with_layout<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut Layout<U, S>, &mut WidgetStore<U, S>) -> Option<NodeId>,
+    {
+        f(&mut self.layout, &mut self.widgets).map(|target| self.set_focus(target).ok());
+        self
+    }
+
+    /// Calls a closure, passing in a mutable reference to the layout and a function that registers
+    /// a new widget.
+    pub fn update_layout<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Layout<U, S>, &mut WidgetStore<U, S>) -> R,
+        R: Sized,
+    {
+        f(&mut self.layout, &mut self.widgets)
+    }
+
+    /// Calls a closure, passing in an immutable reference to the layout.
+    pub fn inspect_layout<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Layout<U, S>, &WidgetStore<U, S>) -> R,
+        R: Sized,
+    {
+        f(&self.layout, &self.widgets)
+    }
+
+    /// Handles and propagates events, returning whether or not the app should continue running.
+    ///
+    /// This should be used as the condition (or part of the condition) for an application's render loop.
+    pub fn handle_events(&mut self) -> Result<bool> {
+        self.expire_notifications();
+        self.handle_user_events()?;
+        self.handle_input_events(self.config.poll_timeout)?;
+        Ok(!self.exiting())
+    }
+
+    fn exiting(&self) -> bool {
+        self.exit.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Suspend the app like <kbd>ctrl</kbd>+<kbd>z</kbd> does for other programs: restore the
+    /// terminal, stop the process, and set the terminal up again and redraw everything once the
+    /// process is continued (with `fg`, for example).
+    pub fn suspend(&mut self) -> Result<()> {
+        self.term.backend_mut().suspend()?;
+        // The terminal may have been resized while the app was stopped
+        self.check_size()?;
+        self.repaint = true;
+        self.request_redraw();
+        Ok(())
+    }
+
+    /// Change which mouse events the terminal reports while the app is running, and update
+    /// [`Config::mouse`]. Turning the mouse off clears the hover and any tooltip, and lets the
+    /// user select text with the mouse again.
+    ///
+    /// ```
+    /// use sanguine::{backend::TestBackend, App, MouseMode};
+    ///
+    /// let mut app = App::<(), ()>::test(12, 3);
+    /// assert_eq!(app.backend::<TestBackend>().unwrap().mouse_mode(), MouseMode::Full);
+    ///
+    /// app.set_mouse_mode(MouseMode::Off).unwrap();
+    /// assert_eq!(app.config().mouse, MouseMode::Off);
+    /// assert_eq!(app.backend::<TestBackend>().unwrap().mouse_mode(), MouseMode::Off);
+    /// ```
+    pub fn set_mouse_mode(&mut self, mode: MouseMode) -> Result<()> {
+        self.term.backend_mut().set_mouse_mode(mode)?;
+        self.config.mouse = mode;
+        if mode == MouseMode::Off {
+            self.set_hover(None)?;
+            self.forget_tooltip();
+            self.pointer = None;
+        }
+        Ok(())
+    }
+
+    /// Sets the focus to the given node. Windows below a modal float can't be focused while it's
+    /// shown, and are left unfocused.
+    ///
+    /// ```
+    /// use sanguine::{error::Error, layout::NodeId, App};
+    ///
+    /// let mut app = App::<(), ()>::test(20, 10);
+    /// let missing = NodeId::default();
+    /// let result = app.set_focus(missing);
+    /// assert!(matches!(result, Err(Error::NodeNotFound(node)) if node == missing));
+    /// ```
+    pub fn set_focus(&mut self, node: NodeId) -> Result<()> {
+        let Some(floating) = self.layout.node(node).map(|n| n.is_floating()) else {
+            return Err(Error::NodeNotFound(node));
+        };
+        if self.layout.is_container(node) {
+            return Err(Error::ExpectedLeaf(node));
+        }
+        if self.below_modal(node) {
+            return Ok(());
+        }
+        if self.zoomed.is_some_and(|z| z != node) && !floating {
+            self.set_zoom(None);
+        }
+        self.focus = Some(node);
+        if self.config.raise_float_on_focus
+            && self.layout.node(node).is_some_and(|n| n.is_floating())
+        {
+            self.layout.raise_float(node);
+        }
+        Ok(())
+    }
+
+    /// Get the id of the currently focused node, if any
+    pub fn get_focus(&self) -> Option<NodeId> {
+        self.focus
+    }
+
+    /// Cycle focus to the next window. Docked windows are skipped unless
+    /// [`Config::cycle_docked`] is set.
+    pub fn cycle_focus(&mut self) -> Result<()> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        let cycle_docked = self.config.cycle_docked;
+        let next = self.inspect_layout(|l, _| {
+            let mut windows = l.leaves();
+            if cycle_docked {
+                windows.extend(l.docked());
+            }
+            if !windows.contains(&current) {
+                // Focus is on a window outside the cycle, such as a float
+                return windows.first().copied().ok_or(Error::NoFocus);
+            }
+            windows
+                .into_iter()
+                .cycle()
+                .skip_while(|v| *v != current)
+                .nth(1)
+                .ok_or(Error::NoFocus)
+        })?;
+        self.set_focus(next)?;
+        Ok(())
+    }
+
+    /// Register a widget and show it in a new floating window of the given size, centered on the
+    /// screen and kept centered when the terminal is resized. The new window is focused, and focus
+    /// returns to the previously focused window when it is closed with [`App::close_float`] or
+    /// [`UserEvent::Close`].
+    pub fn open_float(
+        &mut self,
+        widget: impl Widget<U, S> + 'static,
+        width: usize,
+        height: usize,
+    ) -> NodeId {
+        let widget = self.widgets.register(widget);
+        let width = (width as f32).min(self.size.width);
+        let height = (height as f32).min(self.size.height);
+        let (x, y) = Anchor::Center.position(&self.size, width, height);
+        let node = self
+            .layout
+            .add_floating(widget, Rect::new(x, y, width, height));
+        self.layout.set_anchor(node, Some(Anchor::Center));
+        if let Some(focus) = self.focus {
+            self.float_return.insert(node, focus);
+        }
+        // Floats aren't containers, so this can't fail
+        let _ = self.set_focus(node);
+        node
+    }
+
+    /// Like [`App::open_float`], but the window is modal: until it's closed, the windows below it
+    /// can't be focused and don't receive mouse events. Key bindings still work, but can't move
+    /// focus out of the window.
+    pub fn open_modal(
+        &mut self,
+        widget: impl Widget<U, S> + 'static,
+        width: usize,
+        height: usize,
+    ) -> NodeId {
+        let node = self.open_float(widget, width, height);
+        self.layout.set_modal(node, true);
+        node
+    }
+
+    /// Close a floating window and remove its widget, unless the widget is still shown elsewhere.
+    /// If the window is focused, focus returns to the window that was focused when it was opened,
+    /// or the first leaf if that no longer exists.
+    pub fn close_float(&mut self, node: NodeId) -> Result<()> {
+        if !self.layout.node(node).is_some_and(|n| n.is_floating()) {
+            return Err(Error::ExpectedFloating(node));
+        }
+        self.close_node(node, true)
+    }
+
+    /// Close a leaf, docked leaf or floating window. If the window is focused, focus moves to an
+    /// adjacent window, or for floating windows to the window that was focused when it was
+    /// opened.
+    ///
+    /// If `remove_widgets` is true, the window's widget is removed from the widget store once
+    /// nothing references it (see [`Layout::widget_refcount`]), along with the inner widgets it
+    /// leaves unreferenced. Widgets shared with [`Layout::clone_leaf`] are kept until their last
+    /// window is closed.
+    ///
+    /// ```
+    /// use sanguine::{widgets::{Border, TextBox}, App};
+    ///
+    /// let mut app = App::<(), ()>::test(80, 24);
+    /// let (left, right, border, textbox) = app.update_layout(|layout, widgets| {
+    ///     let textbox = widgets.register(TextBox::new());
+    ///     let border = widgets.register(Border::new("Shared", textbox));
+    ///     let left = layout.add_leaf_id(border);
+    ///     let right = layout.clone_leaf(left).unwrap();
+    ///     let other = layout.add_leaf(widgets, TextBox::new());
+    ///     for node in [left, right, other] {
+    ///         layout.add_child(layout.root(), node).unwrap();
+    ///     }
+    ///     (left, right, border, textbox)
+    /// });
+    ///
+    /// // The border is still shown on the right
+    /// app.close_node(left, true).unwrap();
+    /// assert!(app.get_widget(border).is_some());
+    ///
+    /// // Closing its last window removes the border, and the textbox inside it
+    /// app.close_node(right, true).unwrap();
+    /// assert!(app.get_widget(border).is_none());
+    /// assert!(app.get_widget(textbox).is_none());
+    /// ```
+    pub fn close_node(&mut self, node: NodeId, remove_widgets: bool) -> Result<()> {
+        let widget = self.layout.widget(node).ok_or(Error::ExpectedLeaf(node))?;
+        if self.layout.node(node).is_some_and(|n| n.is_floating()) {
+            self.layout.remove_float(node);
+            let previous = self.float_return.remove(node);
+            if self.focus == Some(node) {
+                self.focus = previous
+                    .filter(|prev| self.layout.node(*prev).is_some())
+                    .or_else(|| self.layout.leaves().first().copied());
+            }
+        } else {
+            let next = self.layout.parent(node).and_then(|_| {
+                self.layout
+                    .adjacent(node)
+                    .into_iter()
+                    .map(|(node, _)| node)
+                    .find(|node| self.layout.is_leaf(*node))
+            });
+            self.layout.close_leaf(node)?;
+            if self.focus == Some(node) {
+                self.focus = next
+                    .filter(|node| self.layout.is_leaf(*node))
+                    .or_else(|| self.layout.leaves().first().copied());
+            }
+        }
+        self.rendered.remove(node);
+        self.child_focus.remove(node);
+        if remove_widgets {
+            self.release_widget(widget);
+        }
+        Ok(())
+    }
+
+    /// Removes a widget if nothing references it anymore, and then any of its inner widgets that
+    /// are left unreferenced
+    fn release_widget(&mut self, widget: WidgetId) {
+        if self.layout.widget_refcount(&self.widgets, widget) > 0 {
+            return;
+        }
+        if let Some(removed) = self.widgets.remove(widget) {
+            for child in removed.children() {
+                self.release_widget(child);
+            }
+        }
+    }
+
+    /// Pop the focused leaf out of the tree into a floating window at `rect`. The window stays
+    /// focused, and is raised if [`Config::raise_float_on_focus`] is set. See
+    /// [`Layout::make_float`].
+    pub fn float_focused(&mut self, rect: Rect) -> Result<()> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        self.layout.make_float(current, rect)?;
+        self.rendered.remove(current);
+        self.set_focus(current)
+    }
+
+    /// Tile the focused floating window at the end of the root container. The window stays
+    /// focused. See [`Layout::make_leaf`].
+    pub fn unfloat_focused(&mut self) -> Result<()> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        if !self.layout.node(current).is_some_and(|n| n.is_floating()) {
+            return Err(Error::ExpectedFloating(current));
+        }
+        self.layout.make_leaf(current)?;
+        self.rendered.remove(current);
+        self.float_return.remove(current);
+        if self.float_drag.as_ref().is_some_and(|d| d.node == current) {
+            self.float_drag = None;
+        }
+        Ok(())
+    }
+
+    /// Reset the size of every window so that each container's space is shared equally between its
+    /// children. See [`Layout::equalize`].
+    pub fn equalize(&mut self) {
+        let root = self.layout.root();
+        self.layout.equalize(root, true);
+    }
+
+    /// Split the focused window along `axis`, showing `widget` in the new window. The new window
+    /// is focused, and its id is returned.
+    ///
+    /// See [`Layout::split`].
+    pub fn split_focused(
+        &mut self,
+        axis: Axis,
+        widget: impl Widget<U, S> + 'static,
+    ) -> Result<NodeId> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        if !self.layout.is_leaf(current) {
+            return Err(Error::ExpectedLeaf(current));
+        }
+        let widget = self.widgets.register(widget);
+        let node = self.layout.split(current, axis, widget)?;
+        self.layout.mark_dirty(Some(node));
+        self.set_focus(node)?;
+        Ok(node)
+    }
+
+    /// Close the focused window, moving focus to an adjacent window. Floating windows are closed
+    /// with [`App::close_float`].
+    ///
+    /// If `remove_widget` is true, the window's widget is also removed from the widget store,
+    /// unless it is still shown in another window. See [`App::close_node`].
+    pub fn close_focused(&mut self, remove_widget: bool) -> Result<()> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        if self.layout.node(current).is_some_and(|n| n.is_floating()) {
+            return self.close_float(current);
+        }
+        self.close_node(current, remove_widget)
+    }
+
+    /// Focus the window in the given direction from the currently focused one. See
+    /// [`Layout::node_in_direction`].
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     hsplit,
+    ///     layout::{leaf, Direction::*},
+    ///     vsplit,
+    ///     widgets::TextBox,
+    ///     App,
+    /// };
+    ///
+    /// let mut built = None;
+    /// let mut app = App::<(), ()>::test(20, 10).with_layout(|layout, widgets| {
+    ///     let grid = hsplit![
+    ///         vsplit![leaf(TextBox::new()).named("top left"), leaf(TextBox::new())],
+    ///         vsplit![
+    ///             leaf(TextBox::new()).named("top right"),
+    ///             leaf(TextBox::new()).named("bottom right"),
+    ///         ],
+    ///     ];
+    ///     built = Some(layout.build(widgets, grid).ok()?);
+    ///     built.as_ref()?.get("top left")
+    /// });
+    /// app.render().unwrap();
+    /// let node = |name| built.as_ref().unwrap().get(name);
+    ///
+    /// app.focus_direction(Right).unwrap();
+    /// assert_eq!(app.get_focus(), node("top right"));
+    /// app.focus_direction(Down).unwrap();
+    /// assert_eq!(app.get_focus(), node("bottom right"));
+    /// // Nothing is further right, so focus stays put
+    /// app.focus_direction(Right).unwrap();
+    /// assert_eq!(app.get_focus(), node("bottom right"));
+    /// ```
+    pub fn focus_direction(&mut self, direction: Direction) -> Result<()> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        let Some(next) = self.layout.node_in_direction(current, direction) else {
+            return Ok(());
+        };
+        self.set_focus(next)?;
+        Ok(())
+    }
+
+    /// Grow the focused window by `delta` cells towards `direction`, or shrink it if `delta` is
+    /// negative. See [`Layout::resize_node`].
+    pub fn resize_focused(&mut self, direction: Direction, delta: i32) -> Result<()> {
+        let current = self.get_focus().ok_or(Error::NoFocus)?;
+        self.layout.resize_node(current, direction, delta)
+    }
+
+    /// Hide or show a window or container without removing it from the layout. See
+    /// [`Layout::set_hidden`].
+    ///
+    /// If the focused window is hidden, focus moves to a neighbouring window, or back to the
+    /// window a hidden float was opened from.
+    ///
+    /// ```
+    /// use sanguine::{layout::Axis, widgets::TextBox, App};
+    ///
+    /// let mut app = App::<(), ()>::test(80, 24);
+    /// let (sidebar, editor) = app.update_layout(|layout, widgets| {
+    ///     let sidebar = layout.add_leaf(widgets, TextBox::new());
+    ///     let editor = layout.add_leaf(widgets, TextBox::new());
+    ///     layout.set_direction(layout.root(), Axis::Horizontal);
+    ///     layout.add_child(layout.root(), sidebar).unwrap();
+    ///     layout.add_child(layout.root(), editor).unwrap();
+    ///     (sidebar, editor)
+    /// });
+    /// app.set_focus(sidebar).unwrap();
+    ///
+    /// assert!(app.toggle_node(sidebar).unwrap());
+    /// assert_eq!(app.get_focus(), Some(editor));
+    /// assert!(!app.toggle_node(sidebar).unwrap());
+    /// ```
+    pub fn set_hidden(&mut self, node: NodeId, hidden: bool) -> Result<()> {
+        if self.layout.node(node).is_none() {
+            return Err(Error::NodeNotFound(node));
+        }
+        self.layout.set_hidden(node, hidden);
+        let Some(focus) = self.focus.filter(|focus| !self.layout.is_shown(*focus)) else {
+            return Ok(());
+        };
+        let next = self
+            .float_return
+            .get(focus)
+            .copied()
+            .filter(|prev| self.layout.node(*prev).is_some() && self.layout.is_shown(*prev))
+            .or_else(|| {
+                [
+                    Direction::Left,
+                    Direction::Up,
+                    Direction::Right,
+                    Direction::Down,
+                ]
+                .into_iter()
+                .find_map(|direction| self.layout.node_in_direction(focus, direction))
+            })
+            .or_else(|| self.layout.leaves().first().copied());
+        self.focus = None;
+        if let Some(next) = next {
+            self.set_focus(next)?;
+        }
+        Ok(())
+    }
+
+    /// Hide a window or container if it's shown, or show it if it's hidden, returning whether it's
+    /// now hidden. See [`App::set_hidden`].
+    pub fn toggle_node(&mut self, node: NodeId) -> Result<bool> {
+        let hidden = !self.layout.is_hidden(node);
+        self.set_hidden(node, hidden)?;
+        Ok(hidden)
+    }
+
+    /// Draw the focused window at the size of the terminal in place of the layout, or go back to
+    /// the layout if a window is already zoomed. Returns whether a window is now zoomed.
+    ///
+    /// Floating windows are still drawn on top of a zoomed window, and can be focused without
+    /// leaving zoom. Focusing any other window returns to the layout. The layout itself isn't
+    /// changed, so the sizes of the other windows are kept.
+    ///
+    /// Returns [`Error::NoFocus`] if no window is focused, or [`Error::ExpectedLeaf`] if the
+    /// focused window is floating or docked.
+    ///
+    /// ```
+    /// use sanguine::{widgets::TextBox, App};
+    ///
+    /// let mut app = App::<(), ()>::test(80, 24).with_layout(|layout, widgets| {
+    ///     let left = layout.add_leaf(widgets, TextBox::new());
+    ///     let right = layout.add_leaf(widgets, TextBox::new());
+    ///     layout.add_child(layout.root(), left).ok()?;
+    ///     layout.add_child(layout.root(), right).ok()?;
+    ///     Some(left)
+    /// });
+    /// let left = app.get_focus().unwrap();
+    /// assert!(app.toggle_zoom().unwrap());
+    /// assert_eq!(app.zoomed(), Some(left));
+    /// assert!(!app.toggle_zoom().unwrap());
+    /// assert_eq!(app.zoomed(), None);
+    /// ```
+    pub fn toggle_zoom(&mut self) -> Result<bool> {
+        if self.zoomed.is_some() {
+            self.set_zoom(None);
+            return Ok(false);
+        }
+        let focus = self.focus.ok_or(Error::NoFocus)?;
+        let in_tree = self.layout.node(focus).is_some_and(|n| !n.is_floating())
+            && !self.layout.is_docked(focus);
+        if !in_tree {
+            return Err(Error::ExpectedLeaf(focus));
+        }
+        self.set_zoom(Some(focus));
+        Ok(true)
+    }
+
+    /// The window that is zoomed to fill the terminal, if any. See [`App::toggle_zoom`].
+    pub fn zoomed(&self) -> Option<NodeId> {
+        self.zoomed
+    }
+
+    fn set_zoom(&mut self, zoomed: Option<NodeId>) {
+        if self.zoomed != zoomed {
+            self.zoomed = zoomed;
+            self.request_redraw();
+        }
+    }
+
+    /// Renders a node's widget and any inner widgets it returns onto `screen`, whose top left
+    /// corner is at `origin`. Output is clipped to `clip`, so inner widgets can't draw outside of
+    /// their owner and windows can't draw outside of the terminal. Inner widgets are only
+    /// `focused` if their window is, and they have the window's inner focus or contain it.
+    #[allow(clippy::too_many_arguments)]
+    fn render_recursive(
+        &mut self,
+        owner: NodeId,
+        inner: Option<(usize, WidgetId, Rect)>,
+        focused: bool,
+        clip: &Rect,
+        origin: (f32, f32),
+        screen: &mut Surface,
+    ) -> Result<()> {
+        let (index, widget, layout) = match inner {
+            Some((index, widget, layout)) => (Some(index), widget, layout),
+            None => {
+                // The window hasn't been laid out, so there's nowhere to draw it
+                let Some(layout) = self.window_rect(owner) else {
+                    return Ok(());
+                };
+                let widget = self
+                    .layout
+                    .widget(owner)
+                    .ok_or(Error::ExpectedLeaf(owner))?;
+                if let Some(rendered) = self.rendered.entry(owner) {
+                    rendered.or_default().clear();
+                }
+                (None, widget, layout)
+            }
+        };
+        let Some(clip) = layout.intersection(clip) else {
+            return Ok(());
+        };
+
+        // Draw onto widget screen for composition, reusing the surface the widget was drawn on in
+        // the last frame
+        let (width, height) = layout.size_usize();
+        let slot = index.map_or(0, |index| index + 1);
+        let pool = self.surface_pool.entry(owner).map(|e| e.or_default());
+        let PooledSurface {
+            widget: last_widget,
+            current: previous,
+            spare: mut widget_screen,
+        } = pool
+            .and_then(|pool| pool.get_mut(slot))
+            .map(std::mem::take)
+            .unwrap_or_default();
+        recycle_surface(&mut widget_screen, width, height, ColorAttribute::Default);
+        let transparent = self.widgets.get(widget).is_some_and(|w| w.transparent());
+        if transparent {
+            // Start from what's already drawn beneath the widget, so the cells it doesn't draw on
+            // show through
+            let changes = widget_screen.diff_region(
+                (clip.x - layout.x) as usize,
+                (clip.y - layout.y) as usize,
+                clip.width as usize,
+                clip.height as usize,
+                screen,
+                (clip.x - origin.0) as usize,
+                (clip.y - origin.1) as usize,
+            );
+            widget_screen.add_changes(changes);
+        }
+        // The last surface is only useful to a widget if the same widget drew it at this size
+        let previous_valid =
+            last_widget == Some(widget) && previous.dimensions() == (width, height);
 
         // Render widget onto widget screen
-        let focused = self.focus.map(|f| f == owner).unwrap_or(false);
-        let cx = RenderCtx::new(focused, &self.layout, &self.widgets, &self.state);
-        let inner_widgets = match self.widgets.get(widget) {
+        let mut cx = RenderCtx::new(focused, &self.layout, &self.widgets, &self.state)
+            .with_theme(&self.theme);
+        cx.widget = widget;
+        cx.owner = owner;
+        cx.zoomed = self.zoomed == Some(owner);
+        cx.previous = previous_valid.then_some(&previous);
+        cx.focused_child = self
+            .child_focus
+            .get(owner)
+            .filter(|(parent, _)| *parent == widget)
+            .map(|(_, child)| *child);
+        let focused_child = cx.focused_child;
+        let started = Instant::now();
+        let rendered = match self.widgets.get(widget) {
+            // Widgets that failed aren't rendered until their error is cleared
+            _ if self.widget_errors.contains_key(widget) => Ok(vec![]),
             Some(widget) => widget.render(&cx, &mut widget_screen),
-            None => return,
-        };
-
-        // Draw widget onto background screen
-        screen.draw_from_screen(&widget_screen, layout.x as usize, layout.y as usize);
-        if inner_widget.is_some() {
-            self.rendered.get_mut(owner).unwrap().push((
-                Rect {
-                    x: layout.x,
-                    y: layout.y,
-                    width: layout.width,
-                    height: layout.height,
-                },
+            None => Err(Error::WidgetRenderFailed(
                 widget,
-            ));
-        } else {
-            self.rendered.insert(owner, vec![]);
-        }
-
-        if let Some(inner_widgets) = inner_widgets {
-            inner_widgets.into_iter().for_each(|(rect, widget)| {
-                self.render_recursive(
-                    owner,
-                    Some(widget.clone()),
-                    Some(Rect {
-                        x: layout.x + rect.x,
-                        y: layout.y + rect.y,
-                        width: rect.width,
-                        height: rect.height,
-                    }),
-                    &mut screen,
-                );
-                self.rendered.get_mut(owner).unwrap().push((
-                    Rect {
-                        x: layout.x + rect.x,
-                        y: layout.y + rect.y,
-                        width: rect.width,
-                        height: rect.height,
-                    },
-                    widget,
-                ));
-            });
+                "widget not registered".to_owned(),
+            )),
+        };
+        self.time_widget(widget, started.elapsed());
+        let inner_widgets = match rendered {
+            Ok(inner_widgets) => inner_widgets,
+            Err(err) if self.config.contain_widget_errors => {
+                self.contain_error(widget, err);
+                vec![]
+            }
+            Err(err) => {
+                return Err(match err {
+                    // Keep the innermost widget that failed
+                    Error::WidgetRenderFailed(..) => err,
+                    err => Error::WidgetRenderFailed(widget, err.to_string()),
+                });
+            }
+        };
+        if let Some(message) = self.widget_errors.get(widget) {
+            let attrs = self.theme.style("error");
+            error_panel(&mut widget_screen, widget, message, attrs);
+        }
+
+        // Draw the visible part of the widget onto the background screen
+        let changes = screen.diff_region(
+            (clip.x - origin.0) as usize,
+            (clip.y - origin.1) as usize,
+            clip.width as usize,
+            clip.height as usize,
+            &widget_screen,
+            (clip.x - layout.x) as usize,
+            (clip.y - layout.y) as usize,
+        );
+        screen.add_changes(changes);
+        if let Some(pool) = self.surface_pool.get_mut(owner) {
+            if pool.len() <= slot {
+                pool.resize_with(slot + 1, PooledSurface::default);
+            }
+            // An error panel isn't what the widget drew, so it's not offered back to the widget
+            let failed = self.widget_errors.contains_key(widget);
+            pool[slot] = PooledSurface {
+                widget: (!failed).then_some(widget),
+                current: widget_screen,
+                spare: previous,
+            };
+        }
+
+        for (i, (rect, widget)) in inner_widgets.into_iter().enumerate() {
+            let focused = focused && focused_child.is_none_or(|child| child == i);
+            let rect = Rect {
+                x: layout.x + rect.x,
+                y: layout.y + rect.y,
+                width: rect.width,
+                height: rect.height,
+            };
+            let Some(rendered) = self.rendered.get_mut(owner) else {
+                continue;
+            };
+            rendered.push((rect, widget, index));
+            let inner = Some((rendered.len() - 1, widget, rect));
+            self.render_recursive(owner, inner, focused, &clip, origin, screen)?;
+        }
+        Ok(())
+    }
+
+    /// The text of each row of the last frame drawn, without styling
+    pub fn screenshot(&self) -> Vec<String> {
+        self.frame
+            .screen_lines()
+            .iter()
+            .map(|line| line.as_str().into_owned())
+            .collect()
+    }
+
+    /// The last frame drawn, with SGR escape sequences for the styling of each cell. Rows are
+    /// separated by newlines.
+    pub fn screenshot_ansi(&self) -> String {
+        let mut out = String::new();
+        for (y, line) in self.frame.screen_lines().iter().enumerate() {
+            if y > 0 {
+                out.push('\n');
+            }
+            let mut current = CellAttributes::default();
+            for cell in line.visible_cells() {
+                if *cell.attrs() != current {
+                    write_sgr(&mut out, cell.attrs());
+                    current = cell.attrs().clone();
+                }
+                out.push_str(cell.str());
+            }
+            if current != CellAttributes::default() {
+                write_sgr(&mut out, &CellAttributes::default());
+            }
+        }
+        out
+    }
+
+    /// Describe the layout tree, followed by docked and floating windows, with the ids, size
+    /// constraints and computed rects of every node. See [`Layout::dump`].
+    pub fn dump_layout(&self) -> String {
+        let mut out = self.layout.dump(self.layout.root());
+        for node in self.layout.docked().into_iter().chain(self.layout.floats()) {
+            out.push_str(&self.layout.dump(node));
+        }
+        out
+    }
+
+    /// How long until the next frame can be drawn, if frames are limited by [`Config::max_fps`]
+    /// and the last one was drawn too recently
+    fn frame_delay(&self) -> Option<Duration> {
+        let (fps, last) = (self.config.max_fps?, self.last_render?);
+        let interval = Duration::from_secs(1) / fps.max(1);
+        interval
+            .checked_sub(last.elapsed())
+            .filter(|delay| !delay.is_zero())
+    }
+
+    /// Whether a blinking cursor should be visible at this moment
+    fn blink_phase(&self) -> bool {
+        let Some(interval) = self.config.cursor_blink else {
+            return true;
+        };
+        let interval = interval.as_millis().max(1);
+        (self.blink_start.elapsed().as_millis() / interval) % 2 == 0
+    }
+
+    /// Places the cursor in the focused window, if its widget has one. Focus is cleared if the
+    /// focused window no longer exists.
+    fn place_cursor(&mut self) {
+        if self
+            .focus
+            .is_some_and(|focus| self.layout.node(focus).is_none())
+        {
+            self.focus = None;
         }
+        let cursor = self.focus.and_then(|focus| {
+            let layout = self.window_rect(focus)?;
+            let widget = self.layout.widget(focus)?;
+            // Widgets that failed are drawn as an error panel, which has no cursor
+            if self.widget_errors.contains_key(widget) {
+                return None;
+            }
+            // The cursor of a window with several inner widgets is the focused one's
+            let (layout, widget, index) = match self.focused_stop(focus) {
+                Some((rect, child, index)) => (rect, child, Some(index)),
+                None => (layout, widget, None),
+            };
+            if self.widget_errors.contains_key(widget) {
+                return None;
+            }
+            // Follow the inner widgets the cursor is delegated to, down to the one that places it
+            let (mut origin, mut index) = (layout, index);
+            let mut cursor = self.get_widget(widget)?.cursor(&self.widgets)?;
+            while let Some(child) = cursor.child {
+                let child = self.inner_widgets(focus, index).get(child).copied()?;
+                let (rect, widget, _) = *self.rendered.get(focus)?.get(child)?;
+                if self.widget_errors.contains_key(widget) {
+                    return None;
+                }
+                cursor = self.get_widget(widget)?.cursor(&self.widgets)?;
+                (origin, index) = (rect, Some(child));
+            }
+            let (left, top) = origin.origin_usize();
+            let (x, y) = cursor.position;
+            Some((left + x, top + y, cursor.shape)).filter(|_| cursor.visible)
+        });
+        self.has_cursor = cursor.is_some();
+        self.cursor_shown = self.has_cursor && self.blink_phase();
+        match cursor.filter(|_| self.cursor_shown) {
+            Some((x, y, shape)) => self.term.add_changes(vec![
+                Change::CursorShape(shape),
+                Change::CursorVisibility(CursorVisibility::Visible),
+                Change::CursorPosition {
+                    x: Position::Absolute(x),
+                    y: Position::Absolute(y),
+                },
+            ]),
+            None => self
+                .term
+                .add_change(Change::CursorVisibility(CursorVisibility::Hidden)),
+        };
+    }
+
+    /// Discard all cached windows, so that every widget is redrawn on the next render. Needed
+    /// when widgets render state that changed outside of [`Widget::update`] without calling
+    /// [`RenderCtx::request_redraw`].
+    pub fn request_redraw(&mut self) {
+        self.cache.clear();
+        // What widgets drew last time may be out of date too, for example after a theme change
+        for pooled in self.surface_pool.values_mut().flatten() {
+            pooled.widget = None;
+        }
+    }
+
+    /// The indices in `rendered` of the inner widgets returned by a window's widget, or by the
+    /// inner widget at `parent`
+    fn inner_widgets(&self, node: NodeId, parent: Option<usize>) -> Vec<usize> {
+        let rendered = self.rendered.get(node).map(Vec::as_slice).unwrap_or(&[]);
+        let children = rendered.iter().enumerate();
+        children
+            .filter(|(_, (_, _, p))| *p == parent)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// The inner widgets of a window that Tab moves focus between: the focusable inner widgets of
+    /// the first widget that returned more than one, looking through widgets with a single inner
+    /// widget like [`crate::widgets::Border`]. Returns the widget that returned them, and the
+    /// index of each one in that widget's inner widgets and in `rendered`.
+    fn tab_stops(&self, node: NodeId) -> Option<(WidgetId, Vec<(usize, usize)>)> {
+        let rendered = self.rendered.get(node)?;
+        let mut parent = None;
+        loop {
+            let children = self.inner_widgets(node, parent);
+            match children.as_slice() {
+                [] => return None,
+                [only] => parent = Some(*only),
+                _ => {
+                    let widget = match parent {
+                        Some(parent) => rendered.get(parent)?.1,
+                        None => self.layout.widget(node)?,
+                    };
+                    let stops = children
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(_, index)| {
+                            let widget = self.widgets.get(rendered[*index].1);
+                            widget.is_some_and(|w| w.focusable())
+                        })
+                        .collect::<Vec<_>>();
+                    return (stops.len() > 1).then_some((widget, stops));
+                }
+            }
+        }
+    }
+
+    /// The bounds, widget and index in `rendered` of the inner widget with focus in a window
+    fn focused_stop(&self, node: NodeId) -> Option<(Rect, WidgetId, usize)> {
+        let (parent, child) = self.child_focus.get(node)?;
+        let (widget, stops) = self.tab_stops(node)?;
+        if widget != *parent {
+            return None;
+        }
+        let (_, index) = stops.into_iter().find(|(c, _)| c == child)?;
+        let (rect, widget, _) = self.rendered.get(node)?.get(index)?;
+        Some((*rect, *widget, index))
+    }
+
+    /// Moves the inner focus of a window to one of its tab stops if it isn't on one, returning
+    /// whether it changed
+    fn sync_child_focus(&mut self, node: NodeId) -> bool {
+        let current = self.child_focus.get(node).copied();
+        let synced = self.tab_stops(node).map(|(widget, stops)| match current {
+            Some((parent, child)) if parent == widget && stops.iter().any(|(c, _)| *c == child) => {
+                (parent, child)
+            }
+            _ => (widget, stops[0].0),
+        });
+        match synced {
+            Some(synced) => self.child_focus.insert(node, synced),
+            None => self.child_focus.remove(node),
+        };
+        current != synced
+    }
+
+    /// Moves focus between the inner widgets of the focused window on <kbd>Tab</kbd> and
+    /// <kbd>Shift</kbd>+<kbd>Tab</kbd>, returning whether the key was consumed
+    fn cycle_child_focus(&mut self, key: &KeyEvent) -> bool {
+        let forward = match (key.key, key.modifiers) {
+            (KeyCode::Tab, Modifiers::NONE) => true,
+            (KeyCode::Tab, Modifiers::SHIFT) => false,
+            _ => return false,
+        };
+        let Some(focus) = self.focus else {
+            return false;
+        };
+        let Some((widget, stops)) = self.tab_stops(focus) else {
+            return false;
+        };
+        let current = self.child_focus.get(focus).and_then(|(parent, child)| {
+            let position = stops.iter().position(|(c, _)| c == child);
+            position.filter(|_| *parent == widget)
+        });
+        let len = stops.len();
+        let next = match (current, forward) {
+            (Some(current), true) => (current + 1) % len,
+            (Some(current), false) => (current + len - 1) % len,
+            (None, _) => 0,
+        };
+        self.child_focus.insert(focus, (widget, stops[next].0));
+        self.widgets.mark_dirty(widget);
+        true
+    }
+
+    /// Focuses the inner widget of a window at the given position, if it's one of the window's
+    /// tab stops
+    fn focus_child_at(&mut self, node: NodeId, x: u16, y: u16) {
+        let Some((widget, stops)) = self.tab_stops(node) else {
+            return;
+        };
+        let Some(rendered) = self.rendered.get(node) else {
+            return;
+        };
+        let clicked = stops.into_iter().find(|(_, index)| {
+            let (rect, _, _) = rendered[*index];
+            rect.contains(x as f32, y as f32)
+        });
+        if let Some((child, _)) = clicked {
+            if self.child_focus.insert(node, (widget, child)) != Some((widget, child)) {
+                self.widgets.mark_dirty(widget);
+            }
+        }
+    }
+
+    /// The index of the inner widget with focus in a window, among the inner widgets returned by
+    /// the widget that contains it. Windows have inner focus when their widget, or the inner
+    /// widget of a [`crate::widgets::Border`] around it, returns several inner widgets that are
+    /// [`Widget::focusable`]. <kbd>Tab</kbd> and <kbd>Shift</kbd>+<kbd>Tab</kbd> move the inner
+    /// focus, and key presses go to the inner widget with focus. See [`RenderCtx::focused_child`].
+    pub fn focused_child(&self, node: NodeId) -> Option<usize> {
+        self.child_focus.get(node).map(|(_, child)| *child)
+    }
+
+    /// The bounds of a window, which fill the terminal if the window is zoomed
+    fn window_rect(&self, node: NodeId) -> Option<Rect> {
+        if self.zoomed == Some(node) {
+            return Some(self.size);
+        }
+        self.layout.layout(node).cloned()
+    }
+
+    /// The window at a position on screen. While a window is zoomed, it's the only window under
+    /// the floating windows.
+    fn node_at_pos(&self, pos: (u16, u16)) -> Option<NodeId> {
+        // Only floats are drawn over a zoomed window
+        let node = match (self.layout.topmost_float_at(pos), self.zoomed) {
+            (Some(float), _) => Some(float),
+            (None, Some(zoomed)) => Some(zoomed),
+            (None, None) => self.layout.node_at_pos(pos),
+        };
+        // Windows below a modal float don't receive mouse events
+        node.filter(|node| !self.below_modal(*node))
+    }
+
+    /// Whether a window is below the topmost modal float, if one is shown
+    fn below_modal(&self, node: NodeId) -> bool {
+        let floats = self.layout.floats();
+        let modal = floats
+            .iter()
+            .rposition(|float| self.layout.node(*float).is_some_and(|n| n.is_modal()));
+        modal.is_some_and(|modal| !floats[modal..].contains(&node))
+    }
+
+    /// The part of a window that is on screen, relative to the window's position
+    fn visible(&self, node: NodeId) -> Option<Rect> {
+        let layout = self.window_rect(node)?;
+        let visible = layout.intersection(&self.size)?;
+        Some(Rect {
+            x: visible.x - layout.x,
+            y: visible.y - layout.y,
+            ..visible
+        })
+    }
+
+    /// Whether the widget shown in a window is [transparent](Widget::transparent)
+    fn is_transparent(&self, node: NodeId) -> bool {
+        let widget = self.layout.widget(node).and_then(|w| self.widgets.get(w));
+        widget.is_some_and(|w| w.transparent())
     }
 
-    /// Render the entire application to the terminal
+    /// Renders a window into the cache. For transparent windows, `background` is the frame being
+    /// composed, and the window is drawn over the part of it beneath the window.
+    fn render_window(&mut self, node: NodeId, background: Option<&Surface>) -> Result<()> {
+        // The window's surface is drawn on again, and only reallocated if its size changed
+        let cached = self.cache.remove(node).map(|(_, window)| window);
+        // Windows that are entirely off screen aren't drawn
+        let (Some(layout), Some(visible)) = (self.window_rect(node), self.visible(node)) else {
+            return Ok(());
+        };
+        let (width, height) = visible.size_usize();
+        let origin = (layout.x + visible.x, layout.y + visible.y);
+        let prepare = |window: &mut Surface| {
+            recycle_surface(window, width, height, ColorAttribute::Default);
+            if let Some(background) = background {
+                let (x, y) = (origin.0 as usize, origin.1 as usize);
+                let changes = window.diff_region(0, 0, width, height, background, x, y);
+                window.add_changes(changes);
+            }
+        };
+        let mut window = cached.unwrap_or_default();
+        prepare(&mut window);
+        let clip = self.size;
+        let focused = self.focus == Some(node);
+        self.render_recursive(node, None, focused, &clip, origin, &mut window)?;
+        // Widgets are told which inner widget has focus when they render, so the window is
+        // drawn again if that changed
+        if self.sync_child_focus(node) {
+            prepare(&mut window);
+            self.render_recursive(node, None, focused, &clip, origin, &mut window)?;
+        }
+        self.cache.insert(node, (visible, window));
+        Ok(())
+    }
+
+    /// Lists the windows shown in a frame into `nodes`, in the order they're drawn
+    fn frame_windows(&self, nodes: &mut Vec<NodeId>) {
+        match self.zoomed {
+            Some(zoomed) => nodes.push(zoomed),
+            None => {
+                self.layout.extend_leaves(nodes);
+                nodes.extend(self.layout.shown_docked());
+            }
+        }
+        // Floats go last, from the bottom of the stack to the top, so the topmost is drawn over the
+        // rest
+        nodes.extend(self.layout.shown_floats());
+    }
+
+    /// Whether a window's cached contents are out of date
+    fn needs_redraw(&self, node: NodeId, focus_changed: bool) -> bool {
+        let dirty = &self.frame_dirty;
+        let Some((cached, _)) = self.cache.get(node) else {
+            return true;
+        };
+        if self.visible(node).as_ref() != Some(cached) {
+            return true;
+        }
+        if focus_changed && (self.focus == Some(node) || self.last_focus == Some(node)) {
+            return true;
+        }
+        let widget = self.layout.widget(node);
+        widget.is_some_and(|w| dirty.contains_key(w))
+            || self
+                .rendered
+                .get(node)
+                .is_some_and(|inner| inner.iter().any(|(_, w, _)| dirty.contains_key(*w)))
+    }
+
+    /// Render the entire application to the terminal.
+    ///
+    /// Only windows whose widgets changed since the last frame are re-rendered, the rest are
+    /// drawn from a cache. If nothing changed at all, the frame is skipped. Widgets that animate
+    /// should change on [`UserEvent::Tick`] (see [`App::with_tick_interval`]) or call
+    /// [`RenderCtx::request_redraw`] to keep being drawn.
+    ///
+    /// If [`Config::max_fps`] is set, frames that would come too soon after the last one are
+    /// postponed until a later call.
     pub fn render(&mut self) -> Result<()> {
-        self.rendered.clear();
+        if self.frame_delay().is_some() {
+            self.frame_postponed = true;
+            return Ok(());
+        }
+        self.frame_postponed = false;
+        // A zoomed window that was removed or hidden can't be shown anymore
+        if let Some(zoomed) = self.zoomed {
+            if self.layout.node(zoomed).is_none() || !self.layout.is_shown(zoomed) {
+                self.set_zoom(None);
+            }
+        }
+        let started = Instant::now();
+        // The windows are listed into a buffer kept between frames
+        let mut nodes = std::mem::take(&mut self.frame_nodes);
+        nodes.clear();
+        self.frame_windows(&mut nodes);
+
+        self.layout.update_constraints(&self.widgets);
+        let constraints = started.elapsed();
+        let focus_changed = self.focus != self.last_focus;
+        self.widgets.swap_dirty(&mut self.frame_dirty);
+        if self.frame_dirty.is_empty()
+            && !focus_changed
+            && !self.layout.is_dirty()
+            && !self.notifications_changed
+            && !self.tooltip_changed()
+            && nodes == self.last_frame
+            && nodes.iter().all(|node| self.cache.contains_key(*node))
+        {
+            self.frame_nodes = nodes;
+            // Nothing needs to be drawn, but the cursor may need to blink
+            if self.has_cursor && self.blink_phase() != self.cursor_shown {
+                self.place_cursor();
+                self.term.flush()?;
+            }
+            return Ok(());
+        }
+        let layout_started = Instant::now();
         self.layout.compute(&self.size);
+        self.frame_timing.layout = constraints + layout_started.elapsed();
 
-        // Create temporary background screen
-        let mut screen = Surface::new(self.size.width as usize, self.size.height as usize);
+        // Re-render changed windows into the cache. Transparent windows are drawn while the frame
+        // is composed instead, over what's beneath them.
+        for node in nodes.iter().copied() {
+            if self.is_transparent(node) || !self.needs_redraw(node, focus_changed) {
+                continue;
+            }
+            self.render_window(node, None)?;
+        }
+        self.cache.retain(|node, _| nodes.contains(&node));
+        self.rendered.retain(|node, _| nodes.contains(&node));
+        self.surface_pool.retain(|node, _| nodes.contains(&node));
+        self.child_focus.retain(|node, _| nodes.contains(&node));
+
+        // The background screen is kept between frames, and only reallocated when the terminal
+        // is resized
+        let compose_started = Instant::now();
+        let (width, height) = self.size.size_usize();
+        let mut screen = std::mem::take(&mut self.frame);
+        recycle_surface(&mut screen, width, height, self.config.background);
+
+        let mut dimmed = !self.config.dim_background_for_floats;
+        for node in nodes.iter().copied() {
+            let float = self.layout.node(node).filter(|n| n.is_floating());
+            let (floating, shadow) = (float.is_some(), float.is_some_and(|f| f.has_shadow()));
+            if floating && !dimmed {
+                // Floats come last, so everything drawn so far is behind them
+                shade(&mut screen, &self.size, None);
+                dimmed = true;
+            }
+            if self.is_transparent(node) {
+                self.render_window(node, Some(&screen))?;
+            }
+            let (Some((visible, window)), Some(layout)) =
+                (self.cache.get(node), self.window_rect(node))
+            else {
+                continue;
+            };
 
-        let leaves = self.layout.leaves();
-        let floats = self.layout.floats();
+            let x = layout.x + visible.x;
+            let y = layout.y + visible.y;
+            screen.draw_from_screen(window, x as usize, y as usize);
 
-        for node in leaves.into_iter().chain(floats) {
-            self.render_recursive(node, None, None, &mut screen);
+            if shadow {
+                let right = Rect::new(layout.right(), layout.y + 1., 1., layout.height);
+                let bottom = Rect::new(layout.x + 1., layout.bottom(), layout.width, 1.);
+                for rect in [right, bottom] {
+                    shade(&mut screen, &rect, Some(AnsiColor::Black.into()));
+                }
+            }
         }
+        // Notifications are drawn over everything, including floats
+        self.draw_notifications(&mut screen);
+        self.draw_tooltip(&mut screen);
+        if self.config.show_metrics {
+            self.draw_metrics(&mut screen);
+        }
+        // The last frame's list becomes the buffer for the next one
+        self.frame_nodes = std::mem::replace(&mut self.last_frame, nodes);
+        self.last_focus = self.focus;
+        self.last_render = Some(Instant::now());
 
         // Draw contents of background screen to terminal
         self.term.draw_from_screen(&screen, 0, 0);
+        self.frame = screen;
 
-        if let Some(focus) = self.focus {
-            if let Some(layout) = self.layout.layout(focus) {
-                let widget_id = self.layout.node(focus).unwrap().widget().unwrap();
-                if let Some(cursor) = self
-                    .get_widget(widget_id)
-                    .map(|w| w.cursor(&self.widgets))
-                    .flatten()
-                {
-                    if let Some(child) = cursor.0 {
-                        let child = self.rendered.get(focus).unwrap().get(child).unwrap();
-                        // let cursor = child.1.read().unwrap().cursor().unwrap();
-                        self.term.add_changes(vec![
-                            Change::CursorVisibility(CursorVisibility::Visible),
-                            Change::CursorPosition {
-                                x: Position::Absolute((child.0.x) as usize + cursor.1),
-                                y: Position::Absolute((child.0.y) as usize + cursor.2),
-                            },
-                        ]);
-                    } else {
-                        self.term.add_changes(vec![
-                            Change::CursorVisibility(CursorVisibility::Visible),
-                            Change::CursorPosition {
-                                x: Position::Absolute(layout.x as usize + cursor.1),
-                                y: Position::Absolute(layout.y as usize + cursor.2),
-                            },
-                        ]);
-                    }
-                } else {
-                    self.term
-                        .add_changes(vec![Change::CursorVisibility(CursorVisibility::Hidden)]);
-                }
+        self.place_cursor();
+        self.frame_timing.compose = compose_started.elapsed();
+
+        // Compute optimized diff and flush, or redraw everything after a resize
+        let flush_started = Instant::now();
+        let flushed = if std::mem::take(&mut self.repaint) {
+            self.term.repaint()
+        } else {
+            self.term.flush()
+        };
+        self.frame_timing.flush = flush_started.elapsed();
+        self.finish_frame(started.elapsed());
+        if flushed.is_err() {
+            // Writing can fail if the terminal was resized without a resize event, in which case
+            // the next frame is drawn at the right size
+            if self.check_size()? {
+                return Ok(());
             }
+            return Err(Error::external("could not flush terminal"));
         }
 
-        // Compute optimized diff and flush
-        self.term
-            .flush()
-            .map_err(|_| Error::external("could not flush terminal"))?;
-
         Ok(())
     }
 }
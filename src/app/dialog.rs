@@ -0,0 +1,373 @@
+//! Modal confirmation and text input dialogs, built from the public widgets
+
+use super::App;
+use crate::{
+    error::Result,
+    event::{Event, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent, UserEvent},
+    layout::{NodeId, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    widget::{CursorState, RenderCtx, UpdateCtx, Widget},
+    widgets::{Border, Button, Input},
+    WidgetStore,
+};
+
+/// Called with the answer when a dialog is closed: the text of a prompt, or an empty string
+/// for a confirmed confirmation. `None` if it was cancelled.
+type DialogCallback = dyn FnOnce(Option<String>);
+
+enum Controls {
+    /// Yes and No buttons, side by side
+    Confirm {
+        yes: WidgetId,
+        no: WidgetId,
+    },
+    Prompt {
+        input: WidgetId,
+    },
+}
+
+/// The contents of a dialog opened with [`App::confirm`] or [`App::prompt`]: the message, with
+/// the controls below it, and a column of padding on either side
+struct Dialog {
+    message: Vec<String>,
+    controls: Controls,
+    /// The answer, set once the dialog is finished
+    answer: Option<String>,
+    finished: bool,
+    /// Called when the dialog is dropped, which happens when its window is closed
+    on_result: Option<Box<DialogCallback>>,
+}
+
+impl Drop for Dialog {
+    fn drop(&mut self) {
+        if let Some(on_result) = self.on_result.take() {
+            on_result(self.answer.take());
+        }
+    }
+}
+
+impl Dialog {
+    /// The bounds of the controls, in the order they're returned from render
+    fn controls(&self, width: usize) -> Vec<(Rect, WidgetId)> {
+        let y = if self.message.is_empty() {
+            0.
+        } else {
+            self.message.len() as f32 + 1.
+        };
+        match self.controls {
+            Controls::Confirm { yes, no } => {
+                // `[ Yes ] [ No ]`, centered
+                let left = (width.saturating_sub(14) / 2) as f32;
+                vec![
+                    (Rect::new(left, y, 7., 1.), yes),
+                    (Rect::new(left + 8., y, 6., 1.), no),
+                ]
+            }
+            Controls::Prompt { input } => {
+                vec![(Rect::new(1., y, width.saturating_sub(2) as f32, 1.), input)]
+            }
+        }
+    }
+
+    /// Store the answer and close the dialog's window, which calls the callback
+    fn finish<U, S>(&mut self, cx: &mut UpdateCtx<U, S>, answer: Option<String>) -> Result<()> {
+        if !std::mem::replace(&mut self.finished, true) {
+            self.answer = answer;
+            cx.tx.send(UserEvent::Close(cx.owner))?;
+        }
+        Ok(())
+    }
+}
+
+impl<U: 'static, S: 'static> Widget<U, S> for Dialog {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        for (y, line) in self.message.iter().enumerate() {
+            surface.add_changes(vec![
+                Change::CursorPosition {
+                    x: Position::Absolute(1),
+                    y: Position::Absolute(y),
+                },
+                Change::Text(line.clone()),
+            ]);
+        }
+        Ok(self.controls(width))
+    }
+
+    /// The cursor of the input, which is the first inner widget of a prompt
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        match self.controls {
+            Controls::Prompt { .. } => Some(CursorState::delegate(0)),
+            _ => None,
+        }
+    }
+
+    fn child_event(
+        &mut self,
+        cx: &mut UpdateCtx<U, S>,
+        child: usize,
+        event: &Event<U>,
+    ) -> Result<bool> {
+        // The input of a prompt handles its own events
+        if matches!(self.controls, Controls::Prompt { .. }) {
+            return Ok(false);
+        }
+        match event {
+            Event::Key(KeyEvent {
+                key: KeyCode::Escape,
+                modifiers: Modifiers::NONE,
+            }) => self.finish(cx, None)?,
+            Event::Key(KeyEvent {
+                key: KeyCode::Enter | KeyCode::Char(' '),
+                modifiers: Modifiers::NONE,
+            })
+            | Event::Mouse(MouseEvent {
+                mouse_buttons: MouseButtons::LEFT,
+                ..
+            }) => self.finish(cx, (child == 0).then(String::new))?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn update(&mut self, cx: &mut UpdateCtx<U, S>, event: Event<U>) -> Result<()> {
+        let controls = self.controls(cx.bounds.width_usize());
+        match (&event, &self.controls) {
+            (
+                Event::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    modifiers: Modifiers::NONE,
+                }),
+                _,
+            ) => self.finish(cx, None),
+            (
+                Event::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    modifiers: Modifiers::NONE,
+                }),
+                Controls::Prompt { input },
+            ) => {
+                let text = cx.widgets().get(*input).and_then(|w| w.value());
+                self.finish(cx, Some(text.unwrap_or_default()))
+            }
+            (Event::Key(_) | Event::Paste(_), Controls::Prompt { input }) => {
+                let bounds = Rect {
+                    x: cx.bounds.x + controls[0].0.x,
+                    y: cx.bounds.y + controls[0].0.y,
+                    ..controls[0].0
+                };
+                let input = *input;
+                cx.with_rect(bounds).update_widget(input, event)
+            }
+            // Mouse events reach the dialog instead of the controls, since it's inside a border
+            (Event::Mouse(mouse), _) => {
+                let (x, y) = (mouse.x as f32, mouse.y as f32);
+                let Some(child) = controls.iter().position(|(r, _)| r.contains(x, y)) else {
+                    return Ok(());
+                };
+                let (rect, widget) = controls[child];
+                let event = Event::Mouse(MouseEvent {
+                    x: mouse.x - rect.x as u16,
+                    y: mouse.y - rect.y as u16,
+                    ..mouse.clone()
+                });
+                if matches!(self.controls, Controls::Confirm { .. }) {
+                    self.child_event(cx, child, &event)?;
+                    return Ok(());
+                }
+                let bounds = Rect {
+                    x: cx.bounds.x + rect.x,
+                    y: cx.bounds.y + rect.y,
+                    ..rect
+                };
+                cx.with_rect(bounds).update_widget(widget, event)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn children(&self) -> Vec<WidgetId> {
+        match self.controls {
+            Controls::Confirm { yes, no } => vec![yes, no],
+            Controls::Prompt { input } => vec![input],
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Ask a yes or no question in a modal floating window, centered and sized to fit the message.
+    /// The window has focus until it's closed, and then focus returns to the window that had it
+    /// before.
+    ///
+    /// <kbd>Tab</kbd> moves between the Yes and No buttons, and <kbd>Enter</kbd> presses the one
+    /// with focus, which is Yes to begin with. <kbd>Escape</kbd> cancels, like No. The window is
+    /// closed and `on_result` is called with the answer once the app handles the
+    /// [`UserEvent::Close`] the dialog sends. Closing the window any other way also counts as No.
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+    ///     App,
+    /// };
+    ///
+    /// let mut app = App::<(), ()>::test(30, 7);
+    /// let answer = Rc::new(Cell::new(None));
+    /// let dialog = app.confirm("Quit", "Discard changes?", {
+    ///     let answer = Rc::clone(&answer);
+    ///     move |yes| answer.set(Some(yes))
+    /// });
+    /// assert_eq!(app.get_focus(), Some(dialog));
+    ///
+    /// app.render().unwrap();
+    /// app.backend::<TestBackend>().unwrap().assert_screen(&[
+    ///     "",
+    ///     "     ┌Quit*─────────────┐",
+    ///     "     │ Discard changes? │",
+    ///     "     │                  │",
+    ///     "     │  [ Yes ] [ No ]  │",
+    ///     "     └──────────────────┘",
+    /// ]);
+    ///
+    /// app.backend_mut::<TestBackend>().unwrap().push_input(InputEvent::Key(KeyEvent {
+    ///     key: KeyCode::Enter,
+    ///     modifiers: Modifiers::NONE,
+    /// }));
+    /// // The first call handles the key, and the second closes the dialog
+    /// app.handle_events().unwrap();
+    /// app.handle_events().unwrap();
+    /// assert_eq!(answer.get(), Some(true));
+    /// assert_eq!(app.get_focus(), None);
+    /// ```
+    pub fn confirm(
+        &mut self,
+        title: impl Into<String>,
+        message: impl AsRef<str>,
+        on_result: impl FnOnce(bool) + 'static,
+    ) -> NodeId {
+        let yes = self.widgets.register(Button::<U>::new("Yes"));
+        let no = self.widgets.register(Button::<U>::new("No"));
+        // Wide enough for the buttons
+        self.open_dialog(
+            title.into(),
+            message.as_ref(),
+            Controls::Confirm { yes, no },
+            14,
+            Box::new(move |answer: Option<String>| on_result(answer.is_some())),
+        )
+    }
+
+    /// Ask for a line of text in a modal floating window, centered and starting with `initial`.
+    /// The window has focus until it's closed, and then focus returns to the window that had it
+    /// before.
+    ///
+    /// <kbd>Enter</kbd> accepts the text, and <kbd>Escape</kbd> cancels. The window is closed and
+    /// `on_result` is called with the text, or `None` if the prompt was cancelled, once the app
+    /// handles the [`UserEvent::Close`] the dialog sends. Closing the window any other way also
+    /// cancels.
+    ///
+    /// ```
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+    ///     App,
+    /// };
+    ///
+    /// let mut app = App::<(), ()>::test(40, 5);
+    /// let name = Rc::new(RefCell::new(None));
+    /// app.prompt("Rename", "main.rs", {
+    ///     let name = Rc::clone(&name);
+    ///     move |text| *name.borrow_mut() = text
+    /// });
+    ///
+    /// let backend = app.backend_mut::<TestBackend>().unwrap();
+    /// for key in [KeyCode::Backspace, KeyCode::Backspace, KeyCode::Enter] {
+    ///     backend.push_input(InputEvent::Key(KeyEvent {
+    ///         key,
+    ///         modifiers: Modifiers::NONE,
+    ///     }));
+    /// }
+    /// app.handle_events().unwrap();
+    /// app.handle_events().unwrap();
+    /// assert_eq!(name.borrow().as_deref(), Some("main."));
+    /// ```
+    pub fn prompt(
+        &mut self,
+        title: impl Into<String>,
+        initial: impl Into<String>,
+        on_result: impl FnOnce(Option<String>) + 'static,
+    ) -> NodeId {
+        let initial = initial.into();
+        // Leave room to type, and for the cursor after the text
+        let width = (initial.chars().count() + 1).max(30);
+        let input = self.widgets.register(Input::new().with_text(initial));
+        self.open_dialog(
+            title.into(),
+            "",
+            Controls::Prompt { input },
+            width + 2,
+            Box::new(on_result),
+        )
+    }
+
+    /// Opens a dialog in a modal float sized to fit its message and controls, wrapping the
+    /// message if the terminal is too narrow
+    fn open_dialog(
+        &mut self,
+        title: String,
+        message: &str,
+        controls: Controls,
+        controls_width: usize,
+        on_result: Box<DialogCallback>,
+    ) -> NodeId {
+        let (columns, _) = self.size();
+        let widest = message.lines().map(|line| line.chars().count());
+        let widest = widest.chain([controls_width, title.chars().count() + 1]);
+        // Leave room for the border and the padding
+        let inner = widest
+            .max()
+            .unwrap_or(0)
+            .min(columns.saturating_sub(4))
+            .max(1);
+        let message = message
+            .lines()
+            .flat_map(|line| {
+                let chars = line.chars().collect::<Vec<_>>();
+                let lines = chars.chunks(inner).map(|chunk| chunk.iter().collect());
+                let lines = lines.collect::<Vec<String>>();
+                if lines.is_empty() {
+                    vec![String::new()]
+                } else {
+                    lines
+                }
+            })
+            .collect::<Vec<_>>();
+        let height = if message.is_empty() {
+            1
+        } else {
+            message.len() + 2
+        };
+        let dialog = self.widgets.register(Dialog {
+            message,
+            controls,
+            answer: None,
+            finished: false,
+            on_result: Some(on_result),
+        });
+        self.open_modal(Border::new(title, dialog), inner + 4, height + 2)
+    }
+}
@@ -0,0 +1,111 @@
+//! Queueing terminal input between frames, merging events that later ones make redundant
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::{App, MouseMode, RecordedEvent};
+use crate::{
+    error::Result,
+    event::{MouseButtons, MouseEvent},
+};
+
+/// Input read from the terminal and not processed yet, oldest first
+#[derive(Debug, Default)]
+pub(super) struct InputQueue {
+    events: VecDeque<RecordedEvent>,
+    /// The buttons held in the last mouse event queued
+    buttons: MouseButtons,
+    /// Whether the last event queued is a mouse motion, which the next motion can replace
+    motion: bool,
+}
+
+impl InputQueue {
+    /// Queues an event, returning whether it was merged into the last event queued instead.
+    /// Consecutive resizes are merged, and so are consecutive mouse motions without a button
+    /// being pressed or released. Presses, releases and wheel events are always kept.
+    pub(super) fn push(&mut self, event: RecordedEvent) -> bool {
+        let motion = match &event {
+            RecordedEvent::Mouse(m) => {
+                let wheel = MouseButtons::VERT_WHEEL | MouseButtons::HORZ_WHEEL;
+                let held = std::mem::replace(&mut self.buttons, m.mouse_buttons);
+                held == m.mouse_buttons && !m.mouse_buttons.intersects(wheel)
+            }
+            _ => false,
+        };
+        let merged = match (self.events.back_mut(), &event) {
+            (Some(last @ RecordedEvent::Resize { .. }), RecordedEvent::Resize { .. }) => {
+                *last = event;
+                true
+            }
+            (Some(RecordedEvent::Mouse(last)), RecordedEvent::Mouse(m))
+                if motion && self.motion && last.modifiers == m.modifiers =>
+            {
+                *last = m.clone();
+                true
+            }
+            _ => {
+                self.events.push_back(event);
+                false
+            }
+        };
+        self.motion = motion;
+        merged
+    }
+
+    pub(super) fn pop(&mut self) -> Option<RecordedEvent> {
+        self.events.pop_front()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Reads the input waiting in the terminal, waiting up to `timeout` for some if none is
+    /// queued, and processes it until [`Config::input_budget`](super::Config::input_budget) runs
+    /// out. Input that's left is processed by the next call, after the next frame is drawn.
+    pub(super) fn handle_input_events(&mut self, timeout: Duration) -> Result<()> {
+        let mut wait = if self.input.is_empty() {
+            timeout
+        } else {
+            Duration::ZERO
+        };
+        while let Some(event) = self.term.backend_mut().poll_input(Some(wait))? {
+            wait = Duration::ZERO;
+            if let Some(event) = self.translate_input(event) {
+                if self.input.push(event) {
+                    self.frame_timing.coalesced += 1;
+                }
+            }
+        }
+        let started = Instant::now();
+        while let Some(event) = self.input.pop() {
+            self.process_input(event)?;
+            if started.elapsed() >= self.config.input_budget {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Translates terminal input to the events the app handles, dropping the rest
+    fn translate_input(&self, event: termwiz::input::InputEvent) -> Option<RecordedEvent> {
+        use termwiz::input::InputEvent;
+        match event {
+            InputEvent::Key(k) => Some(RecordedEvent::Key(k)),
+            InputEvent::Mouse(_) if self.config.mouse == MouseMode::Off => None,
+            InputEvent::Mouse(m) => Some(RecordedEvent::Mouse(MouseEvent {
+                // Terminals report one-based positions
+                x: m.x.saturating_sub(1),
+                y: m.y.saturating_sub(1),
+                ..m
+            })),
+            InputEvent::Paste(s) => Some(RecordedEvent::Paste(s)),
+            InputEvent::Resized { rows, cols } => Some(RecordedEvent::Resize { cols, rows }),
+            _ => None,
+        }
+    }
+}
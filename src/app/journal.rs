@@ -0,0 +1,431 @@
+//! Recording input to a file with [`Config::record_events`](super::Config::record_events) and
+//! replaying it with [`App::replay`].
+//!
+//! Journals are text files with one event per line: the milliseconds since recording started,
+//! the kind of event, and its fields, separated by spaces. Blank lines and lines starting with
+//! `#` are ignored.
+//!
+//! ```text
+//! 0 resize 80 24
+//! 1520 key none j
+//! 1710 key shift+ctrl Tab
+//! 2003 mouse 12 4 left none
+//! 2100 mouse 12 4 none none
+//! 3480 paste first line\nsecond line
+//! ```
+//!
+//! Modifiers (`shift`, `alt`, `ctrl` and `super`) and mouse buttons (`left`, `right`, `middle`,
+//! `vwheel`, `hwheel` and `positive`) are joined with `+`, or written as `none`. Keys are a single
+//! character, `U+` and a hex code point for whitespace and control characters, `F1` to `F24`, or
+//! the name of the [`KeyCode`]. Mouse positions are zero-based, and pasted text escapes
+//! backslashes and line breaks.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::Write,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::App;
+use crate::{
+    error::{Error, Result},
+    event::{Event, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+};
+
+/// An input event as it's recorded in a journal
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RecordedEvent {
+    Key(KeyEvent),
+    /// A mouse event, with a zero-based position like [`Event::Mouse`]
+    Mouse(MouseEvent),
+    Resize {
+        cols: usize,
+        rows: usize,
+    },
+    Paste(String),
+}
+
+impl<U> From<RecordedEvent> for Event<U> {
+    fn from(value: RecordedEvent) -> Self {
+        match value {
+            RecordedEvent::Key(key) => Event::Key(key),
+            RecordedEvent::Mouse(mouse) => Event::Mouse(mouse),
+            RecordedEvent::Resize { cols, rows } => Event::Resize { rows, cols },
+            RecordedEvent::Paste(text) => Event::Paste(text),
+        }
+    }
+}
+
+/// A line of a journal: an event and when it was received. See the [module docs](self) for the
+/// format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JournalEntry {
+    /// The time since recording started
+    pub at: Duration,
+    pub event: RecordedEvent,
+}
+
+impl JournalEntry {
+    /// Read the entries of the journal at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Self>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| Error::external(format!("{}: {err}", path.display())))?;
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| {
+                line.parse().map_err(|err: Error| {
+                    Error::external(format!("{}:{}: {err}", path.display(), i + 1))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Keys written by name, as they're spelled in [`KeyCode`]
+const KEYS: &[(&str, KeyCode)] = &[
+    ("Backspace", KeyCode::Backspace),
+    ("Tab", KeyCode::Tab),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("LeftArrow", KeyCode::LeftArrow),
+    ("RightArrow", KeyCode::RightArrow),
+    ("UpArrow", KeyCode::UpArrow),
+    ("DownArrow", KeyCode::DownArrow),
+    ("ApplicationLeftArrow", KeyCode::ApplicationLeftArrow),
+    ("ApplicationRightArrow", KeyCode::ApplicationRightArrow),
+    ("ApplicationUpArrow", KeyCode::ApplicationUpArrow),
+    ("ApplicationDownArrow", KeyCode::ApplicationDownArrow),
+    ("Clear", KeyCode::Clear),
+    ("Cancel", KeyCode::Cancel),
+    ("Pause", KeyCode::Pause),
+    ("CapsLock", KeyCode::CapsLock),
+    ("NumLock", KeyCode::NumLock),
+    ("ScrollLock", KeyCode::ScrollLock),
+    ("PrintScreen", KeyCode::PrintScreen),
+    ("Help", KeyCode::Help),
+    ("Menu", KeyCode::Menu),
+    ("Numpad0", KeyCode::Numpad0),
+    ("Numpad1", KeyCode::Numpad1),
+    ("Numpad2", KeyCode::Numpad2),
+    ("Numpad3", KeyCode::Numpad3),
+    ("Numpad4", KeyCode::Numpad4),
+    ("Numpad5", KeyCode::Numpad5),
+    ("Numpad6", KeyCode::Numpad6),
+    ("Numpad7", KeyCode::Numpad7),
+    ("Numpad8", KeyCode::Numpad8),
+    ("Numpad9", KeyCode::Numpad9),
+    ("Multiply", KeyCode::Multiply),
+    ("Add", KeyCode::Add),
+    ("Separator", KeyCode::Separator),
+    ("Subtract", KeyCode::Subtract),
+    ("Decimal", KeyCode::Decimal),
+    ("Divide", KeyCode::Divide),
+];
+
+const MODIFIERS: &[(&str, Modifiers)] = &[
+    ("shift", Modifiers::SHIFT),
+    ("alt", Modifiers::ALT),
+    ("ctrl", Modifiers::CTRL),
+    ("super", Modifiers::SUPER),
+];
+
+const BUTTONS: &[(&str, MouseButtons)] = &[
+    ("left", MouseButtons::LEFT),
+    ("right", MouseButtons::RIGHT),
+    ("middle", MouseButtons::MIDDLE),
+    ("vwheel", MouseButtons::VERT_WHEEL),
+    ("hwheel", MouseButtons::HORZ_WHEEL),
+    ("positive", MouseButtons::WHEEL_POSITIVE),
+];
+
+/// The name of a key, or `None` if it can't be written to a journal
+fn key_name(key: KeyCode) -> Option<String> {
+    match key {
+        KeyCode::Char(c) if c.is_whitespace() || c.is_control() => {
+            Some(format!("U+{:04X}", c as u32))
+        }
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::Function(n) => Some(format!("F{n}")),
+        key => KEYS
+            .iter()
+            .find(|(_, k)| *k == key)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+fn parse_key(name: &str) -> std::result::Result<KeyCode, String> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let code_point = name
+        .strip_prefix("U+")
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+    if let Some(c) = code_point.and_then(char::from_u32) {
+        return Ok(KeyCode::Char(c));
+    }
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+        return Ok(KeyCode::Function(n));
+    }
+    KEYS.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, key)| *key)
+        .ok_or_else(|| format!("unknown key {name:?}"))
+}
+
+/// Writes a set of flags as their names joined with `+`
+fn flag_names<T: Copy>(names: &[(&str, T)], contains: impl Fn(T) -> bool) -> String {
+    let set = names.iter().filter(|(_, flag)| contains(*flag));
+    let set = set.map(|(name, _)| *name).collect::<Vec<_>>();
+    if set.is_empty() {
+        "none".to_owned()
+    } else {
+        set.join("+")
+    }
+}
+
+fn parse_flags<T: Copy>(
+    names: &[(&str, T)],
+    text: &str,
+    mut add: impl FnMut(T),
+) -> std::result::Result<(), String> {
+    if text == "none" {
+        return Ok(());
+    }
+    for name in text.split('+') {
+        let (_, flag) = names
+            .iter()
+            .find(|(n, _)| *n == name)
+            .ok_or_else(|| format!("unknown flag {name:?}"))?;
+        add(*flag);
+    }
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('\\' | 'n' | 'r'))) => {
+                chars.next();
+                out.push(match next {
+                    'n' => '\n',
+                    'r' => '\r',
+                    _ => '\\',
+                });
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+impl JournalEntry {
+    /// The entry as a line of a journal, or `None` for keys that can't be written, like media keys
+    fn line(&self) -> Option<String> {
+        let event = match &self.event {
+            RecordedEvent::Key(key) => format!(
+                "key {} {}",
+                flag_names(MODIFIERS, |m| key.modifiers.contains(m)),
+                key_name(key.key)?
+            ),
+            RecordedEvent::Mouse(mouse) => format!(
+                "mouse {} {} {} {}",
+                mouse.x,
+                mouse.y,
+                flag_names(BUTTONS, |b| mouse.mouse_buttons.contains(b)),
+                flag_names(MODIFIERS, |m| mouse.modifiers.contains(m))
+            ),
+            RecordedEvent::Resize { cols, rows } => format!("resize {cols} {rows}"),
+            RecordedEvent::Paste(text) => format!("paste {}", escape(text)),
+        };
+        Some(format!("{} {event}", self.at.as_millis()))
+    }
+}
+
+/// Writes the entry as a line of a journal. Keys that can't be written, like media keys, are
+/// written as nothing.
+impl Display for JournalEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.line().unwrap_or_default())
+    }
+}
+
+/// Parses a line of a journal
+impl FromStr for JournalEntry {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        parse_entry(line).map_err(Error::External)
+    }
+}
+
+fn parse_entry(line: &str) -> std::result::Result<JournalEntry, String> {
+    let (at, rest) = line.split_once(' ').ok_or("missing event")?;
+    let at = at
+        .parse()
+        .map(Duration::from_millis)
+        .map_err(|_| format!("invalid timestamp {at:?}"))?;
+    let (kind, fields) = rest.split_once(' ').unwrap_or((rest, ""));
+    let number = |field: Option<&str>| -> std::result::Result<usize, String> {
+        let field = field.ok_or_else(|| format!("missing field for {kind}"))?;
+        field
+            .parse()
+            .map_err(|_| format!("invalid number {field:?}"))
+    };
+    let mut parts = fields.split(' ');
+    let event = match kind {
+        "key" => {
+            let mut modifiers = Modifiers::NONE;
+            parse_flags(MODIFIERS, parts.next().unwrap_or_default(), |m| {
+                modifiers |= m
+            })?;
+            let key = parse_key(parts.next().ok_or("missing key")?)?;
+            RecordedEvent::Key(KeyEvent { key, modifiers })
+        }
+        "mouse" => {
+            let x = number(parts.next())? as u16;
+            let y = number(parts.next())? as u16;
+            let mut mouse_buttons = MouseButtons::NONE;
+            parse_flags(BUTTONS, parts.next().unwrap_or_default(), |b| {
+                mouse_buttons |= b
+            })?;
+            let mut modifiers = Modifiers::NONE;
+            parse_flags(MODIFIERS, parts.next().unwrap_or("none"), |m| {
+                modifiers |= m
+            })?;
+            RecordedEvent::Mouse(MouseEvent {
+                x,
+                y,
+                mouse_buttons,
+                modifiers,
+            })
+        }
+        "resize" => RecordedEvent::Resize {
+            cols: number(parts.next())?,
+            rows: number(parts.next())?,
+        },
+        "paste" => RecordedEvent::Paste(unescape(fields)),
+        kind => return Err(format!("unknown event {kind:?}")),
+    };
+    Ok(JournalEntry { at, event })
+}
+
+/// Appends the input the app receives to a journal
+pub(super) struct Recorder {
+    file: File,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Starts a journal at `path`, replacing any file there, with the terminal's size as the first
+    /// event so a replay starts out the same size
+    pub(super) fn create(path: &Path, (cols, rows): (usize, usize)) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|err| Error::external(format!("{}: {err}", path.display())))?;
+        let mut recorder = Self {
+            file,
+            start: Instant::now(),
+        };
+        recorder.record(&RecordedEvent::Resize { cols, rows })?;
+        Ok(recorder)
+    }
+
+    /// Appends an event. Each line is written as it's received, so the journal is complete up to
+    /// a crash.
+    pub(super) fn record(&mut self, event: &RecordedEvent) -> Result<()> {
+        let entry = JournalEntry {
+            at: self.start.elapsed(),
+            event: event.clone(),
+        };
+        let Some(line) = entry.line() else {
+            return Ok(());
+        };
+        writeln!(self.file, "{line}").map_err(Error::external)
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Feed the events of a journal recorded with
+    /// [`Config::record_events`](super::Config::record_events) through the app instead of reading
+    /// the terminal, drawing a frame after each one. The events are spaced out like they were
+    /// recorded, sped up by `speed`, and a `speed` of [`f32::INFINITY`] plays them without
+    /// waiting. User events are handled between input events as usual.
+    ///
+    /// Returns once every event was played, or the app was asked to exit.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+    ///     widgets::TextBox,
+    ///     App, Config,
+    /// };
+    ///
+    /// let path = std::env::temp_dir().join("sanguine-replay-doctest.events");
+    /// let app = |config| {
+    ///     App::<(), ()>::with_backend(config, (), TestBackend::new(12, 2))
+    ///         .unwrap()
+    ///         .with_layout(|layout, widgets| {
+    ///             let leaf = layout.add_leaf(widgets, TextBox::new());
+    ///             layout.add_child(layout.root(), leaf).ok()?;
+    ///             Some(leaf)
+    ///         })
+    /// };
+    ///
+    /// let mut recording = app(Config::default().record_events(&path));
+    /// for c in "hi".chars() {
+    ///     recording.backend_mut::<TestBackend>().unwrap().push_input(InputEvent::Key(KeyEvent {
+    ///         key: KeyCode::Char(c),
+    ///         modifiers: Modifiers::NONE,
+    ///     }));
+    /// }
+    /// recording.handle_events().unwrap();
+    ///
+    /// let mut replay = app(Config::default());
+    /// replay.replay(&path, f32::INFINITY).unwrap();
+    /// replay.backend::<TestBackend>().unwrap().assert_screen(&["hi"]);
+    /// ```
+    pub fn replay(&mut self, path: impl AsRef<Path>, speed: f32) -> Result<()> {
+        let entries = JournalEntry::load(path)?;
+        let start = Instant::now();
+        self.render()?;
+        for entry in entries {
+            if speed > 0. {
+                let due = start + entry.at.div_f32(speed);
+                std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            }
+            self.expire_notifications();
+            self.handle_user_events()?;
+            self.process_input(entry.event)?;
+            if self.exiting() {
+                break;
+            }
+            self.render()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+//! The built-in log view, showing the records kept by [`crate::log`]
+
+use super::App;
+use crate::{
+    error::Result,
+    log::{self, LogRecord},
+    widgets::{Border, LogView},
+};
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// The records kept by the logger installed with [`crate::log::init`], oldest first. Empty if
+    /// another logger is installed.
+    pub fn logs(&self) -> Vec<LogRecord> {
+        log::records()
+    }
+
+    /// Open a floating window showing the records kept by the logger, or close it if it's open.
+    /// Bound to [`Config::log_view_key`](super::Config::log_view_key).
+    ///
+    /// ```
+    /// use sanguine::{backend::TestBackend, App};
+    ///
+    /// sanguine::log::init().unwrap();
+    /// log::info!(target: "demo", "started");
+    ///
+    /// let mut app = App::<(), ()>::test(40, 10);
+    /// app.toggle_log_view().unwrap();
+    /// app.render().unwrap();
+    /// let screen = app.backend::<TestBackend>().unwrap().screen_text();
+    /// assert!(screen.iter().any(|line| line.contains("INFO  demo: started")));
+    ///
+    /// app.toggle_log_view().unwrap();
+    /// assert!(app.inspect_layout(|layout, _| layout.floats().is_empty()));
+    /// ```
+    pub fn toggle_log_view(&mut self) -> Result<()> {
+        let open = self.log_view.take();
+        if let Some(node) = open.filter(|node| self.layout.node(*node).is_some()) {
+            return self.close_float(node);
+        }
+        let dim = self.theme.style("text.dim");
+        let view = LogView::from_buffer(log::buffer())
+            .with_level("DEBUG", dim.clone())
+            .with_level("TRACE", dim);
+        let view = self.register_widget(view);
+        let (columns, rows) = self.size();
+        let node = self.open_float(
+            Border::new("Logs", view),
+            (columns * 3 / 4).max(3),
+            (rows / 2).max(3),
+        );
+        self.log_view = Some(node);
+        Ok(())
+    }
+}
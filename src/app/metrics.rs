@@ -0,0 +1,216 @@
+//! Timings of the frames the app draws, for finding slow widgets
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use super::App;
+use crate::{
+    layout::{Anchor, WidgetId},
+    surface::{Change, Position, Surface},
+};
+
+/// How many frames [`FrameMetrics`] keeps
+const FRAMES: usize = 60;
+
+/// How long the parts of a frame took. See [`App::metrics`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameTiming {
+    /// The whole frame, from the start of [`App::render`] until it was flushed
+    pub total: Duration,
+    /// Computing the layout and the widgets' size constraints
+    pub layout: Duration,
+    /// Each widget's [`Widget::render`](crate::Widget::render), not counting its inner widgets.
+    /// Only the windows that changed are drawn again, so widgets in other windows are left out.
+    pub widgets: HashMap<WidgetId, Duration>,
+    /// Drawing the windows, floats and notifications onto the frame
+    pub compose: Duration,
+    /// Writing the frame to the terminal
+    pub flush: Duration,
+    /// How many events were processed since the previous frame
+    pub events: usize,
+    /// How many input events were merged into later ones since the previous frame, like mouse
+    /// motions that were followed by another before they were processed
+    pub coalesced: usize,
+}
+
+impl FrameTiming {
+    /// The time spent in every widget's render
+    pub fn widgets_total(&self) -> Duration {
+        self.widgets.values().sum()
+    }
+
+    /// The widget whose render took the longest, and how long it took
+    pub fn slowest_widget(&self) -> Option<(WidgetId, Duration)> {
+        let widgets = self.widgets.iter().map(|(id, time)| (*id, *time));
+        widgets.max_by_key(|(_, time)| *time)
+    }
+}
+
+/// The timings of the last 60 frames drawn, with their averages and maxima
+#[derive(Debug, Clone, Default)]
+pub struct FrameMetrics {
+    frames: VecDeque<FrameTiming>,
+}
+
+impl FrameMetrics {
+    /// The frames kept, oldest first
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTiming> {
+        self.frames.iter()
+    }
+
+    /// The last frame drawn
+    pub fn last(&self) -> Option<&FrameTiming> {
+        self.frames.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The average of each timing over the frames kept. Widgets are averaged over the frames they
+    /// were drawn in, and events over every frame.
+    pub fn average(&self) -> FrameTiming {
+        let count = self.frames.len().max(1) as u32;
+        let mut widgets = HashMap::<WidgetId, (Duration, u32)>::new();
+        for (id, time) in self.frames.iter().flat_map(|f| &f.widgets) {
+            let (total, frames) = widgets.entry(*id).or_default();
+            *total += *time;
+            *frames += 1;
+        }
+        let sum = |f: fn(&FrameTiming) -> Duration| self.frames.iter().map(f).sum::<Duration>();
+        FrameTiming {
+            total: sum(|f| f.total) / count,
+            layout: sum(|f| f.layout) / count,
+            widgets: widgets
+                .into_iter()
+                .map(|(id, (total, frames))| (id, total / frames))
+                .collect(),
+            compose: sum(|f| f.compose) / count,
+            flush: sum(|f| f.flush) / count,
+            events: self.frames.iter().map(|f| f.events).sum::<usize>() / count as usize,
+            coalesced: self.frames.iter().map(|f| f.coalesced).sum::<usize>() / count as usize,
+        }
+    }
+
+    /// The longest of each timing over the frames kept, which may come from different frames
+    pub fn max(&self) -> FrameTiming {
+        let mut max = FrameTiming::default();
+        for frame in &self.frames {
+            max.total = max.total.max(frame.total);
+            max.layout = max.layout.max(frame.layout);
+            for (id, time) in &frame.widgets {
+                let slowest = max.widgets.entry(*id).or_default();
+                *slowest = (*slowest).max(*time);
+            }
+            max.compose = max.compose.max(frame.compose);
+            max.flush = max.flush.max(frame.flush);
+            max.events = max.events.max(frame.events);
+            max.coalesced = max.coalesced.max(frame.coalesced);
+        }
+        max
+    }
+
+    /// Adds a frame, dropping the oldest past the limit
+    fn push(&mut self, frame: FrameTiming) {
+        self.frames.push_back(frame);
+        let excess = self.frames.len().saturating_sub(FRAMES);
+        self.frames.drain(..excess);
+    }
+}
+
+/// Formats a duration in milliseconds
+fn ms(time: Duration) -> String {
+    format!("{:.2}ms", time.as_secs_f64() * 1000.)
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Timings of the last frames drawn, and of the events processed between them. Frames that
+    /// were skipped because nothing changed aren't counted.
+    ///
+    /// Set [`Config::show_metrics`](super::Config::show_metrics) to show them in the bottom right
+    /// corner of the terminal.
+    ///
+    /// ```
+    /// use sanguine::{widgets::TextBox, App};
+    ///
+    /// let mut app = App::<(), ()>::test(20, 5).with_layout(|layout, widgets| {
+    ///     let leaf = layout.add_leaf(widgets, TextBox::new());
+    ///     layout.add_child(layout.root(), leaf).ok()?;
+    ///     Some(leaf)
+    /// });
+    /// app.render().unwrap();
+    ///
+    /// let frame = app.metrics().last().unwrap();
+    /// assert_eq!(frame.widgets.len(), 1);
+    /// assert!(frame.total >= frame.widgets_total());
+    /// ```
+    pub fn metrics(&self) -> &FrameMetrics {
+        &self.metrics
+    }
+
+    /// Adds the time a widget took to render to the frame being drawn
+    pub(super) fn time_widget(&mut self, widget: WidgetId, time: Duration) {
+        *self.frame_timing.widgets.entry(widget).or_default() += time;
+    }
+
+    /// Finishes the timings of the frame being drawn
+    pub(super) fn finish_frame(&mut self, total: Duration) {
+        let mut frame = std::mem::take(&mut self.frame_timing);
+        frame.total = total;
+        self.metrics.push(frame);
+    }
+
+    /// Draws the timings of the last frame over the rest of the frame
+    pub(super) fn draw_metrics(&self, screen: &mut Surface) {
+        let Some(last) = self.metrics.last() else {
+            return;
+        };
+        let (avg, max) = (self.metrics.average(), self.metrics.max());
+        let mut lines = vec![
+            format!(
+                "frame {} avg {} max {}",
+                ms(last.total),
+                ms(avg.total),
+                ms(max.total)
+            ),
+            format!(
+                "layout {} widgets {}",
+                ms(last.layout),
+                ms(last.widgets_total())
+            ),
+            format!("compose {} flush {}", ms(last.compose), ms(last.flush)),
+            format!("events {} merged {}", last.events, last.coalesced),
+        ];
+        if let Some((widget, time)) = last.slowest_widget() {
+            lines.push(format!("slowest {widget:?} {}", ms(time)));
+        }
+        let (columns, rows) = self.size();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) + 2;
+        let width = width.min(columns);
+        let height = lines.len().min(rows);
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (x, y) = Anchor::BottomRight.position(&self.size, width as f32, height as f32);
+        let mut overlay = Surface::new(width, height);
+        let attrs = self.theme.style("metrics");
+        for (y, line) in lines.iter().take(height).enumerate() {
+            let line = format!(" {line:<0$}", width - 1);
+            overlay.add_changes(vec![
+                Change::CursorPosition {
+                    x: Position::Absolute(0),
+                    y: Position::Absolute(y),
+                },
+                Change::AllAttributes(attrs.clone()),
+                Change::Text(line.chars().take(width).collect()),
+            ]);
+        }
+        screen.draw_from_screen(&overlay, x as usize, y as usize);
+    }
+}
@@ -0,0 +1,249 @@
+//! Short-lived messages drawn above every window
+
+use std::time::{Duration, Instant};
+
+use super::App;
+use crate::{
+    event::MouseButtons,
+    layout::{Anchor, Rect},
+    surface::{Change, Position, Surface},
+};
+
+/// How important a notification is, which decides the theme key it's drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotifyLevel {
+    /// Drawn with the `notify.info` style
+    Info,
+    /// Drawn with the `notify.warn` style
+    Warn,
+    /// Drawn with the `notify.error` style
+    Error,
+}
+
+impl NotifyLevel {
+    /// The theme key the notification's border and title are drawn with
+    pub fn theme_key(&self) -> &'static str {
+        match self {
+            NotifyLevel::Info => "notify.info",
+            NotifyLevel::Warn => "notify.warn",
+            NotifyLevel::Error => "notify.error",
+        }
+    }
+
+    /// The title of the notification's border
+    fn title(&self) -> &'static str {
+        match self {
+            NotifyLevel::Info => "Info",
+            NotifyLevel::Warn => "Warning",
+            NotifyLevel::Error => "Error",
+        }
+    }
+}
+
+/// A message shown with [`App::notify`]
+#[derive(Debug, Clone)]
+pub struct Notification {
+    message: String,
+    level: NotifyLevel,
+    /// When the notification is dismissed, or `None` if it stays until clicked
+    expires: Option<Instant>,
+}
+
+impl Notification {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn level(&self) -> NotifyLevel {
+        self.level
+    }
+
+    /// How long until the notification is dismissed, or `None` if it stays until clicked
+    pub fn remaining(&self) -> Option<Duration> {
+        Some(self.expires?.saturating_duration_since(Instant::now()))
+    }
+
+    /// The lines of the message, wrapped to `width` columns
+    fn lines(&self, width: usize) -> Vec<String> {
+        self.message
+            .lines()
+            .flat_map(|line| {
+                let chars = line.chars().collect::<Vec<_>>();
+                if chars.is_empty() {
+                    return vec![String::new()];
+                }
+                chars
+                    .chunks(width.max(1))
+                    .map(|chunk| chunk.iter().collect())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Show a message in a small bordered box in the corner of the terminal set by
+    /// [`Config::notify_anchor`](super::Config::notify_anchor), above every window including
+    /// floats. Notifications are stacked from the corner outwards in the order they were shown,
+    /// and ones that don't fit are shown once earlier ones are gone.
+    ///
+    /// The notification is dismissed after `timeout`, or when it's clicked. A timeout of
+    /// [`Duration::MAX`] keeps it until it's clicked. Notifications don't take focus, and mouse
+    /// events other than clicks pass through them to the windows below.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use sanguine::{App, NotifyLevel};
+    ///
+    /// let mut app = App::<(), ()>::test(24, 5);
+    /// app.notify("Saved", NotifyLevel::Info, Duration::from_secs(3));
+    /// app.render().unwrap();
+    /// assert_eq!(app.notifications()[0].message(), "Saved");
+    ///
+    /// let screen = app.backend::<sanguine::backend::TestBackend>().unwrap().screen_text();
+    /// assert_eq!(screen[0], "               ┌Info───┐");
+    /// assert_eq!(screen[1], "               │ Saved │");
+    ///
+    /// app.dismiss_all();
+    /// assert!(app.notifications().is_empty());
+    /// ```
+    pub fn notify(&mut self, message: impl Into<String>, level: NotifyLevel, timeout: Duration) {
+        self.notifications.push(Notification {
+            message: message.into(),
+            level,
+            expires: Instant::now().checked_add(timeout),
+        });
+        self.notifications_changed = true;
+    }
+
+    /// The notifications that haven't been dismissed, oldest first
+    pub fn notifications(&self) -> &[Notification] {
+        &self.notifications
+    }
+
+    /// Dismiss every notification
+    pub fn dismiss_all(&mut self) {
+        if !self.notifications.is_empty() {
+            self.notifications.clear();
+            self.notifications_changed = true;
+        }
+    }
+
+    /// Dismisses notifications whose timeout has passed
+    pub(super) fn expire_notifications(&mut self) {
+        let now = Instant::now();
+        let count = self.notifications.len();
+        self.notifications
+            .retain(|n| n.expires.is_none_or(|expires| expires > now));
+        if self.notifications.len() != count {
+            self.notifications_changed = true;
+        }
+    }
+
+    /// How long until the next notification expires, if any will
+    #[cfg(feature = "tokio")]
+    pub(super) fn notify_delay(&self) -> Option<Duration> {
+        let next = self.notifications.iter().filter_map(|n| n.expires).min()?;
+        Some(next.saturating_duration_since(Instant::now()))
+    }
+
+    /// The bounds of the notifications that fit on screen, with their index in the queue. They're
+    /// laid out for the current size of the terminal, so they follow it when it's resized.
+    fn toast_rects(&self) -> Vec<(usize, Rect)> {
+        let bounds = self.size;
+        let max_width = (bounds.width / 3.).max(24.).min(bounds.width);
+        let anchor = self.config.notify_anchor;
+        let upwards = matches!(anchor, Anchor::BottomLeft | Anchor::BottomRight);
+        let mut y = if upwards {
+            bounds.bottom()
+        } else {
+            bounds.top()
+        };
+        let mut rects = vec![];
+        for (i, notification) in self.notifications.iter().enumerate() {
+            // Leave room for the border and a space on either side of the message
+            let widest = notification.message.lines().map(|l| l.chars().count());
+            let widest = widest.chain([notification.level.title().len()]).max();
+            let width = (widest.unwrap_or(0) as f32 + 4.).min(max_width);
+            if width < 5. {
+                break;
+            }
+            let height = notification.lines(width as usize - 4).len() as f32 + 2.;
+            let top = if upwards { y - height } else { y };
+            if top < bounds.top() || top + height > bounds.bottom() {
+                break;
+            }
+            let (x, _) = anchor.position(&bounds, width, height);
+            rects.push((i, Rect::new(x, top, width, height)));
+            y = if upwards { top } else { top + height };
+        }
+        rects
+    }
+
+    /// Dismisses the notification under a mouse press, returning whether the event was for a
+    /// notification. The rest of the click is consumed too, so it doesn't reach the window below.
+    pub(super) fn click_notification(&mut self, x: u16, y: u16, buttons: MouseButtons) -> bool {
+        if self.notification_press {
+            if buttons == MouseButtons::NONE {
+                self.notification_press = false;
+            }
+            return true;
+        }
+        if buttons == MouseButtons::NONE || buttons.contains(MouseButtons::VERT_WHEEL) {
+            return false;
+        }
+        let rects = self.toast_rects();
+        let Some((i, _)) = rects
+            .into_iter()
+            .find(|(_, rect)| rect.contains(x as f32, y as f32))
+        else {
+            return false;
+        };
+        self.notifications.remove(i);
+        self.notifications_changed = true;
+        self.notification_press = true;
+        true
+    }
+
+    /// Draws the notifications that fit on screen over the rest of the frame
+    pub(super) fn draw_notifications(&mut self, screen: &mut Surface) {
+        self.notifications_changed = false;
+        for (i, rect) in self.toast_rects() {
+            let notification = &self.notifications[i];
+            let (width, height) = rect.size_usize();
+            let inner = width - 2;
+            let attrs = self.theme.style(notification.level.theme_key());
+            let title = notification.level.title().chars().take(inner);
+            let title = title.collect::<String>();
+            let mut toast = Surface::new(width, height);
+            toast.add_changes(vec![
+                Change::AllAttributes(attrs.clone()),
+                Change::Text(format!("┌{title}{}┐", "─".repeat(inner - title.len()))),
+            ]);
+            let lines = notification.lines(width - 4);
+            for (y, line) in lines.iter().enumerate() {
+                let fill = inner - 2 - line.chars().count();
+                toast.add_changes(vec![
+                    Change::CursorPosition {
+                        x: Position::Absolute(0),
+                        y: Position::Absolute(y + 1),
+                    },
+                    Change::AllAttributes(attrs.clone()),
+                    Change::Text("│".into()),
+                    Change::AllAttributes(Default::default()),
+                    Change::Text(format!(" {line}{} ", " ".repeat(fill))),
+                    Change::AllAttributes(attrs.clone()),
+                    Change::Text("│".into()),
+                ]);
+            }
+            toast.add_changes(vec![
+                Change::CursorPosition {
+                    x: Position::Absolute(0),
+                    y: Position::Absolute(height - 1),
+                },
+                Change::Text(format!("└{}┘", "─".repeat(inner))),
+            ]);
+            screen.draw_from_screen(&toast, rect.x as usize, rect.y as usize);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Restoring the terminal when the program panics
+
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Once,
+    },
+};
+
+use termwiz::escape::{
+    csi::{DecPrivateMode, DecPrivateModeCode, Mode, CSI},
+    Esc, EscCode,
+};
+
+use super::reset_terminal_modes;
+
+/// The number of apps that currently have the terminal set up
+static ACTIVE_APPS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts as an app that has the terminal set up while it exists, so the panic hook knows to
+/// restore the terminal
+pub(crate) struct ActiveApp(());
+
+impl ActiveApp {
+    pub fn new() -> Self {
+        ACTIVE_APPS.fetch_add(1, Ordering::SeqCst);
+        Self(())
+    }
+}
+
+impl Drop for ActiveApp {
+    fn drop(&mut self) {
+        ACTIVE_APPS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Install a panic hook that puts the terminal back the way it was before the app started, so
+/// the panic message is readable and the shell keeps working. The previous hook is called
+/// afterwards to print the message. Only the first call installs the hook.
+///
+/// This must be called before the terminal is put in raw mode, since the terminal settings it
+/// restores are saved here.
+pub(crate) fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let termios = saved_termios();
+        let prev = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            // Leave the terminal alone if no app is running
+            if ACTIVE_APPS.load(Ordering::SeqCst) == 0 {
+                prev(info);
+                return;
+            }
+            restore_terminal(termios.as_ref()).ok();
+            prev(info);
+            // The terminal is dropped while unwinding, which leaves the alternate screen again
+            // and restores the saved cursor position. Save the position below the panic message
+            // so that it isn't drawn over.
+            save_cursor().ok();
+        }));
+    });
+}
+
+/// The terminal settings restored after a panic, which only exist on Unix
+#[cfg(unix)]
+type Termios = libc::termios;
+#[cfg(not(unix))]
+type Termios = ();
+
+#[cfg(unix)]
+fn saved_termios() -> Option<Termios> {
+    let tty = std::fs::File::open("/dev/tty").ok()?;
+    let mut termios = std::mem::MaybeUninit::uninit();
+    if unsafe { libc::tcgetattr(tty.as_raw_fd(), termios.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    Some(unsafe { termios.assume_init() })
+}
+
+#[cfg(not(unix))]
+fn saved_termios() -> Option<Termios> {
+    None
+}
+
+/// Does what dropping the app does, without access to the app's terminal
+fn restore_terminal(termios: Option<&Termios>) -> std::io::Result<()> {
+    reset_terminal_modes()?;
+    let mut tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    let mode = Mode::ResetDecPrivateMode(DecPrivateMode::Code(
+        DecPrivateModeCode::ClearAndEnableAlternateScreen,
+    ));
+    write!(tty, "{}", CSI::Mode(mode))?;
+    tty.flush()?;
+    #[cfg(unix)]
+    if let Some(termios) = termios {
+        unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, termios) };
+    }
+    #[cfg(not(unix))]
+    let _ = termios;
+    Ok(())
+}
+
+fn save_cursor() -> std::io::Result<()> {
+    let mut tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    write!(tty, "{}", Esc::Code(EscCode::DecSaveCursorPosition))?;
+    tty.flush()
+}
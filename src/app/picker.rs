@@ -0,0 +1,181 @@
+//! Labels drawn over every window so one can be focused with a single key press, like tmux's
+//! `display-panes`
+
+use super::App;
+use crate::{
+    error::Result,
+    event::KeyCode,
+    layout::{NodeId, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    text::fill,
+    widget::{RenderCtx, Widget},
+};
+
+/// The keys windows are labeled with, in the order they're drawn
+const LABELS: &str = "abcdefghijklmnopqrstuvwxyz0123456789";
+
+/// A window picker opened with [`App::pick_window`]
+pub(super) struct WindowPicker {
+    /// Each label's key, the window it focuses, and the float it's drawn in
+    labels: Vec<(char, NodeId, NodeId)>,
+}
+
+/// Draws the key of a window in the picker, framed if there's room
+struct PickerLabel {
+    label: char,
+}
+
+impl<U, S> Widget<U, S> for PickerLabel {
+    fn render(&self, cx: &RenderCtx<U, S>, surface: &mut Surface) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        let attrs = cx.theme().style("picker");
+        fill(surface, Rect::from_size((width, height)), &attrs);
+        if width < 3 || height < 3 {
+            surface.add_changes(vec![
+                Change::AllAttributes(attrs),
+                Change::Text(self.label.to_string()),
+            ]);
+            return Ok(vec![]);
+        }
+        let row = |y: usize| Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(y),
+        };
+        surface.add_changes(vec![
+            Change::AllAttributes(attrs),
+            Change::Text("┌─┐".to_owned()),
+            row(1),
+            Change::Text(format!("│{}│", self.label)),
+            row(2),
+            Change::Text("└─┘".to_owned()),
+        ]);
+        Ok(vec![])
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Label every window on screen, including floats, with a key drawn in the middle of it, and
+    /// focus the window whose key is pressed next. Escape, or any other key or click, cancels
+    /// without changing focus, and the labels are removed either way.
+    ///
+    /// Windows are labeled `a` to `z` and then `0` to `9` in the order they're drawn, so the same
+    /// layout always gets the same labels. Labels are drawn in floats above every other window,
+    /// framed in windows at least three cells wide and tall, and as a single cell otherwise.
+    ///
+    /// Bound to a key with [`Action::PickWindow`](super::Action::PickWindow), or `pick_window` in
+    /// a config file.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+    ///     layout::Axis,
+    ///     widgets::TextBox,
+    ///     App,
+    /// };
+    ///
+    /// let mut right = None;
+    /// let mut app = App::<(), ()>::test(12, 3).with_layout(|layout, widgets| {
+    ///     let left = layout.add_leaf(widgets, TextBox::new());
+    ///     right = Some(layout.add_leaf(widgets, TextBox::new()));
+    ///     layout.set_direction(layout.root(), Axis::Horizontal);
+    ///     layout.add_child(layout.root(), left).ok()?;
+    ///     layout.add_child(layout.root(), right?).ok()?;
+    ///     Some(left)
+    /// });
+    /// app.render().unwrap();
+    ///
+    /// app.pick_window();
+    /// app.render().unwrap();
+    /// app.backend::<TestBackend>().unwrap().assert_screen(&[
+    ///     " ┌─┐   ┌─┐",
+    ///     " │a│   │b│",
+    ///     " └─┘   └─┘",
+    /// ]);
+    ///
+    /// let backend = app.backend_mut::<TestBackend>().unwrap();
+    /// backend.push_input(InputEvent::Key(KeyEvent {
+    ///     key: KeyCode::Char('b'),
+    ///     modifiers: Modifiers::NONE,
+    /// }));
+    /// app.handle_events().unwrap();
+    /// app.render().unwrap();
+    ///
+    /// assert_eq!(app.get_focus(), right);
+    /// assert!(!app.is_picking_window());
+    /// app.backend::<TestBackend>().unwrap().assert_screen(&[]);
+    /// ```
+    pub fn pick_window(&mut self) {
+        if self.picker.is_some() {
+            return;
+        }
+        let mut windows = vec![];
+        self.frame_windows(&mut windows);
+        let mut labels = vec![];
+        let mut keys = LABELS.chars();
+        for window in windows {
+            // Windows below a modal float can't be focused, so they aren't labeled
+            if self.below_modal(window) {
+                continue;
+            }
+            let Some(rect) = self.window_rect(window) else {
+                continue;
+            };
+            let Some(rect) = rect.intersection(&self.size) else {
+                continue;
+            };
+            let Some(label) = keys.next() else {
+                break;
+            };
+            let size = if rect.width >= 3. && rect.height >= 3. {
+                3.
+            } else {
+                1.
+            };
+            let x = (rect.x + (rect.width - size) / 2.).floor();
+            let y = (rect.y + (rect.height - size) / 2.).floor();
+            let widget = self.widgets.register(PickerLabel { label });
+            let float = self
+                .layout
+                .add_floating(widget, Rect::new(x, y, size, size));
+            labels.push((label, window, float));
+        }
+        self.picker = Some(WindowPicker { labels });
+    }
+
+    /// Whether the labels of [`App::pick_window`] are shown, waiting for a key press
+    pub fn is_picking_window(&self) -> bool {
+        self.picker.is_some()
+    }
+
+    /// Closes the window picker, focusing the window labeled with `key` if there is one
+    pub(super) fn finish_pick(&mut self, key: &KeyCode) -> Result<()> {
+        let Some(picker) = self.picker.take() else {
+            return Ok(());
+        };
+        let mut target = None;
+        for (label, window, float) in picker.labels {
+            if *key == KeyCode::Char(label) {
+                target = Some(window);
+            }
+            // The label floats are never focused, so closing them leaves focus alone
+            self.close_node(float, true).ok();
+        }
+        match target.filter(|window| self.layout.node(*window).is_some()) {
+            Some(window) => self.set_focus(window),
+            None => Ok(()),
+        }
+    }
+}
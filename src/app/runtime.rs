@@ -0,0 +1,129 @@
+//! Async event loop, enabled with the `tokio` feature
+#![cfg(feature = "tokio")]
+
+use std::{fs::File, os::fd::AsRawFd, time::Duration};
+
+use filedescriptor::{poll, pollfd, POLLIN};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::mpsc,
+};
+
+use super::App;
+use crate::{
+    error::{Error, Result},
+    event::{Event, UserEvent},
+};
+
+/// How many user events sent with [`App::event_sender_async`] can be queued before senders wait
+const ASYNC_CHANNEL_CAPACITY: usize = 256;
+
+pub(super) type AsyncChannel<U> = (mpsc::Sender<U>, mpsc::Receiver<U>);
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Get a sender for pushing user events into the app from async tasks. The events are
+    /// delivered by [`App::exec_async`] like events sent with
+    /// [`EventSender::send`](crate::event::EventSender::send).
+    pub fn event_sender_async(&mut self) -> mpsc::Sender<U> {
+        self.async_events
+            .get_or_insert_with(|| mpsc::channel(ASYNC_CHANNEL_CAPACITY))
+            .0
+            .clone()
+    }
+
+    /// Run the app until it exits, like [`App::exec`], but wait for terminal input and user
+    /// events asynchronously instead of polling for them.
+    ///
+    /// The app isn't `Send`, so the returned future must be run with `Runtime::block_on` (as
+    /// `#[tokio::main]` does) or on a `LocalSet` rather than with `tokio::spawn`. The runtime needs
+    /// its signal driver enabled to receive terminal resizes.
+    pub async fn exec_async(mut self) -> Result<()>
+    where
+        U: Send + 'static,
+    {
+        let (_async_tx, mut async_rx) = self
+            .async_events
+            .take()
+            .unwrap_or_else(|| mpsc::channel(ASYNC_CHANNEL_CAPACITY));
+
+        // Forward events sent with the synchronous event channel, such as ticks and the results of
+        // spawned tasks. The thread exits once the app has been dropped and the next event arrives.
+        let event_rx = std::mem::replace(&mut self.event_rx, std::sync::mpsc::channel().1);
+        let (envelope_tx, mut envelopes) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Ok(envelope) = event_rx.recv() {
+                if envelope_tx.send(envelope).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Wait for the terminal to become readable on a separate thread, and let termwiz read the
+        // input once it is. The thread waits to be resumed so that it doesn't report the same
+        // input twice.
+        let tty = File::open("/dev/tty").map_err(|_| Error::TerminalError)?;
+        let (ready_tx, mut ready) = mpsc::channel(1);
+        let (resume_tx, resume) = std::sync::mpsc::channel::<()>();
+        std::thread::spawn(move || loop {
+            let mut fds = [pollfd {
+                fd: tty.as_raw_fd(),
+                events: POLLIN,
+                revents: 0,
+            }];
+            if poll(&mut fds, None).is_err()
+                || ready_tx.blocking_send(()).is_err()
+                || resume.recv().is_err()
+            {
+                break;
+            }
+        });
+        let mut resized = signal(SignalKind::window_change()).map_err(|_| Error::TerminalError)?;
+
+        self.check_size()?;
+        self.render()?;
+        while !self.exiting() {
+            // Wake up to draw a frame that was postponed by `Config::max_fps`
+            let frame_delay = self
+                .frame_delay()
+                .filter(|_| self.frame_postponed)
+                .unwrap_or_default();
+            let blink_delay = self.blink_delay();
+            let notify_delay = self.notify_delay();
+            let tooltip_delay = self.tooltip_delay();
+            tokio::select! {
+                Some(envelope) = envelopes.recv() => self.process_envelope(envelope)?,
+                Some(event) = async_rx.recv() => {
+                    self.process_event(Event::User(UserEvent::User(event)))?;
+                }
+                Some(()) = ready.recv() => {
+                    self.handle_input_events(Duration::ZERO)?;
+                    resume_tx.send(()).ok();
+                }
+                Some(()) = resized.recv() => self.handle_input_events(Duration::ZERO)?,
+                // Process input left over when the last batch ran out of time
+                _ = tokio::task::yield_now(), if !self.input.is_empty() => {
+                    self.handle_input_events(Duration::ZERO)?;
+                }
+                _ = tokio::time::sleep(frame_delay), if self.frame_postponed => {}
+                // Wake up to blink the cursor
+                _ = tokio::time::sleep(blink_delay.unwrap_or_default()), if blink_delay.is_some() => {}
+                // Wake up to dismiss a notification whose timeout has passed
+                _ = tokio::time::sleep(notify_delay.unwrap_or_default()), if notify_delay.is_some() => {
+                    self.expire_notifications();
+                }
+                // Wake up to show a tooltip once the pointer has rested long enough
+                _ = tokio::time::sleep(tooltip_delay.unwrap_or_default()), if tooltip_delay.is_some() => {}
+            }
+            self.render()?;
+        }
+        Ok(())
+    }
+
+    /// How long until the cursor should blink, or `None` if it isn't blinking
+    fn blink_delay(&self) -> Option<Duration> {
+        let interval = self.config.cursor_blink.filter(|_| self.has_cursor)?;
+        let interval = interval.as_millis().max(1);
+        let elapsed = self.blink_start.elapsed().as_millis() % interval;
+        Some(Duration::from_millis((interval - elapsed) as u64))
+    }
+}
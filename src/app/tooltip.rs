@@ -0,0 +1,232 @@
+//! Single-line hints shown next to the mouse pointer, see [`UpdateCtx::set_tooltip`]
+
+#[cfg(feature = "tokio")]
+use std::time::Duration;
+use std::time::Instant;
+
+use super::App;
+use crate::{
+    event::MouseButtons,
+    layout::{Rect, WidgetId},
+    surface::{Change, Surface},
+    UpdateCtx,
+};
+
+/// How far the pointer can move, in cells, before the tooltip it stopped on is dismissed
+const SLACK: u16 = 2;
+
+/// What a widget asked for with [`UpdateCtx::set_tooltip`] or [`UpdateCtx::clear_tooltip`]
+/// during an update
+pub(crate) enum TooltipRequest {
+    Show(String),
+    Hide,
+}
+
+/// A tooltip requested by the widget under the pointer
+pub(super) struct Tooltip {
+    text: String,
+    /// The hovered widget that asked for it
+    widget: WidgetId,
+    /// Where the pointer was when it was asked for, which it's shown next to
+    pointer: (u16, u16),
+    /// When it was asked for, so that it's shown once the pointer has rested for
+    /// [`Config::tooltip_delay`](super::Config::tooltip_delay)
+    since: Instant,
+    /// Whether a key press or click dismissed it. It stays dismissed until the pointer moves away,
+    /// so the widget asking for it again doesn't bring it back.
+    dismissed: bool,
+}
+
+impl<U, S> UpdateCtx<'_, U, S> {
+    /// Show `text` in a tooltip next to the mouse pointer once it has rested over the widget for
+    /// [`Config::tooltip_delay`](crate::Config::tooltip_delay). Widgets call this when they're
+    /// sent mouse motion, for the part of the widget under the pointer.
+    ///
+    /// The tooltip is dismissed by a key press, a click, the pointer moving more than a couple
+    /// of cells, or the pointer leaving the widget. Tooltips never take focus, and mouse events
+    /// pass through them to the windows below.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{Event, InputEvent, Modifiers, MouseButtons, MouseEvent},
+    ///     error::Result,
+    ///     layout::{Rect, WidgetId},
+    ///     surface::Surface,
+    ///     App, RenderCtx, UpdateCtx, Widget,
+    /// };
+    ///
+    /// struct Help;
+    ///
+    /// impl Widget<(), ()> for Help {
+    ///     fn render(&self, _: &RenderCtx<(), ()>, _: &mut Surface) -> Result<Vec<(Rect, WidgetId)>> {
+    ///         Ok(vec![])
+    ///     }
+    ///
+    ///     fn update(&mut self, cx: &mut UpdateCtx<(), ()>, event: Event<()>) -> Result<()> {
+    ///         if let Event::Mouse(_) = event {
+    ///             cx.set_tooltip("Help");
+    ///         }
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn as_any(&self) -> &dyn std::any::Any {
+    ///         self
+    ///     }
+    ///
+    ///     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+    ///         self
+    ///     }
+    /// }
+    ///
+    /// let mut app = App::<(), ()>::test(12, 3).with_layout(|layout, widgets| {
+    ///     let leaf = layout.add_leaf(widgets, Help);
+    ///     layout.add_child(layout.root(), leaf).ok()?;
+    ///     Some(leaf)
+    /// });
+    /// app.config_mut().tooltip_delay = Duration::ZERO;
+    ///
+    /// let backend = app.backend_mut::<TestBackend>().unwrap();
+    /// backend.push_input(InputEvent::Mouse(MouseEvent {
+    ///     x: 3,
+    ///     y: 2,
+    ///     mouse_buttons: MouseButtons::NONE,
+    ///     modifiers: Modifiers::NONE,
+    /// }));
+    /// app.handle_events().unwrap();
+    /// app.render().unwrap();
+    ///
+    /// // Near the bottom edge, the tooltip is shown above the pointer
+    /// app.backend::<TestBackend>().unwrap().assert_screen(&["", "    Help"]);
+    /// ```
+    pub fn set_tooltip(&mut self, text: impl Into<String>) {
+        if let Some(slot) = self.tooltip.as_deref_mut() {
+            *slot = Some(TooltipRequest::Show(text.into()));
+        }
+    }
+
+    /// Hide the tooltip shown with [`UpdateCtx::set_tooltip`], for example when the pointer moved
+    /// to a part of the widget without one
+    pub fn clear_tooltip(&mut self) {
+        if let Some(slot) = self.tooltip.as_deref_mut() {
+            *slot = Some(TooltipRequest::Hide);
+        }
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Applies the tooltip request made by `widget` during the update that just ran, if any
+    pub(super) fn take_tooltip_request(&mut self, widget: WidgetId) {
+        let (Some(request), Some(pointer)) = (self.tooltip_request.take(), self.pointer) else {
+            return;
+        };
+        match request {
+            TooltipRequest::Show(text) => {
+                // Asking again for the same tooltip doesn't restart the delay, or bring it back
+                // if it was dismissed
+                let current = self.tooltip.as_ref();
+                if current.is_some_and(|t| t.widget == widget && t.text == text) {
+                    return;
+                }
+                self.tooltip = Some(Tooltip {
+                    text,
+                    widget,
+                    pointer,
+                    since: Instant::now(),
+                    dismissed: false,
+                });
+            }
+            TooltipRequest::Hide => {
+                if self.tooltip.as_ref().is_some_and(|t| t.widget == widget) {
+                    self.tooltip = None;
+                }
+            }
+        }
+    }
+
+    /// Dismisses the tooltip on clicks, and forgets it once the pointer moves away from where it
+    /// was asked for
+    pub(super) fn track_tooltip(&mut self, x: u16, y: u16, buttons: MouseButtons) {
+        self.pointer = Some((x, y));
+        let Some(tooltip) = &mut self.tooltip else {
+            return;
+        };
+        let (px, py) = tooltip.pointer;
+        if px.abs_diff(x) > SLACK || py.abs_diff(y) > SLACK {
+            self.tooltip = None;
+        } else if buttons != MouseButtons::NONE {
+            tooltip.dismissed = true;
+        }
+    }
+
+    /// Hides the tooltip until the pointer moves away, as a key press does
+    pub(super) fn dismiss_tooltip(&mut self) {
+        if let Some(tooltip) = &mut self.tooltip {
+            tooltip.dismissed = true;
+        }
+    }
+
+    /// Forgets the tooltip, when the pointer leaves the widget that asked for it
+    pub(super) fn forget_tooltip(&mut self) {
+        self.tooltip = None;
+    }
+
+    /// Whether the tooltip should be on screen now
+    fn tooltip_visible(&self) -> bool {
+        let delay = self.config.tooltip_delay;
+        let tooltip = self.tooltip.as_ref();
+        tooltip.is_some_and(|t| !t.dismissed && t.since.elapsed() >= delay)
+    }
+
+    /// Whether the tooltip appeared or disappeared since the last frame
+    pub(super) fn tooltip_changed(&self) -> bool {
+        self.tooltip_visible() != self.tooltip_shown
+    }
+
+    /// How long until the tooltip should appear, if it's waiting to
+    #[cfg(feature = "tokio")]
+    pub(super) fn tooltip_delay(&self) -> Option<Duration> {
+        let tooltip = self.tooltip.as_ref().filter(|t| !t.dismissed)?;
+        let shown_at = tooltip.since + self.config.tooltip_delay;
+        Some(shown_at.saturating_duration_since(Instant::now())).filter(|_| !self.tooltip_shown)
+    }
+
+    /// The bounds of the tooltip, below the pointer or above it near the bottom of the terminal,
+    /// and moved left to fit
+    fn tooltip_rect(&self, tooltip: &Tooltip) -> Option<Rect> {
+        let bounds = self.size;
+        // A space either side of the text
+        let width = (tooltip.text.chars().count() as f32 + 2.).min(bounds.width);
+        let (x, y) = (tooltip.pointer.0 as f32, tooltip.pointer.1 as f32);
+        let y = if y + 1. < bounds.bottom() {
+            y + 1.
+        } else {
+            y - 1.
+        };
+        if width < 3. || y < bounds.top() {
+            return None;
+        }
+        let x = x.min(bounds.right() - width).max(bounds.left());
+        Some(Rect::new(x, y, width, 1.))
+    }
+
+    /// Draws the tooltip over the rest of the frame, if it's due
+    pub(super) fn draw_tooltip(&mut self, screen: &mut Surface) {
+        self.tooltip_shown = self.tooltip_visible();
+        let Some(tooltip) = self.tooltip.as_ref().filter(|_| self.tooltip_shown) else {
+            return;
+        };
+        let Some(rect) = self.tooltip_rect(tooltip) else {
+            return;
+        };
+        let width = rect.width_usize();
+        let text = tooltip.text.chars().take(width - 2).collect::<String>();
+        let mut surface = Surface::new(width, 1);
+        surface.add_changes(vec![
+            Change::AllAttributes(self.theme.style("tooltip")),
+            Change::Text(format!(" {text:<pad$} ", pad = width - 2)),
+        ]);
+        screen.draw_from_screen(&surface, rect.x as usize, rect.y as usize);
+    }
+}
@@ -0,0 +1,220 @@
+//! Multiple layouts in one app, switched between like tmux windows
+
+use slotmap::{new_key_type, SecondaryMap};
+
+use super::App;
+use crate::{
+    error::{Error, Result},
+    layout::{Layout, NodeId, WidgetId},
+    WidgetStore,
+};
+
+new_key_type! {
+    pub struct WorkspaceId;
+}
+
+/// A named layout with its own focus. The state of the active workspace is kept in the [`App`],
+/// and only saved here while another workspace is active.
+pub(super) struct Workspace<U, S> {
+    pub(super) name: String,
+    pub(super) saved: Option<SavedWorkspace<U, S>>,
+}
+
+/// The parts of an [`App`] that belong to a workspace
+pub(super) struct SavedWorkspace<U, S> {
+    layout: Layout<U, S>,
+    focus: Option<NodeId>,
+    float_return: SecondaryMap<NodeId, NodeId>,
+    child_focus: SecondaryMap<NodeId, (WidgetId, usize)>,
+    zoomed: Option<NodeId>,
+}
+
+impl<U, S> Workspace<U, S> {
+    pub(super) fn new(name: impl Into<String>, layout: Option<Layout<U, S>>) -> Self {
+        Self {
+            name: name.into(),
+            saved: layout.map(|layout| SavedWorkspace {
+                layout,
+                focus: None,
+                float_return: SecondaryMap::new(),
+                child_focus: SecondaryMap::new(),
+                zoomed: None,
+            }),
+        }
+    }
+}
+
+impl<S: 'static, U: 'static> App<S, U> {
+    /// Add an empty workspace after the existing ones, without switching to it. Each workspace
+    /// has its own layout and focus, and widgets from the app's [`WidgetStore`] can be shown in
+    /// any number of workspaces.
+    ///
+    /// The app starts with a single workspace named `main`.
+    pub fn add_workspace(&mut self, name: impl Into<String>) -> WorkspaceId {
+        let id = self
+            .workspaces
+            .insert(Workspace::new(name, Some(Layout::new())));
+        self.workspace_order.push(id);
+        id
+    }
+
+    /// Calls a closure with the layout of a workspace and the widget store, like
+    /// [`App::with_layout`]. The node returned by the closure is focused when the workspace is
+    /// active.
+    ///
+    /// Returns [`Error::WorkspaceNotFound`] if the workspace doesn't exist.
+    pub fn update_workspace<F>(&mut self, id: WorkspaceId, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Layout<U, S>, &mut WidgetStore<U, S>) -> Option<NodeId>,
+    {
+        let workspace = self
+            .workspaces
+            .get_mut(id)
+            .ok_or(Error::WorkspaceNotFound(id))?;
+        match &mut workspace.saved {
+            Some(saved) => {
+                let focus = f(&mut saved.layout, &mut self.widgets);
+                if let Some(focus) = focus.filter(|f| !saved.layout.is_container(*f)) {
+                    saved.focus = Some(focus);
+                }
+                Ok(())
+            }
+            None => match f(&mut self.layout, &mut self.widgets) {
+                Some(focus) => self.set_focus(focus),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Make a workspace active, so that its layout is drawn and receives events. The whole
+    /// terminal is repainted on the next render.
+    ///
+    /// ```
+    /// use sanguine::{widgets::TextBox, App};
+    ///
+    /// let mut app = App::<(), ()>::test(80, 24);
+    /// let main = app.active_workspace();
+    /// let logs = app.add_workspace("logs");
+    /// app.update_workspace(logs, |layout, widgets| {
+    ///     let node = layout.add_leaf(widgets, TextBox::new());
+    ///     layout.add_child(layout.root(), node).ok()?;
+    ///     Some(node)
+    /// })
+    /// .unwrap();
+    ///
+    /// app.switch_workspace(logs).unwrap();
+    /// assert_eq!(app.active_workspace(), logs);
+    /// assert!(app.get_focus().is_some());
+    ///
+    /// let names = app
+    ///     .workspace_names()
+    ///     .into_iter()
+    ///     .enumerate()
+    ///     .map(|(i, (_, name))| format!("{}:{name}", i + 1))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(names.join(" "), "1:main 2:logs");
+    ///
+    /// app.next_workspace().unwrap();
+    /// assert_eq!(app.active_workspace(), main);
+    /// assert_eq!(app.get_focus(), None);
+    /// ```
+    pub fn switch_workspace(&mut self, id: WorkspaceId) -> Result<()> {
+        if id == self.active_workspace {
+            return Ok(());
+        }
+        let saved = self
+            .workspaces
+            .get_mut(id)
+            .ok_or(Error::WorkspaceNotFound(id))?
+            .saved
+            .take()
+            .ok_or(Error::WorkspaceNotFound(id))?;
+        // The widget under the mouse belongs to the old layout
+        self.set_hover(None)?;
+
+        let current = SavedWorkspace {
+            layout: std::mem::replace(&mut self.layout, saved.layout),
+            focus: std::mem::replace(&mut self.focus, saved.focus),
+            float_return: std::mem::replace(&mut self.float_return, saved.float_return),
+            child_focus: std::mem::replace(&mut self.child_focus, saved.child_focus),
+            zoomed: std::mem::replace(&mut self.zoomed, saved.zoomed),
+        };
+        if let Some(workspace) = self.workspaces.get_mut(self.active_workspace) {
+            workspace.saved = Some(current);
+        }
+        self.active_workspace = id;
+
+        // Node ids from different layouts can collide, so nothing from the old layout is kept
+        self.drag = None;
+        self.float_drag = None;
+        self.rendered.clear();
+        self.last_frame.clear();
+        self.last_focus = None;
+        self.request_redraw();
+        self.layout.mark_dirty(None);
+        self.repaint = true;
+        Ok(())
+    }
+
+    /// Switch to the workspace after the active one, wrapping around to the first
+    pub fn next_workspace(&mut self) -> Result<()> {
+        self.cycle_workspace(1)
+    }
+
+    /// Switch to the workspace before the active one, wrapping around to the last
+    pub fn prev_workspace(&mut self) -> Result<()> {
+        self.cycle_workspace(self.workspace_order.len() - 1)
+    }
+
+    fn cycle_workspace(&mut self, offset: usize) -> Result<()> {
+        let order = &self.workspace_order;
+        let Some(current) = order.iter().position(|id| *id == self.active_workspace) else {
+            return Ok(());
+        };
+        self.switch_workspace(order[(current + offset) % order.len()])
+    }
+
+    /// Remove a workspace and its layout. Its widgets stay registered, since they can be shown in
+    /// other workspaces. If the workspace is active, the previous workspace becomes active.
+    ///
+    /// Returns [`Error::LastWorkspace`] if it's the only workspace, or
+    /// [`Error::WorkspaceNotFound`] if it doesn't exist.
+    pub fn remove_workspace(&mut self, id: WorkspaceId) -> Result<()> {
+        if !self.workspaces.contains_key(id) {
+            return Err(Error::WorkspaceNotFound(id));
+        }
+        if self.workspace_order.len() == 1 {
+            return Err(Error::LastWorkspace(id));
+        }
+        if id == self.active_workspace {
+            self.prev_workspace()?;
+        }
+        self.workspaces.remove(id);
+        self.workspace_order.retain(|w| *w != id);
+        Ok(())
+    }
+
+    /// The active workspace, whose layout is drawn and receives events
+    pub fn active_workspace(&self) -> WorkspaceId {
+        self.active_workspace
+    }
+
+    /// The ids and names of every workspace, in order. Useful for showing the workspaces in a
+    /// [`StatusLine`](crate::widgets::StatusLine).
+    pub fn workspace_names(&self) -> Vec<(WorkspaceId, &str)> {
+        self.workspace_order
+            .iter()
+            .filter_map(|id| Some((*id, self.workspaces.get(*id)?.name.as_str())))
+            .collect()
+    }
+
+    /// Rename a workspace. Returns [`Error::WorkspaceNotFound`] if it doesn't exist.
+    pub fn rename_workspace(&mut self, id: WorkspaceId, name: impl Into<String>) -> Result<()> {
+        let workspace = self
+            .workspaces
+            .get_mut(id)
+            .ok_or(Error::WorkspaceNotFound(id))?;
+        workspace.name = name.into();
+        Ok(())
+    }
+}
@@ -0,0 +1,532 @@
+//! Terminal backends that the app draws to and reads input from
+
+use std::{
+    any::Any,
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+use termwiz::{
+    caps::{Capabilities, ProbeHints},
+    input::InputEvent,
+    surface::{Change, CursorVisibility, Position, SequenceNo, Surface},
+    terminal::{Terminal, UnixTerminal},
+};
+
+use crate::{
+    app::{install_panic_hook, request_mouse_mode, reset_terminal_modes, ActiveApp},
+    error::{Error, Result},
+    style::CellAttributes,
+    Config, MouseMode,
+};
+
+/// Something the app can be drawn to and receive input from, usually a real terminal.
+pub trait Backend {
+    /// The size of the terminal, as `(columns, rows)`
+    fn size(&mut self) -> Result<(usize, usize)>;
+
+    /// Apply changes to the terminal. They may be buffered until [`Backend::flush`] is called.
+    fn render(&mut self, changes: &[Change]) -> Result<()>;
+
+    fn flush(&mut self) -> Result<()>;
+
+    /// Wait up to `wait` for an input event, or indefinitely if `wait` is `None`
+    fn poll_input(&mut self, wait: Option<Duration>) -> Result<Option<InputEvent>>;
+
+    /// Restore the terminal's normal screen, when the app exits
+    fn exit_alternate_screen(&mut self) -> Result<()>;
+
+    /// Hand the terminal back to the shell until the process is continued. Does nothing by
+    /// default.
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Ask the terminal to report the mouse events of `mode` from now on. Does nothing by
+    /// default.
+    fn set_mouse_mode(&mut self, _mode: MouseMode) -> Result<()> {
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// The controlling terminal of the process, in raw mode and showing the alternate screen while
+/// the backend exists. This is the backend used by [`crate::App::new`] and the other constructors.
+pub struct TerminalBackend {
+    term: UnixTerminal,
+    /// Which part of the terminal the app is drawn on
+    mode: ScreenMode,
+    /// The mouse events the terminal was asked to report
+    mouse: MouseMode,
+    _active: ActiveApp,
+}
+
+/// Which part of the terminal a [`TerminalBackend`] draws on
+#[derive(Debug, Clone, Copy)]
+enum ScreenMode {
+    /// The whole alternate screen
+    Alternate,
+    /// The whole normal screen
+    Normal,
+    /// A fixed number of rows of the normal screen, starting at the line the cursor was on
+    Inline {
+        height: usize,
+        /// The row of the region the cursor is on
+        row: usize,
+    },
+}
+
+impl TerminalBackend {
+    /// Open the controlling terminal (`/dev/tty`) and set it up for drawing the app
+    pub fn new() -> Result<Self> {
+        Self::with_config(&Config::default())
+    }
+
+    /// Open the controlling terminal, requesting mouse events and entering the alternate screen
+    /// according to [`Config::mouse`] and [`Config::alternate_screen`]
+    pub fn with_config(config: &Config) -> Result<Self> {
+        // The hook saves the terminal settings, so it needs to be installed before raw mode is set
+        install_panic_hook();
+        // Termwiz can only request every mouse event, so the mode is requested here instead
+        let hints = ProbeHints::new_from_env().mouse_reporting(Some(false));
+        let mut term = Capabilities::new_with_hints(hints)
+            .and_then(UnixTerminal::new)
+            .map_err(|_| Error::TerminalError)?;
+        term.set_raw_mode().map_err(|_| Error::TerminalError)?;
+        let mode = if config.alternate_screen {
+            term.enter_alternate_screen()
+                .map_err(|_| Error::TerminalError)?;
+            ScreenMode::Alternate
+        } else {
+            ScreenMode::Normal
+        };
+        term.flush().map_err(|_| Error::TerminalError)?;
+        request_mouse_mode(config.mouse).map_err(|_| Error::TerminalError)?;
+        Ok(Self {
+            term,
+            mode,
+            mouse: config.mouse,
+            _active: ActiveApp::new(),
+        })
+    }
+
+    /// Open the controlling terminal and draw on `height` rows of the normal screen, starting at
+    /// the line the cursor is on, rather than the whole screen. The terminal is scrolled if there
+    /// isn't enough room below the cursor. [`Config::alternate_screen`] is ignored.
+    pub fn inline(height: usize, config: &Config) -> Result<Self> {
+        let normal = Config::new().alternate_screen(false).mouse(config.mouse);
+        let mut backend = Self::with_config(&normal)?;
+        let rows = backend.size()?.1;
+        backend.mode = ScreenMode::Inline {
+            height: height.clamp(1, rows.max(1)),
+            row: 0,
+        };
+        backend.reserve()?;
+        Ok(backend)
+    }
+
+    /// Make room for an inline region below the cursor, and move the cursor to its first row
+    fn reserve(&mut self) -> Result<()> {
+        let ScreenMode::Inline {
+            height,
+            ref mut row,
+        } = self.mode
+        else {
+            return Ok(());
+        };
+        *row = 0;
+        // Newlines scroll the terminal when the cursor is on the last line, unlike cursor movement
+        self.term
+            .render(&[
+                Change::Text(format!("\r{}", "\n".repeat(height - 1))),
+                Change::CursorPosition {
+                    x: Position::Absolute(0),
+                    y: Position::Relative(1 - height as isize),
+                },
+            ])
+            .and_then(|_| self.term.flush())
+            .map_err(|_| Error::TerminalError)
+    }
+
+    /// Clear the inline region, and move the cursor back to its first row
+    fn release(&mut self) -> Result<()> {
+        if !matches!(self.mode, ScreenMode::Inline { .. }) {
+            return Ok(());
+        }
+        self.render(&[Change::ClearScreen(Default::default())])?;
+        self.flush()
+    }
+
+    /// Positions changes within the inline region, which the terminal would otherwise position
+    /// relative to the whole screen
+    fn translate(&mut self, changes: &[Change]) -> Vec<Change> {
+        let ScreenMode::Inline {
+            height,
+            ref mut row,
+        } = self.mode
+        else {
+            return changes.to_vec();
+        };
+        let mut translated = Vec::with_capacity(changes.len());
+        for change in changes {
+            match change {
+                Change::CursorPosition { x, y } => {
+                    let target = match *y {
+                        Position::Absolute(y) => y as isize,
+                        Position::Relative(dy) => *row as isize + dy,
+                        Position::EndRelative(y) => height as isize - 1 - y as isize,
+                    }
+                    .clamp(0, height as isize - 1);
+                    translated.push(Change::CursorPosition {
+                        x: *x,
+                        y: Position::Relative(target - *row as isize),
+                    });
+                    *row = target as usize;
+                }
+                Change::ClearScreen(color) => {
+                    translated.push(Change::CursorPosition {
+                        x: Position::Absolute(0),
+                        y: Position::Relative(-(*row as isize)),
+                    });
+                    translated.push(Change::ClearToEndOfScreen(*color));
+                    *row = 0;
+                }
+                change => translated.push(change.clone()),
+            }
+        }
+        translated
+    }
+}
+
+impl Backend for TerminalBackend {
+    fn size(&mut self) -> Result<(usize, usize)> {
+        let size = self
+            .term
+            .get_screen_size()
+            .map_err(|_| Error::TerminalError)?;
+        match self.mode {
+            ScreenMode::Inline { height, .. } => Ok((size.cols, height)),
+            _ => Ok((size.cols, size.rows)),
+        }
+    }
+
+    fn render(&mut self, changes: &[Change]) -> Result<()> {
+        let changes = self.translate(changes);
+        self.term.render(&changes).map_err(|_| Error::TerminalError)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.term.flush().map_err(|_| Error::TerminalError)
+    }
+
+    fn poll_input(&mut self, wait: Option<Duration>) -> Result<Option<InputEvent>> {
+        let event = self
+            .term
+            .poll_input(wait)
+            .map_err(|_| Error::PollInputFailed)?;
+        match (event, self.mode) {
+            // Inline regions keep their height when the terminal is resized
+            (Some(InputEvent::Resized { cols, .. }), ScreenMode::Inline { height, .. }) => {
+                Ok(Some(InputEvent::Resized { cols, rows: height }))
+            }
+            (event, _) => Ok(event),
+        }
+    }
+
+    fn exit_alternate_screen(&mut self) -> Result<()> {
+        match self.mode {
+            ScreenMode::Alternate => self
+                .term
+                .exit_alternate_screen()
+                .map_err(|_| Error::TerminalError),
+            // Leave the app on the normal screen, and start the shell's next line below it
+            ScreenMode::Normal => {
+                let rows = self.size()?.1;
+                self.render(&[
+                    Change::CursorPosition {
+                        x: Position::Absolute(0),
+                        y: Position::Absolute(rows.saturating_sub(1)),
+                    },
+                    Change::Text("\r\n".to_owned()),
+                ])?;
+                self.flush()
+            }
+            ScreenMode::Inline { .. } => self.release(),
+        }
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        self.release()?;
+        self.term
+            .exit_alternate_screen()
+            .map_err(|_| Error::TerminalError)?;
+        reset_terminal_modes().map_err(|_| Error::TerminalError)?;
+        self.term
+            .set_cooked_mode()
+            .map_err(|_| Error::TerminalError)?;
+
+        // Stops the process until it receives SIGCONT
+        #[cfg(unix)]
+        unsafe {
+            libc::raise(libc::SIGTSTP)
+        };
+
+        self.term.set_raw_mode().map_err(|_| Error::TerminalError)?;
+        request_mouse_mode(self.mouse).map_err(|_| Error::TerminalError)?;
+        match self.mode {
+            ScreenMode::Alternate => self
+                .term
+                .enter_alternate_screen()
+                .map_err(|_| Error::TerminalError),
+            ScreenMode::Normal => Ok(()),
+            ScreenMode::Inline { .. } => self.reserve(),
+        }
+    }
+
+    fn set_mouse_mode(&mut self, mode: MouseMode) -> Result<()> {
+        // Anything buffered is written first, so the mode changes after it
+        self.flush()?;
+        request_mouse_mode(mode).map_err(|_| Error::TerminalError)?;
+        self.mouse = mode;
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Drop for TerminalBackend {
+    fn drop(&mut self) {
+        // Termwiz doesn't know about the mouse mode, so it won't turn it off
+        if self.mouse != MouseMode::Off {
+            request_mouse_mode(MouseMode::Off).ok();
+        }
+    }
+}
+
+/// An in-memory terminal for testing apps without a TTY. Input is taken from a queue filled with
+/// [`TestBackend::push_input`], and what the app draws can be inspected with
+/// [`TestBackend::screen_text`] and [`TestBackend::cell_attributes`].
+///
+/// Use [`crate::App::test`] to create an app with a test backend, and
+/// [`crate::App::backend_mut`] to reach it.
+///
+/// ```
+/// use sanguine::{
+///     backend::TestBackend,
+///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+///     widgets::{Border, TextBox},
+///     App,
+/// };
+///
+/// let mut app = App::<(), ()>::test(12, 3).with_layout(|layout, widgets| {
+///     let textbox = widgets.register(TextBox::new());
+///     let border = widgets.register(Border::new("Test", textbox));
+///     let leaf = layout.add_leaf_id(border);
+///     layout.add_child(layout.root(), leaf).ok()?;
+///     Some(leaf)
+/// });
+///
+/// let backend = app.backend_mut::<TestBackend>().unwrap();
+/// for c in "hi".chars() {
+///     backend.push_input(InputEvent::Key(KeyEvent {
+///         key: KeyCode::Char(c),
+///         modifiers: Modifiers::NONE,
+///     }));
+/// }
+/// app.handle_events().unwrap();
+/// app.render().unwrap();
+///
+/// app.backend::<TestBackend>().unwrap().assert_screen(&[
+///     "┌Test*─────┐",
+///     "│hi        │",
+///     "└──────────┘",
+/// ]);
+/// ```
+pub struct TestBackend {
+    surface: Surface,
+    input: VecDeque<InputEvent>,
+    mouse: MouseMode,
+}
+
+impl TestBackend {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            surface: Surface::new(width, height),
+            input: VecDeque::new(),
+            mouse: MouseMode::Off,
+        }
+    }
+
+    /// Queue an input event, to be received by the app the next time it handles events. Mouse
+    /// positions are one-based, as they are reported by terminals.
+    pub fn push_input(&mut self, event: InputEvent) {
+        self.input.push_back(event);
+    }
+
+    /// Resize the terminal, and queue the resize event a real terminal would send
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.surface.resize(width, height);
+        self.input.push_back(InputEvent::Resized {
+            cols: width,
+            rows: height,
+        });
+    }
+
+    /// The text on each row of the screen, without trailing whitespace
+    pub fn screen_text(&self) -> Vec<String> {
+        self.surface
+            .screen_lines()
+            .iter()
+            .map(|line| line.as_str().trim_end().to_owned())
+            .collect()
+    }
+
+    /// The mouse events the app asked the terminal to report, `Off` until an app is created with
+    /// the backend
+    pub fn mouse_mode(&self) -> MouseMode {
+        self.mouse
+    }
+
+    /// The position of the cursor, or `None` if it's hidden
+    pub fn cursor(&self) -> Option<(usize, usize)> {
+        let visible = self.surface.cursor_visibility() == CursorVisibility::Visible;
+        visible.then(|| self.surface.cursor_position())
+    }
+
+    /// The attributes of the cell at the given position, or `None` if it's off screen
+    pub fn cell_attributes(&self, x: usize, y: usize) -> Option<CellAttributes> {
+        let lines = self.surface.screen_lines();
+        let cell = lines
+            .get(y)?
+            .visible_cells()
+            .find(|cell| cell.cell_index() == x)?
+            .attrs()
+            .clone();
+        Some(cell)
+    }
+
+    /// Assert that the screen shows the given rows of text, ignoring trailing whitespace. Rows
+    /// below the expected ones must be blank.
+    #[track_caller]
+    pub fn assert_screen(&self, expected: &[&str]) {
+        let actual = self.screen_text();
+        let matches = actual.len() >= expected.len()
+            && actual
+                .iter()
+                .zip(expected)
+                .all(|(actual, expected)| actual == expected.trim_end())
+            && actual[expected.len()..].iter().all(String::is_empty);
+        if !matches {
+            panic!(
+                "screen doesn't match\n\nexpected:\n{}\n\nactual:\n{}\n",
+                expected.join("\n"),
+                actual.join("\n")
+            );
+        }
+    }
+}
+
+impl Backend for TestBackend {
+    fn size(&mut self) -> Result<(usize, usize)> {
+        Ok(self.surface.dimensions())
+    }
+
+    fn render(&mut self, changes: &[Change]) -> Result<()> {
+        self.surface.add_changes(changes.to_vec());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn poll_input(&mut self, _wait: Option<Duration>) -> Result<Option<InputEvent>> {
+        Ok(self.input.pop_front())
+    }
+
+    fn exit_alternate_screen(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_mouse_mode(&mut self, mode: MouseMode) -> Result<()> {
+        self.mouse = mode;
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A backend with a surface holding what should be on screen, which is written to the backend
+/// as a diff of what changed since the last flush
+pub(crate) struct Screen {
+    backend: Box<dyn Backend>,
+    surface: Surface,
+    seqno: SequenceNo,
+}
+
+impl Screen {
+    pub fn new(mut backend: Box<dyn Backend>) -> Result<Self> {
+        let (width, height) = backend.size()?;
+        Ok(Self {
+            backend,
+            surface: Surface::new(width, height),
+            seqno: 0,
+        })
+    }
+
+    pub fn backend(&self) -> &dyn Backend {
+        &*self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut dyn Backend {
+        &mut *self.backend
+    }
+
+    /// Write the changes made to the surface since the last flush to the backend
+    pub fn flush(&mut self) -> Result<()> {
+        let (seq, changes) = self.surface.get_changes(self.seqno);
+        // If rendering fails, the next flush needs to render everything
+        self.seqno = 0;
+        self.backend.render(&changes)?;
+        self.backend.flush()?;
+        self.seqno = seq;
+        self.surface.flush_changes_older_than(self.seqno);
+        Ok(())
+    }
+
+    /// Clear the screen and draw the whole surface to the backend
+    pub fn repaint(&mut self) -> Result<()> {
+        self.seqno = 0;
+        self.flush()
+    }
+}
+
+impl Deref for Screen {
+    type Target = Surface;
+
+    fn deref(&self) -> &Surface {
+        &self.surface
+    }
+}
+
+impl DerefMut for Screen {
+    fn deref_mut(&mut self) -> &mut Surface {
+        &mut self.surface
+    }
+}
@@ -1,16 +1,30 @@
 //! Bridge for rendering [`ratatui`] apps into Sanguine widgets
 //!
+//! The `tui` feature targets ratatui 0.30, and the `tui-legacy` feature supports ratatui 0.20 to
+//! 0.22, where cells exposed their symbol as a field. Both can be enabled at once.
+//!
 //! Will support other sources in the future - for now, they can be implemented using the
 //! [`crate::ansi`] utility.
-#![cfg(feature = "tui")]
+#![cfg(any(feature = "tui", feature = "tui-legacy"))]
 
-use ratatui::style::Modifier;
+use termwiz::surface::Surface;
+#[cfg(feature = "tui")]
 use termwiz::{
-    cell::{CellAttributes, Intensity, Underline},
-    color::{AnsiColor, ColorAttribute, RgbColor},
-    surface::{Change, Position, Surface},
+    cell::CellAttributes,
+    color::ColorAttribute,
+    surface::{Change, Position},
 };
 
+#[cfg(feature = "tui-legacy")]
+mod legacy;
+#[cfg(feature = "tui")]
+mod widget;
+
+#[cfg(feature = "tui")]
+pub use crossterm;
+#[cfg(feature = "tui")]
+pub use widget::RatatuiWidget;
+
 /// Bridge for implementing backends for other TUI libraries
 ///
 /// Required since [`Surface`] isn't implemented in this crate.
@@ -19,104 +33,160 @@ pub struct BridgeInner<'a>(&'a mut Surface);
 /// Provides the methods for creating a temporary backend for another TUI library to render onto a
 /// [`Surface`]
 pub trait Bridge {
+    /// Create a ratatui terminal drawing onto the surface
+    ///
+    /// ```
+    /// use ratatui::widgets::Paragraph;
+    /// use sanguine::{bridge::Bridge, surface::Surface};
+    ///
+    /// let mut surface = Surface::new(8, 2);
+    /// (&mut surface)
+    ///     .ratatui()
+    ///     .draw(|frame| frame.render_widget(Paragraph::new("hello"), frame.area()))
+    ///     .unwrap();
+    /// assert_eq!(surface.screen_chars_to_string(), "hello   \n        \n");
+    /// ```
+    #[cfg(feature = "tui")]
     fn ratatui<'a>(&'a mut self) -> ratatui::Terminal<BridgeInner<'a>>;
+
+    /// Same as [`Bridge::ratatui`], for ratatui 0.20 to 0.22
+    ///
+    /// ```
+    /// use ratatui_legacy::widgets::Paragraph;
+    /// use sanguine::{bridge::Bridge, surface::Surface};
+    ///
+    /// let mut surface = Surface::new(8, 2);
+    /// (&mut surface)
+    ///     .ratatui_legacy()
+    ///     .draw(|frame| frame.render_widget(Paragraph::new("hello"), frame.size()))
+    ///     .unwrap();
+    /// assert_eq!(surface.screen_chars_to_string(), "hello   \n        \n");
+    /// ```
+    #[cfg(feature = "tui-legacy")]
+    fn ratatui_legacy<'a>(&'a mut self) -> ratatui_legacy::Terminal<BridgeInner<'a>>;
 }
 
 impl Bridge for &mut Surface {
+    #[cfg(feature = "tui")]
     fn ratatui<'a>(&'a mut self) -> ratatui::Terminal<BridgeInner<'a>> {
         ratatui::Terminal::new(BridgeInner(self)).expect("this should not fail")
     }
-}
 
-/// Wrapper type for converting [`ratatui`] colors into other color types
-pub(crate) struct TuiColor(pub(crate) ratatui::style::Color);
-/// Wrapper type for converting [`ratatui`] styles into other style types
-pub(crate) struct TuiStyle(pub(crate) ratatui::style::Style);
-
-/// Convert [`ratatui`] style into [`termwiz`] style
-impl Into<CellAttributes> for TuiStyle {
-    fn into(self) -> CellAttributes {
-        let fg = self
-            .0
-            .fg
-            .map(|v| TuiColor(v).into())
-            .unwrap_or(ColorAttribute::Default);
-        let bg = self
-            .0
-            .bg
-            .map(|v| TuiColor(v).into())
-            .unwrap_or(ColorAttribute::Default);
-
-        let modifier = self.0.add_modifier;
-        let slow_blink = modifier.contains(Modifier::SLOW_BLINK);
-        let rapid_blink = modifier.contains(Modifier::RAPID_BLINK);
-
-        // add style
-        let mut attr = CellAttributes::default();
-        attr.set_foreground(fg);
-        attr.set_background(bg);
-        attr.set_intensity(if modifier.contains(Modifier::BOLD) {
-            Intensity::Bold
-        } else if modifier.contains(Modifier::DIM) {
-            Intensity::Half
-        } else {
-            Intensity::Normal
-        });
-        attr.set_italic(modifier.contains(Modifier::ITALIC));
-        attr.set_underline(if modifier.contains(Modifier::UNDERLINED) {
-            Underline::Single
-        } else {
-            Underline::None
-        });
-        attr.set_reverse(modifier.contains(Modifier::REVERSED));
-        attr.set_invisible(modifier.contains(Modifier::HIDDEN));
-        attr.set_strikethrough(modifier.contains(Modifier::CROSSED_OUT));
-        attr.set_blink(match (slow_blink, rapid_blink) {
-            (_, true) => termwiz::cell::Blink::Rapid,
-            (true, false) => termwiz::cell::Blink::Slow,
-            (false, false) => termwiz::cell::Blink::None,
-        });
-        attr
+    #[cfg(feature = "tui-legacy")]
+    fn ratatui_legacy<'a>(&'a mut self) -> ratatui_legacy::Terminal<BridgeInner<'a>> {
+        ratatui_legacy::Terminal::new(BridgeInner(self)).expect("this should not fail")
     }
 }
 
-/// Convert [`ratatui`] colors into [`termwiz`] colors
-impl Into<ColorAttribute> for TuiColor {
-    fn into(self) -> ColorAttribute {
-        use ratatui::style::Color::*;
-        match self {
-            TuiColor(Reset) => ColorAttribute::Default,
-            TuiColor(Black) => AnsiColor::Black.into(),
-            TuiColor(Red) => AnsiColor::Maroon.into(),
-            TuiColor(Green) => AnsiColor::Green.into(),
-            TuiColor(Yellow) => AnsiColor::Olive.into(),
-            TuiColor(Blue) => AnsiColor::Navy.into(),
-            TuiColor(Magenta) => AnsiColor::Purple.into(),
-            TuiColor(Cyan) => AnsiColor::Teal.into(),
-            TuiColor(Gray) => AnsiColor::Grey.into(),
-            TuiColor(DarkGray) => AnsiColor::Grey.into(),
-            TuiColor(LightRed) => AnsiColor::Red.into(),
-            TuiColor(LightGreen) => AnsiColor::Lime.into(),
-            TuiColor(LightYellow) => AnsiColor::Yellow.into(),
-            TuiColor(LightBlue) => AnsiColor::Blue.into(),
-            TuiColor(LightMagenta) => AnsiColor::Fuchsia.into(),
-            TuiColor(LightCyan) => AnsiColor::Aqua.into(),
-            TuiColor(White) => AnsiColor::White.into(),
-            TuiColor(Rgb(r, g, b)) => {
-                ColorAttribute::TrueColorWithDefaultFallback(RgbColor::new_8bpc(r, g, b).into())
+/// Defines the `TuiColor` and `TuiStyle` wrappers converting the styles of the given ratatui crate
+/// into [`termwiz`] attributes, since every version of ratatui has its own style types
+macro_rules! tui_conversions {
+    ($tui:ident) => {
+        /// Wrapper type for converting ratatui colors into other color types
+        pub(crate) struct TuiColor(pub(crate) $tui::style::Color);
+        /// Wrapper type for converting ratatui styles into other style types
+        pub(crate) struct TuiStyle(pub(crate) $tui::style::Style);
+
+        /// Convert ratatui style into [`termwiz`] style
+        impl From<TuiStyle> for termwiz::cell::CellAttributes {
+            fn from(style: TuiStyle) -> Self {
+                use termwiz::{
+                    cell::{Blink, CellAttributes, Intensity, Underline},
+                    color::ColorAttribute,
+                };
+                use $tui::style::Modifier;
+
+                let fg = style
+                    .0
+                    .fg
+                    .map(|v| TuiColor(v).into())
+                    .unwrap_or(ColorAttribute::Default);
+                let bg = style
+                    .0
+                    .bg
+                    .map(|v| TuiColor(v).into())
+                    .unwrap_or(ColorAttribute::Default);
+
+                let modifier = style.0.add_modifier;
+                let slow_blink = modifier.contains(Modifier::SLOW_BLINK);
+                let rapid_blink = modifier.contains(Modifier::RAPID_BLINK);
+
+                // add style
+                let mut attr = CellAttributes::default();
+                attr.set_foreground(fg);
+                attr.set_background(bg);
+                attr.set_intensity(if modifier.contains(Modifier::BOLD) {
+                    Intensity::Bold
+                } else if modifier.contains(Modifier::DIM) {
+                    Intensity::Half
+                } else {
+                    Intensity::Normal
+                });
+                attr.set_italic(modifier.contains(Modifier::ITALIC));
+                attr.set_underline(if modifier.contains(Modifier::UNDERLINED) {
+                    Underline::Single
+                } else {
+                    Underline::None
+                });
+                attr.set_reverse(modifier.contains(Modifier::REVERSED));
+                attr.set_invisible(modifier.contains(Modifier::HIDDEN));
+                attr.set_strikethrough(modifier.contains(Modifier::CROSSED_OUT));
+                attr.set_blink(match (slow_blink, rapid_blink) {
+                    (_, true) => Blink::Rapid,
+                    (true, false) => Blink::Slow,
+                    (false, false) => Blink::None,
+                });
+                attr
             }
-            TuiColor(Indexed(idx)) => ColorAttribute::PaletteIndex(idx),
         }
-    }
+
+        /// Convert ratatui colors into [`termwiz`] colors
+        impl From<TuiColor> for termwiz::color::ColorAttribute {
+            fn from(color: TuiColor) -> Self {
+                use termwiz::color::{AnsiColor, ColorAttribute, RgbColor};
+                use $tui::style::Color::*;
+                match color {
+                    TuiColor(Reset) => ColorAttribute::Default,
+                    TuiColor(Black) => AnsiColor::Black.into(),
+                    TuiColor(Red) => AnsiColor::Maroon.into(),
+                    TuiColor(Green) => AnsiColor::Green.into(),
+                    TuiColor(Yellow) => AnsiColor::Olive.into(),
+                    TuiColor(Blue) => AnsiColor::Navy.into(),
+                    TuiColor(Magenta) => AnsiColor::Purple.into(),
+                    TuiColor(Cyan) => AnsiColor::Teal.into(),
+                    TuiColor(Gray) => AnsiColor::Grey.into(),
+                    TuiColor(DarkGray) => AnsiColor::Grey.into(),
+                    TuiColor(LightRed) => AnsiColor::Red.into(),
+                    TuiColor(LightGreen) => AnsiColor::Lime.into(),
+                    TuiColor(LightYellow) => AnsiColor::Yellow.into(),
+                    TuiColor(LightBlue) => AnsiColor::Blue.into(),
+                    TuiColor(LightMagenta) => AnsiColor::Fuchsia.into(),
+                    TuiColor(LightCyan) => AnsiColor::Aqua.into(),
+                    TuiColor(White) => AnsiColor::White.into(),
+                    TuiColor(Rgb(r, g, b)) => ColorAttribute::TrueColorWithDefaultFallback(
+                        RgbColor::new_8bpc(r, g, b).into(),
+                    ),
+                    TuiColor(Indexed(idx)) => ColorAttribute::PaletteIndex(idx),
+                }
+            }
+        }
+    };
 }
+#[cfg(feature = "tui-legacy")]
+pub(crate) use tui_conversions;
+
+#[cfg(feature = "tui")]
+tui_conversions!(ratatui);
 
+#[cfg(feature = "tui")]
 impl<'surface> ratatui::backend::Backend for BridgeInner<'surface> {
+    type Error = std::io::Error;
+
     fn draw<'a, I>(&mut self, content: I) -> std::result::Result<(), std::io::Error>
     where
         I: Iterator<Item = (u16, u16, &'a ratatui::buffer::Cell)>,
     {
         for (x, y, cell) in content {
-            // set position
             self.0.add_changes(vec![
                 // Set cursor position
                 Change::CursorPosition {
@@ -126,7 +196,7 @@ impl<'surface> ratatui::backend::Backend for BridgeInner<'surface> {
                 // Set the style
                 Change::AllAttributes(TuiStyle(cell.style()).into()),
                 // Write the text
-                Change::Text(cell.symbol.clone()),
+                Change::Text(cell.symbol().to_owned()),
                 // Reset attributes
                 Change::AllAttributes(CellAttributes::default()),
             ]);
@@ -142,15 +212,21 @@ impl<'surface> ratatui::backend::Backend for BridgeInner<'surface> {
         Ok(())
     }
 
-    fn get_cursor(&mut self) -> std::result::Result<(u16, u16), std::io::Error> {
+    fn get_cursor_position(
+        &mut self,
+    ) -> std::result::Result<ratatui::layout::Position, std::io::Error> {
         let pos = self.0.cursor_position();
-        Ok((pos.0 as u16, pos.1 as u16))
+        Ok(ratatui::layout::Position::new(pos.0 as u16, pos.1 as u16))
     }
 
-    fn set_cursor(&mut self, x: u16, y: u16) -> std::result::Result<(), std::io::Error> {
+    fn set_cursor_position<P: Into<ratatui::layout::Position>>(
+        &mut self,
+        position: P,
+    ) -> std::result::Result<(), std::io::Error> {
+        let position = position.into();
         self.0.add_change(Change::CursorPosition {
-            x: Position::Absolute(x as usize),
-            y: Position::Absolute(y as usize),
+            x: Position::Absolute(position.x as usize),
+            y: Position::Absolute(position.y as usize),
         });
         Ok(())
     }
@@ -161,14 +237,63 @@ impl<'surface> ratatui::backend::Backend for BridgeInner<'surface> {
         Ok(())
     }
 
-    fn size(&self) -> std::result::Result<ratatui::layout::Rect, std::io::Error> {
+    fn clear_region(
+        &mut self,
+        clear_type: ratatui::backend::ClearType,
+    ) -> std::result::Result<(), std::io::Error> {
+        use ratatui::backend::ClearType;
+        let (x, y) = self.0.cursor_position();
+        let width = self.0.dimensions().0;
+        let blank = ColorAttribute::default();
+        match clear_type {
+            ClearType::All => return self.clear(),
+            ClearType::AfterCursor => {
+                self.0.add_change(Change::ClearToEndOfScreen(blank));
+            }
+            ClearType::UntilNewLine => {
+                self.0.add_change(Change::ClearToEndOfLine(blank));
+            }
+            ClearType::CurrentLine => {
+                self.0.add_changes(vec![
+                    Change::CursorPosition {
+                        x: Position::Absolute(0),
+                        y: Position::Relative(0),
+                    },
+                    Change::ClearToEndOfLine(blank),
+                ]);
+            }
+            // termwiz can only clear forwards, so the cells are overwritten with spaces instead
+            ClearType::BeforeCursor => {
+                self.0.add_changes(vec![
+                    Change::CursorPosition {
+                        x: Position::Absolute(0),
+                        y: Position::Absolute(0),
+                    },
+                    Change::AllAttributes(CellAttributes::default()),
+                    Change::Text(" ".repeat(y * width + x + 1)),
+                ]);
+            }
+        }
+        // Clearing doesn't move the cursor
+        self.0.add_change(Change::CursorPosition {
+            x: Position::Absolute(x),
+            y: Position::Absolute(y),
+        });
+        Ok(())
+    }
+
+    fn size(&self) -> std::result::Result<ratatui::layout::Size, std::io::Error> {
         let dims = self.0.dimensions();
-        Ok(ratatui::layout::Rect::new(
-            0,
-            0,
-            dims.0 as u16,
-            dims.1 as u16,
-        ))
+        Ok(ratatui::layout::Size::new(dims.0 as u16, dims.1 as u16))
+    }
+
+    /// The surface has no pixel size, so the pixel dimensions are always zero, as they are for
+    /// terminals that don't report them.
+    fn window_size(&mut self) -> std::result::Result<ratatui::backend::WindowSize, std::io::Error> {
+        Ok(ratatui::backend::WindowSize {
+            columns_rows: self.size()?,
+            pixels: ratatui::layout::Size::default(),
+        })
     }
 
     fn flush(&mut self) -> std::result::Result<(), std::io::Error> {
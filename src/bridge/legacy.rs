@@ -0,0 +1,76 @@
+//! Backend for ratatui 0.20 to 0.22, enabled with the `tui-legacy` feature
+
+use termwiz::{
+    cell::CellAttributes,
+    color::ColorAttribute,
+    surface::{Change, Position},
+};
+
+use super::{tui_conversions, BridgeInner};
+
+tui_conversions!(ratatui_legacy);
+
+impl<'surface> ratatui_legacy::backend::Backend for BridgeInner<'surface> {
+    fn draw<'a, I>(&mut self, content: I) -> std::result::Result<(), std::io::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a ratatui_legacy::buffer::Cell)>,
+    {
+        for (x, y, cell) in content {
+            self.0.add_changes(vec![
+                // Set cursor position
+                Change::CursorPosition {
+                    x: Position::Absolute(x as usize),
+                    y: Position::Absolute(y as usize),
+                },
+                // Set the style
+                Change::AllAttributes(TuiStyle(cell.style()).into()),
+                // Write the text
+                Change::Text(cell.symbol.clone()),
+                // Reset attributes
+                Change::AllAttributes(CellAttributes::default()),
+            ]);
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> std::result::Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> std::result::Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn get_cursor(&mut self) -> std::result::Result<(u16, u16), std::io::Error> {
+        let pos = self.0.cursor_position();
+        Ok((pos.0 as u16, pos.1 as u16))
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> std::result::Result<(), std::io::Error> {
+        self.0.add_change(Change::CursorPosition {
+            x: Position::Absolute(x as usize),
+            y: Position::Absolute(y as usize),
+        });
+        Ok(())
+    }
+
+    fn clear(&mut self) -> std::result::Result<(), std::io::Error> {
+        self.0
+            .add_change(Change::ClearScreen(ColorAttribute::default()));
+        Ok(())
+    }
+
+    fn size(&self) -> std::result::Result<ratatui_legacy::layout::Rect, std::io::Error> {
+        let dims = self.0.dimensions();
+        Ok(ratatui_legacy::layout::Rect::new(
+            0,
+            0,
+            dims.0 as u16,
+            dims.1 as u16,
+        ))
+    }
+
+    fn flush(&mut self) -> std::result::Result<(), std::io::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+//! Widget adapter for embedding a whole ratatui application
+
+use std::cell::RefCell;
+
+use crossterm::event as ct;
+
+use super::Bridge;
+use crate::{
+    error::{Error, Result},
+    event::{Event, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+    layout::{Rect, WidgetId},
+    surface::Surface,
+    widget::{RenderCtx, UpdateCtx, Widget},
+};
+
+/// Callback receiving the input events sent to a [`RatatuiWidget`]
+type EventHandler = Box<dyn FnMut(ct::Event)>;
+
+/// A widget that draws a ratatui application with a render callback, like the closure passed to
+/// [`ratatui::Terminal::draw`].
+///
+/// Input sent to the widget is converted into [`crossterm`] events, with mouse positions relative
+/// to the widget, and passed to the handler set with [`RatatuiWidget::with_event_handler`]. Events
+/// can be forwarded to the application through a channel that the render callback drains.
+///
+/// ```
+/// use std::sync::mpsc::channel;
+/// use sanguine::bridge::{crossterm::event::Event, RatatuiWidget};
+/// use ratatui::widgets::Paragraph;
+///
+/// let (tx, rx) = channel();
+/// let mut text = String::new();
+/// let widget = RatatuiWidget::new(move |frame, _cx: &sanguine::RenderCtx<(), ()>| {
+///     for event in rx.try_iter() {
+///         if let Event::Paste(pasted) = event {
+///             text.push_str(&pasted);
+///         }
+///     }
+///     frame.render_widget(Paragraph::new(text.as_str()), frame.area());
+/// })
+/// .with_event_handler(move |event| {
+///     tx.send(event).ok();
+/// });
+/// ```
+pub struct RatatuiWidget<F> {
+    draw: RefCell<F>,
+    on_event: Option<EventHandler>,
+    /// The mouse buttons held during the last mouse event, to tell presses, drags and releases
+    /// apart
+    buttons: MouseButtons,
+}
+
+impl<F> RatatuiWidget<F> {
+    pub fn new<U, S>(draw: F) -> Self
+    where
+        F: FnMut(&mut ratatui::Frame, &RenderCtx<U, S>),
+    {
+        Self {
+            draw: RefCell::new(draw),
+            on_event: None,
+            buttons: MouseButtons::NONE,
+        }
+    }
+
+    /// Set the callback receiving input events converted into [`crossterm`] events
+    pub fn with_event_handler(mut self, handler: impl FnMut(ct::Event) + 'static) -> Self {
+        self.on_event = Some(Box::new(handler));
+        self
+    }
+
+    /// Converts an event into the matching [`crossterm`] event, if there is one
+    fn convert<U>(&mut self, event: &Event<U>) -> Option<ct::Event> {
+        match event {
+            Event::Key(key) => convert_key(key).map(ct::Event::Key),
+            Event::Mouse(mouse) => Some(ct::Event::Mouse(self.convert_mouse(mouse))),
+            Event::Scroll { delta, x, y } => Some(ct::Event::Mouse(ct::MouseEvent {
+                kind: if *delta < 0 {
+                    ct::MouseEventKind::ScrollUp
+                } else {
+                    ct::MouseEventKind::ScrollDown
+                },
+                column: *x,
+                row: *y,
+                modifiers: ct::KeyModifiers::NONE,
+            })),
+            Event::Paste(text) => Some(ct::Event::Paste(text.clone())),
+            _ => None,
+        }
+    }
+
+    fn convert_mouse(&mut self, mouse: &MouseEvent) -> ct::MouseEvent {
+        let held =
+            mouse.mouse_buttons & (MouseButtons::LEFT | MouseButtons::MIDDLE | MouseButtons::RIGHT);
+        let previous = std::mem::replace(&mut self.buttons, held);
+        let button = |buttons: MouseButtons| {
+            if buttons.contains(MouseButtons::LEFT) {
+                ct::MouseButton::Left
+            } else if buttons.contains(MouseButtons::MIDDLE) {
+                ct::MouseButton::Middle
+            } else {
+                ct::MouseButton::Right
+            }
+        };
+        let kind = if held.is_empty() && previous.is_empty() {
+            ct::MouseEventKind::Moved
+        } else if held.is_empty() {
+            ct::MouseEventKind::Up(button(previous))
+        } else if held == previous {
+            ct::MouseEventKind::Drag(button(held))
+        } else {
+            ct::MouseEventKind::Down(button(held))
+        };
+        ct::MouseEvent {
+            kind,
+            column: mouse.x,
+            row: mouse.y,
+            modifiers: convert_modifiers(mouse.modifiers),
+        }
+    }
+}
+
+fn convert_modifiers(modifiers: Modifiers) -> ct::KeyModifiers {
+    let mut converted = ct::KeyModifiers::NONE;
+    for (from, to) in [
+        (Modifiers::SHIFT, ct::KeyModifiers::SHIFT),
+        (Modifiers::CTRL, ct::KeyModifiers::CONTROL),
+        (Modifiers::ALT, ct::KeyModifiers::ALT),
+        (Modifiers::SUPER, ct::KeyModifiers::SUPER),
+    ] {
+        if modifiers.contains(from) {
+            converted |= to;
+        }
+    }
+    converted
+}
+
+fn convert_key(key: &KeyEvent) -> Option<ct::KeyEvent> {
+    let modifiers = convert_modifiers(key.modifiers);
+    let code = match key.key {
+        KeyCode::Tab if modifiers.contains(ct::KeyModifiers::SHIFT) => ct::KeyCode::BackTab,
+        KeyCode::Char(c) => ct::KeyCode::Char(c),
+        KeyCode::Backspace => ct::KeyCode::Backspace,
+        KeyCode::Enter => ct::KeyCode::Enter,
+        KeyCode::Tab => ct::KeyCode::Tab,
+        KeyCode::Escape => ct::KeyCode::Esc,
+        KeyCode::Delete => ct::KeyCode::Delete,
+        KeyCode::Insert => ct::KeyCode::Insert,
+        KeyCode::LeftArrow => ct::KeyCode::Left,
+        KeyCode::RightArrow => ct::KeyCode::Right,
+        KeyCode::UpArrow => ct::KeyCode::Up,
+        KeyCode::DownArrow => ct::KeyCode::Down,
+        KeyCode::Home => ct::KeyCode::Home,
+        KeyCode::End => ct::KeyCode::End,
+        KeyCode::PageUp => ct::KeyCode::PageUp,
+        KeyCode::PageDown => ct::KeyCode::PageDown,
+        KeyCode::Function(n) => ct::KeyCode::F(n),
+        _ => return None,
+    };
+    Some(ct::KeyEvent::new(code, modifiers))
+}
+
+impl<U, S, F> Widget<U, S> for RatatuiWidget<F>
+where
+    F: FnMut(&mut ratatui::Frame, &RenderCtx<U, S>) + 'static,
+{
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        mut surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let mut draw = self.draw.borrow_mut();
+        surface
+            .ratatui()
+            .draw(|frame| draw(frame, cx))
+            .map_err(Error::external)?;
+        Ok(vec![])
+    }
+
+    fn update<'u>(&mut self, _cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        if let Some(event) = self.convert(&event) {
+            if let Some(on_event) = &mut self.on_event {
+                on_event(event);
+            }
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,511 @@
+#![cfg(feature = "config-file")]
+//! Key bindings, theme styles and [`Config`] flags loaded from a user's config file.
+//!
+//! Config files are written in TOML, with `[keys]`, `[theme]` and `[config]` tables, all of
+//! which are optional.
+//!
+//! ```toml
+//! [keys]
+//! "ctrl+q" = "quit"
+//! "shift+tab" = "cycle_focus"
+//! # Sequences are separated by spaces, like a tmux prefix
+//! "ctrl+w l" = "focus_right"
+//!
+//! [theme]
+//! base = "light"
+//! "border.focused" = "bold #ff8800"
+//! "menu.selected" = "black on aqua"
+//!
+//! [config]
+//! focus_follows_hover = true
+//! max_fps = 60
+//! ```
+//!
+//! The `[keys]` section binds key chords to actions. Chords are modifiers and a key joined with
+//! `+`, such as `ctrl+shift+left`, and a binding can be a sequence of chords separated by
+//! spaces. The built-in actions are `quit`, `cycle_focus`, `close_focused`, `focus_up`,
+//! `focus_down`, `focus_left`, `focus_right`, `next_workspace`, `prev_workspace` and
+//! `pick_window`, and `none` removes an existing binding. Other action names can be resolved by
+//! the app with [`AppConfigFile::apply_keymap_with`].
+//!
+//! The `[theme]` section sets [`Theme`] styles. A style is made of colors and attributes
+//! separated by spaces: the first color is the foreground, a color after `on` is the
+//! background, and `bold`, `dim`, `italic`, `underline`, `reverse` and `strikethrough` add
+//! attributes. Colors are ANSI color names such as `red` or `aqua`, `default`, or hex colors
+//! such as `#ff8800`. The special `base` key chooses the preset the styles are added to, either
+//! `dark` (the default) or `light`. Style names containing dots can also be written as dotted
+//! keys or nested tables, so `[theme.menu]` followed by `selected = "black on aqua"` sets the
+//! `menu.selected` style.
+//!
+//! The `[config]` section sets the [`Config`] field of the same name. Durations are given in
+//! milliseconds, and `0` turns off `max_fps` and `cursor_blink`. `mouse` is one of `off`,
+//! `clicks`, `drag` or `full`.
+//!
+//! Errors are returned as [`Error::External`], with the file and the section and key they were
+//! found in, or the line and column of TOML syntax errors.
+//!
+//! ```
+//! use sanguine::{config_file::AppConfigFile, MouseMode};
+//!
+//! let file = AppConfigFile::parse(
+//!     r#"
+//! [keys]
+//! "ctrl+w l" = "focus_right"
+//!
+//! [config]
+//! mouse = "off"
+//! "#,
+//! )
+//! .unwrap();
+//! assert_eq!(file.bindings()[0].keys.len(), 2);
+//! assert_eq!(file.apply_config(Default::default()).mouse, MouseMode::Off);
+//!
+//! let file = AppConfigFile::parse("[keys]\n\"ctrl+w\" = \"explode\"").unwrap();
+//! let err = file.keymap::<(), ()>().err().unwrap();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "config: [keys] \"ctrl+w\": unknown action \"explode\""
+//! );
+//! ```
+
+use std::{fmt::Display, path::Path, time::Duration};
+
+use serde::Deserialize;
+use toml::{Table, Value};
+
+use crate::{
+    error::{Error, Result},
+    event::{KeyCode, Modifiers},
+    layout::Direction,
+    style::{AnsiColor, CellAttributes, ColorAttribute, Intensity, RgbColor, Underline},
+    theme::Theme,
+    Action, Config, Keymap, MouseMode,
+};
+
+/// The sections of a config file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Sections {
+    #[serde(default)]
+    keys: Table,
+    #[serde(default)]
+    theme: Table,
+    #[serde(default)]
+    config: Table,
+}
+
+/// Describes the type of a value for error messages
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "a string",
+        Value::Integer(_) => "an integer",
+        Value::Float(_) => "a float",
+        Value::Boolean(_) => "a boolean",
+        Value::Datetime(_) => "a date",
+        Value::Array(_) => "an array",
+        Value::Table(_) => "a table",
+    }
+}
+
+/// An error in the entry for `key` in the `[section]` table of the file named `source`
+fn entry_error(source: &str, section: &str, key: &str, msg: impl Display) -> Error {
+    Error::external(format!("{source}: [{section}] \"{key}\": {msg}"))
+}
+
+/// A sequence of key chords bound to an action name in the `[keys]` section
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBinding {
+    /// The chords that make up the binding, in the order they're pressed
+    pub keys: Vec<(Modifiers, KeyCode)>,
+    /// The chords as written in the file
+    pub chord: String,
+    /// The name of the action
+    pub action: String,
+}
+
+/// Changes a flag of a [`Config`]
+type Setting = Box<dyn Fn(&mut Config)>;
+
+/// The contents of a config file. See the [module docs](self) for the format.
+pub struct AppConfigFile {
+    /// The file name used in error messages
+    source: String,
+    bindings: Vec<KeyBinding>,
+    theme: Theme,
+    settings: Vec<Setting>,
+}
+
+impl AppConfigFile {
+    /// Read and parse the config file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| Error::external(format!("{}: {err}", path.display())))?;
+        Self::parse_named(&path.display().to_string(), &text)
+    }
+
+    /// Parse the contents of a config file. Errors refer to the file as `config`.
+    pub fn parse(text: &str) -> Result<Self> {
+        Self::parse_named("config", text)
+    }
+
+    fn parse_named(source: &str, text: &str) -> Result<Self> {
+        let sections: Sections =
+            toml::from_str(text).map_err(|err| Error::external(format!("{source}: {err}")))?;
+        let mut file = Self {
+            source: source.to_owned(),
+            bindings: vec![],
+            theme: Theme::dark(),
+            settings: vec![],
+        };
+
+        for (chord, action) in sections.keys {
+            let err = |msg: String| entry_error(source, "keys", &chord, msg);
+            let Value::String(action) = action else {
+                return Err(err(format!(
+                    "expected an action name, found {}",
+                    kind(&action)
+                )));
+            };
+            let keys = parse_sequence(&chord).map_err(err)?;
+            file.bindings.push(KeyBinding {
+                keys,
+                chord,
+                action,
+            });
+        }
+
+        // The preset is chosen first, so styles can be given before or after it
+        let mut theme = sections.theme;
+        if let Some(base) = theme.remove("base") {
+            file.theme = match base.as_str() {
+                Some("dark") => Theme::dark(),
+                Some("light") => Theme::light(),
+                _ => {
+                    return Err(entry_error(
+                        source,
+                        "theme",
+                        "base",
+                        "expected \"dark\" or \"light\"",
+                    ))
+                }
+            };
+        }
+        let mut styles = vec![];
+        style_entries(None, theme, &mut styles);
+        for (key, value) in styles {
+            let Value::String(style) = value else {
+                return Err(entry_error(
+                    source,
+                    "theme",
+                    &key,
+                    format!("expected a style, found {}", kind(&value)),
+                ));
+            };
+            let attrs =
+                parse_style(&style).map_err(|msg| entry_error(source, "theme", &key, msg))?;
+            file.theme.set(key, attrs);
+        }
+
+        for (key, value) in sections.config {
+            let setting = config_setting(&key, value)
+                .map_err(|msg| entry_error(source, "config", &key, msg))?;
+            file.settings.push(setting);
+        }
+        Ok(file)
+    }
+
+    /// The bindings from the `[keys]` section, in the order they were written
+    pub fn bindings(&self) -> &[KeyBinding] {
+        &self.bindings
+    }
+
+    /// The theme from the `[theme]` section, or the default theme if there isn't one
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Apply the flags from the `[config]` section to `config`
+    pub fn apply_config(&self, mut config: Config) -> Config {
+        for setting in &self.settings {
+            setting(&mut config);
+        }
+        config
+    }
+
+    /// Create a keymap with the bindings from the `[keys]` section. Returns an error if a
+    /// binding uses an unknown action.
+    pub fn keymap<S, U>(&self) -> Result<Keymap<S, U>> {
+        let mut keymap = Keymap::new();
+        self.apply_keymap(&mut keymap)?;
+        Ok(keymap)
+    }
+
+    /// Add the bindings from the `[keys]` section to an existing keymap, replacing bindings for
+    /// the same keys. Returns an error if a binding uses an unknown action.
+    pub fn apply_keymap<S, U>(&self, keymap: &mut Keymap<S, U>) -> Result<()> {
+        self.apply_keymap_with(keymap, |_| None)
+    }
+
+    /// Add the bindings from the `[keys]` section to an existing keymap, using `resolve` to
+    /// create the actions for names that aren't built in. Returns an error if a binding uses an
+    /// action that's neither built in nor resolved.
+    pub fn apply_keymap_with<S, U>(
+        &self,
+        keymap: &mut Keymap<S, U>,
+        mut resolve: impl FnMut(&str) -> Option<Action<S, U>>,
+    ) -> Result<()> {
+        for binding in &self.bindings {
+            let keys = binding.keys.iter().cloned();
+            if binding.action == "none" {
+                keymap.unbind_sequence(keys);
+                continue;
+            }
+            let action = builtin_action(&binding.action)
+                .or_else(|| resolve(&binding.action))
+                .ok_or_else(|| {
+                    entry_error(
+                        &self.source,
+                        "keys",
+                        &binding.chord,
+                        format!("unknown action \"{}\"", binding.action),
+                    )
+                })?;
+            keymap.bind_sequence(keys, action);
+        }
+        Ok(())
+    }
+}
+
+fn builtin_action<S, U>(name: &str) -> Option<Action<S, U>> {
+    Some(match name {
+        "quit" => Action::Quit,
+        "cycle_focus" => Action::CycleFocus,
+        "close_focused" => Action::CloseFocused,
+        "next_workspace" => Action::NextWorkspace,
+        "prev_workspace" => Action::PrevWorkspace,
+        "pick_window" => Action::PickWindow,
+        "focus_up" => Action::FocusDirection(Direction::Up),
+        "focus_down" => Action::FocusDirection(Direction::Down),
+        "focus_left" => Action::FocusDirection(Direction::Left),
+        "focus_right" => Action::FocusDirection(Direction::Right),
+        _ => return None,
+    })
+}
+
+/// Parse a sequence of key chords separated by spaces, such as `ctrl+w l` or `ctrl+shift+left`.
+///
+/// Modifiers are `ctrl`, `alt`, `shift` and `super`, and keys are single characters or names
+/// such as `tab`, `enter`, `esc`, `space`, `left`, `pageup` or `f1`. Use `plus` for the `+` key.
+///
+/// ```
+/// use sanguine::{
+///     config_file::parse_keys,
+///     event::{KeyCode, Modifiers},
+/// };
+///
+/// assert_eq!(
+///     parse_keys("ctrl+w shift+left").unwrap(),
+///     vec![
+///         (Modifiers::CTRL, KeyCode::Char('w')),
+///         (Modifiers::SHIFT, KeyCode::LeftArrow),
+///     ]
+/// );
+/// assert!(parse_keys("ctrl+").is_err());
+/// ```
+pub fn parse_keys(keys: &str) -> Result<Vec<(Modifiers, KeyCode)>> {
+    parse_sequence(keys).map_err(Error::external)
+}
+
+fn parse_sequence(keys: &str) -> std::result::Result<Vec<(Modifiers, KeyCode)>, String> {
+    let chords = keys
+        .split_whitespace()
+        .map(parse_chord)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if chords.is_empty() {
+        return Err("no keys given".to_owned());
+    }
+    Ok(chords)
+}
+
+fn parse_chord(chord: &str) -> std::result::Result<(Modifiers, KeyCode), String> {
+    let mut parts = chord.split('+').collect::<Vec<_>>();
+    let key = parts.pop().unwrap_or_default();
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers |= match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Modifiers::CTRL,
+            "alt" | "meta" => Modifiers::ALT,
+            "shift" => Modifiers::SHIFT,
+            "super" | "cmd" => Modifiers::SUPER,
+            "" => return Err(format!("empty modifier in \"{chord}\"")),
+            other => return Err(format!("unknown modifier \"{other}\" in \"{chord}\"")),
+        };
+    }
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok((modifiers, KeyCode::Char(c)));
+    }
+    let lower = key.to_ascii_lowercase();
+    let code = match lower.as_str() {
+        "" => return Err(format!("missing key after \"{chord}\"")),
+        "tab" => KeyCode::Tab,
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Escape,
+        "space" => KeyCode::Char(' '),
+        "plus" => KeyCode::Char('+'),
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "left" => KeyCode::LeftArrow,
+        "right" => KeyCode::RightArrow,
+        "up" => KeyCode::UpArrow,
+        "down" => KeyCode::DownArrow,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n @ 1..=24) => KeyCode::Function(n),
+            _ => return Err(format!("unknown key \"{key}\" in \"{chord}\"")),
+        },
+    };
+    Ok((modifiers, code))
+}
+
+/// Collect the styles in a table, joining the names of nested tables with dots
+fn style_entries(prefix: Option<&str>, table: Table, styles: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let key = match prefix {
+            Some(prefix) => format!("{prefix}.{key}"),
+            None => key,
+        };
+        match value {
+            Value::Table(table) => style_entries(Some(&key), table, styles),
+            value => styles.push((key, value)),
+        }
+    }
+}
+
+/// Parse a style made of colors and attributes, such as `bold white on #202020`
+fn parse_style(style: &str) -> std::result::Result<CellAttributes, String> {
+    let mut attrs = CellAttributes::default();
+    let mut words = style.split_whitespace();
+    let mut has_foreground = false;
+    while let Some(word) = words.next() {
+        match word.to_ascii_lowercase().as_str() {
+            "bold" => attrs.set_intensity(Intensity::Bold),
+            "dim" => attrs.set_intensity(Intensity::Half),
+            "italic" => attrs.set_italic(true),
+            "underline" => attrs.set_underline(Underline::Single),
+            "reverse" => attrs.set_reverse(true),
+            "strikethrough" => attrs.set_strikethrough(true),
+            "on" => {
+                let color = words
+                    .next()
+                    .ok_or_else(|| "missing color after \"on\"".to_owned())?;
+                attrs.set_background(parse_color(color)?)
+            }
+            _ if has_foreground => return Err(format!("unexpected \"{word}\"")),
+            _ => {
+                has_foreground = true;
+                attrs.set_foreground(parse_color(word)?)
+            }
+        };
+    }
+    Ok(attrs)
+}
+
+fn parse_color(color: &str) -> std::result::Result<ColorAttribute, String> {
+    let ansi = match color.to_ascii_lowercase().as_str() {
+        "default" => return Ok(ColorAttribute::Default),
+        "black" => AnsiColor::Black,
+        "maroon" => AnsiColor::Maroon,
+        "green" => AnsiColor::Green,
+        "olive" => AnsiColor::Olive,
+        "navy" => AnsiColor::Navy,
+        "purple" => AnsiColor::Purple,
+        "teal" => AnsiColor::Teal,
+        "silver" => AnsiColor::Silver,
+        "grey" | "gray" => AnsiColor::Grey,
+        "red" => AnsiColor::Red,
+        "lime" => AnsiColor::Lime,
+        "yellow" => AnsiColor::Yellow,
+        "blue" => AnsiColor::Blue,
+        "fuchsia" | "magenta" => AnsiColor::Fuchsia,
+        "aqua" | "cyan" => AnsiColor::Aqua,
+        "white" => AnsiColor::White,
+        _ if color.starts_with('#') => {
+            let rgb = RgbColor::from_rgb_str(color)
+                .ok_or_else(|| format!("invalid hex color \"{color}\""))?;
+            return Ok(ColorAttribute::TrueColorWithDefaultFallback(rgb.into()));
+        }
+        _ => return Err(format!("unknown color or attribute \"{color}\"")),
+    };
+    Ok(ansi.into())
+}
+
+fn config_setting(key: &str, value: Value) -> std::result::Result<Setting, String> {
+    let kind = kind(&value);
+    let expected = |expected: &str| format!("expected {expected}, found {kind}");
+    let flag = |set: fn(&mut Config, bool)| match value {
+        Value::Boolean(v) => Ok(Box::new(move |config: &mut Config| set(config, v)) as Setting),
+        _ => Err(expected("a boolean")),
+    };
+    let millis = |value: &Value| match value {
+        Value::Integer(ms) if *ms >= 0 => Ok(Duration::from_millis(*ms as u64)),
+        _ => Err(expected("a number of milliseconds")),
+    };
+    Ok(match key {
+        "ctrl_q_quit" => flag(|c, v| c.ctrl_q_quit = v)?,
+        "enable_suspend" => flag(|c, v| c.enable_suspend = v)?,
+        "focus_follows_hover" => flag(|c, v| c.focus_follows_hover = v)?,
+        "cycle_docked" => flag(|c, v| c.cycle_docked = v)?,
+        "raise_float_on_focus" => flag(|c, v| c.raise_float_on_focus = v)?,
+        "dim_background_for_floats" => flag(|c, v| c.dim_background_for_floats = v)?,
+        "alternate_screen" => flag(|c, v| c.alternate_screen = v)?,
+        "contain_widget_errors" => flag(|c, v| c.contain_widget_errors = v)?,
+        "double_click_interval" => {
+            let interval = millis(&value)?;
+            Box::new(move |c| c.double_click_interval = interval)
+        }
+        "tooltip_delay" => {
+            let delay = millis(&value)?;
+            Box::new(move |c| c.tooltip_delay = delay)
+        }
+        "poll_timeout" => {
+            let timeout = millis(&value)?;
+            Box::new(move |c| c.poll_timeout = timeout)
+        }
+        "cursor_blink" => {
+            let blink = Some(millis(&value)?).filter(|blink| !blink.is_zero());
+            Box::new(move |c| c.cursor_blink = blink)
+        }
+        "max_fps" => {
+            let fps = match value {
+                Value::Integer(fps) => u32::try_from(fps).map_err(|_| expected("a frame rate"))?,
+                _ => return Err(expected("a frame rate")),
+            };
+            let fps = Some(fps).filter(|fps| *fps > 0);
+            Box::new(move |c| c.max_fps = fps)
+        }
+        "mouse" => {
+            let mode = match value {
+                Value::String(mode) if mode == "off" => MouseMode::Off,
+                Value::String(mode) if mode == "clicks" => MouseMode::Clicks,
+                Value::String(mode) if mode == "drag" => MouseMode::Drag,
+                Value::String(mode) if mode == "full" => MouseMode::Full,
+                _ => return Err(expected("\"off\", \"clicks\", \"drag\" or \"full\"")),
+            };
+            Box::new(move |c| c.mouse = mode)
+        }
+        "background" => {
+            let Value::String(color) = value else {
+                return Err(expected("a color"));
+            };
+            let color = parse_color(&color)?;
+            Box::new(move |c| c.background = color)
+        }
+        _ => return Err("unknown config flag".to_owned()),
+    })
+}
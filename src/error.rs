@@ -1,8 +1,14 @@
 //! Error handling
 
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
 
-use crate::layout::NodeId;
+use crate::{
+    layout::{Constraint, NodeId, WidgetId},
+    WorkspaceId,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -10,6 +16,14 @@ pub enum Error {
     External(String),
     #[error("Widget not found: {0:?}")]
     WidgetNotFound(NodeId),
+    #[error("Node not found: {0:?}")]
+    NodeNotFound(NodeId),
+    #[error("Expected node {0:?} to be a container")]
+    NotAContainer(NodeId),
+    #[error("Node {1:?} is not a child of {0:?}")]
+    NotAChild(NodeId, NodeId),
+    #[error("Node {0:?} is not attached to the layout tree")]
+    Detached(NodeId),
     #[error("Signal send failed")]
     SignalSendFail,
     #[error("Could not acquire widget read lock for {0:?}")]
@@ -20,10 +34,32 @@ pub enum Error {
     PollInputFailed,
     #[error("Expected node {0:?} to be a leaf")]
     ExpectedLeaf(NodeId),
+    #[error("Expected node {0:?} to be a floating window")]
+    ExpectedFloating(NodeId),
+    #[error("Cannot close {0:?}, the last window in the layout")]
+    LastLeaf(NodeId),
+    #[error("Invalid size constraint: {0:?}")]
+    InvalidConstraint(Constraint),
+    #[error("A widget named {0:?} is already registered")]
+    DuplicateName(String),
+    #[error("No widgets found for tags: {0:?}")]
+    UnresolvedTags(Vec<String>),
     #[error("Failed to flush terminal")]
     TerminalError,
     #[error("No focused window")]
     NoFocus,
+    #[error("Widget {0:?} is not shown in any window")]
+    WidgetNotShown(WidgetId),
+    #[error("Failed to render widget {0:?}: {1}")]
+    WidgetRenderFailed(WidgetId, String),
+    #[error("Lock on shared state was poisoned by a panic")]
+    LockPoisoned,
+    #[error("Workspace not found: {0:?}")]
+    WorkspaceNotFound(WorkspaceId),
+    #[error("Cannot remove {0:?}, the last workspace")]
+    LastWorkspace(WorkspaceId),
+    #[error("A global logger is already installed")]
+    LoggerAlreadySet,
 }
 
 impl Error {
@@ -33,3 +69,15 @@ impl Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Lock shared state for reading, returning [`Error::LockPoisoned`] instead of panicking if a
+/// thread panicked while holding the lock
+pub fn read_or_err<T>(lock: &RwLock<T>) -> Result<RwLockReadGuard<'_, T>> {
+    lock.read().map_err(|_| Error::LockPoisoned)
+}
+
+/// Lock shared state for writing, returning [`Error::LockPoisoned`] instead of panicking if a
+/// thread panicked while holding the lock
+pub fn write_or_err<T>(lock: &RwLock<T>) -> Result<RwLockWriteGuard<'_, T>> {
+    lock.write().map_err(|_| Error::LockPoisoned)
+}
@@ -1,20 +1,345 @@
 //! Types relating to input and event handling
 
-pub use termwiz::input::{KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+pub use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+
+use crate::{
+    error::{Error, Result},
+    layout::{NodeId, WidgetId},
+};
 
 #[derive(Debug)]
 pub enum UserEvent<U> {
     Exit,
     Tick,
+    /// Close the given floating window and remove its widget, restoring focus to the window that
+    /// was focused before it was opened.
+    Close(NodeId),
+    /// A widget failed while [`crate::Config::contain_widget_errors`] was set, with its error
+    /// message. Only sent to the global event handlers, and the widget is drawn as an error panel
+    /// until [`crate::App::clear_widget_error`] is called.
+    WidgetError(WidgetId, String),
+    /// A process started by a widget, like [`crate::widgets::Terminal`], exited, with its process
+    /// id and its exit code, or `None` if it was killed by a signal. Only sent to the global
+    /// event handlers.
+    ProcessExited(u32, Option<i32>),
     User(U),
 }
 
+impl<U> From<U> for UserEvent<U> {
+    fn from(value: U) -> Self {
+        Self::User(value)
+    }
+}
+
+/// A user event on its way to the app, addressed to a single widget or to the app as a whole
+pub(crate) type Envelope<U> = (Option<WidgetId>, UserEvent<U>);
+
+/// Sends user events to the app. Widgets receive one in [`crate::UpdateCtx::tx`], and it can be
+/// cloned and moved to other threads to send events from outside of the app.
+pub struct EventSender<U> {
+    inner: SenderInner<U>,
+}
+
+enum SenderInner<U> {
+    Envelope(Sender<Envelope<U>>),
+    /// A plain channel sender, which can't address events to a single widget
+    Legacy(Arc<Sender<UserEvent<U>>>),
+}
+
+impl<U> Clone for EventSender<U> {
+    fn clone(&self) -> Self {
+        let inner = match &self.inner {
+            SenderInner::Envelope(tx) => SenderInner::Envelope(tx.clone()),
+            SenderInner::Legacy(tx) => SenderInner::Legacy(Arc::clone(tx)),
+        };
+        Self { inner }
+    }
+}
+
+impl<U> From<Sender<Envelope<U>>> for EventSender<U> {
+    fn from(value: Sender<Envelope<U>>) -> Self {
+        Self {
+            inner: SenderInner::Envelope(value),
+        }
+    }
+}
+
+/// Wraps a sender for an existing channel of [`UserEvent`]s. Events sent with
+/// [`EventSender::send_to`] lose their address and are sent like [`EventSender::send`].
+impl<U> From<Arc<Sender<UserEvent<U>>>> for EventSender<U> {
+    fn from(value: Arc<Sender<UserEvent<U>>>) -> Self {
+        Self {
+            inner: SenderInner::Legacy(value),
+        }
+    }
+}
+
+impl<U> EventSender<U> {
+    fn send_envelope(&self, to: Option<WidgetId>, event: UserEvent<U>) -> Result<()> {
+        match &self.inner {
+            SenderInner::Envelope(tx) => tx.send((to, event)).map_err(|_| Error::SignalSendFail),
+            SenderInner::Legacy(tx) => tx.send(event).map_err(|_| Error::SignalSendFail),
+        }
+    }
+
+    /// Send an event to the app. User events are delivered according to the app's
+    /// [`crate::UserEventDelivery`].
+    pub fn send(&self, event: impl Into<UserEvent<U>>) -> Result<()> {
+        self.send_envelope(None, event.into())
+    }
+
+    /// Send a user event to a single widget. The event is dropped if the widget isn't shown when
+    /// the event is received.
+    pub fn send_to(&self, widget: WidgetId, event: U) -> Result<()> {
+        self.send_envelope(Some(widget), UserEvent::User(event))
+    }
+
+    /// Tell the app to exit.
+    pub fn quit(&self) -> Result<()> {
+        self.send_envelope(None, UserEvent::Exit)
+    }
+
+    /// Run `f` on a worker thread, and send its result to the app as a user event once it's done.
+    pub fn spawn<R>(
+        &self,
+        f: impl FnOnce() -> R + Send + 'static,
+        map: impl FnOnce(R) -> U + Send + 'static,
+    ) -> TaskHandle
+    where
+        U: Send + 'static,
+    {
+        self.spawn_to(None, f, map)
+    }
+
+    /// Like [`EventSender::spawn`], but the result is sent to a single widget with
+    /// [`EventSender::send_to`].
+    pub fn spawn_to<R>(
+        &self,
+        widget: Option<WidgetId>,
+        f: impl FnOnce() -> R + Send + 'static,
+        map: impl FnOnce(R) -> U + Send + 'static,
+    ) -> TaskHandle
+    where
+        U: Send + 'static,
+    {
+        let handle = TaskHandle::default();
+        let task = handle.clone();
+        let tx = self.clone();
+        std::thread::spawn(move || {
+            let res = f();
+            if !task.is_cancelled() {
+                // The app may have exited while the task was running
+                tx.send_envelope(widget, UserEvent::User(map(res))).ok();
+            }
+            task.finished.store(true, Ordering::SeqCst);
+        });
+        handle
+    }
+}
+
+/// A handle to a task started with [`EventSender::spawn`], [`crate::App::spawn`] or
+/// [`crate::UpdateCtx::spawn`]. Dropping the handle doesn't cancel the task.
+#[derive(Debug, Clone, Default)]
+pub struct TaskHandle {
+    cancelled: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Discard the task's result. The task itself can't be interrupted and keeps running until
+    /// it returns, but no event is sent.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Whether the task has returned, whether or not its result was sent
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+}
+
+/// The recipient of an event sent with [`crate::App::send_to`]: a widget, or the widget shown in a
+/// window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Widget(WidgetId),
+    Node(NodeId),
+}
+
+impl From<WidgetId> for Target {
+    fn from(value: WidgetId) -> Self {
+        Self::Widget(value)
+    }
+}
+
+impl From<NodeId> for Target {
+    fn from(value: NodeId) -> Self {
+        Self::Node(value)
+    }
+}
+
 /// An event that can be sent to a widget or handled by the global event handler.
 #[derive(Debug)]
 pub enum Event<U> {
     Key(KeyEvent),
+    /// A mouse event. `x` and `y` are zero-based and relative to the widget, rather than the
+    /// one-based screen positions reported by the terminal.
     Mouse(MouseEvent),
-    Resize { rows: usize, cols: usize },
+    Resize {
+        rows: usize,
+        cols: usize,
+    },
     Paste(String),
+    /// The mouse wheel was scrolled over the widget. `delta` is the number of lines to scroll,
+    /// negative when scrolling up. `x` and `y` are relative to the widget, like mouse events.
+    Scroll {
+        delta: i32,
+        x: u16,
+        y: u16,
+    },
+    /// A mouse gesture recognized from a series of mouse events. Gestures are sent after the mouse
+    /// event that completes them.
+    MouseGesture(Gesture),
+    /// The mouse moved over the widget. Mouse events with no buttons held are sent to the widget
+    /// under the mouse while it's hovered, whether or not it's focused.
+    MouseEnter,
+    /// The mouse moved off of the widget
+    MouseLeave,
     User(UserEvent<U>),
 }
+
+/// Mouse gestures synthesized from raw mouse events. Positions are relative to the widget, like
+/// mouse events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// The same button was pressed twice in the same place within
+    /// [`crate::Config::double_click_interval`]
+    DoubleClick {
+        x: u16,
+        y: u16,
+        button: MouseButtons,
+    },
+    /// The mouse moved while a button was held. `x` and `y` are where the button was pressed.
+    DragStart {
+        x: u16,
+        y: u16,
+        button: MouseButtons,
+    },
+    /// The mouse moved during a drag, by the given number of cells since the last move
+    DragMove { dx: i32, dy: i32 },
+    /// The button held during a drag was released
+    DragEnd,
+}
+
+/// A mouse button press
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    x: u16,
+    y: u16,
+    button: MouseButtons,
+    at: Instant,
+}
+
+/// Recognizes [`Gesture`]s from raw mouse events
+#[derive(Debug, Default)]
+pub(crate) struct GestureTracker {
+    /// The button currently held, if any
+    press: Option<Press>,
+    /// The last press that ended in a click, which a double click can follow
+    last_click: Option<Press>,
+    /// Whether the current press was the second click of a double click
+    double: bool,
+    dragging: bool,
+    /// The last position of the mouse while the button is held
+    last: (u16, u16),
+}
+
+impl GestureTracker {
+    /// Feeds a mouse event to the tracker, returning the gestures it completes along with where
+    /// the button was pressed, which decides the widget that receives them. Gestures are returned
+    /// in screen coordinates.
+    pub(crate) fn track(
+        &mut self,
+        event: &MouseEvent,
+        double_click: Duration,
+    ) -> Vec<(Gesture, (u16, u16))> {
+        let buttons =
+            event.mouse_buttons & (MouseButtons::LEFT | MouseButtons::RIGHT | MouseButtons::MIDDLE);
+        if event.mouse_buttons.contains(MouseButtons::VERT_WHEEL)
+            || event.mouse_buttons.contains(MouseButtons::HORZ_WHEEL)
+        {
+            return vec![];
+        }
+        let pos = (event.x, event.y);
+        let mut gestures = vec![];
+        match self.press {
+            None if !buttons.is_empty() => {
+                let now = Instant::now();
+                self.double = self.last_click.is_some_and(|click| {
+                    click.button == buttons
+                        && (click.x, click.y) == pos
+                        && now.duration_since(click.at) <= double_click
+                });
+                if self.double {
+                    self.last_click = None;
+                    let click = Gesture::DoubleClick {
+                        x: pos.0,
+                        y: pos.1,
+                        button: buttons,
+                    };
+                    gestures.push((click, pos));
+                }
+                self.press = Some(Press {
+                    x: pos.0,
+                    y: pos.1,
+                    button: buttons,
+                    at: now,
+                });
+                self.last = pos;
+            }
+            Some(press) if !buttons.is_empty() => {
+                let origin = (press.x, press.y);
+                if pos != self.last {
+                    if !self.dragging {
+                        self.dragging = true;
+                        let start = Gesture::DragStart {
+                            x: press.x,
+                            y: press.y,
+                            button: press.button,
+                        };
+                        gestures.push((start, origin));
+                    }
+                    let dx = pos.0 as i32 - self.last.0 as i32;
+                    let dy = pos.1 as i32 - self.last.1 as i32;
+                    gestures.push((Gesture::DragMove { dx, dy }, origin));
+                    self.last = pos;
+                }
+            }
+            Some(press) => {
+                if self.dragging {
+                    gestures.push((Gesture::DragEnd, (press.x, press.y)));
+                    self.last_click = None;
+                } else if !self.double {
+                    self.last_click = Some(press);
+                }
+                self.press = None;
+                self.dragging = false;
+            }
+            None => {}
+        }
+        gestures
+    }
+}
@@ -0,0 +1,311 @@
+//! A declarative way to describe a layout tree, built with [`Layout::build`].
+
+use std::collections::HashMap;
+
+use crate::{error::Result, Widget, WidgetStore};
+
+use super::{
+    geometry::{Axis, Constraint, Rect},
+    tree::{Layout, NodeId, WidgetId},
+};
+
+/// The widget shown in a [`Pane`], either already registered or registered when the pane is built
+pub enum PaneWidget<U, S> {
+    Id(WidgetId),
+    Widget(Box<dyn Widget<U, S>>),
+}
+
+/// Converts a [`WidgetId`] or a widget into the widget shown by a [`Pane`]. The `Marker` type
+/// only tells the two implementations apart.
+pub trait IntoPaneWidget<U, S, Marker> {
+    fn into_pane_widget(self) -> PaneWidget<U, S>;
+}
+
+impl<U, S> IntoPaneWidget<U, S, WidgetId> for WidgetId {
+    fn into_pane_widget(self) -> PaneWidget<U, S> {
+        PaneWidget::Id(self)
+    }
+}
+
+impl<U, S, W: Widget<U, S> + 'static> IntoPaneWidget<U, S, ()> for W {
+    fn into_pane_widget(self) -> PaneWidget<U, S> {
+        PaneWidget::Widget(Box::new(self))
+    }
+}
+
+enum PaneKind<U, S> {
+    Split {
+        axis: Axis,
+        gap: usize,
+        padding: usize,
+        children: Vec<Pane<U, S>>,
+    },
+    Leaf(PaneWidget<U, S>),
+    Floating(PaneWidget<U, S>, Rect),
+}
+
+/// A description of a split, window or floating window, added to a layout with [`Layout::build`].
+///
+/// Splits are created with [`Pane::row`] and [`Pane::column`], or the [`hsplit!`](crate::hsplit)
+/// and [`vsplit!`](crate::vsplit) macros, and windows with [`leaf`] and [`floating`].
+///
+/// ```
+/// use sanguine::{
+///     layout::{floating, leaf, Constraint, Layout, Pane, Rect},
+///     widgets::TextBox,
+///     WidgetStore,
+/// };
+///
+/// let mut layout = Layout::<(), ()>::new();
+/// let mut widgets = WidgetStore::new();
+/// let notes = widgets.register(TextBox::new());
+///
+/// let panes = layout
+///     .build(
+///         &mut widgets,
+///         Pane::row()
+///             .child(leaf(TextBox::new()).named("editor"))
+///             .child(
+///                 Pane::column()
+///                     .child(leaf(notes))
+///                     .child(leaf(TextBox::new()))
+///                     .constraint(Constraint::percent(40)),
+///             )
+///             .child(floating(TextBox::new(), Rect::new(10., 5., 20., 4.))),
+///     )
+///     .unwrap();
+///
+/// let editor = panes.get("editor").unwrap();
+/// assert_eq!(layout.parent(editor), Some(layout.root()));
+/// assert_eq!(layout.leaves().len(), 3);
+/// assert_eq!(layout.floats().len(), 1);
+/// ```
+pub struct Pane<U, S> {
+    kind: PaneKind<U, S>,
+    size: Option<Constraint>,
+    name: Option<String>,
+}
+
+/// A window showing a widget, which can be a [`WidgetId`] or a widget to register
+pub fn leaf<U, S, M>(widget: impl IntoPaneWidget<U, S, M>) -> Pane<U, S> {
+    Pane::new(PaneKind::Leaf(widget.into_pane_widget()))
+}
+
+/// A floating window showing a widget, at `rect` in screen coordinates. Floating windows can be
+/// given anywhere in a tree of panes, and are always added on top of the layout.
+pub fn floating<U, S, M>(widget: impl IntoPaneWidget<U, S, M>, rect: Rect) -> Pane<U, S> {
+    Pane::new(PaneKind::Floating(widget.into_pane_widget(), rect))
+}
+
+impl<U, S> Pane<U, S> {
+    fn new(kind: PaneKind<U, S>) -> Self {
+        Self {
+            kind,
+            size: None,
+            name: None,
+        }
+    }
+
+    /// A split with its children laid out left to right
+    pub fn row() -> Self {
+        Self::split(Axis::Horizontal)
+    }
+
+    /// A split with its children laid out top to bottom
+    pub fn column() -> Self {
+        Self::split(Axis::Vertical)
+    }
+
+    /// A split with its children laid out along `axis`
+    pub fn split(axis: Axis) -> Self {
+        Self::new(PaneKind::Split {
+            axis,
+            gap: 0,
+            padding: 0,
+            children: vec![],
+        })
+    }
+
+    /// Add a pane to the end of a split. Has no effect on windows.
+    pub fn child(mut self, child: impl Into<Pane<U, S>>) -> Self {
+        if let PaneKind::Split { children, .. } = &mut self.kind {
+            children.push(child.into());
+        }
+        self
+    }
+
+    /// Set the size of the pane within its parent. See [`Layout::set_size`].
+    pub fn constraint(mut self, size: Constraint) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Set the space between the children of a split. See [`Layout::set_gap`].
+    pub fn gap(mut self, gap: usize) -> Self {
+        if let PaneKind::Split { gap: g, .. } = &mut self.kind {
+            *g = gap;
+        }
+        self
+    }
+
+    /// Set the inset of a split's children from its edges. See [`Layout::set_padding`].
+    pub fn padding(mut self, padding: usize) -> Self {
+        if let PaneKind::Split { padding: p, .. } = &mut self.kind {
+            *p = padding;
+        }
+        self
+    }
+
+    /// Name the pane, so that its node can be found in the [`BuiltLayout`] returned by
+    /// [`Layout::build`]. Windows are also tagged with their name, see [`Layout::set_tag`].
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// The nodes of the named panes added by [`Layout::build`]
+#[derive(Debug, Clone)]
+pub struct BuiltLayout {
+    /// The node the top level pane was built into
+    pub root: NodeId,
+    names: HashMap<String, NodeId>,
+}
+
+impl BuiltLayout {
+    /// The node of the pane with the given name
+    pub fn get(&self, name: &str) -> Option<NodeId> {
+        self.names.get(name).copied()
+    }
+
+    /// Iterate over the names and nodes of every named pane
+    pub fn iter(&self) -> impl Iterator<Item = (&str, NodeId)> {
+        self.names.iter().map(|(name, node)| (name.as_str(), *node))
+    }
+}
+
+impl<U, S> Layout<U, S> {
+    /// Add a tree of [`Pane`]s to the layout, registering their widgets with `widgets`.
+    ///
+    /// If `pane` is a split, its direction, size, gap and padding are applied to the root
+    /// container and its children are added to the end of the root. Otherwise the window is added
+    /// to the root. Returns the nodes of the panes given a name with [`Pane::named`], or
+    /// [`Error::InvalidConstraint`](crate::error::Error::InvalidConstraint) if a pane has an
+    /// invalid size.
+    pub fn build(
+        &mut self,
+        widgets: &mut WidgetStore<U, S>,
+        pane: impl Into<Pane<U, S>>,
+    ) -> Result<BuiltLayout> {
+        let mut names = HashMap::new();
+        let root = self.root();
+        let pane = pane.into();
+        if let PaneKind::Split { .. } = pane.kind {
+            self.build_split(widgets, root, pane, &mut names)?;
+        } else if let Some(node) = self.build_pane(widgets, pane, &mut names)? {
+            self.add_child(root, node)?;
+        }
+        self.mark_dirty(None);
+        Ok(BuiltLayout { root, names })
+    }
+
+    /// Builds a pane into a new node, returning it unless the pane is a floating window
+    fn build_pane(
+        &mut self,
+        widgets: &mut WidgetStore<U, S>,
+        pane: Pane<U, S>,
+        names: &mut HashMap<String, NodeId>,
+    ) -> Result<Option<NodeId>> {
+        let register = |widgets: &mut WidgetStore<U, S>, widget| match widget {
+            PaneWidget::Id(id) => id,
+            PaneWidget::Widget(widget) => widgets.register_boxed(widget),
+        };
+        let (node, in_tree) = match pane.kind {
+            PaneKind::Split { axis, .. } => {
+                let node = self.add_container(axis, None);
+                self.build_split(widgets, node, pane, names)?;
+                return Ok(Some(node));
+            }
+            PaneKind::Leaf(widget) => (self.add_leaf_id(register(widgets, widget)), true),
+            PaneKind::Floating(widget, rect) => {
+                (self.add_floating(register(widgets, widget), rect), false)
+            }
+        };
+        if let Some(size) = pane.size {
+            self.set_size(node, size)?;
+        }
+        if let Some(name) = pane.name {
+            self.set_tag(node, &name);
+            names.insert(name, node);
+        }
+        Ok(in_tree.then_some(node))
+    }
+
+    /// Applies a split pane to an existing container and adds its children
+    fn build_split(
+        &mut self,
+        widgets: &mut WidgetStore<U, S>,
+        node: NodeId,
+        pane: Pane<U, S>,
+        names: &mut HashMap<String, NodeId>,
+    ) -> Result<()> {
+        let PaneKind::Split {
+            axis,
+            gap,
+            padding,
+            children,
+        } = pane.kind
+        else {
+            return Ok(());
+        };
+        self.set_direction(node, axis);
+        self.set_gap(node, gap);
+        self.set_padding(node, padding);
+        if let Some(size) = pane.size {
+            self.set_size(node, size)?;
+        }
+        if let Some(name) = pane.name {
+            names.insert(name, node);
+        }
+        for child in children {
+            if let Some(child) = self.build_pane(widgets, child, names)? {
+                self.add_child(node, child)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Creates a [`Pane`] with its children laid out left to right, like [`Pane::row`].
+///
+/// ```
+/// use sanguine::{hsplit, layout::{leaf, Layout}, vsplit, widgets::TextBox, WidgetStore};
+///
+/// let mut layout = Layout::<(), ()>::new();
+/// let mut widgets = WidgetStore::new();
+/// layout
+///     .build(
+///         &mut widgets,
+///         hsplit![
+///             leaf(TextBox::new()),
+///             vsplit![leaf(TextBox::new()), leaf(TextBox::new())],
+///         ],
+///     )
+///     .unwrap();
+/// assert_eq!(layout.leaves().len(), 3);
+/// ```
+#[macro_export]
+macro_rules! hsplit {
+    ($($child:expr),* $(,)?) => {
+        $crate::layout::Pane::row()$(.child($child))*
+    };
+}
+
+/// Creates a [`Pane`] with its children laid out top to bottom, like [`Pane::column`]. See
+/// [`hsplit!`](crate::hsplit).
+#[macro_export]
+macro_rules! vsplit {
+    ($($child:expr),* $(,)?) => {
+        $crate::layout::Pane::column()$(.child($child))*
+    };
+}
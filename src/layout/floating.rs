@@ -1,50 +1,73 @@
-use std::sync::{Arc, RwLock};
-
 use slotmap::SlotMap;
 
-use crate::Widget;
-
-use super::{Direction, LayoutNode, NodeId, Rect, WidgetId};
+use super::{Anchor, LayoutNode, NodeId, Rect, WidgetId};
 
 pub struct Floating {
     /// The widget to be rendered
     widget: WidgetId,
-    /// Position and size of the floating window
+    /// Position and size of the floating window. The window may be shown smaller than this if the
+    /// terminal is too small to fit it.
     pos: Rect,
-    /// Z-index of the window (only applies when not focused)
+    /// Z-index of the window. Windows with a higher z-index are drawn above those with a lower one.
     z_index: usize,
+    /// The point of the screen the window stays attached to on resize, if any
+    anchor: Option<Anchor>,
+    /// Whether a shadow is drawn below and to the right of the window
+    shadow: bool,
+    /// Whether the window keeps focus and mouse events from the windows below it
+    modal: bool,
 }
 
 impl Floating {
-    pub fn new<U, S>(widget: WidgetId, pos: Rect) -> Self {
-        Self {
-            widget,
-            pos,
-            z_index: 1,
-        }
-    }
-
-    pub fn from_widget<U, S>(
-        widget: Arc<RwLock<dyn Widget<U, S>>>,
-        pos: Rect,
-        widgets: &mut SlotMap<WidgetId, Arc<RwLock<dyn Widget<U, S>>>>,
-    ) -> Self {
-        let widget = widgets.insert(widget);
+    pub fn new(widget: WidgetId, pos: Rect) -> Self {
         Self {
             widget,
             pos,
             z_index: 1,
+            anchor: None,
+            shadow: false,
+            modal: false,
         }
     }
 
-    pub fn with_z_index(self, z_index: usize) -> Self {
-        Self { z_index, ..self }
+    pub fn set_z_index(&mut self, z_index: usize) {
+        self.z_index = z_index;
     }
 
     pub fn z_index(&self) -> usize {
         self.z_index
     }
 
+    pub fn set_shadow(&mut self, shadow: bool) {
+        self.shadow = shadow;
+    }
+
+    pub fn has_shadow(&self) -> bool {
+        self.shadow
+    }
+
+    pub fn set_modal(&mut self, modal: bool) {
+        self.modal = modal;
+    }
+
+    /// Whether the window keeps focus and mouse events from the windows below it while it's shown
+    pub fn is_modal(&self) -> bool {
+        self.modal
+    }
+
+    pub fn set_anchor(&mut self, anchor: Option<Anchor>) {
+        self.anchor = anchor;
+    }
+
+    pub fn anchor(&self) -> Option<Anchor> {
+        self.anchor
+    }
+
+    /// The requested position and size of the window
+    pub fn pos(&self) -> &Rect {
+        &self.pos
+    }
+
     pub fn widget(&self) -> WidgetId {
         self.widget
     }
@@ -53,22 +76,23 @@ impl Floating {
         self.pos.x = pos.0 as f32;
         self.pos.y = pos.1 as f32;
     }
-
-    pub fn move_dir(&mut self, direction: Direction) {
-        match direction {
-            Direction::Up => self.pos.y -= 1.,
-            Direction::Down => self.pos.y += 1.,
-            Direction::Left => self.pos.x -= 1.,
-            Direction::Right => self.pos.x += 1.,
-        }
-    }
 }
 
+/// The floating windows of a layout, ordered from the bottom of the stack to the top, which is
+/// the order they're drawn in. The stack is kept sorted by z-index: windows with the same z-index
+/// are in the order they were pushed or moved in, so the newest is on top.
 pub struct FloatStack<U, S> {
     inner: Vec<NodeId>,
     marker: std::marker::PhantomData<(U, S)>,
 }
 
+fn z_index(nodes: &SlotMap<NodeId, LayoutNode>, node: NodeId) -> usize {
+    nodes
+        .get(node)
+        .and_then(|v| v.floating())
+        .map_or(1, |v| v.z_index)
+}
+
 #[allow(unused)]
 impl<U, S> FloatStack<U, S> {
     pub fn new() -> Self {
@@ -78,42 +102,50 @@ impl<U, S> FloatStack<U, S> {
         }
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &NodeId> {
+    /// The windows from the bottom of the stack to the top
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &NodeId> {
         self.inner.iter()
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut NodeId> {
-        self.inner.iter_mut()
-    }
-
     pub fn remove(&mut self, node: NodeId) {
         self.inner.retain(|v| *v != node);
     }
 
-    pub fn sort(&mut self, nodes: &SlotMap<NodeId, LayoutNode>) {
-        self.inner.sort_by(|a, b| {
-            nodes
-                .get(*b)
-                .map(|v| v.floating().unwrap().z_index)
-                .unwrap_or(1)
-                .cmp(
-                    &nodes
-                        .get(*a)
-                        .map(|v| v.floating().unwrap().z_index)
-                        .unwrap_or(1),
-                )
-        })
+    /// Sorts the stack from the bottom up by z-index, for when z-indices were changed without
+    /// moving the windows. Windows with the same z-index keep their order.
+    pub fn sort_by_z(&mut self, nodes: &SlotMap<NodeId, LayoutNode>) {
+        self.inner.sort_by_key(|node| z_index(nodes, *node))
     }
 
+    /// Puts a window on top of the windows with the same or a lower z-index
     pub fn push(&mut self, node: NodeId, nodes: &SlotMap<NodeId, LayoutNode>) {
+        let z = z_index(nodes, node);
+        let at = self
+            .inner
+            .partition_point(|other| z_index(nodes, *other) <= z);
+        self.inner.insert(at, node);
+    }
+
+    /// Moves a window to the top of the stack. Its z-index should be raised to match, so that
+    /// the stack stays sorted.
+    pub fn bring_to_front(&mut self, node: NodeId) {
+        self.remove(node);
         self.inner.push(node);
-        self.sort(nodes);
     }
 
-    pub fn pop(&mut self, nodes: &SlotMap<NodeId, LayoutNode>) -> Option<NodeId> {
+    /// Moves a window to the bottom of the stack. Its z-index should be lowered to match, so that
+    /// the stack stays sorted.
+    pub fn send_to_back(&mut self, node: NodeId) {
+        self.remove(node);
+        self.inner.insert(0, node);
+    }
+
+    /// Removes the topmost window
+    pub fn pop(&mut self) -> Option<NodeId> {
         self.inner.pop()
     }
 
+    /// The topmost window
     pub fn peek(&self) -> Option<NodeId> {
         self.inner.last().copied()
     }
@@ -1,4 +1,7 @@
-#[derive(Debug, Clone)]
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -19,13 +22,37 @@ impl From<ratatui::layout::Rect> for Rect {
 }
 
 #[cfg(feature = "tui")]
-impl Into<ratatui::layout::Rect> for Rect {
-    fn into(self) -> ratatui::layout::Rect {
+impl From<Rect> for ratatui::layout::Rect {
+    fn from(value: Rect) -> Self {
         ratatui::layout::Rect {
-            x: self.x as u16,
-            y: self.y as u16,
-            width: self.width as u16,
-            height: self.height as u16,
+            x: value.x as u16,
+            y: value.y as u16,
+            width: value.width as u16,
+            height: value.height as u16,
+        }
+    }
+}
+
+#[cfg(feature = "tui-legacy")]
+impl From<ratatui_legacy::layout::Rect> for Rect {
+    fn from(value: ratatui_legacy::layout::Rect) -> Self {
+        Self {
+            x: value.x as f32,
+            y: value.y as f32,
+            width: value.width as f32,
+            height: value.height as f32,
+        }
+    }
+}
+
+#[cfg(feature = "tui-legacy")]
+impl From<Rect> for ratatui_legacy::layout::Rect {
+    fn from(value: Rect) -> Self {
+        ratatui_legacy::layout::Rect {
+            x: value.x as u16,
+            y: value.y as u16,
+            width: value.width as u16,
+            height: value.height as u16,
         }
     }
 }
@@ -49,15 +76,51 @@ impl Rect {
         (self.x + self.width / 2.0, self.y + self.height / 2.0)
     }
 
+    /// Whether the point is inside the rect. The right and bottom edges are exclusive, so a point
+    /// on the edge shared by two adjacent rects is only inside one of them.
     pub fn contains(&self, x: f32, y: f32) -> bool {
-        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+        x >= self.left() && x < self.right() && y >= self.top() && y < self.bottom()
     }
 
+    /// Whether the two rects overlap. Rects that only share an edge don't intersect.
+    ///
+    /// ```
+    /// use sanguine::layout::Rect;
+    ///
+    /// let horizontal = Rect::new(0., 4., 10., 2.);
+    /// let vertical = Rect::new(4., 0., 2., 10.);
+    /// assert!(horizontal.intersects(&vertical) && vertical.intersects(&horizontal));
+    ///
+    /// let right = Rect::new(10., 4., 5., 2.);
+    /// assert!(!horizontal.intersects(&right));
+    /// ```
     pub fn intersects(&self, other: &Rect) -> bool {
-        self.contains(other.x, other.y)
-            || self.contains(other.x + other.width, other.y)
-            || self.contains(other.x, other.y + other.height)
-            || self.contains(other.x + other.width, other.y + other.height)
+        self.left() < other.right()
+            && other.left() < self.right()
+            && self.top() < other.bottom()
+            && other.top() < self.bottom()
+    }
+
+    /// The overlapping area of two rects, or `None` if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.left().max(other.left());
+        let y = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (right > x && bottom > y).then(|| Rect::new(x, y, right - x, bottom - y))
+    }
+
+    /// The smallest rect containing both rects
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.left().min(other.left());
+        let y = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width * self.height
     }
 
     pub fn left(&self) -> f32 {
@@ -83,6 +146,40 @@ impl Rect {
             ..Default::default()
         }
     }
+
+    /// The width in cells, rounded to the nearest cell
+    pub fn width_usize(&self) -> usize {
+        self.width.round() as usize
+    }
+
+    /// The height in cells, rounded to the nearest cell
+    pub fn height_usize(&self) -> usize {
+        self.height.round() as usize
+    }
+
+    /// The position of the top left corner in cells, rounded to the nearest cell
+    pub fn origin_usize(&self) -> (usize, usize) {
+        (self.x.round() as usize, self.y.round() as usize)
+    }
+
+    /// The size in cells, as `(width, height)` like [`Surface::dimensions`](crate::surface::Surface::dimensions)
+    pub fn size_usize(&self) -> (usize, usize) {
+        (self.width_usize(), self.height_usize())
+    }
+}
+
+/// A rect of the given `(width, height)` at the origin
+impl From<(usize, usize)> for Rect {
+    fn from(dims: (usize, usize)) -> Self {
+        Rect::from_size(dims)
+    }
+}
+
+/// A rect of the given `(width, height)` at the origin
+impl From<(u16, u16)> for Rect {
+    fn from(dims: (u16, u16)) -> Self {
+        Rect::from_size((dims.0 as usize, dims.1 as usize))
+    }
 }
 
 impl Default for Rect {
@@ -97,6 +194,7 @@ impl Default for Rect {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Left,
     Right,
@@ -104,21 +202,105 @@ pub enum Direction {
     Down,
 }
 
+/// A point of the screen that a floating window stays attached to when the terminal is resized
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anchor {
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Anchor {
+    /// The position of a window of the given size anchored within `bounds`
+    pub fn position(&self, bounds: &Rect, width: f32, height: f32) -> (f32, f32) {
+        let left = bounds.left();
+        let top = bounds.top();
+        let right = (bounds.right() - width).max(left);
+        let bottom = (bounds.bottom() - height).max(top);
+        match self {
+            Anchor::Center => (
+                left + ((bounds.width - width) / 2.).max(0.).floor(),
+                top + ((bounds.height - height) / 2.).max(0.).floor(),
+            ),
+            Anchor::TopLeft => (left, top),
+            Anchor::TopRight => (right, top),
+            Anchor::BottomLeft => (left, bottom),
+            Anchor::BottomRight => (right, bottom),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Axis {
     Horizontal,
     Vertical,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Constraint {
+    /// Exactly this many cells, shrunk in proportion to its fixed size siblings when they don't
+    /// all fit
     Fixed(usize),
+    /// A fraction of the container's space, from 0.0 to 1.0, after fixed size siblings have been
+    /// laid out. If the percentages of siblings add up to more than 100%, they are scaled down to
+    /// fit. See [`Constraint::percent`] and [`Constraint::ratio`].
     Percentage(f32),
     Fill,
+    /// Fill the available space, but take up at least this many cells
+    Min(usize),
+    /// Fill the available space, but take up at most this many cells
+    Max(usize),
+    /// Keep a fixed width / height ratio, sizing along the container's axis from the size of the
+    /// cross axis
+    Aspect(f32),
 }
 
 impl Constraint {
     pub fn fill() -> Constraint {
         Constraint::Fill
     }
+
+    /// A percentage of the container's space, from 0 to 100. Larger values are clamped to 100.
+    pub fn percent(percent: u8) -> Constraint {
+        Constraint::Percentage(percent.min(100) as f32 / 100.)
+    }
+
+    /// A `numerator / denominator` fraction of the container's space. Fractions larger than one
+    /// are clamped to one, and a denominator of zero gives an empty fraction.
+    pub fn ratio(numerator: u32, denominator: u32) -> Constraint {
+        if denominator == 0 {
+            return Constraint::Percentage(0.);
+        }
+        Constraint::Percentage((numerator as f32 / denominator as f32).min(1.))
+    }
+
+    /// Checks that a percentage is between 0.0 and 1.0, and that an aspect ratio is positive
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            Constraint::Percentage(percent) => (0. ..=1.).contains(percent),
+            Constraint::Aspect(ratio) => ratio.is_finite() && *ratio > 0.,
+            _ => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidConstraint(self.clone()))
+        }
+    }
+
+    /// Grow a fixed or bounded constraint by `cells`, for widgets that wrap another widget and add
+    /// to its size, such as [`crate::widgets::Border`]. Other constraints are unchanged.
+    pub fn grow(self, cells: usize) -> Constraint {
+        match self {
+            Constraint::Fixed(size) => Constraint::Fixed(size + cells),
+            Constraint::Min(min) => Constraint::Min(min + cells),
+            Constraint::Max(max) => Constraint::Max(max + cells),
+            other => other,
+        }
+    }
 }
@@ -1,8 +1,12 @@
 //! The implementation of Sanguine's layout engine and related types
 
+mod builder;
 mod floating;
 mod geometry;
+mod spec;
 mod tree;
 
+pub use builder::*;
 pub use geometry::*;
+pub use spec::*;
 pub use tree::*;
@@ -0,0 +1,62 @@
+//! Serializable descriptions of a layout, used to save and restore window arrangements.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Anchor, Axis, Constraint, Rect};
+
+/// A snapshot of a [`super::Layout`]'s arrangement, created with [`super::Layout::serialize`] and
+/// applied with [`super::Layout::restore`].
+///
+/// Widgets are not part of the snapshot. Leaves are identified by the tags set with
+/// [`super::Layout::set_tag`], which are resolved to freshly registered widgets on restore.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LayoutSpec {
+    /// The root container
+    pub root: ContainerSpec,
+    /// Leaves docked to the top of the layout, outermost first
+    pub docked_top: Vec<LeafSpec>,
+    /// Leaves docked to the bottom of the layout, outermost first
+    pub docked_bottom: Vec<LeafSpec>,
+    /// Floating windows
+    pub floating: Vec<FloatSpec>,
+}
+
+/// A node in the layout tree
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NodeSpec {
+    Container(ContainerSpec),
+    Leaf(LeafSpec),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ContainerSpec {
+    pub axis: Axis,
+    pub size: Option<Constraint>,
+    pub gap: usize,
+    pub padding: usize,
+    pub children: Vec<NodeSpec>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LeafSpec {
+    /// The leaf's tag, or `None` if it wasn't tagged
+    pub tag: Option<String>,
+    pub size: Option<Constraint>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FloatSpec {
+    /// The window's tag, or `None` if it wasn't tagged
+    pub tag: Option<String>,
+    pub rect: Rect,
+    pub z_index: usize,
+    /// The point of the screen the window is anchored to, if any
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub anchor: Option<Anchor>,
+}
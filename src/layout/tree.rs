@@ -1,8 +1,16 @@
+use std::any::Any;
+
 use slotmap::{new_key_type, SecondaryMap, SlotMap};
 
+use crate::{
+    error::{Error, Result},
+    Widget, WidgetStore,
+};
+
 use super::{
     floating::{FloatStack, Floating},
-    geometry::{Axis, Constraint, Direction, Rect},
+    geometry::{Anchor, Axis, Constraint, Direction, Rect},
+    spec::{ContainerSpec, FloatSpec, LayoutSpec, LeafSpec, NodeSpec},
 };
 
 new_key_type! {
@@ -10,8 +18,15 @@ new_key_type! {
     pub struct WidgetId;
 }
 
-pub struct Leaf {
+/// The smallest size, in cells, that a split can be resized to. This leaves enough room for a
+/// [`crate::widgets::Border`].
+const MIN_SPLIT_SIZE: f32 = 3.;
+
+pub(crate) struct Leaf {
     widget: WidgetId,
+    size: Option<Constraint>,
+    /// The constraint reported by the widget, used when no size has been set
+    widget_size: Option<Constraint>,
     parent: Option<NodeId>,
 }
 
@@ -19,6 +34,8 @@ impl Leaf {
     pub fn new(widget: WidgetId) -> Self {
         Self {
             widget,
+            size: None,
+            widget_size: None,
             parent: None,
         }
     }
@@ -32,6 +49,8 @@ impl Clone for Leaf {
     fn clone(&self) -> Self {
         Self {
             widget: self.widget,
+            size: self.size.clone(),
+            widget_size: self.widget_size.clone(),
             // When a leaf is cloned, the intention is to clone its widget. Parent can be set
             // separately if needed.
             parent: None,
@@ -40,14 +59,18 @@ impl Clone for Leaf {
 }
 
 #[derive(Debug)]
-pub struct Container {
+pub(crate) struct Container {
     direction: Axis,
     size: Option<Constraint>,
     children: Vec<NodeId>,
     parent: Option<NodeId>,
+    /// Space between children along the container's axis
+    gap: usize,
+    /// Inset from the container's bounds on all sides
+    padding: usize,
 }
 
-pub enum LayoutNode {
+pub(crate) enum LayoutNode {
     Container(Container),
     Leaf(Leaf),
     Floating(Floating),
@@ -66,13 +89,6 @@ impl LayoutNode {
         matches!(self, Self::Floating(_))
     }
 
-    pub fn leaf(&self) -> Option<&Leaf> {
-        match self {
-            Self::Leaf(leaf) => Some(leaf),
-            _ => None,
-        }
-    }
-
     pub fn container(&self) -> Option<&Container> {
         match self {
             Self::Container(container) => Some(container),
@@ -96,6 +112,230 @@ impl LayoutNode {
     }
 }
 
+/// The kinds of node in a [`Layout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A split holding other nodes
+    Container,
+    /// A window in the tree, or docked to the top or bottom of the layout
+    Leaf,
+    /// A window drawn over the tree
+    Floating,
+}
+
+/// A read-only view of a node in a [`Layout`], returned by [`Layout::node`].
+///
+/// ```
+/// use sanguine::{
+///     hsplit,
+///     layout::{leaf, Axis, Layout, NodeKind},
+///     widgets::TextBox,
+///     WidgetStore,
+/// };
+///
+/// let mut layout = Layout::<(), ()>::new();
+/// let mut widgets = WidgetStore::new();
+/// let tree = hsplit![leaf(TextBox::new()).named("left"), leaf(TextBox::new())];
+/// let built = layout.build(&mut widgets, tree).unwrap();
+/// let left = built.get("left").unwrap();
+/// layout.compute(&sanguine::layout::Rect::new(0., 0., 80., 24.));
+///
+/// let root = layout.node(layout.root()).unwrap();
+/// assert_eq!(root.kind(), NodeKind::Container);
+/// assert_eq!(root.direction(), Some(Axis::Horizontal));
+/// assert_eq!(root.children().next(), Some(left));
+/// assert_eq!(root.widget_id(), None);
+///
+/// let node = layout.node(left).unwrap();
+/// assert_eq!(node.kind(), NodeKind::Leaf);
+/// assert_eq!(node.parent(), Some(layout.root()));
+/// assert_eq!(node.tag(), Some("left"));
+/// assert_eq!(node.rect().map(|r| r.width), Some(40.));
+/// assert!(node.widget_id().is_some());
+/// ```
+pub struct NodeRef<'a, U, S> {
+    layout: &'a Layout<U, S>,
+    id: NodeId,
+    node: &'a LayoutNode,
+}
+
+impl<U, S> Clone for NodeRef<'_, U, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U, S> Copy for NodeRef<'_, U, S> {}
+
+impl<'a, U, S> NodeRef<'a, U, S> {
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        match self.node {
+            LayoutNode::Container(_) => NodeKind::Container,
+            LayoutNode::Leaf(_) => NodeKind::Leaf,
+            LayoutNode::Floating(_) => NodeKind::Floating,
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.node.is_leaf()
+    }
+
+    pub fn is_container(&self) -> bool {
+        self.node.is_container()
+    }
+
+    pub fn is_floating(&self) -> bool {
+        self.node.is_floating()
+    }
+
+    /// The widget shown by a leaf or floating window
+    pub fn widget_id(&self) -> Option<WidgetId> {
+        self.node.widget()
+    }
+
+    /// The children of a container, which is empty for other nodes
+    pub fn children(&self) -> impl Iterator<Item = NodeId> + 'a {
+        self.node
+            .container()
+            .into_iter()
+            .flat_map(|c| &c.children)
+            .copied()
+    }
+
+    /// The container holding the node, if it's in the tree
+    pub fn parent(&self) -> Option<NodeId> {
+        self.layout.parent(self.id)
+    }
+
+    /// The axis a container lays its children out along
+    pub fn direction(&self) -> Option<Axis> {
+        self.node.container().map(|c| c.direction)
+    }
+
+    /// The bounds of the node from the last time the layout was computed
+    pub fn rect(&self) -> Option<Rect> {
+        self.layout.layout(self.id).copied()
+    }
+
+    /// The tag set with [`Layout::set_tag`]
+    pub fn tag(&self) -> Option<&'a str> {
+        self.layout.tag(self.id)
+    }
+
+    /// The z-index of a floating window
+    pub fn z_index(&self) -> Option<usize> {
+        self.node.floating().map(Floating::z_index)
+    }
+
+    /// Whether the node is a floating window with a shadow
+    pub fn has_shadow(&self) -> bool {
+        self.node.floating().is_some_and(Floating::has_shadow)
+    }
+
+    /// Whether the node is a modal floating window, see [`Layout::set_modal`]
+    pub fn is_modal(&self) -> bool {
+        self.node.floating().is_some_and(Floating::is_modal)
+    }
+}
+
+/// Rounds each of `targets` to a whole number of cells such that the result sums to the rounded
+/// sum of the targets, giving the leftover cells to the targets with the largest fractional parts.
+fn largest_remainder(targets: &[f32]) -> Vec<usize> {
+    let mut sizes = targets
+        .iter()
+        .map(|t| t.floor() as usize)
+        .collect::<Vec<_>>();
+    let total = targets.iter().sum::<f32>().round() as usize;
+    let leftover = total.saturating_sub(sizes.iter().sum::<usize>());
+
+    let mut order = (0..targets.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        let a = targets[*a] - targets[*a].floor();
+        let b = targets[*b] - targets[*b].floor();
+        b.total_cmp(&a)
+    });
+    order.into_iter().take(leftover).for_each(|i| sizes[i] += 1);
+    sizes
+}
+
+/// Splits `total` cells between nodes with the given `(min, max)` bounds, as evenly as the bounds
+/// allow.
+///
+/// Nodes whose even share would violate their bounds are clamped, and the rest of the space is
+/// shared between the remaining nodes until every node is within its bounds. If the minimums
+/// can't all be satisfied, each node gets space in proportion to its minimum instead.
+fn distribute(total: usize, bounds: &[(usize, usize)]) -> Vec<usize> {
+    let min_total = bounds.iter().map(|(min, _)| *min).sum::<usize>();
+    if min_total > total {
+        // Over-constrained, so truncate proportionally
+        let mut sizes = bounds
+            .iter()
+            .map(|(min, _)| min * total / min_total)
+            .collect::<Vec<_>>();
+        let mut leftover = total - sizes.iter().sum::<usize>();
+        for size in sizes.iter_mut() {
+            if leftover == 0 {
+                break;
+            }
+            *size += 1;
+            leftover -= 1;
+        }
+        return sizes;
+    }
+
+    let mut sizes = vec![0; bounds.len()];
+    let mut clamped = vec![false; bounds.len()];
+    loop {
+        let free = clamped.iter().filter(|c| !**c).count();
+        if free == 0 {
+            return sizes;
+        }
+        let used = sizes
+            .iter()
+            .zip(&clamped)
+            .filter(|(_, c)| **c)
+            .map(|(s, _)| *s)
+            .sum::<usize>();
+        let available = total.saturating_sub(used);
+        let share = available / free;
+        let mut extra = available % free;
+        for (size, _) in sizes.iter_mut().zip(&clamped).filter(|(_, c)| !**c) {
+            *size = share;
+            if extra > 0 {
+                *size += 1;
+                extra -= 1;
+            }
+        }
+
+        // Clamp nodes below their minimum first, since growing them takes space from the rest.
+        // Only once every minimum is met are nodes above their maximum clamped.
+        let mut changed = false;
+        for (i, (min, _)) in bounds.iter().enumerate() {
+            if !clamped[i] && sizes[i] < *min {
+                sizes[i] = *min;
+                clamped[i] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            for (i, (_, max)) in bounds.iter().enumerate() {
+                if !clamped[i] && sizes[i] > *max {
+                    sizes[i] = *max;
+                    clamped[i] = true;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return sizes;
+        }
+    }
+}
+
 /// The struct that manages layout for a Sanguine app
 pub struct Layout<U = (), S = ()> {
     /// The arena containing all nodes
@@ -107,8 +347,23 @@ pub struct Layout<U = (), S = ()> {
     root: NodeId,
     /// Floating windows attached to the layout
     floating: FloatStack<U, S>,
-    /// Whether the layout should be recomputed
-    dirty: bool,
+    /// Leaves docked to the top of the layout, outermost first
+    docked_top: Vec<NodeId>,
+    /// Leaves docked to the bottom of the layout, outermost first
+    docked_bottom: Vec<NodeId>,
+    /// User-supplied tags identifying leaves and floats in a [`LayoutSpec`]
+    tags: SecondaryMap<NodeId, String>,
+    /// Application data attached to nodes with [`Layout::set_data`]
+    data: SecondaryMap<NodeId, Box<dyn Any + Send>>,
+    /// Nodes hidden with [`Layout::set_hidden`]
+    hidden: SecondaryMap<NodeId, ()>,
+    /// Containers whose children need to be laid out again. Their own bounds are unaffected, so
+    /// only their subtrees are recomputed.
+    dirty: SecondaryMap<NodeId, ()>,
+    /// Whether the whole layout should be recomputed
+    dirty_all: bool,
+    /// The bounds passed to the last call to [`Layout::compute`]
+    bounds: Rect,
 }
 
 impl<U, S> Default for Layout<U, S> {
@@ -117,6 +372,16 @@ impl<U, S> Default for Layout<U, S> {
     }
 }
 
+/// The side of a node that one of its siblings is on, in a container laid out along `axis`
+fn sibling_side(axis: Axis, before: bool) -> Direction {
+    match (axis, before) {
+        (Axis::Horizontal, true) => Direction::Left,
+        (Axis::Horizontal, false) => Direction::Right,
+        (Axis::Vertical, true) => Direction::Up,
+        (Axis::Vertical, false) => Direction::Down,
+    }
+}
+
 impl<U, S> Layout<U, S> {
     /// Initializes a new layout, and creates a root node
     pub fn new() -> Self {
@@ -127,6 +392,8 @@ impl<U, S> Layout<U, S> {
             size: None,
             children: vec![],
             parent: None,
+            gap: 0,
+            padding: 0,
         }));
         layout.insert(root, Rect::default());
         Self {
@@ -134,116 +401,305 @@ impl<U, S> Layout<U, S> {
             layout,
             root,
             floating: FloatStack::new(),
+            docked_top: vec![],
+            docked_bottom: vec![],
+            tags: SecondaryMap::new(),
+            data: SecondaryMap::new(),
+            hidden: SecondaryMap::new(),
+            dirty: SecondaryMap::new(),
             // The first call to `compute` should always recompute the layout
-            dirty: true,
+            dirty_all: true,
+            bounds: Rect::default(),
         }
     }
 
+    /// The window at a position on screen: the topmost float there, or else the leaf or docked
+    /// window there
     pub fn node_at_pos(&self, pos: (u16, u16)) -> Option<NodeId> {
-        self.floating
-            .iter()
-            .find_map(|id| {
-                self.layout(*id)
-                    .map(|rect| (id, rect))
-                    .and_then(|(id, rect)| {
-                        if rect.contains(pos.0 as f32, pos.1 as f32) {
-                            Some(*id)
-                        } else {
-                            None
-                        }
-                    })
-            })
-            .or_else(|| {
-                self.leaves().into_iter().find(|v| {
-                    let Some(rect) = self.layout(*v) else {
-                        return false;
-                    };
+        let (x, y) = (pos.0 as f32, pos.1 as f32);
+        self.topmost_float_at(pos).or_else(|| {
+            let mut windows = self.leaves().into_iter().chain(self.shown_docked());
+            windows.find(|node| self.layout(*node).is_some_and(|rect| rect.contains(x, y)))
+        })
+    }
 
-                    rect.contains(pos.0 as f32, pos.1 as f32)
-                })
-            })
+    /// The float drawn on top at a position on screen, if any. Floats are drawn in the order of
+    /// [`Layout::floats`], which is sorted by z-index, so they're checked from the last one back
+    /// and the window that's visible there is the one found.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, Modifiers, MouseButtons, MouseEvent},
+    ///     widgets::TextBox,
+    ///     App,
+    /// };
+    ///
+    /// let mut app = App::<(), ()>::test(40, 12);
+    /// let below = app.open_float(TextBox::new(), 20, 6);
+    /// let above = app.open_float(TextBox::new(), 20, 6);
+    /// app.render().unwrap();
+    ///
+    /// // The float opened last is on top, until the other one is given a higher z-index
+    /// assert_eq!(app.inspect_layout(|l, _| l.topmost_float_at((20, 6))), Some(above));
+    /// app.update_layout(|layout, _| layout.set_z_index(below, 5));
+    /// assert_eq!(app.inspect_layout(|l, _| l.floats()), [above, below]);
+    /// assert_eq!(app.inspect_layout(|l, _| l.topmost_float_at((20, 6))), Some(below));
+    /// assert_eq!(app.inspect_layout(|l, _| l.topmost_float_at((0, 0))), None);
+    ///
+    /// // Clicking where they overlap focuses the float drawn on top
+    /// app.backend_mut::<TestBackend>().unwrap().push_input(InputEvent::Mouse(MouseEvent {
+    ///     x: 21,
+    ///     y: 7,
+    ///     mouse_buttons: MouseButtons::LEFT,
+    ///     modifiers: Modifiers::NONE,
+    /// }));
+    /// app.handle_events().unwrap();
+    /// assert_eq!(app.get_focus(), Some(below));
+    /// ```
+    pub fn topmost_float_at(&self, pos: (u16, u16)) -> Option<NodeId> {
+        let (x, y) = (pos.0 as f32, pos.1 as f32);
+        let mut floats = self.shown_floats().rev();
+        floats.find(|node| self.layout(*node).is_some_and(|rect| rect.contains(x, y)))
     }
 
-    /// Returns nodes adjacent to the given node, along with the direction to get to them
+    /// Returns nodes adjacent to the given node, along with the direction to get to them: the
+    /// siblings next to it, or the children of those siblings if they're containers, and the
+    /// siblings of its parent. Siblings before a node are above it or to its left, and siblings
+    /// after it are below it or to its right.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     hsplit,
+    ///     layout::{leaf, Direction::*, Layout, Pane},
+    ///     vsplit,
+    ///     widgets::TextBox,
+    ///     WidgetStore,
+    /// };
+    ///
+    /// // The layout of the demo: a window on the left, and a column holding two windows side by
+    /// // side above a third
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let demo = hsplit![
+    ///     leaf(TextBox::new()).named("left"),
+    ///     vsplit![
+    ///         hsplit![
+    ///             leaf(TextBox::new()).named("menu"),
+    ///             leaf(TextBox::new()).named("canvas"),
+    ///         ],
+    ///         leaf(TextBox::new()).named("editor"),
+    ///     ],
+    /// ];
+    /// let built = layout.build(&mut widgets, demo).unwrap();
+    /// let node = |name| built.get(name).unwrap();
+    /// let top = layout.parent(node("menu")).unwrap();
+    ///
+    /// assert_eq!(
+    ///     layout.adjacent(node("left")),
+    ///     [(top, Right), (node("editor"), Right)]
+    /// );
+    /// assert_eq!(
+    ///     layout.adjacent(node("editor")),
+    ///     [(node("menu"), Up), (node("canvas"), Up), (node("left"), Left)]
+    /// );
+    /// assert_eq!(
+    ///     layout.adjacent(node("canvas")),
+    ///     [(node("menu"), Left), (node("editor"), Down)]
+    /// );
+    ///
+    /// // Three columns of three windows, named by their column and row
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut grid = Pane::row();
+    /// for column in 0..3 {
+    ///     let mut cells = Pane::column();
+    ///     for row in 0..3 {
+    ///         cells = cells.child(leaf(TextBox::new()).named(format!("{column}{row}")));
+    ///     }
+    ///     grid = grid.child(cells);
+    /// }
+    /// let built = layout.build(&mut widgets, grid).unwrap();
+    /// let node = |name: &str| built.get(name).unwrap();
+    /// let column = |index| layout.parent(node(&format!("{index}0"))).unwrap();
+    ///
+    /// assert_eq!(
+    ///     layout.adjacent(node("11")),
+    ///     [(node("10"), Up), (node("12"), Down), (column(0), Left), (column(2), Right)]
+    /// );
+    /// assert_eq!(
+    ///     layout.adjacent(node("00")),
+    ///     [(node("01"), Down), (column(1), Right), (column(2), Right)]
+    /// );
+    /// assert_eq!(
+    ///     layout.adjacent(node("22")),
+    ///     [(node("21"), Up), (column(0), Left), (column(1), Left)]
+    /// );
+    /// ```
     pub fn adjacent(&self, node: NodeId) -> Vec<(NodeId, Direction)> {
         let mut neighbors = Vec::new();
         if self.is_floating(node) {
             return neighbors;
         }
-        let parent = self.parent(node).unwrap();
-        let direction = self.direction(parent).unwrap();
-        let children = self.children(parent).unwrap();
-        let index = children.iter().position(|id| *id == node).unwrap();
-        if index > 0 {
-            let node = children[index - 1];
-            if self.is_leaf(node) {
-                neighbors.push((
-                    node,
-                    match direction {
-                        Axis::Vertical => Direction::Up,
-                        Axis::Horizontal => Direction::Left,
-                    },
-                ));
-            } else {
-                let direction = self.direction(node).unwrap();
-                let children = self.children(node).unwrap();
-                children.iter().for_each(|id| {
-                    neighbors.push((
-                        *id,
-                        match direction {
-                            Axis::Vertical => Direction::Up,
-                            Axis::Horizontal => Direction::Left,
-                        },
-                    ));
-                });
-            }
-        }
-        if index < children.len() - 1 {
-            let node = children[index + 1];
-            if self.is_leaf(node) {
-                neighbors.push((
-                    node,
-                    match direction {
-                        Axis::Vertical => Direction::Down,
-                        Axis::Horizontal => Direction::Right,
-                    },
-                ));
-            } else {
-                let direction = self.direction(node).unwrap();
-                let children = self.children(node).unwrap();
-                children.iter().for_each(|id| {
-                    neighbors.push((
-                        *id,
-                        match direction {
-                            Axis::Vertical => Direction::Right,
-                            Axis::Horizontal => Direction::Down,
-                        },
-                    ));
-                });
+        // Docked and detached leaves have no neighbors
+        let Some(parent) = self.parent(node) else {
+            return neighbors;
+        };
+        let (Some(direction), Some(children), Some(index)) = (
+            self.direction(parent),
+            self.children(parent),
+            self.child_index(parent, node),
+        ) else {
+            return neighbors;
+        };
+        let siblings = [(index.checked_sub(1), true), (Some(index + 1), false)];
+        for (sibling, before) in siblings {
+            let Some(sibling) = sibling.and_then(|i| children.get(i)).copied() else {
+                continue;
+            };
+            // A container is entirely on one side, so its children are too
+            let side = sibling_side(direction, before);
+            if self.is_leaf(sibling) {
+                neighbors.push((sibling, side));
+            } else if let Some(children) = self.children(sibling) {
+                neighbors.extend(children.iter().map(|child| (*child, side)));
             }
         }
 
-        let parent_parent = self.parent(parent);
-        if let Some(grandparent) = parent_parent {
-            let direction = self.direction(grandparent).unwrap();
-            let children = self.children(grandparent).unwrap();
-            children.iter().for_each(|id| {
-                if *id == parent {
-                    return;
+        // The root has no parent, so a node directly under it has no grandparent
+        let grandparent = self.parent(parent);
+        if let Some((direction, children, index)) = grandparent.and_then(|grandparent| {
+            Some((
+                self.direction(grandparent)?,
+                self.children(grandparent)?,
+                self.child_index(grandparent, parent)?,
+            ))
+        }) {
+            for (i, id) in children.iter().enumerate() {
+                if i != index {
+                    neighbors.push((*id, sibling_side(direction, i < index)));
                 }
-                neighbors.push((
-                    *id,
-                    match direction {
-                        Axis::Vertical => Direction::Down,
-                        Axis::Horizontal => Direction::Left,
-                    },
-                ));
-            });
+            }
         }
 
         neighbors
     }
 
+    /// Finds the window on screen in the given direction from a node, using the computed layout
+    /// rather than the tree. Candidates must lie entirely on that side of the node and overlap it
+    /// on the other axis. The nearest is chosen, preferring the one with the largest overlap.
+    ///
+    /// Floating windows are only candidates when moving from another floating window.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     hsplit,
+    ///     layout::{leaf, BuiltLayout, Constraint, Direction::*, Layout, Pane, Rect},
+    ///     vsplit,
+    ///     widgets::TextBox,
+    ///     WidgetStore,
+    /// };
+    ///
+    /// // The window to the left, right, above and below each window
+    /// fn check(layout: &Layout<(), ()>, built: &BuiltLayout, cases: &[(&str, [Option<&str>; 4])]) {
+    ///     let node = |name: &str| built.get(name).unwrap();
+    ///     for &(from, expected) in cases {
+    ///         for (direction, expected) in [Left, Right, Up, Down].into_iter().zip(expected) {
+    ///             assert_eq!(
+    ///                 layout.node_in_direction(node(from), direction),
+    ///                 expected.map(node),
+    ///                 "{direction:?} from {from}"
+    ///             );
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut widgets = WidgetStore::new();
+    /// let window = |name: &str| -> Pane<(), ()> { leaf(TextBox::new()).named(name) };
+    ///
+    /// // A 2x2 grid
+    /// let mut layout = Layout::new();
+    /// let grid = hsplit![
+    ///     vsplit![window("top left"), window("bottom left")],
+    ///     vsplit![window("top right"), window("bottom right")],
+    /// ];
+    /// let built = layout.build(&mut widgets, grid).unwrap();
+    /// layout.compute(&Rect::from_size((20, 10)));
+    /// check(
+    ///     &layout,
+    ///     &built,
+    ///     &[
+    ///         ("top left", [None, Some("top right"), None, Some("bottom left")]),
+    ///         ("top right", [Some("top left"), None, None, Some("bottom right")]),
+    ///         ("bottom left", [None, Some("bottom right"), Some("top left"), None]),
+    ///         ("bottom right", [Some("bottom left"), None, Some("top right"), None]),
+    ///     ],
+    /// );
+    ///
+    /// // A tall window beside a T: two windows of different widths above a wide one
+    /// let mut layout = Layout::new();
+    /// let t = hsplit![
+    ///     window("left"),
+    ///     vsplit![
+    ///         hsplit![window("menu").constraint(Constraint::Fixed(6)), window("canvas")]
+    ///             .constraint(Constraint::Fixed(6)),
+    ///         window("editor"),
+    ///     ],
+    /// ];
+    /// let built = layout.build(&mut widgets, t).unwrap();
+    /// layout.compute(&Rect::from_size((20, 10)));
+    /// check(
+    ///     &layout,
+    ///     &built,
+    ///     &[
+    ///         ("left", [None, Some("menu"), None, None]),
+    ///         ("menu", [Some("left"), Some("canvas"), None, Some("editor")]),
+    ///         ("canvas", [Some("menu"), None, None, Some("editor")]),
+    ///         ("editor", [Some("left"), None, Some("menu"), None]),
+    ///     ],
+    /// );
+    /// ```
+    pub fn node_in_direction(&self, node: NodeId, direction: Direction) -> Option<NodeId> {
+        let from = self.layout(node)?;
+        let floats = if self.is_floating(node) {
+            self.floats()
+        } else {
+            vec![]
+        };
+        self.leaves()
+            .into_iter()
+            .chain(self.docked())
+            .chain(floats)
+            .filter(|candidate| *candidate != node)
+            .filter_map(|candidate| {
+                let rect = self.layout(candidate)?;
+                let (distance, overlap) = match direction {
+                    Direction::Left => (
+                        from.left() - rect.right(),
+                        rect.bottom().min(from.bottom()) - rect.top().max(from.top()),
+                    ),
+                    Direction::Right => (
+                        rect.left() - from.right(),
+                        rect.bottom().min(from.bottom()) - rect.top().max(from.top()),
+                    ),
+                    Direction::Up => (
+                        from.top() - rect.bottom(),
+                        rect.right().min(from.right()) - rect.left().max(from.left()),
+                    ),
+                    Direction::Down => (
+                        rect.top() - from.bottom(),
+                        rect.right().min(from.right()) - rect.left().max(from.left()),
+                    ),
+                };
+                (distance >= 0. && overlap > 0.).then_some((candidate, distance, overlap))
+            })
+            .min_by(|(_, a_distance, a_overlap), (_, b_distance, b_overlap)| {
+                a_distance
+                    .total_cmp(b_distance)
+                    .then(b_overlap.total_cmp(a_overlap))
+            })
+            .map(|(candidate, _, _)| candidate)
+    }
+
     /// Returns nodes that are adjacent to the given node on the given side.
     pub fn adjacent_on_side(&self, node: NodeId, side: Direction) -> Vec<NodeId> {
         self.adjacent(node)
@@ -298,55 +754,169 @@ impl<U, S> Layout<U, S> {
 
     /// Clears the layout and **drops** all nodes that are not part of the tree.
     pub fn clean(&mut self) {
-        self.dirty = true;
+        self.dirty_all = true;
+        self.dirty.clear();
         self.layout.clear();
         self.nodes.clear();
+        self.docked_top.clear();
+        self.docked_bottom.clear();
     }
 
     /// Computes the layout of the tree for the given bounds. This must be called after each change to the tree.
+    ///
+    /// Only the subtrees affected by changes since the last call are recomputed, unless the bounds
+    /// have changed or the whole layout was marked dirty.
     pub fn compute(&mut self, bounds: &Rect) {
-        if self.dirty {
-            self.compute_tree(None, bounds);
-            self.dirty = false;
+        if self.dirty_all || self.bounds != *bounds {
+            self.bounds = *bounds;
+            let bounds = self.compute_docked(bounds);
+            self.compute_tree(None, &bounds);
+            self.dirty_all = false;
+        } else {
+            let dirty = self.dirty.keys().collect::<Vec<_>>();
+            for node in dirty {
+                // Subtrees of dirty nodes are recomputed along with them
+                if !self.in_tree(node) || self.has_dirty_ancestor(node) {
+                    continue;
+                }
+                if let Some(bounds) = self.layout(node).cloned() {
+                    self.compute_tree(Some(node), &bounds);
+                }
+            }
+        }
+        self.dirty.clear();
+    }
+
+    /// Checks whether the node is reachable from the root
+    fn in_tree(&self, node: NodeId) -> bool {
+        self.is_root(self.ancestors(node).last().unwrap_or(node))
+    }
+
+    fn has_dirty_ancestor(&self, node: NodeId) -> bool {
+        self.ancestors(node)
+            .any(|parent| self.dirty.contains_key(parent))
+    }
+
+    /// Marks a container's children as needing to be laid out again
+    fn invalidate(&mut self, node: NodeId) {
+        self.dirty.insert(node, ());
+    }
+
+    /// Marks a node's siblings as needing to be laid out again after the node's size changes. The
+    /// whole layout is invalidated for docked nodes, since they affect the bounds of the tree.
+    fn invalidate_parent(&mut self, node: NodeId) {
+        if self.is_docked(node) {
+            self.dirty_all = true;
+        } else if let Some(parent) = self.parent(node) {
+            self.invalidate(parent);
+        }
+    }
+
+    /// Reserves rows for docked leaves from the edges of the bounds, returning the bounds left
+    /// over for the rest of the tree.
+    fn compute_docked(&mut self, bounds: &Rect) -> Rect {
+        let mut top = bounds.top();
+        let mut bottom = bounds.bottom();
+        let shown = |nodes: &[NodeId]| -> Vec<NodeId> {
+            nodes
+                .iter()
+                .filter(|node| !self.hidden.contains_key(**node))
+                .copied()
+                .collect()
+        };
+        for node in shown(&self.docked_top) {
+            let height = self.dock_height(node).min(bottom - top);
+            self.layout
+                .insert(node, Rect::new(bounds.x, top, bounds.width, height));
+            top += height;
+        }
+        for node in shown(&self.docked_bottom) {
+            let height = self.dock_height(node).min(bottom - top);
+            bottom -= height;
+            self.layout
+                .insert(node, Rect::new(bounds.x, bottom, bounds.width, height));
+        }
+        Rect::new(bounds.x, top, bounds.width, bottom - top)
+    }
+
+    /// The number of rows reserved for a docked leaf: its fixed size if it has one, otherwise a
+    /// single row.
+    fn dock_height(&self, node: NodeId) -> f32 {
+        match self.size(node) {
+            Constraint::Fixed(rows) => rows as f32,
+            _ => 1.,
         }
     }
 
-    pub fn mark_dirty(&mut self) {
-        self.dirty = true;
+    /// Marks part of the layout as needing to be recomputed on the next call to
+    /// [`Layout::compute`]. Passing a node recomputes the container it belongs to, since a change
+    /// to a node can affect the size of its siblings. Passing `None` recomputes the whole layout.
+    pub fn mark_dirty(&mut self, node: Option<NodeId>) {
+        match node {
+            Some(node) if self.parent(node).is_some() || self.is_docked(node) => {
+                self.invalidate_parent(node)
+            }
+            Some(node) => self.invalidate(node),
+            None => self.dirty_all = true,
+        }
+    }
+
+    /// Whether any part of the layout needs to be recomputed
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_all || !self.dirty.is_empty()
     }
 
     /// Recursively computes the layout of the tree.
     fn compute_tree(&mut self, node: Option<NodeId>, bounds: &Rect) {
         let node = node.unwrap_or(self.root());
         self.compute_node(node, bounds);
-        if self.is_leaf(node) {
-        } else {
-            let children = self.children(node).unwrap().clone();
-            children.iter().for_each(|id| {
-                let bounds = self.layout(*id).unwrap().clone();
-                self.compute_tree(Some(*id), &bounds);
-            })
+        // Children are visited by index rather than cloning the list, since laying them out needs
+        // the layout mutably
+        let mut index = 0;
+        while let Some(child) = self.children(node).and_then(|c| c.get(index)).copied() {
+            index += 1;
+            if self.hidden.contains_key(child) {
+                continue;
+            }
+            let Some(bounds) = self.layout(child).copied() else {
+                continue;
+            };
+            self.compute_tree(Some(child), &bounds);
         }
     }
 
     /// Computes layout for an individual node
     fn compute_node(&mut self, node: NodeId, bounds: &Rect) {
-        self.layout.insert(node, bounds.clone());
+        self.layout.insert(node, *bounds);
         if self.is_leaf(node) {
         } else {
             // TODO: Handle size hints
-            let children = self.children(node).unwrap();
+            let children = self.shown_children(node);
             let axis = self.direction(node).unwrap();
             let sizes = children
                 .iter()
                 .map(|id| (*id, self.size(*id)))
                 .collect::<Vec<_>>();
 
+            let (bounds, gap) = self.content_bounds(node, bounds);
+            let bounds = &bounds;
+            let gaps = (gap * sizes.len().saturating_sub(1)) as f32;
+            let distributable = match &axis {
+                Axis::Horizontal => Rect {
+                    width: bounds.width - gaps,
+                    ..*bounds
+                },
+                Axis::Vertical => Rect {
+                    height: bounds.height - gaps,
+                    ..*bounds
+                },
+            };
+
             let mut current = match &axis {
                 Axis::Horizontal => bounds.x,
                 Axis::Vertical => bounds.y,
             };
-            self.compute_sizes(bounds, &sizes, &axis)
+            self.compute_sizes(&distributable, &sizes, &axis)
                 .iter()
                 .for_each(|(k, v)| {
                     let size = match v {
@@ -375,12 +945,54 @@ impl<U, S> Layout<U, S> {
                         width,
                         height,
                     };
-                    current += size;
+                    current += size + gap as f32;
                     self.layout.insert(*k, widget_rect);
                 });
         }
     }
 
+    /// Returns the bounds that a container's children are laid out in, inset by its padding, along
+    /// with the gap between children. When the container is too small to fit a cell for every
+    /// child, the gap is dropped first, then the padding.
+    fn content_bounds(&self, node: NodeId, bounds: &Rect) -> (Rect, usize) {
+        let Some(LayoutNode::Container(container)) = self.nodes.get(node) else {
+            return (*bounds, 0);
+        };
+        let count = container
+            .children
+            .iter()
+            .filter(|child| !self.hidden.contains_key(**child))
+            .count();
+        let (extent, cross) = match container.direction {
+            Axis::Horizontal => (bounds.width, bounds.height),
+            Axis::Vertical => (bounds.height, bounds.width),
+        };
+        let fits = |padding: usize, gap: usize| {
+            let padding = (padding * 2) as f32;
+            extent - padding - (gap * count.saturating_sub(1)) as f32 >= count as f32
+                && cross - padding >= 1.
+        };
+
+        let mut gap = container.gap;
+        let mut padding = container.padding;
+        if !fits(padding, gap) {
+            gap = 0;
+        }
+        if !fits(padding, gap) {
+            padding = 0;
+        }
+        let inset = padding as f32;
+        (
+            Rect {
+                x: bounds.x + inset,
+                y: bounds.y + inset,
+                width: bounds.width - inset * 2.,
+                height: bounds.height - inset * 2.,
+            },
+            gap,
+        )
+    }
+
     /// Actual size computation for layout
     fn compute_sizes(
         &mut self,
@@ -393,92 +1005,316 @@ impl<U, S> Layout<U, S> {
             Axis::Horizontal => bounds.width,
             Axis::Vertical => bounds.height,
         };
-        let mut remaining = width;
+        let extent = width.max(0.).floor() as usize;
 
         let fixed = sizes
             .iter()
             .filter_map(|(k, size)| match size {
-                Constraint::Fixed(size) => {
-                    new_sizes.push((*k, Constraint::Fixed(*size)));
-                    Some(size)
-                }
+                Constraint::Fixed(size) => Some((*k, *size)),
                 _ => None,
             })
-            .sum::<usize>();
-
-        remaining -= fixed as f32;
+            .collect::<Vec<_>>();
+        let fixed_total = fixed.iter().map(|(_, size)| size).sum::<usize>();
+        if fixed_total > extent {
+            // Over-committed, so shrink the fixed children proportionally to fit
+            let targets = fixed
+                .iter()
+                .map(|(_, size)| (*size * extent) as f32 / fixed_total as f32)
+                .collect::<Vec<_>>();
+            fixed
+                .iter()
+                .zip(largest_remainder(&targets))
+                .for_each(|((k, _), size)| new_sizes.push((*k, Constraint::Fixed(size))));
+        } else {
+            fixed
+                .iter()
+                .for_each(|(k, size)| new_sizes.push((*k, Constraint::Fixed(*size))));
+        }
+        let mut remaining = extent.saturating_sub(fixed_total) as f32;
 
         let mut percents = sizes
             .iter()
             .filter_map(|(k, size)| match size {
-                Constraint::Percentage(percent) => Some((k, *percent)),
+                Constraint::Percentage(percent) => Some((k, percent.max(0.))),
                 _ => None,
             })
             .collect::<Vec<_>>();
-        let n_percent = percents.len();
         let percent = percents.iter().map(|(_, f)| f).sum::<f32>();
 
         if percent > 1.0 {
-            let diff = percent - 1.0;
-            let avg = diff / n_percent as f32;
-            percents.iter_mut().for_each(|(_, f)| *f -= avg);
-        }
-        let mut pct_total = 0;
-        percents.iter_mut().for_each(|(k, f)| {
-            *f *= remaining;
-            let size = f.round() as usize;
-            pct_total += size;
-            new_sizes.push((**k, Constraint::Fixed(size)));
-        });
+            // Over-committed, so scale the percentages down proportionally to sum to 100%
+            percents.iter_mut().for_each(|(_, f)| *f /= percent);
+        }
+        let targets = percents
+            .iter()
+            .map(|(_, f)| (f * remaining).max(0.))
+            .collect::<Vec<_>>();
+        let pct_sizes = largest_remainder(&targets);
+        let pct_total = pct_sizes.iter().sum::<usize>();
+        percents
+            .iter()
+            .zip(pct_sizes)
+            .for_each(|((k, _), size)| new_sizes.push((**k, Constraint::Fixed(size))));
         remaining -= pct_total as f32;
 
-        let fill = sizes
+        // Aspect nodes are sized from the cross axis, and shrunk proportionally if they don't fit
+        let cross = match axis {
+            Axis::Horizontal => bounds.height,
+            Axis::Vertical => bounds.width,
+        }
+        .max(0.);
+        let aspects = sizes
+            .iter()
+            .filter_map(|(k, size)| match size {
+                Constraint::Aspect(ratio) if *ratio > 0. => Some((
+                    *k,
+                    match axis {
+                        Axis::Horizontal => cross * ratio,
+                        Axis::Vertical => cross / ratio,
+                    },
+                )),
+                Constraint::Aspect(_) => Some((*k, 0.)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let aspect_total = aspects.iter().map(|(_, size)| size).sum::<f32>();
+        let scale = if aspect_total > remaining.max(0.) {
+            remaining.max(0.) / aspect_total
+        } else {
+            1.
+        };
+        let targets = aspects
+            .iter()
+            .map(|(_, size)| size * scale)
+            .collect::<Vec<_>>();
+        let aspect_sizes = largest_remainder(&targets);
+        remaining -= aspect_sizes.iter().sum::<usize>() as f32;
+        aspects
+            .iter()
+            .zip(aspect_sizes)
+            .for_each(|((k, _), size)| new_sizes.push((*k, Constraint::Fixed(size))));
+
+        // Fill, Min and Max nodes share the remaining space, within their bounds
+        let flexible = sizes
             .iter()
-            .enumerate()
-            .filter_map(|(i, (k, size))| match size {
-                Constraint::Fill => Some((k, i)),
+            .filter_map(|(k, size)| match size {
+                Constraint::Fill => Some((*k, (0, usize::MAX))),
+                Constraint::Min(min) => Some((*k, (*min, usize::MAX))),
+                Constraint::Max(max) => Some((*k, (0, *max))),
                 _ => None,
             })
             .collect::<Vec<_>>();
+        let bounds = flexible.iter().map(|(_, b)| *b).collect::<Vec<_>>();
+        distribute(remaining.max(0.).floor() as usize, &bounds)
+            .into_iter()
+            .zip(flexible)
+            .for_each(|(size, (k, _))| new_sizes.push((k, Constraint::Fixed(size))));
 
-        let nfill = fill.len();
+        // Sizes are computed grouped by constraint type, but must be laid out in child order
+        new_sizes.sort_by_key(|(k, _)| sizes.iter().position(|(id, _)| id == k));
 
-        let fill_size = (remaining.floor() as usize / nfill) as f32;
-        let mut diff = remaining.floor() as usize % nfill;
-        fill.iter()
-            .map(|(k, _)| {
-                if diff > 0 {
-                    diff -= 1;
-                    (k, fill_size.floor() + 1.)
-                } else {
-                    (
-                        k,
-                        match &axis {
-                            Axis::Horizontal => fill_size, /* .floor() */
-                            Axis::Vertical => fill_size.ceil(),
-                        },
-                    )
-                }
+        // The last child absorbs any residual so that the children exactly fill the container,
+        // unless it has a maximum size or aspect ratio and would need to grow
+        let total = new_sizes
+            .iter()
+            .map(|(_, size)| match size {
+                Constraint::Fixed(size) => *size,
+                _ => 0,
             })
-            .for_each(|(k, v)| {
-                new_sizes.push((**k, Constraint::Fixed(v/* fill_size.floor() */ as usize)));
-            });
+            .sum::<usize>();
+        let last_is_bounded = matches!(
+            sizes.last(),
+            Some((_, Constraint::Max(_) | Constraint::Aspect(_)))
+        );
+        if let Some((_, Constraint::Fixed(last))) = new_sizes.last_mut() {
+            if total > extent {
+                *last = last.saturating_sub(total - extent);
+            } else if total < extent && !last_is_bounded {
+                *last += extent - total;
+            }
+        }
 
         new_sizes
     }
 
-    /// Get the size hint of a given node
+    /// Finds the pair of adjacent siblings whose shared edge is within one cell of the given
+    /// position, returning them in layout order. Positions covered by a floating window never
+    /// hit an edge.
+    pub fn split_at(&self, pos: (u16, u16)) -> Option<(NodeId, NodeId)> {
+        let node = self.node_at_pos(pos)?;
+        if self.is_floating(node) {
+            return None;
+        }
+        let (x, y) = (pos.0 as f32, pos.1 as f32);
+        let mut current = node;
+        while let Some(parent) = self.parent(current) {
+            let rect = self.layout(current)?;
+            let children = self.children(parent)?;
+            let index = self.child_index(parent, current)?;
+            let (pos, start, end) = match self.direction(parent)? {
+                Axis::Horizontal => (x, rect.left(), rect.right()),
+                Axis::Vertical => (y, rect.top(), rect.bottom()),
+            };
+            if index + 1 < children.len() && pos >= end - 1. {
+                return Some((current, children[index + 1]));
+            }
+            if index > 0 && pos < start + 1. {
+                return Some((children[index - 1], current));
+            }
+            current = parent;
+        }
+        None
+    }
+
+    /// Grows the node by `delta` cells towards `direction`, or shrinks it if `delta` is negative,
+    /// by moving the edge it shares with a sibling along its parent's axis.
+    ///
+    /// If the node's parent is laid out along the other axis, the nearest ancestor that can be
+    /// resized in that direction is resized instead. A node with no sibling on the given side
+    /// moves its edge with the sibling on the other side, so the last child in a container
+    /// shrinks its previous sibling. Resizing against the edge of the layout does nothing, and
+    /// neither node sharing the edge is made smaller than three cells.
+    ///
+    /// Returns [`Error::NodeNotFound`] if the node doesn't exist.
+    pub fn resize_node(&mut self, node: NodeId, direction: Direction, delta: i32) -> Result<()> {
+        if !self.nodes.contains_key(node) {
+            return Err(Error::NodeNotFound(node));
+        }
+        let axis = match direction {
+            Direction::Left | Direction::Right => Axis::Horizontal,
+            Direction::Up | Direction::Down => Axis::Vertical,
+        };
+        let forward = matches!(direction, Direction::Right | Direction::Down);
+        let extent = |rect: &Rect| match axis {
+            Axis::Horizontal => rect.width,
+            Axis::Vertical => rect.height,
+        };
+
+        let mut current = node;
+        while let Some(parent) = self.parent(current) {
+            // Hidden siblings take up no space, so the edge is shared with the nearest shown one
+            let children = self.shown_children(parent);
+            let Some(index) = children.iter().position(|child| *child == current) else {
+                return Ok(());
+            };
+            let prev = index.checked_sub(1).map(|i| children[i]);
+            let next = children.get(index + 1).copied();
+            if self.direction(parent) == Some(axis) && (prev.is_some() || next.is_some()) {
+                let delta = delta as f32;
+                let (first, second, size) = match (forward, prev, next) {
+                    (true, _, Some(next)) | (false, None, Some(next)) => (
+                        current,
+                        next,
+                        self.layout(current).map(extent).unwrap_or(0.) + delta,
+                    ),
+                    (_, Some(prev), _) => (
+                        prev,
+                        current,
+                        self.layout(prev).map(extent).unwrap_or(0.) - delta,
+                    ),
+                    (_, None, None) => unreachable!(),
+                };
+                self.resize_split(first, second, size);
+                return Ok(());
+            }
+            current = parent;
+        }
+        Ok(())
+    }
+
+    /// Moves the edge between two adjacent siblings so that `first` is `size` cells along their
+    /// parent's axis, and `second` takes up the rest of the space they previously shared. Both
+    /// nodes are given percentage constraints, and neither is made smaller than
+    /// [`MIN_SPLIT_SIZE`]. Does nothing if the nodes aren't adjacent siblings.
+    pub(crate) fn resize_split(&mut self, first: NodeId, second: NodeId, size: f32) {
+        let Some(parent) = self.parent(first) else {
+            return;
+        };
+        let children = self.shown_children(parent);
+        let (Some(index), Some(axis), Some(bounds)) = (
+            children.iter().position(|child| *child == first),
+            self.direction(parent),
+            self.layout(parent).cloned(),
+        ) else {
+            return;
+        };
+        if children.get(index + 1) != Some(&second) {
+            return;
+        }
+        let extent = |rect: &Rect| match axis {
+            Axis::Horizontal => rect.width,
+            Axis::Vertical => rect.height,
+        };
+        let (Some(a), Some(b)) = (self.layout(first), self.layout(second)) else {
+            return;
+        };
+        let total = extent(a) + extent(b);
+        if total < MIN_SPLIT_SIZE * 2. {
+            return;
+        }
+        let size = size.round().clamp(MIN_SPLIT_SIZE, total - MIN_SPLIT_SIZE);
+
+        // Percentages are relative to the space left over after fixed-size siblings
+        let fixed = children
+            .iter()
+            .filter(|id| **id != first && **id != second)
+            .filter_map(|id| match self.size(*id) {
+                Constraint::Fixed(size) => Some(size),
+                _ => None,
+            })
+            .sum::<usize>();
+        let (bounds, gap) = self.content_bounds(parent, &bounds);
+        let gaps = gap * children.len().saturating_sub(1);
+        let remaining = extent(&bounds) - (fixed + gaps) as f32;
+        if remaining <= 0. {
+            return;
+        }
+        self.store_size(first, Constraint::Percentage(size / remaining));
+        self.store_size(second, Constraint::Percentage((total - size) / remaining));
+    }
+
+    /// Get the size hint of a given node. Leaves without a size set with [`Layout::set_size`] use
+    /// the [`Widget::constraint`](crate::Widget::constraint) of their widget, as of the last call
+    /// to [`Layout::update_constraints`].
     pub fn size(&self, node: NodeId) -> Constraint {
         match self.nodes.get(node) {
             Some(LayoutNode::Container(container)) => {
                 container.size.clone().unwrap_or(Constraint::Fill)
             }
-            Some(LayoutNode::Leaf(_)) => Constraint::Fill,
+            Some(LayoutNode::Leaf(leaf)) => leaf
+                .size
+                .clone()
+                .or_else(|| leaf.widget_size.clone())
+                .unwrap_or(Constraint::Fill),
             Some(LayoutNode::Floating(_)) => Constraint::Fill,
             None => Constraint::Fill,
         }
     }
 
+    /// Queries the [`Widget::constraint`](crate::Widget::constraint) of every leaf's widget, to be
+    /// used as its size unless one has been set with [`Layout::set_size`]. Leaves whose widget's
+    /// constraint changed are laid out again on the next call to [`Layout::compute`].
+    ///
+    /// This is called by the app before each frame is laid out.
+    pub fn update_constraints(&mut self, widgets: &WidgetStore<U, S>) {
+        // Only the leaves whose constraint changed are collected, so nothing is allocated while
+        // the layout is unchanged
+        let mut changed = vec![];
+        for (node, leaf) in self.nodes.iter_mut() {
+            let LayoutNode::Leaf(leaf) = leaf else {
+                continue;
+            };
+            let size = widgets.get(leaf.widget).map(|w| w.constraint(widgets));
+            if leaf.widget_size != size {
+                leaf.widget_size = size;
+                changed.push(node);
+            }
+        }
+        for node in changed {
+            self.invalidate_parent(node);
+        }
+    }
+
     /// Get the id of the root node
     pub fn root(&self) -> NodeId {
         self.root
@@ -491,96 +1327,350 @@ impl<U, S> Layout<U, S> {
 
     /// Helper for gathering leaves recursively
     fn leaves_inner(&self, node: NodeId, leaves: &mut Vec<NodeId>) {
-        match self.children(node) {
-            Some(children) => {
-                for child in children {
-                    self.leaves_inner(*child, leaves);
-                }
-            }
-            None => leaves.push(node),
+        if self.hidden.contains_key(node) {
+            return;
+        }
+        if !self.is_container(node) {
+            leaves.push(node);
+        }
+        for child in self.children_iter(node) {
+            self.leaves_inner(child, leaves);
         }
     }
 
-    /// Get the leaves of the layout tree
+    /// Get the leaves of the layout tree, except hidden leaves and leaves in hidden containers
     pub fn leaves(&self) -> Vec<NodeId> {
         let mut leaves = vec![];
 
-        self.leaves_inner(self.root, &mut leaves);
+        self.extend_leaves(&mut leaves);
 
         leaves
     }
 
-    /// Get the floats of the layout tree
+    /// Like [`Layout::leaves`], but adds the leaves to an existing buffer, which lets the app list
+    /// the windows of every frame without allocating
+    pub(crate) fn extend_leaves(&self, leaves: &mut Vec<NodeId>) {
+        self.leaves_inner(self.root, leaves);
+    }
+
+    /// Get the docked leaves of the layout, top first, except hidden leaves
+    pub fn docked(&self) -> Vec<NodeId> {
+        self.shown_docked().collect()
+    }
+
+    /// Like [`Layout::docked`], without collecting the leaves
+    pub(crate) fn shown_docked(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.docked_top
+            .iter()
+            .chain(self.docked_bottom.iter().rev())
+            .filter(|node| !self.hidden.contains_key(**node))
+            .copied()
+    }
+
+    /// Checks if the given node is docked to the top or bottom of the layout.
+    pub fn is_docked(&self, node: NodeId) -> bool {
+        self.docked_top.contains(&node) || self.docked_bottom.contains(&node)
+    }
+
+    /// Get the floats of the layout tree, from the bottom of the stack to the top, except hidden
+    /// floats. This is the order they're drawn in, so the last float is drawn over the others.
+    ///
+    /// The stack is sorted by z-index. A new float goes on top of the floats with the same or a
+    /// lower z-index, and [`Layout::raise_float`] and [`Layout::lower_float`] move a float to the
+    /// top or bottom of the whole stack.
+    ///
+    /// ```
+    /// use sanguine::{layout::{Layout, Rect}, WidgetStore, widgets::TextBox};
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::<(), ()>::new();
+    /// let mut float = || {
+    ///     let widget = widgets.register(TextBox::new());
+    ///     layout.add_floating(widget, Rect::new(0., 0., 10., 5.))
+    /// };
+    /// let (a, b, c) = (float(), float(), float());
+    /// assert_eq!(layout.floats(), [a, b, c]);
+    ///
+    /// layout.raise_float(a);
+    /// assert_eq!(layout.floats(), [b, c, a]);
+    /// layout.lower_float(c);
+    /// assert_eq!(layout.floats(), [c, b, a]);
+    ///
+    /// // Setting a z-index moves the float past the others
+    /// layout.set_z_index(c, 10);
+    /// assert_eq!(layout.floats(), [b, a, c]);
+    ///
+    /// // A new float with the default z-index goes above the others with that z-index, but below
+    /// // the floats that were raised above them
+    /// let d = layout.add_floating(widgets.register(TextBox::new()), Rect::new(0., 0., 10., 5.));
+    /// assert_eq!(layout.floats(), [b, d, a, c]);
+    ///
+    /// layout.remove_float(a);
+    /// layout.set_hidden(d, true);
+    /// assert_eq!(layout.floats(), [b, c]);
+    /// ```
     pub fn floats(&self) -> Vec<NodeId> {
-        self.floating.iter().copied().collect()
+        self.shown_floats().collect()
     }
 
-    /// Traverse the layout tree
-    pub fn traverse(&self, mut f: impl FnMut(NodeId, &LayoutNode)) {
-        self.traverse_recursive(self.root, &mut f);
+    /// Like [`Layout::floats`], without collecting the floats
+    pub(crate) fn shown_floats(&self) -> impl DoubleEndedIterator<Item = NodeId> + '_ {
+        self.floating
+            .iter()
+            .filter(|node| !self.hidden.contains_key(**node))
+            .copied()
     }
 
-    /// Recursive traversal helper
-    fn traverse_recursive(&self, node_id: NodeId, f: &mut impl FnMut(NodeId, &LayoutNode)) {
-        let node = self.nodes.get(node_id).unwrap();
-        f(node_id, node);
-        match node {
-            LayoutNode::Container(container) => {
-                for child in &container.children {
-                    self.traverse_recursive(*child, f);
-                }
-            }
-            LayoutNode::Leaf(_) => {}
-            LayoutNode::Floating(_) => {}
-        }
+    /// Traverse the layout tree from the root, depth first
+    pub fn traverse(&self, f: impl FnMut(NodeRef<'_, U, S>)) {
+        let nodes = std::iter::once(self.root).chain(self.descendants(self.root));
+        nodes.filter_map(|node| self.node(node)).for_each(f);
     }
 
-    /// Recursively print the layout.
+    /// Log the layout at the debug level, as described by [`Layout::dump`], with the
+    /// `sanguine::layout` target. Records are shown in the log view rather than drawn over the app.
+    /// See [`crate::log`].
     ///
     /// Intended for debug use only
     pub fn print_recursive(&self, node_id: NodeId) {
-        let node = self.nodes.get(node_id).unwrap();
-        match node {
-            LayoutNode::Container(container) => {
-                println!("Container: {:?}", self.layout(node_id));
-                for child in &container.children {
-                    self.print_recursive(*child);
-                }
+        ::log::debug!(target: "sanguine::layout", "\n{}", self.dump(node_id).trim_end());
+    }
+
+    /// Describe a node and its descendants, one per line and indented by depth, with their ids,
+    /// tags, size constraints and computed rects.
+    ///
+    /// Intended for debug use only
+    pub fn dump(&self, node_id: NodeId) -> String {
+        let mut out = String::new();
+        self.dump_recursive(node_id, 0, &mut out);
+        out
+    }
+
+    fn dump_recursive(&self, node_id: NodeId, depth: usize, out: &mut String) {
+        use std::fmt::Write;
+
+        let Some(node) = self.nodes.get(node_id) else {
+            return;
+        };
+        let kind = match node {
+            LayoutNode::Container(container) => format!("Container({:?})", container.direction),
+            LayoutNode::Leaf(_) => "Leaf".to_owned(),
+            LayoutNode::Floating(_) => "Floating".to_owned(),
+        };
+        write!(out, "{:indent$}{kind} {node_id:?}", "", indent = depth * 2).ok();
+        if let Some(tag) = self.tag(node_id) {
+            write!(out, " {tag:?}").ok();
+        }
+        if self.hidden.contains_key(node_id) {
+            write!(out, " hidden").ok();
+        }
+        write!(out, " size: {:?}", self.size(node_id)).ok();
+        match self.layout(node_id) {
+            Some(rect) => writeln!(
+                out,
+                " rect: {}x{} at ({}, {})",
+                rect.width, rect.height, rect.x, rect.y
+            ),
+            None => writeln!(out, " rect: none"),
+        }
+        .ok();
+        if let LayoutNode::Container(container) = node {
+            for child in &container.children {
+                self.dump_recursive(*child, depth + 1, out);
             }
-            LayoutNode::Leaf(_) => println!("Leaf: {:?}", self.layout(node_id)),
-            LayoutNode::Floating(_) => println!("Floating: {:?}", self.layout(node_id)),
         }
     }
 
     /// Drops a node from the layout. This will not drop children of the node.
     /// Use of the provided NodeId after calling this is invalid.
     pub fn remove_node(&mut self, node: NodeId) {
-        self.dirty = true;
+        self.invalidate_parent(node);
+        self.dirty.remove(node);
         self.nodes.remove(node);
         self.layout.remove(node);
+        self.floating.remove(node);
+        self.docked_top.retain(|n| *n != node);
+        self.docked_bottom.retain(|n| *n != node);
+        self.tags.remove(node);
+        self.data.remove(node);
+        self.hidden.remove(node);
+    }
+
+    /// Removes a leaf from the tree and drops it. If its container is left with a single child,
+    /// the container is replaced by that child in its own parent, and containers left empty are
+    /// removed.
+    ///
+    /// Returns [`Error::LastLeaf`] instead of closing the only leaf in the tree.
+    pub fn close_leaf(&mut self, node: NodeId) -> Result<()> {
+        if !self.is_leaf(node) {
+            return Err(Error::ExpectedLeaf(node));
+        }
+        let Some(parent) = self.parent(node) else {
+            // Not attached to the tree, so there is nothing to collapse
+            self.remove_node(node);
+            return Ok(());
+        };
+        if self.leaves() == [node] {
+            return Err(Error::LastLeaf(node));
+        }
+        self.detach_leaf(node, parent)?;
+        self.remove_node(node);
+        Ok(())
+    }
+
+    /// Removes a leaf from its container without dropping it, collapsing the container if it's
+    /// left with a single child and removing it if it's left empty.
+    fn detach_leaf(&mut self, node: NodeId, parent: NodeId) -> Result<()> {
+        self.remove_child(parent, node)?;
+        self.set_parent(node, None);
+
+        if self.is_root(parent) {
+            return Ok(());
+        }
+        match self.children(parent).map(|c| c.as_slice()) {
+            Some([]) => self.remove_empty(parent),
+            Some([child]) => {
+                // The remaining child takes the container's place, and its size
+                let child = *child;
+                let grandparent = self.parent(parent).ok_or(Error::Detached(parent))?;
+                let size = self.size(parent);
+                self.replace_child(grandparent, parent, child)?;
+                self.store_size(child, size);
+                self.remove_node(parent);
+            }
+            _ => {}
+        }
+        Ok(())
     }
 
-    /// Gets a node based on its id
-    pub fn node(&self, node: NodeId) -> Option<&LayoutNode> {
-        self.nodes.get(node)
+    /// Gets the widget shown by a leaf or floating window
+    pub fn widget(&self, node: NodeId) -> Option<WidgetId> {
+        self.nodes.get(node)?.widget()
     }
 
-    /// Gets a node based on its id
-    pub fn node_mut(&mut self, node: NodeId) -> Option<&mut LayoutNode> {
-        self.nodes.get_mut(node)
+    /// Counts the references to a widget: the nodes showing it, including ones that aren't
+    /// attached to the tree, and the registered widgets that show it inside them (see
+    /// [`Widget::children`]).
+    pub fn widget_refcount(&self, widgets: &WidgetStore<U, S>, widget: WidgetId) -> usize {
+        let nodes = self
+            .nodes
+            .values()
+            .filter(|node| node.widget() == Some(widget))
+            .count();
+        let parents = widgets
+            .iter()
+            .filter(|(_, parent)| parent.children().contains(&widget))
+            .count();
+        nodes + parents
+    }
+
+    /// Gets a read-only view of a node, or `None` if it doesn't exist. Nodes are changed through
+    /// the methods of the layout, which keep it consistent.
+    pub fn node(&self, node: NodeId) -> Option<NodeRef<'_, U, S>> {
+        Some(NodeRef {
+            layout: self,
+            id: node,
+            node: self.nodes.get(node)?,
+        })
     }
 
-    /// Sets the size hint for a container
-    pub fn set_size(&mut self, node: NodeId, size: Constraint) {
-        self.dirty = true;
+    /// Sets the size hint for a container or leaf. Returns [`Error::InvalidConstraint`] for
+    /// percentages outside of 0.0 to 1.0 and non-positive aspect ratios, see
+    /// [`Constraint::validate`].
+    pub fn set_size(&mut self, node: NodeId, size: Constraint) -> Result<()> {
+        size.validate()?;
+        self.store_size(node, size);
+        Ok(())
+    }
+
+    /// Sets the size hint of a node without validating it, for sizes computed by the layout
+    fn store_size(&mut self, node: NodeId, size: Constraint) {
+        self.invalidate_parent(node);
+        match self.nodes.get_mut(node) {
+            Some(LayoutNode::Container(container)) => container.size = Some(size),
+            Some(LayoutNode::Leaf(leaf)) => leaf.size = Some(size),
+            _ => {}
+        }
+    }
+
+    /// Clears the sizes set on every child of a container, so that they share its space equally
+    /// apart from widgets with their own [`Widget::constraint`](crate::Widget::constraint). If
+    /// `recursive` is true, child containers are equalized as well.
+    pub fn equalize(&mut self, node: NodeId, recursive: bool) {
+        let Some(children) = self.children(node).cloned() else {
+            return;
+        };
+        for child in children {
+            self.invalidate_parent(child);
+            match self.nodes.get_mut(child) {
+                Some(LayoutNode::Container(container)) => container.size = None,
+                Some(LayoutNode::Leaf(leaf)) => leaf.size = None,
+                _ => {}
+            }
+            if recursive && self.is_container(child) {
+                self.equalize(child, true);
+            }
+        }
+    }
+
+    /// Rescales the percentage sizes of a container's children so that they sum to 100%. Other
+    /// children are unchanged.
+    pub fn normalize(&mut self, node: NodeId) {
+        let Some(children) = self.children(node).cloned() else {
+            return;
+        };
+        let total = children
+            .iter()
+            .filter_map(|child| match self.size(*child) {
+                Constraint::Percentage(percent) => Some(percent),
+                _ => None,
+            })
+            .sum::<f32>();
+        if total <= 0. {
+            return;
+        }
+        for child in children {
+            if let Constraint::Percentage(percent) = self.size(child) {
+                self.store_size(child, Constraint::Percentage(percent / total));
+            }
+        }
+    }
+
+    /// Sets the space between the children of a container node, along its axis.
+    pub fn set_gap(&mut self, node: NodeId, gap: usize) {
+        self.invalidate(node);
+        if let Some(LayoutNode::Container(container)) = self.nodes.get_mut(node) {
+            container.gap = gap;
+        }
+    }
+
+    /// Sets the space between the bounds of a container node and its children, on all sides.
+    pub fn set_padding(&mut self, node: NodeId, padding: usize) {
+        self.invalidate(node);
         if let Some(LayoutNode::Container(container)) = self.nodes.get_mut(node) {
-            container.size = Some(size);
+            container.padding = padding;
+        }
+    }
+
+    /// If the given node is a container, returns the space between its children.
+    pub fn gap(&self, node: NodeId) -> Option<usize> {
+        match self.nodes.get(node) {
+            Some(LayoutNode::Container(container)) => Some(container.gap),
+            _ => None,
+        }
+    }
+
+    /// If the given node is a container, returns the space between its bounds and its children.
+    pub fn padding(&self, node: NodeId) -> Option<usize> {
+        match self.nodes.get(node) {
+            Some(LayoutNode::Container(container)) => Some(container.padding),
+            _ => None,
         }
     }
 
     /// Sets the direction of a container node.
     pub fn set_direction(&mut self, node: NodeId, axis: Axis) {
-        self.dirty = true;
+        self.invalidate(node);
         if let Some(LayoutNode::Container(container)) = self.nodes.get_mut(node) {
             container.direction = axis;
         }
@@ -593,6 +1683,8 @@ impl<U, S> Layout<U, S> {
             direction,
             size,
             parent: None,
+            gap: 0,
+            padding: 0,
         };
         let node = LayoutNode::Container(container);
         let id = self.nodes.insert(node);
@@ -600,20 +1692,25 @@ impl<U, S> Layout<U, S> {
         id
     }
 
-    /// Adds a new container node to the layout with the given children.
+    /// Adds a new container node to the layout with the given children. Returns
+    /// [`Error::InvalidConstraint`] if the size is invalid, see [`Constraint::validate`].
     pub fn add_with_children(
         &mut self,
         direction: Axis,
         size: Option<Constraint>,
         children: impl Into<Vec<NodeId>>,
-    ) -> NodeId {
-        self.dirty = true;
+    ) -> Result<NodeId> {
+        if let Some(size) = &size {
+            size.validate()?;
+        }
         let c = children.into();
         let container = Container {
             children: c.clone(),
             direction,
             size,
             parent: None,
+            gap: 0,
+            padding: 0,
         };
         let node = LayoutNode::Container(container);
         let id = self.nodes.insert(node);
@@ -627,78 +1724,290 @@ impl<U, S> Layout<U, S> {
             _ => {}
         });
         self.layout.insert(id, Rect::default());
-        id
+        Ok(id)
     }
 
-    /// Adds a new leaf node to the layout.
-    pub fn add_leaf(&mut self, widget: WidgetId) -> NodeId {
-        self.dirty = true;
+    /// Adds a new leaf node to the layout, showing a widget from the [`WidgetStore`]. The leaf
+    /// isn't attached to the tree until it's added to a container with [`Layout::add_child`].
+    pub fn add_leaf_id(&mut self, widget: WidgetId) -> NodeId {
         let node = LayoutNode::Leaf(Leaf::new(widget));
         let id = self.nodes.insert(node);
         self.layout.insert(id, Rect::default());
         id
     }
 
+    /// Registers a widget with the store and adds a new leaf showing it, like
+    /// [`Layout::add_leaf_id`].
+    pub fn add_leaf(
+        &mut self,
+        widgets: &mut WidgetStore<U, S>,
+        widget: impl Widget<U, S> + 'static,
+    ) -> NodeId {
+        let widget = widgets.register(widget);
+        self.add_leaf_id(widget)
+    }
+
+    /// Adds a leaf docked to the top of the layout, below any leaves already docked there.
+    ///
+    /// Docked leaves span the full width of the layout, and their rows are reserved before the
+    /// rest of the tree is laid out, so they aren't affected by splits. They take up a single row
+    /// unless given a [`Constraint::Fixed`] size with [`Layout::set_size`].
+    pub fn dock_top(&mut self, widget: WidgetId) -> NodeId {
+        let id = self.add_leaf_id(widget);
+        self.docked_top.push(id);
+        self.dirty_all = true;
+        id
+    }
+
+    /// Adds a leaf docked to the bottom of the layout, above any leaves already docked there.
+    ///
+    /// See [`Layout::dock_top`].
+    pub fn dock_bottom(&mut self, widget: WidgetId) -> NodeId {
+        let id = self.add_leaf_id(widget);
+        self.docked_bottom.push(id);
+        self.dirty_all = true;
+        id
+    }
+
     pub fn add_floating(&mut self, widget: WidgetId, rect: Rect) -> NodeId {
-        self.dirty = true;
-        let node = LayoutNode::Floating(Floating::new::<U, S>(widget, rect.clone()));
+        let node = LayoutNode::Floating(Floating::new(widget, rect));
         let id = self.nodes.insert(node);
         self.layout.insert(id, rect);
         self.floating.push(id, &self.nodes);
         id
     }
 
-    pub fn make_leaf(&mut self, node: NodeId) {
-        self.dirty = true;
+    /// Moves a floating window by `delta` cells, keeping it within the bounds the layout was last
+    /// computed for.
+    pub fn move_float(&mut self, node: NodeId, delta: (i32, i32)) {
+        let Some(rect) = self.layout(node).cloned() else {
+            return;
+        };
+        let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) else {
+            return;
+        };
+        let clamp = |pos: f32, size: f32, start: f32, extent: f32| {
+            pos.min(start + extent - size).max(start)
+        };
+        let x = clamp(
+            rect.x + delta.0 as f32,
+            rect.width,
+            self.bounds.x,
+            self.bounds.width,
+        );
+        let y = clamp(
+            rect.y + delta.1 as f32,
+            rect.height,
+            self.bounds.y,
+            self.bounds.height,
+        );
+        floating.move_to((x as usize, y as usize));
+        // Moving an anchored window detaches it
+        floating.set_anchor(None);
+        self.layout.insert(node, Rect { x, y, ..rect });
+        self.invalidate(node);
+    }
+
+    /// Attaches a floating window to a point of the screen, or detaches it if `anchor` is `None`.
+    /// Anchored windows are moved to their anchor whenever the floats are reflowed with
+    /// [`Layout::reflow_floats`], which [`crate::App`] does when the terminal is resized.
+    pub fn set_anchor(&mut self, node: NodeId, anchor: Option<Anchor>) {
+        if let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) {
+            floating.set_anchor(anchor);
+        }
+    }
+
+    /// Sets whether a shadow is drawn below and to the right of a floating window
+    pub fn set_shadow(&mut self, node: NodeId, shadow: bool) {
+        if let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) {
+            floating.set_shadow(shadow);
+            self.invalidate(node);
+        }
+    }
+
+    /// Sets whether a floating window is modal. While a modal window is shown, the windows below
+    /// it can't be focused and don't receive mouse events.
+    pub fn set_modal(&mut self, node: NodeId, modal: bool) {
+        if let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) {
+            floating.set_modal(modal);
+        }
+    }
+
+    /// Fits floating windows into new bounds, usually after the terminal is resized. Windows
+    /// larger than the bounds are shrunk to fit, and anchored windows are moved to their anchor.
+    ///
+    /// Other windows are moved so that at least `margin` cells of their top left corner are
+    /// within the bounds, or so that they are entirely within the bounds if `margin` is `None`.
+    pub fn reflow_floats(&mut self, bounds: &Rect, margin: Option<(usize, usize)>) {
+        for node in self.floats() {
+            self.reflow_float(node, bounds, margin);
+        }
+    }
+
+    fn reflow_float(&mut self, node: NodeId, bounds: &Rect, margin: Option<(usize, usize)>) {
+        let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) else {
+            return;
+        };
+        let pos = *floating.pos();
+        let width = pos.width.min(bounds.width);
+        let height = pos.height.min(bounds.height);
+        let (x, y) = match (floating.anchor(), margin) {
+            (Some(anchor), _) => anchor.position(bounds, width, height),
+            (None, margin) => {
+                // The part of the window that has to stay visible
+                let (visible_width, visible_height) = match margin {
+                    Some((w, h)) => ((w as f32).min(width), (h as f32).min(height)),
+                    None => (width, height),
+                };
+                (
+                    pos.x.min(bounds.right() - visible_width).max(bounds.left()),
+                    pos.y
+                        .min(bounds.bottom() - visible_height)
+                        .max(bounds.top()),
+                )
+            }
+        };
+        floating.move_to((x as usize, y as usize));
+        self.layout.insert(node, Rect::new(x, y, width, height));
+        self.invalidate(node);
+    }
+
+    /// The z-indices of all floats except the given one
+    fn other_z_indices(&self, node: NodeId) -> Vec<usize> {
+        self.floating
+            .iter()
+            .filter(|id| **id != node)
+            .filter_map(|id| self.nodes.get(*id)?.floating())
+            .map(|float| float.z_index())
+            .collect()
+    }
+
+    /// Moves a floating window to the top of the stack, above all other floats
+    pub fn raise_float(&mut self, node: NodeId) {
+        let top = self.other_z_indices(node).into_iter().max();
+        let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) else {
+            return;
+        };
+        floating.set_z_index(top.map_or(1, |z| z + 1));
+        self.floating.bring_to_front(node);
+        self.invalidate(node);
+    }
+
+    /// Moves a floating window to the bottom of the stack, below all other floats
+    pub fn lower_float(&mut self, node: NodeId) {
         if !self.is_floating(node) {
             return;
         }
-        if let Some(floating) = self.nodes.get_mut(node) {
-            let leaf = Leaf::new(floating.widget().unwrap());
-            let new = LayoutNode::Leaf(leaf);
-            self.floating.remove(node);
-            *floating = new;
+        let Some(bottom) = self.other_z_indices(node).into_iter().min() else {
+            return;
+        };
+        if bottom == 0 {
+            // Make room below the other floats
+            for id in self.floating.iter() {
+                if let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(*id) {
+                    floating.set_z_index(floating.z_index() + 1);
+                }
+            }
+        }
+        if let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) {
+            floating.set_z_index(bottom.saturating_sub(1));
         }
+        self.floating.send_to_back(node);
+        self.invalidate(node);
     }
 
-    /// Directly adds a leaf node to the layout.
-    pub fn clone_leaf(&mut self, leaf: NodeId) -> NodeId {
-        self.dirty = true;
-        let widget = self
-            .nodes
-            .get(leaf)
-            .map(|l| match l {
-                LayoutNode::Leaf(leaf) => leaf.clone(),
-                _ => panic!("Node is not a leaf"),
-            })
-            .unwrap();
+    /// Sets the z-index of a floating window. Floats with a higher z-index are drawn above those
+    /// with a lower one.
+    pub fn set_z_index(&mut self, node: NodeId, z_index: usize) {
+        let Some(LayoutNode::Floating(floating)) = self.nodes.get_mut(node) else {
+            return;
+        };
+        floating.set_z_index(z_index);
+        self.floating.sort_by_z(&self.nodes);
+        self.invalidate(node);
+    }
+
+    /// Turns a floating window into a leaf, tiled at the end of the root container.
+    pub fn make_leaf(&mut self, node: NodeId) -> Result<()> {
+        let Some(LayoutNode::Floating(floating)) = self.nodes.get(node) else {
+            return Err(Error::ExpectedFloating(node));
+        };
+        let leaf = LayoutNode::Leaf(Leaf::new(floating.widget()));
+        self.floating.remove(node);
+        self.nodes[node] = leaf;
+        self.add_child(self.root(), node)
+    }
+
+    /// Pops a leaf out of the tree (or the docked leaves) into a floating window at `rect`. Its
+    /// container is collapsed if it's left with a single child. The node keeps its id.
+    pub fn make_float(&mut self, node: NodeId, rect: Rect) -> Result<()> {
+        let Some(LayoutNode::Leaf(leaf)) = self.nodes.get(node) else {
+            return Err(Error::ExpectedLeaf(node));
+        };
+        let widget = leaf.widget;
+        if let Some(parent) = self.parent(node) {
+            self.detach_leaf(node, parent)?;
+        } else if self.is_docked(node) {
+            self.invalidate_parent(node);
+            self.docked_top.retain(|n| *n != node);
+            self.docked_bottom.retain(|n| *n != node);
+        }
+        if let Some(leaf) = self.nodes.get_mut(node) {
+            *leaf = LayoutNode::Floating(Floating::new(widget, rect));
+        }
+        self.layout.insert(node, rect);
+        self.floating.push(node, &self.nodes);
+        self.invalidate(node);
+        Ok(())
+    }
+
+    /// Removes a floating window from the layout, returning its widget so that it can be removed
+    /// from the [`crate::WidgetStore`] as well. Returns `None` if the node isn't floating.
+    pub fn remove_float(&mut self, node: NodeId) -> Option<WidgetId> {
+        let widget = self.nodes.get(node)?.floating()?.widget();
+        self.remove_node(node);
+        Some(widget)
+    }
+
+    /// Adds a new leaf showing the same widget as `leaf`. The new leaf isn't attached to the tree.
+    pub fn clone_leaf(&mut self, leaf: NodeId) -> Result<NodeId> {
+        let widget = match self.nodes.get(leaf) {
+            Some(LayoutNode::Leaf(leaf)) => leaf.clone(),
+            Some(_) => return Err(Error::ExpectedLeaf(leaf)),
+            None => return Err(Error::NodeNotFound(leaf)),
+        };
         let node = LayoutNode::Leaf(widget);
         let id = self.nodes.insert(node);
         self.layout.insert(id, Rect::default());
-        id
+        Ok(id)
     }
 
-    /// Adds a new leaf node to the given container.
-    pub fn add_child(&mut self, parent: NodeId, child: NodeId) {
-        self.dirty = true;
-        match self.nodes.get_mut(parent) {
-            Some(LayoutNode::Container(container)) => {
-                container.children.push(child);
-            }
-            _ => panic!("Parent is not a container"),
+    /// Gets a container for modification, or an error if `node` doesn't exist or isn't a
+    /// container.
+    fn container_mut(&mut self, node: NodeId) -> Result<&mut Container> {
+        match self.nodes.get_mut(node) {
+            Some(LayoutNode::Container(container)) => Ok(container),
+            Some(_) => Err(Error::NotAContainer(node)),
+            None => Err(Error::NodeNotFound(node)),
+        }
+    }
+
+    /// Adds a node to the end of the given container.
+    pub fn add_child(&mut self, parent: NodeId, child: NodeId) -> Result<()> {
+        if !self.nodes.contains_key(child) {
+            return Err(Error::NodeNotFound(child));
         }
+        self.container_mut(parent)?.children.push(child);
+        self.invalidate(parent);
         self.set_parent(child, Some(parent));
+        Ok(())
     }
 
     /// Removes a child from the given container. This does not drop the node.
-    pub fn remove_child(&mut self, parent: NodeId, child: NodeId) {
-        self.dirty = true;
-        match self.nodes.get_mut(parent) {
-            Some(LayoutNode::Container(container)) => {
-                container.children.retain(|&x| x != child);
-            }
-            _ => panic!("Parent is not a container"),
-        }
+    pub fn remove_child(&mut self, parent: NodeId, child: NodeId) -> Result<()> {
+        self.container_mut(parent)?.children.retain(|&x| x != child);
+        self.invalidate(parent);
+        Ok(())
     }
 
     pub fn child_index(&self, parent: NodeId, child: NodeId) -> Option<usize> {
@@ -710,38 +2019,37 @@ impl<U, S> Layout<U, S> {
         }
     }
 
-    pub fn remove_child_by_index(&mut self, parent: NodeId, index: usize) {
-        self.dirty = true;
-        match self.nodes.get_mut(parent) {
-            Some(LayoutNode::Container(container)) => {
-                container.children.remove(index);
-            }
-            _ => panic!("Parent is not a container"),
-        }
+    /// Removes the child at `index` from the given container. This does not drop the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_child_by_index(&mut self, parent: NodeId, index: usize) -> Result<()> {
+        self.container_mut(parent)?.children.remove(index);
+        self.invalidate(parent);
+        Ok(())
     }
 
     /// Replace the child of a container with another.
-    pub fn replace_child(&mut self, parent: NodeId, child: NodeId, new: NodeId) {
-        self.dirty = true;
-        let old;
-        match self.nodes.get_mut(parent) {
-            Some(LayoutNode::Container(container)) => {
-                let index = container.children.iter().position(|&x| x == child).unwrap();
-                old = Some(container.children[index]);
-
-                container.children[index] = new;
-            }
-            _ => panic!("Parent is not a container"),
-        }
-        if let Some(old) = old {
-            self.set_parent(old, None);
+    pub fn replace_child(&mut self, parent: NodeId, child: NodeId, new: NodeId) -> Result<()> {
+        if !self.nodes.contains_key(new) {
+            return Err(Error::NodeNotFound(new));
         }
+        let container = self.container_mut(parent)?;
+        let index = container
+            .children
+            .iter()
+            .position(|&x| x == child)
+            .ok_or(Error::NotAChild(parent, child))?;
+        container.children[index] = new;
+        self.invalidate(parent);
+        self.set_parent(child, None);
         self.set_parent(new, Some(parent));
+        Ok(())
     }
 
     /// Sets the parent of the given node.
     fn set_parent(&mut self, node: NodeId, parent: Option<NodeId>) {
-        self.dirty = true;
         match self.nodes.get_mut(node) {
             Some(LayoutNode::Container(container)) => {
                 container.parent = parent;
@@ -796,21 +2104,111 @@ impl<U, S> Layout<U, S> {
         }
     }
 
+    /// Iterates over the children of a container, which is empty for other nodes.
+    pub fn children_iter(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.children(node).into_iter().flatten().copied()
+    }
+
+    /// Iterates over the containers a node is in, from its parent up to the root. Floating and
+    /// docked nodes have no ancestors.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     hsplit,
+    ///     layout::{leaf, Layout},
+    ///     vsplit,
+    ///     widgets::TextBox,
+    ///     WidgetStore,
+    /// };
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let tree = hsplit![
+    ///     leaf(TextBox::new()).named("left"),
+    ///     vsplit![
+    ///         hsplit![
+    ///             leaf(TextBox::new()).named("menu"),
+    ///             leaf(TextBox::new()).named("canvas"),
+    ///         ]
+    ///         .named("top"),
+    ///         leaf(TextBox::new()).named("editor"),
+    ///     ]
+    ///     .named("right"),
+    /// ];
+    /// let built = layout.build(&mut widgets, tree).unwrap();
+    /// let node = |name| built.get(name).unwrap();
+    /// // The outermost split becomes the root
+    /// let root = layout.root();
+    ///
+    /// assert_eq!(
+    ///     layout.ancestors(node("canvas")).collect::<Vec<_>>(),
+    ///     [node("top"), node("right"), root]
+    /// );
+    /// assert_eq!(layout.ancestors(root).count(), 0);
+    ///
+    /// assert_eq!(
+    ///     layout.children_iter(node("right")).collect::<Vec<_>>(),
+    ///     [node("top"), node("editor")]
+    /// );
+    /// assert_eq!(layout.children_iter(node("editor")).count(), 0);
+    ///
+    /// assert_eq!(
+    ///     layout.descendants(root).collect::<Vec<_>>(),
+    ///     ["left", "right", "top", "menu", "canvas", "editor"].map(node)
+    /// );
+    /// assert_eq!(layout.descendants(node("left")).count(), 0);
+    ///
+    /// assert!(layout.contains(node("right"), node("menu")));
+    /// assert!(layout.contains(root, node("editor")));
+    /// assert!(!layout.contains(node("top"), node("editor")));
+    /// assert!(!layout.contains(node("menu"), node("menu")));
+    /// ```
+    pub fn ancestors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(node), |node| self.parent(*node))
+    }
+
+    /// Iterates over the nodes inside a container depth first, each container before its
+    /// children, including hidden nodes. Examples are shown on [`Layout::ancestors`].
+    pub fn descendants(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = vec![self.children_iter(node)];
+        std::iter::from_fn(move || loop {
+            let next = stack.last_mut()?.next();
+            match next {
+                Some(child) => {
+                    stack.push(self.children_iter(child));
+                    return Some(child);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        })
+    }
+
+    /// Checks whether `node` is inside the container `ancestor`, at any depth. A node doesn't
+    /// contain itself.
+    pub fn contains(&self, ancestor: NodeId, node: NodeId) -> bool {
+        self.ancestors(node).any(|parent| parent == ancestor)
+    }
+
     /// Checks if the node is the root node
     pub fn is_root(&self, node: NodeId) -> bool {
         node == self.root()
     }
 
     /// Inserts a new child node at the given index.
-    pub fn insert_child_at(&mut self, parent: NodeId, child: NodeId, index: usize) {
-        self.dirty = true;
-        match self.nodes.get_mut(parent) {
-            Some(LayoutNode::Container(container)) => {
-                container.children.insert(index, child);
-            }
-            _ => panic!("Parent is not a container"),
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of children.
+    pub fn insert_child_at(&mut self, parent: NodeId, child: NodeId, index: usize) -> Result<()> {
+        if !self.nodes.contains_key(child) {
+            return Err(Error::NodeNotFound(child));
         }
+        self.container_mut(parent)?.children.insert(index, child);
+        self.invalidate(parent);
         self.set_parent(child, Some(parent));
+        Ok(())
     }
 
     /// Adds a new container node to the layout by splitting the given node.
@@ -823,38 +2221,735 @@ impl<U, S> Layout<U, S> {
     ///
     /// If the node is a leaf, it will be replaced by a container, which will contain it and the
     /// newly created node.
-    pub fn split(&mut self, node: NodeId, direction: Axis, widget: WidgetId) -> NodeId {
-        self.dirty = true;
-        if self.is_leaf(node) {
-            let new = self.add_container(direction, None);
-            let new_leaf = self.add_leaf(widget);
-            let parent = self.parent(node).unwrap();
-            let index = self.child_index(parent, node).unwrap();
-            self.remove_child_by_index(parent, index);
-            self.add_child(new, node);
-            self.add_child(new, new_leaf);
-            self.insert_child_at(parent, new, index);
-            new_leaf
-        } else {
-            let self_dir = self.direction(node).unwrap();
-            let new_leaf = self.add_leaf(widget);
-            if self_dir == direction {
-                self.add_child(node, new_leaf);
-                new_leaf
+    ///
+    /// Splitting the root in the opposite direction moves its children into a new container, as
+    /// the root can't be given a parent.
+    ///
+    /// Returns the id of the newly created leaf, or an error if the node doesn't exist, is
+    /// floating or docked, or isn't attached to the tree.
+    pub fn split(&mut self, node: NodeId, direction: Axis, widget: WidgetId) -> Result<NodeId> {
+        let wrap = match self.nodes.get(node) {
+            Some(LayoutNode::Leaf(_)) => true,
+            Some(LayoutNode::Container(container)) => container.direction != direction,
+            Some(LayoutNode::Floating(_)) => return Err(Error::ExpectedLeaf(node)),
+            None => return Err(Error::NodeNotFound(node)),
+        };
+        if !wrap {
+            let new_leaf = self.add_leaf_id(widget);
+            self.add_child(node, new_leaf)?;
+            return Ok(new_leaf);
+        }
+        if self.is_root(node) {
+            let root = self.container_mut(node)?;
+            let children = std::mem::take(&mut root.children);
+            let inner = root.direction;
+            root.direction = direction;
+            let new = self.add_with_children(inner, None, children)?;
+            let new_leaf = self.add_leaf_id(widget);
+            self.add_child(node, new)?;
+            self.add_child(node, new_leaf)?;
+            return Ok(new_leaf);
+        }
+        let parent = self.parent(node).ok_or(Error::Detached(node))?;
+        let index = self
+            .child_index(parent, node)
+            .ok_or(Error::NotAChild(parent, node))?;
+        let new = self.add_container(direction, None);
+        let new_leaf = self.add_leaf_id(widget);
+        self.remove_child_by_index(parent, index)?;
+        self.add_child(new, node)?;
+        self.add_child(new, new_leaf)?;
+        self.insert_child_at(parent, new, index)?;
+        Ok(new_leaf)
+    }
+
+    /// Exchanges the positions of two leaves in the tree. Both node ids remain valid and keep
+    /// referring to the same widgets. Does nothing unless both nodes are leaves.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     hsplit,
+    ///     layout::{leaf, Layout},
+    ///     vsplit,
+    ///     widgets::TextBox,
+    ///     WidgetStore,
+    /// };
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let tree = hsplit![
+    ///     leaf(TextBox::new()).named("a"),
+    ///     vsplit![leaf(TextBox::new()).named("b"), leaf(TextBox::new()).named("c")].named("column"),
+    /// ];
+    /// let built = layout.build(&mut widgets, tree).unwrap();
+    /// let node = |name| built.get(name).unwrap();
+    /// let (root, column) = (layout.root(), node("column"));
+    /// let widget = layout.widget(node("a"));
+    ///
+    /// layout.swap(node("a"), node("c"));
+    /// assert_eq!(layout.children(root).unwrap(), &[node("c"), column]);
+    /// assert_eq!(layout.children(column).unwrap(), &[node("b"), node("a")]);
+    /// assert_eq!(layout.parent(node("a")), Some(column));
+    /// assert_eq!(layout.parent(node("c")), Some(root));
+    /// assert_eq!(layout.widget(node("a")), widget);
+    /// ```
+    pub fn swap(&mut self, a: NodeId, b: NodeId) {
+        if a == b || !self.is_leaf(a) || !self.is_leaf(b) {
+            return;
+        }
+        let (Some(parent_a), Some(parent_b)) = (self.parent(a), self.parent(b)) else {
+            return;
+        };
+        let (Some(index_a), Some(index_b)) =
+            (self.child_index(parent_a, a), self.child_index(parent_b, b))
+        else {
+            return;
+        };
+        self.invalidate(parent_a);
+        self.invalidate(parent_b);
+        if let Some(LayoutNode::Container(container)) = self.nodes.get_mut(parent_a) {
+            container.children[index_a] = b;
+        }
+        if let Some(LayoutNode::Container(container)) = self.nodes.get_mut(parent_b) {
+            container.children[index_b] = a;
+        }
+        self.set_parent(a, Some(parent_b));
+        self.set_parent(b, Some(parent_a));
+    }
+
+    /// Moves a leaf one step in the given direction, like moving a window in a tiling window
+    /// manager.
+    ///
+    /// If the leaf's parent is laid out along the direction's axis and the leaf has a sibling on
+    /// that side, the leaf moves past it. Otherwise the leaf is hoisted out of its container and
+    /// placed beside the nearest ancestor whose parent is laid out along that axis. Containers
+    /// left empty by the move are removed. Does nothing at the edge of the layout, or if the node
+    /// is not a leaf.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     hsplit,
+    ///     layout::{leaf, Direction, Layout},
+    ///     vsplit,
+    ///     widgets::TextBox,
+    ///     WidgetStore,
+    /// };
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let tree = hsplit![
+    ///     leaf(TextBox::new()).named("a"),
+    ///     leaf(TextBox::new()).named("b"),
+    ///     vsplit![leaf(TextBox::new()).named("c"), leaf(TextBox::new()).named("d")].named("column"),
+    /// ];
+    /// let built = layout.build(&mut widgets, tree).unwrap();
+    /// let node = |name| built.get(name).unwrap();
+    /// let (root, column) = (layout.root(), node("column"));
+    ///
+    /// // Past a sibling in the same container
+    /// layout.move_node(node("a"), Direction::Right);
+    /// assert_eq!(layout.children(root).unwrap(), &[node("b"), node("a"), column]);
+    ///
+    /// // Out of a container laid out along the other axis
+    /// layout.move_node(node("c"), Direction::Left);
+    /// assert_eq!(layout.children(root).unwrap(), &[node("b"), node("a"), node("c"), column]);
+    /// assert_eq!(layout.children(column).unwrap(), &[node("d")]);
+    /// assert_eq!(layout.parent(node("c")), Some(root));
+    ///
+    /// // Moving the last child out removes the empty container
+    /// layout.move_node(node("d"), Direction::Left);
+    /// assert_eq!(
+    ///     layout.children(root).unwrap(),
+    ///     &[node("b"), node("a"), node("c"), node("d")]
+    /// );
+    /// assert!(layout.node(column).is_none());
+    ///
+    /// // Nothing happens at the edge of the layout, or to the root
+    /// layout.move_node(node("b"), Direction::Left);
+    /// layout.move_node(node("b"), Direction::Up);
+    /// layout.move_node(root, Direction::Right);
+    /// assert_eq!(
+    ///     layout.children(root).unwrap(),
+    ///     &[node("b"), node("a"), node("c"), node("d")]
+    /// );
+    /// ```
+    ///
+    /// Moved windows keep their node id, so the app's focus stays on the same window:
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     layout::{Axis, Direction},
+    ///     widgets::TextBox,
+    ///     App,
+    /// };
+    ///
+    /// let mut right = None;
+    /// let mut app = App::<(), ()>::test(20, 5).with_layout(|layout, widgets| {
+    ///     let left = layout.add_leaf(widgets, TextBox::new());
+    ///     right = Some(layout.add_leaf(widgets, TextBox::new()));
+    ///     layout.set_direction(layout.root(), Axis::Horizontal);
+    ///     layout.add_child(layout.root(), left).ok()?;
+    ///     layout.add_child(layout.root(), right?).ok()?;
+    ///     right
+    /// });
+    /// let right = right.unwrap();
+    /// let widget = app.inspect_layout(|layout, _| layout.widget(right));
+    ///
+    /// app.update_layout(|layout, _| layout.move_node(right, Direction::Left));
+    /// assert_eq!(app.get_focus(), Some(right));
+    /// assert_eq!(app.inspect_layout(|layout, _| layout.widget(right)), widget);
+    /// assert_eq!(
+    ///     app.inspect_layout(|layout, _| layout.children(layout.root()).unwrap()[0]),
+    ///     right
+    /// );
+    /// ```
+    pub fn move_node(&mut self, node: NodeId, direction: Direction) {
+        if !self.is_leaf(node) {
+            return;
+        }
+        let axis = match direction {
+            Direction::Left | Direction::Right => Axis::Horizontal,
+            Direction::Up | Direction::Down => Axis::Vertical,
+        };
+        let forward = matches!(direction, Direction::Right | Direction::Down);
+
+        let mut current = node;
+        while let Some(parent) = self.parent(current) {
+            if self.direction(parent) != Some(axis) {
+                current = parent;
+                continue;
+            }
+            let (Some(index), Some(len)) =
+                (self.child_index(parent, current), self.child_count(parent))
+            else {
+                return;
+            };
+            if current == node {
+                // Move past the adjacent sibling, if there is one
+                let target = if forward {
+                    (index + 1 < len).then_some(index + 1)
+                } else {
+                    index.checked_sub(1)
+                };
+                if let Some(target) = target {
+                    self.invalidate(parent);
+                    if let Some(LayoutNode::Container(container)) = self.nodes.get_mut(parent) {
+                        container.children.swap(index, target);
+                    }
+                    return;
+                }
             } else {
-                let new = self.add_container(direction, None);
-                let parent = self.parent(node).unwrap();
-                let index = self.child_index(parent, node).unwrap();
-                self.remove_child_by_index(parent, index);
-                self.add_child(new, node);
-                self.add_child(new, new_leaf);
-                self.insert_child_at(parent, new, index);
-                node
+                // Hoist the node out of its container, placing it beside the ancestor
+                let Some(old_parent) = self.parent(node) else {
+                    return;
+                };
+                let index = if forward { index + 1 } else { index };
+                if self.remove_child(old_parent, node).is_ok()
+                    && self.insert_child_at(parent, node, index).is_ok()
+                {
+                    self.remove_empty(old_parent);
+                }
+                return;
+            }
+            current = parent;
+        }
+    }
+
+    /// Removes the given container if it has no children, along with any ancestors that are left
+    /// empty as a result. The root node is never removed.
+    fn remove_empty(&mut self, node: NodeId) {
+        if self.is_root(node) || self.child_count(node) != Some(0) {
+            return;
+        }
+        if let Some(parent) = self.parent(node) {
+            if self.remove_child(parent, node).is_ok() {
+                self.remove_node(node);
+                self.remove_empty(parent);
+            }
+        }
+    }
+
+    /// Tags a leaf or floating window, so that it can be identified in a [`LayoutSpec`].
+    pub fn set_tag(&mut self, node: NodeId, tag: &str) {
+        self.tags.insert(node, tag.to_owned());
+    }
+
+    /// Gets the tag of a node, if it has one
+    pub fn tag(&self, node: NodeId) -> Option<&str> {
+        self.tags.get(node).map(String::as_str)
+    }
+
+    /// Hides or shows a leaf, container or floating window without removing it from the layout.
+    ///
+    /// Hidden nodes take up no space, so their siblings share the space they leave, and they
+    /// aren't returned by [`Layout::leaves`], [`Layout::docked`] or [`Layout::floats`]. Hiding a
+    /// container hides all of its children. Their sizes are kept, and are used again once they're
+    /// shown.
+    ///
+    /// ```
+    /// use sanguine::{layout::{Constraint, Layout, Rect}, widgets::TextBox, WidgetStore};
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let sidebar = layout.add_leaf(&mut widgets, TextBox::new());
+    /// let editor = layout.add_leaf(&mut widgets, TextBox::new());
+    /// layout.set_direction(layout.root(), sanguine::layout::Axis::Horizontal);
+    /// layout.add_child(layout.root(), sidebar).unwrap();
+    /// layout.add_child(layout.root(), editor).unwrap();
+    /// layout.set_size(sidebar, Constraint::Fixed(20)).unwrap();
+    ///
+    /// let bounds = Rect::new(0., 0., 80., 24.);
+    /// layout.set_hidden(sidebar, true);
+    /// layout.compute(&bounds);
+    /// assert_eq!(layout.leaves(), [editor]);
+    /// assert_eq!(layout.layout(editor).unwrap().width, 80.);
+    ///
+    /// layout.set_hidden(sidebar, false);
+    /// layout.compute(&bounds);
+    /// assert_eq!(layout.layout(sidebar).unwrap().width, 20.);
+    /// assert_eq!(layout.layout(editor).unwrap().width, 60.);
+    /// ```
+    pub fn set_hidden(&mut self, node: NodeId, hidden: bool) {
+        if !self.nodes.contains_key(node) || self.hidden.contains_key(node) == hidden {
+            return;
+        }
+        if hidden {
+            self.hidden.insert(node, ());
+        } else {
+            self.hidden.remove(node);
+        }
+        if self.is_floating(node) {
+            self.invalidate(node);
+        } else if self.parent(node).is_some() || self.is_docked(node) {
+            self.invalidate_parent(node);
+        } else {
+            self.dirty_all = true;
+        }
+    }
+
+    /// Checks whether a node was hidden with [`Layout::set_hidden`]. Nodes in a hidden container
+    /// aren't hidden themselves, see [`Layout::is_shown`].
+    pub fn is_hidden(&self, node: NodeId) -> bool {
+        self.hidden.contains_key(node)
+    }
+
+    /// Checks whether a node is neither hidden nor inside a hidden container
+    pub fn is_shown(&self, node: NodeId) -> bool {
+        let mut nodes = std::iter::once(node).chain(self.ancestors(node));
+        !nodes.any(|node| self.hidden.contains_key(node))
+    }
+
+    /// The children of a container that aren't hidden
+    fn shown_children(&self, node: NodeId) -> Vec<NodeId> {
+        let children = self.children_iter(node);
+        children
+            .filter(|child| !self.hidden.contains_key(*child))
+            .collect()
+    }
+
+    /// Attaches application data to a node, such as the path of the file shown in a window,
+    /// replacing any data it already had. The data is dropped when the node is removed.
+    ///
+    /// ```
+    /// use sanguine::{layout::Layout, widgets::TextBox, WidgetStore};
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let first = layout.add_leaf(&mut widgets, TextBox::new());
+    /// let second = layout.add_leaf(&mut widgets, TextBox::new());
+    /// layout.add_child(layout.root(), first).unwrap();
+    /// layout.add_child(layout.root(), second).unwrap();
+    ///
+    /// layout.set_data(first, Box::new(String::from("src/main.rs")));
+    /// assert_eq!(layout.get_data::<String>(first).unwrap(), "src/main.rs");
+    /// // Data of another type isn't returned
+    /// assert_eq!(layout.get_data::<usize>(first), None);
+    ///
+    /// layout.close_leaf(first).unwrap();
+    /// assert_eq!(layout.get_data::<String>(first), None);
+    /// ```
+    pub fn set_data(&mut self, node: NodeId, data: Box<dyn Any + Send>) {
+        if self.nodes.contains_key(node) {
+            self.data.insert(node, data);
+        }
+    }
+
+    /// Gets the data attached to a node, if it has data of type `T`
+    pub fn get_data<T: 'static>(&self, node: NodeId) -> Option<&T> {
+        self.data.get(node)?.downcast_ref()
+    }
+
+    /// Gets the data attached to a node mutably, if it has data of type `T`
+    pub fn get_data_mut<T: 'static>(&mut self, node: NodeId) -> Option<&mut T> {
+        self.data.get_mut(node)?.downcast_mut()
+    }
+
+    /// Removes the data attached to a node, returning it if there was any
+    pub fn take_data(&mut self, node: NodeId) -> Option<Box<dyn Any + Send>> {
+        self.data.remove(node)
+    }
+
+    /// Creates a snapshot of the layout's arrangement, which can be saved and later applied with
+    /// [`Layout::restore`]. Leaves and floating windows are identified by their tags.
+    pub fn serialize(&self) -> LayoutSpec {
+        let leaf = |node: NodeId| LeafSpec {
+            tag: self.tags.get(node).cloned(),
+            size: self.leaf_size(node),
+        };
+        LayoutSpec {
+            root: self.serialize_container(self.root),
+            docked_top: self.docked_top.iter().map(|n| leaf(*n)).collect(),
+            docked_bottom: self.docked_bottom.iter().map(|n| leaf(*n)).collect(),
+            floating: self
+                .floating
+                .iter()
+                .filter_map(|node| {
+                    let float = self.nodes.get(*node)?.floating()?;
+                    Some(FloatSpec {
+                        tag: self.tags.get(*node).cloned(),
+                        rect: *self.layout(*node)?,
+                        z_index: float.z_index(),
+                        anchor: float.anchor(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn serialize_container(&self, node: NodeId) -> ContainerSpec {
+        let container = self.nodes.get(node).and_then(|n| n.container());
+        ContainerSpec {
+            axis: self.direction(node).unwrap_or(Axis::Vertical),
+            size: container.and_then(|c| c.size.clone()),
+            gap: self.gap(node).unwrap_or(0),
+            padding: self.padding(node).unwrap_or(0),
+            children: self
+                .children(node)
+                .into_iter()
+                .flatten()
+                .map(|child| {
+                    if self.is_container(*child) {
+                        NodeSpec::Container(self.serialize_container(*child))
+                    } else {
+                        NodeSpec::Leaf(LeafSpec {
+                            tag: self.tags.get(*child).cloned(),
+                            size: self.leaf_size(*child),
+                        })
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn leaf_size(&self, node: NodeId) -> Option<Constraint> {
+        match self.nodes.get(node) {
+            Some(LayoutNode::Leaf(leaf)) => leaf.size.clone(),
+            _ => None,
+        }
+    }
+
+    /// Replaces the layout with the arrangement described by `spec`, calling `resolver` with each
+    /// tag to get the widget to show in that window.
+    ///
+    /// Windows whose tag can't be resolved (or which have no tag) are skipped, along with any
+    /// containers left empty as a result, and the rest of the layout is still restored. In that
+    /// case [`Error::UnresolvedTags`] is returned listing the tags that couldn't be resolved.
+    ///
+    /// All previously created node ids are invalidated, so focus should be reset afterwards.
+    pub fn restore(
+        &mut self,
+        spec: LayoutSpec,
+        mut resolver: impl FnMut(&str) -> Option<WidgetId>,
+    ) -> Result<()> {
+        let mut layout = Self::new();
+        let mut unresolved = vec![];
+        let mut resolve = |tag: Option<String>| {
+            let tag = tag.unwrap_or_default();
+            let widget = resolver(&tag);
+            if widget.is_none() {
+                unresolved.push(tag.clone());
+            }
+            widget.map(|widget| (widget, tag))
+        };
+
+        let root = layout.root;
+        layout.restore_container(root, spec.root, &mut resolve)?;
+        for leaf in spec.docked_top {
+            if let Some((widget, tag)) = resolve(leaf.tag) {
+                let node = layout.dock_top(widget);
+                layout.restore_leaf(node, tag, leaf.size)?;
+            }
+        }
+        for leaf in spec.docked_bottom {
+            if let Some((widget, tag)) = resolve(leaf.tag) {
+                let node = layout.dock_bottom(widget);
+                layout.restore_leaf(node, tag, leaf.size)?;
+            }
+        }
+        for float in spec.floating {
+            if let Some((widget, tag)) = resolve(float.tag) {
+                let node = layout.add_floating(widget, float.rect);
+                if let Some(LayoutNode::Floating(floating)) = layout.nodes.get_mut(node) {
+                    floating.set_z_index(float.z_index);
+                    floating.set_anchor(float.anchor);
+                }
+                layout.floating.sort_by_z(&layout.nodes);
+                layout.tags.insert(node, tag);
+            }
+        }
+
+        *self = layout;
+        if unresolved.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnresolvedTags(unresolved))
+        }
+    }
+
+    fn restore_container(
+        &mut self,
+        node: NodeId,
+        spec: ContainerSpec,
+        resolve: &mut impl FnMut(Option<String>) -> Option<(WidgetId, String)>,
+    ) -> Result<()> {
+        self.set_direction(node, spec.axis);
+        if let Some(size) = spec.size {
+            self.set_size(node, size)?;
+        }
+        self.set_gap(node, spec.gap);
+        self.set_padding(node, spec.padding);
+        for child in spec.children {
+            match child {
+                NodeSpec::Container(spec) => {
+                    let container = self.add_container(spec.axis, None);
+                    self.add_child(node, container)?;
+                    self.restore_container(container, spec, resolve)?;
+                    self.remove_empty(container);
+                }
+                NodeSpec::Leaf(spec) => {
+                    if let Some((widget, tag)) = resolve(spec.tag) {
+                        let leaf = self.add_leaf_id(widget);
+                        self.add_child(node, leaf)?;
+                        self.restore_leaf(leaf, tag, spec.size)?;
+                    }
+                }
             }
         }
+        Ok(())
+    }
+
+    fn restore_leaf(&mut self, node: NodeId, tag: String, size: Option<Constraint>) -> Result<()> {
+        if let Some(size) = size {
+            self.set_size(node, size)?;
+        }
+        self.tags.insert(node, tag);
+        Ok(())
     }
 
     fn is_floating(&self, node: NodeId) -> bool {
         matches!(self.nodes.get(node), Some(LayoutNode::Floating(_)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lays out leaves with the given constraints side by side along `axis`, in a container
+    /// `extent` cells long, and returns their sizes along the axis
+    fn row(axis: Axis, constraints: &[Constraint], extent: usize) -> Vec<usize> {
+        let mut layout: Layout = Layout::new();
+        let root = layout.root();
+        layout.set_direction(root, axis);
+        let leaves = constraints
+            .iter()
+            .map(|constraint| {
+                let leaf = layout.add_leaf_id(WidgetId::default());
+                layout.add_child(root, leaf).unwrap();
+                layout.set_size(leaf, constraint.clone()).unwrap();
+                leaf
+            })
+            .collect::<Vec<_>>();
+        layout.compute(&Rect::from_size((extent, extent)));
+        leaves
+            .iter()
+            .map(|leaf| {
+                let rect = layout.layout(*leaf).unwrap();
+                match axis {
+                    Axis::Horizontal => rect.width as usize,
+                    Axis::Vertical => rect.height as usize,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn minimums_larger_than_the_container_shrink_proportionally() {
+        let mins = [20, 10, 30].map(Constraint::Min);
+        assert_eq!(row(Axis::Horizontal, &mins, 60), [20, 10, 30]);
+        // The cells left over from rounding go to the first children
+        assert_eq!(row(Axis::Horizontal, &mins, 31), [11, 5, 15]);
+        assert_eq!(row(Axis::Vertical, &mins, 6), [2, 1, 3]);
+        assert_eq!(row(Axis::Horizontal, &mins, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn grown_minimum_includes_the_frame() {
+        // What a bordered widget with a 20 column minimum asks for
+        let min = Constraint::Min(20).grow(2);
+        let sizes = |extent| row(Axis::Horizontal, &[min.clone(), Constraint::Fill], extent);
+        assert_eq!(sizes(30), [22, 8]);
+        assert_eq!(sizes(60), [30, 30]);
+    }
+
+    #[test]
+    fn children_exactly_fill_their_container() {
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            for count in 1..=7 {
+                let share = Constraint::Percentage(1. / count as f32);
+                let mixed = (0..count)
+                    .map(|i| match i % 2 {
+                        0 => Constraint::Fill,
+                        _ => share.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                for constraints in [vec![Constraint::Fill; count], mixed, vec![share; count]] {
+                    for extent in 0..=120 {
+                        let total = row(axis, &constraints, extent).iter().sum::<usize>();
+                        assert_eq!(total, extent, "{constraints:?} along {axis:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_children_that_fit_underfit_and_overfit() {
+        let fixed = [10, 20, 30].map(Constraint::Fixed);
+        assert_eq!(row(Axis::Horizontal, &fixed, 60), [10, 20, 30]);
+        // Nothing fills the space left, so the last child takes it
+        assert_eq!(row(Axis::Horizontal, &fixed, 80), [10, 20, 50]);
+        // Every child shrinks in proportion to its size
+        assert_eq!(row(Axis::Horizontal, &fixed, 40), [7, 13, 20]);
+        assert_eq!(row(Axis::Vertical, &fixed, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn percentages_round_to_whole_cells() {
+        let sizes = [
+            Constraint::percent(30),
+            Constraint::percent(30),
+            Constraint::Fill,
+        ];
+        assert_eq!(row(Axis::Horizontal, &sizes, 100), [30, 30, 40]);
+        assert_eq!(row(Axis::Horizontal, &sizes, 10), [3, 3, 4]);
+        assert_eq!(row(Axis::Horizontal, &sizes, 33), [10, 10, 13]);
+    }
+
+    #[test]
+    fn invalid_constraints_are_rejected() {
+        let mut layout: Layout = Layout::new();
+        let leaf = layout.add_leaf_id(WidgetId::default());
+        for size in [
+            Constraint::Percentage(1.5),
+            Constraint::Percentage(-0.1),
+            Constraint::Aspect(0.),
+            Constraint::Aspect(f32::NAN),
+        ] {
+            assert!(matches!(
+                layout.set_size(leaf, size.clone()),
+                Err(Error::InvalidConstraint(invalid)) if invalid == size
+            ));
+            assert!(matches!(
+                layout.add_with_children(Axis::Horizontal, Some(size), Vec::new()),
+                Err(Error::InvalidConstraint(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn helpers_only_build_valid_constraints() {
+        assert_eq!(Constraint::percent(150), Constraint::Percentage(1.));
+        assert_eq!(Constraint::ratio(3, 2), Constraint::Percentage(1.));
+        assert_eq!(Constraint::ratio(1, 0), Constraint::Percentage(0.));
+        for size in [
+            Constraint::percent(150),
+            Constraint::ratio(3, 2),
+            Constraint::ratio(1, 0),
+        ] {
+            assert!(size.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn incremental_compute_matches_a_full_compute() {
+        let bounds = Rect::from_size((80, 24));
+        let mut layout: Layout = Layout::new();
+        let root = layout.root();
+        let left = layout.add_leaf_id(WidgetId::default());
+        layout.add_child(root, left).unwrap();
+        let right = layout
+            .split(left, Axis::Horizontal, WidgetId::default())
+            .unwrap();
+        layout.compute(&bounds);
+
+        let below = layout
+            .split(right, Axis::Vertical, WidgetId::default())
+            .unwrap();
+        layout.compute(&bounds);
+        let (first, _) = layout.split_at((39, 5)).unwrap();
+        layout.resize_node(first, Direction::Right, 3).unwrap();
+        layout.compute(&bounds);
+        layout.resize_node(below, Direction::Up, 4).unwrap();
+        layout.set_size(left, Constraint::Min(50)).unwrap();
+        layout.compute(&bounds);
+        let float = layout.add_floating(WidgetId::default(), Rect::new(10., 5., 20., 8.));
+        layout.compute(&bounds);
+        let gone = layout.add_floating(WidgetId::default(), Rect::new(2., 2., 6., 4.));
+        layout.compute(&bounds);
+        layout.remove_float(gone);
+        layout.compute(&bounds);
+
+        let rects = |layout: &Layout| {
+            layout
+                .nodes
+                .keys()
+                .map(|node| (node, layout.layout(node).copied()))
+                .collect::<Vec<_>>()
+        };
+        let incremental = rects(&layout);
+        assert!(incremental.iter().any(|(node, _)| *node == float));
+        layout.mark_dirty(None);
+        layout.compute(&bounds);
+        assert_eq!(incremental, rects(&layout));
+    }
+
+    #[test]
+    fn resizing_stops_at_the_minimum_split_size() {
+        let mut layout: Layout = Layout::new();
+        let root = layout.root();
+        layout.set_direction(root, Axis::Horizontal);
+        let [left, right] = [(); 2].map(|_| {
+            let leaf = layout.add_leaf_id(WidgetId::default());
+            layout.add_child(root, leaf).unwrap();
+            leaf
+        });
+        let bounds = Rect::from_size((40, 10));
+        let widths = |layout: &mut Layout| {
+            layout.compute(&bounds);
+            [left, right].map(|leaf| layout.layout(leaf).unwrap().width as usize)
+        };
+        assert_eq!(widths(&mut layout), [20, 20]);
+
+        layout.resize_node(left, Direction::Right, 100).unwrap();
+        assert_eq!(widths(&mut layout), [37, 3]);
+        layout.resize_node(right, Direction::Right, 100).unwrap();
+        assert_eq!(widths(&mut layout), [3, 37]);
+
+        let missing = NodeId::default();
+        assert!(matches!(
+            layout.resize_node(missing, Direction::Left, 1),
+            Err(Error::NodeNotFound(node)) if node == missing
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 #![doc = include_str!("../README.md")]
 //!
-//!```
+//!```no_run
 #![doc = include_str!("../examples/demo.rs")]
 //!```
 
@@ -8,11 +8,6 @@
 pub mod surface {
     pub use termwiz::surface::{Change, CursorShape, CursorVisibility, Position, Surface};
     pub use termwiz::terminal::Terminal;
-
-    pub(crate) mod term {
-        pub use termwiz::caps::Capabilities;
-        pub use termwiz::terminal::{buffered::BufferedTerminal, UnixTerminal};
-    }
 }
 
 /// Re-exports from [`termwiz`] relating to text style
@@ -25,13 +20,20 @@ pub mod style {
 
 pub use app::*;
 pub use layout::Layout;
-pub use widget::Widget;
+pub use widget::{CursorState, Widget};
 
 pub mod ansi;
 mod app;
+pub mod backend;
 pub mod bridge;
+pub mod config_file;
 pub mod error;
 pub mod event;
 pub mod layout;
+pub mod log;
+pub mod render_util;
+pub mod rope;
+pub mod text;
+pub mod theme;
 mod widget;
 pub mod widgets;
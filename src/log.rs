@@ -0,0 +1,140 @@
+//! A global logger that keeps recent records in memory instead of printing them, so logging
+//! doesn't draw over the app.
+//!
+//! Install it with [`init`] at the start of `main`, before the app is created, and records logged
+//! with the [`log`](::log) crate's macros are kept from then on. They can be read with
+//! [`App::logs`](crate::App::logs), and shown in the log view toggled with
+//! [`Config::log_view_key`](crate::Config::log_view_key) (<kbd>F12</kbd> by default).
+//!
+//! ```
+//! sanguine::log::init().unwrap();
+//! log::warn!(target: "demo", "disk almost full");
+//!
+//! let records = sanguine::log::records();
+//! assert_eq!(records.last().unwrap().to_string(), "WARN  demo: disk almost full");
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock, RwLock,
+    },
+};
+
+pub use ::log::{Level, LevelFilter};
+
+use crate::error::{Error, Result};
+
+/// How many records are kept by default
+const DEFAULT_CAPACITY: usize = 1000;
+
+static LOGGER: Logger = Logger {
+    records: Mutex::new(VecDeque::new()),
+    lines: OnceLock::new(),
+    capacity: AtomicUsize::new(DEFAULT_CAPACITY),
+};
+
+/// A record kept by the logger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub level: Level,
+    /// The module the record was logged from, or the target given to the macro
+    pub target: String,
+    pub message: String,
+}
+
+/// Formats the record as a line of the log view, which colors lines by their level
+impl Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+/// Keeps the most recent records, and the lines they're shown as in log views
+struct Logger {
+    records: Mutex<VecDeque<LogRecord>>,
+    /// The records formatted as lines, shared with the log views showing them
+    lines: OnceLock<Arc<RwLock<VecDeque<String>>>>,
+    capacity: AtomicUsize,
+}
+
+impl Logger {
+    fn lines(&self) -> &Arc<RwLock<VecDeque<String>>> {
+        self.lines.get_or_init(Default::default)
+    }
+}
+
+impl ::log::Log for Logger {
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        metadata.level() <= ::log::max_level()
+    }
+
+    fn log(&self, record: &::log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let record = LogRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        };
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        if let Ok(mut lines) = self.lines().write() {
+            // Multi-line messages, like layout dumps, take a line each in the view
+            lines.extend(record.to_string().lines().map(str::to_owned));
+            let excess = lines.len().saturating_sub(capacity);
+            lines.drain(..excess);
+        }
+        if let Ok(mut records) = self.records.lock() {
+            records.push_back(record);
+            let excess = records.len().saturating_sub(capacity);
+            records.drain(..excess);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the in-memory logger as the global logger, keeping records of every level. Fails if
+/// another logger was installed.
+pub fn init() -> Result<()> {
+    init_with_level(LevelFilter::Trace)
+}
+
+/// Install the in-memory logger as the global logger, keeping records up to `level`
+pub fn init_with_level(level: LevelFilter) -> Result<()> {
+    ::log::set_logger(&LOGGER).map_err(|_| Error::LoggerAlreadySet)?;
+    ::log::set_max_level(level);
+    Ok(())
+}
+
+/// The records kept by the logger, oldest first
+pub fn records() -> Vec<LogRecord> {
+    let records = LOGGER.records.lock();
+    records
+        .map(|r| r.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The records formatted as lines, for showing in a
+/// [`LogView::from_buffer`](crate::widgets::LogView::from_buffer)
+pub fn buffer() -> Arc<RwLock<VecDeque<String>>> {
+    LOGGER.lines().clone()
+}
+
+/// Set how many records are kept `default: 1000`. Older records are dropped first.
+pub fn set_capacity(capacity: usize) {
+    LOGGER.capacity.store(capacity.max(1), Ordering::Relaxed);
+}
+
+/// Drop every record kept so far
+pub fn clear() {
+    if let Ok(mut records) = LOGGER.records.lock() {
+        records.clear();
+    }
+    if let Ok(mut lines) = LOGGER.lines().write() {
+        lines.clear();
+    }
+}
@@ -0,0 +1,102 @@
+//! Helpers for widgets that reuse what they drew in the last frame, see
+//! [`RenderCtx::previous_surface`](crate::widget::RenderCtx::previous_surface).
+
+use crate::{
+    style::{CellAttributes, ColorAttribute},
+    surface::{Change, Position, Surface},
+};
+
+/// Fills `surface` with the contents of `prev` scrolled by `lines` rows, and calls `render_line`
+/// with each row that `prev` doesn't cover to draw it. Positive `lines` moves the contents up,
+/// as when scrolling down through text, so the rows at the bottom are drawn, and negative
+/// `lines` moves them down.
+///
+/// Every row is drawn if there's no previous surface, if it's a different size, or if it's
+/// scrolled out of view entirely. `render_line` is called with the cursor at the start of the row
+/// and the default attributes, and with rows in order from the top. Rows copied from `prev` that
+/// the widget knows have changed can be drawn again with [`redraw_line`], see also
+/// [`changed_lines`].
+///
+/// ```
+/// use sanguine::{render_util::scroll_and_render_delta, surface::{Change, Surface}};
+///
+/// let lines = ["one", "two", "three", "four"];
+/// let draw = |top: usize, prev: Option<&Surface>, scrolled: isize, drawn: &mut Vec<usize>| {
+///     let mut surface = Surface::new(5, 3);
+///     scroll_and_render_delta(&mut surface, prev, scrolled, |row, surface| {
+///         drawn.push(row);
+///         surface.add_change(Change::Text(lines[top + row].to_owned()));
+///     });
+///     surface
+/// };
+///
+/// let mut drawn = vec![];
+/// let first = draw(0, None, 0, &mut drawn);
+/// assert_eq!(drawn, [0, 1, 2]);
+///
+/// // Scrolling down a line only draws the line that came into view
+/// drawn.clear();
+/// let second = draw(1, Some(&first), 1, &mut drawn);
+/// assert_eq!(drawn, [2]);
+/// assert_eq!(second.screen_chars_to_string(), "two  \nthree\nfour \n");
+/// ```
+pub fn scroll_and_render_delta(
+    surface: &mut Surface,
+    prev: Option<&Surface>,
+    lines: isize,
+    mut render_line: impl FnMut(usize, &mut Surface),
+) {
+    let (width, height) = surface.dimensions();
+    let shift = lines.unsigned_abs();
+    let prev = prev.filter(|prev| prev.dimensions() == (width, height) && shift < height);
+    let Some(prev) = prev else {
+        for row in 0..height {
+            start_line(surface, row);
+            render_line(row, surface);
+        }
+        return;
+    };
+
+    // Copy the rows that are still in view to where they moved
+    let kept = height - shift;
+    let (to, from) = if lines >= 0 { (0, shift) } else { (shift, 0) };
+    let changes = surface.diff_region(0, to, width, kept, prev, 0, from);
+    surface.add_changes(changes);
+
+    let exposed = if lines >= 0 { kept..height } else { 0..shift };
+    for row in exposed {
+        start_line(surface, row);
+        render_line(row, surface);
+    }
+}
+
+/// Clears row `y` of `surface` and calls `render_line` to draw it again, with the cursor at the
+/// start of the row and the default attributes
+pub fn redraw_line(surface: &mut Surface, y: usize, render_line: impl FnOnce(usize, &mut Surface)) {
+    start_line(surface, y);
+    surface.add_change(Change::ClearToEndOfLine(ColorAttribute::Default));
+    render_line(y, surface);
+}
+
+/// The rows of `surface` whose text, ignoring trailing blanks, isn't `text(row)`. Widgets whose
+/// contents can change anywhere, not only by scrolling, use this to find the rows copied from the
+/// last frame that need to be drawn again.
+pub fn changed_lines(surface: &Surface, mut text: impl FnMut(usize) -> String) -> Vec<usize> {
+    surface
+        .screen_lines()
+        .iter()
+        .enumerate()
+        .filter(|(row, line)| line.as_str().trim_end() != text(*row).trim_end())
+        .map(|(row, _)| row)
+        .collect()
+}
+
+fn start_line(surface: &mut Surface, y: usize) {
+    surface.add_changes(vec![
+        Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(y),
+        },
+        Change::AllAttributes(CellAttributes::default()),
+    ]);
+}
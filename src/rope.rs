@@ -0,0 +1,215 @@
+#![cfg(feature = "rope")]
+//! A text buffer for large files, enabled with the `rope` feature
+//!
+//! [`Rope`] stores lines in chunks of a few hundred, with an index of how many lines each chunk
+//! holds. Finding a line takes O(log n), and an edit only moves the lines of one chunk, so typing
+//! stays fast in buffers of tens of megabytes. Use it with
+//! [`TextBox::from_reader`](crate::widgets::TextBox::from_reader).
+//!
+//! ```
+//! use sanguine::rope::Rope;
+//!
+//! let mut rope = Rope::from("first\nsecond");
+//! rope.insert_char(1, 6, '!');
+//! rope.split_line(0, 3);
+//! assert_eq!(rope.lines().collect::<Vec<_>>(), ["fir", "st", "second!"]);
+//! ```
+
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    error::{Error, Result},
+    text::byte_index,
+};
+
+/// How many lines a chunk holds before it's split in two
+const CHUNK_LINES: usize = 512;
+
+/// Lines of text grouped into chunks. There is always at least one line.
+#[derive(Debug, Clone)]
+pub struct Rope {
+    chunks: Vec<Vec<String>>,
+    /// A Fenwick tree of the number of lines in each chunk, for finding the chunk a line is in
+    index: Vec<usize>,
+    len_lines: usize,
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Self::from_lines(text.lines().map(str::to_owned))
+    }
+}
+
+impl Rope {
+    /// A rope holding one empty line
+    pub fn new() -> Self {
+        Self::from_lines(std::iter::empty())
+    }
+
+    /// Read text into a rope, a line at a time. Both `\n` and `\r\n` end lines, and the text must
+    /// be valid UTF-8.
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        let lines = BufReader::new(reader)
+            .lines()
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(Error::external)?;
+        Ok(Self::from_lines(lines))
+    }
+
+    fn from_lines(lines: impl IntoIterator<Item = String>) -> Self {
+        let mut chunks = vec![];
+        let mut lines = lines.into_iter().peekable();
+        while lines.peek().is_some() {
+            chunks.push(lines.by_ref().take(CHUNK_LINES).collect());
+        }
+        if chunks.is_empty() {
+            chunks.push(vec![String::new()]);
+        }
+        let mut rope = Self {
+            len_lines: chunks.iter().map(Vec::len).sum(),
+            chunks,
+            index: vec![],
+        };
+        rope.rebuild_index();
+        rope
+    }
+
+    /// The number of lines, which is never zero
+    pub fn len_lines(&self) -> usize {
+        self.len_lines
+    }
+
+    /// The line at index `y`, without its line break
+    pub fn line(&self, y: usize) -> Option<&str> {
+        let (chunk, offset) = self.locate(y)?;
+        Some(&self.chunks[chunk][offset])
+    }
+
+    /// The lines from first to last
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().flatten().map(String::as_str)
+    }
+
+    /// Insert a character before character `x` of line `y`, or at its end if it's shorter
+    pub fn insert_char(&mut self, y: usize, x: usize, c: char) {
+        if let Some(line) = self.line_mut(y) {
+            line.insert(byte_index(line, x), c);
+        }
+    }
+
+    /// Remove character `x` of line `y`, returning it
+    pub fn remove_char(&mut self, y: usize, x: usize) -> Option<char> {
+        let line = self.line_mut(y)?;
+        let at = byte_index(line, x);
+        (at < line.len()).then(|| line.remove(at))
+    }
+
+    /// Split line `y` before character `x`, moving the rest of it to a new line below
+    pub fn split_line(&mut self, y: usize, x: usize) {
+        let Some(line) = self.line_mut(y) else {
+            return;
+        };
+        let rest = line.split_off(byte_index(line, x));
+        self.insert_line(y + 1, rest);
+    }
+
+    /// Append line `y + 1` to line `y`, removing it
+    pub fn join_lines(&mut self, y: usize) {
+        if y + 1 >= self.len_lines {
+            return;
+        }
+        if let Some(next) = self.remove_line(y + 1) {
+            if let Some(line) = self.line_mut(y) {
+                line.push_str(&next);
+            }
+        }
+    }
+
+    /// Insert a line before line `y`, or after the last line if `y` is past it
+    pub fn insert_line(&mut self, y: usize, line: String) {
+        let (chunk, offset) = match self.locate(y) {
+            Some(found) => found,
+            None => {
+                let last = self.chunks.len() - 1;
+                (last, self.chunks[last].len())
+            }
+        };
+        self.chunks[chunk].insert(offset, line);
+        self.len_lines += 1;
+        if self.chunks[chunk].len() > CHUNK_LINES {
+            let half = self.chunks[chunk].len() / 2;
+            let tail = self.chunks[chunk].split_off(half);
+            self.chunks.insert(chunk + 1, tail);
+            self.rebuild_index();
+        } else {
+            self.add_to_index(chunk, 1);
+        }
+    }
+
+    /// Remove line `y`, returning it. The last line left is never removed.
+    pub fn remove_line(&mut self, y: usize) -> Option<String> {
+        if self.len_lines == 1 {
+            return None;
+        }
+        let (chunk, offset) = self.locate(y)?;
+        let line = self.chunks[chunk].remove(offset);
+        self.len_lines -= 1;
+        if self.chunks[chunk].is_empty() {
+            self.chunks.remove(chunk);
+            self.rebuild_index();
+        } else {
+            self.add_to_index(chunk, -1);
+        }
+        Some(line)
+    }
+
+    fn line_mut(&mut self, y: usize) -> Option<&mut String> {
+        let (chunk, offset) = self.locate(y)?;
+        Some(&mut self.chunks[chunk][offset])
+    }
+
+    /// The chunk holding line `y`, and the line's index within it
+    fn locate(&self, y: usize) -> Option<(usize, usize)> {
+        if y >= self.len_lines {
+            return None;
+        }
+        // Descend the tree to the last chunk that starts at or before the line
+        let (mut chunk, mut rest) = (0, y);
+        let mut step = self.chunks.len().next_power_of_two();
+        while step > 0 {
+            if chunk + step <= self.chunks.len() && self.index[chunk + step] <= rest {
+                chunk += step;
+                rest -= self.index[chunk];
+            }
+            step /= 2;
+        }
+        Some((chunk, rest))
+    }
+
+    fn add_to_index(&mut self, chunk: usize, delta: isize) {
+        let mut i = chunk + 1;
+        while i < self.index.len() {
+            self.index[i] = self.index[i].wrapping_add_signed(delta);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn rebuild_index(&mut self) {
+        let len = self.chunks.len();
+        self.index.clear();
+        self.index.resize(len + 1, 0);
+        for i in 1..=len {
+            self.index[i] += self.chunks[i - 1].len();
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= len {
+                self.index[parent] += self.index[i];
+            }
+        }
+    }
+}
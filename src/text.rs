@@ -0,0 +1,234 @@
+//! Styled text for widgets to render, made of [`Span`]s of text with their own attributes.
+//!
+//! ```
+//! use sanguine::{
+//!     layout::Rect,
+//!     style::{AnsiColor, CellAttributes},
+//!     surface::Surface,
+//!     text::{render_styled, Alignment, Line, Span, StyledText},
+//! };
+//!
+//! let mut bold = CellAttributes::default();
+//! bold.set_foreground(AnsiColor::Red);
+//! let text = StyledText::from(Line(vec![
+//!     Span::raw("Status: "),
+//!     Span::new("failed", bold),
+//! ]));
+//!
+//! let mut surface = Surface::new(10, 2);
+//! render_styled(&mut surface, &text, Rect::new(0., 0., 10., 2.), true, Alignment::Left);
+//! assert_eq!(surface.screen_chars_to_string(), "Status: fa\niled      \n");
+//! ```
+
+use termwiz::cell::{grapheme_column_width, CellAttributes};
+
+use crate::{
+    layout::Rect,
+    surface::{Change, Position, Surface},
+};
+
+/// How lines are positioned within the width of the rect they're rendered in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A piece of text drawn with the same attributes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub content: String,
+    pub attrs: CellAttributes,
+}
+
+impl Span {
+    pub fn new(content: impl Into<String>, attrs: CellAttributes) -> Self {
+        Self {
+            content: content.into(),
+            attrs,
+        }
+    }
+
+    /// A span with the default attributes
+    pub fn raw(content: impl Into<String>) -> Self {
+        Self::new(content, CellAttributes::default())
+    }
+
+    /// The number of columns the span takes up on screen
+    pub fn width(&self) -> usize {
+        self.content.chars().map(char_width).sum()
+    }
+}
+
+impl From<&str> for Span {
+    fn from(content: &str) -> Self {
+        Self::raw(content)
+    }
+}
+
+impl From<String> for Span {
+    fn from(content: String) -> Self {
+        Self::raw(content)
+    }
+}
+
+/// A single line of spans. Spans shouldn't contain newlines, use more [`Line`]s instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Line(pub Vec<Span>);
+
+impl Line {
+    /// The number of columns the line takes up on screen, before wrapping
+    pub fn width(&self) -> usize {
+        self.0.iter().map(Span::width).sum()
+    }
+}
+
+impl From<Span> for Line {
+    fn from(span: Span) -> Self {
+        Self(vec![span])
+    }
+}
+
+impl From<Vec<Span>> for Line {
+    fn from(spans: Vec<Span>) -> Self {
+        Self(spans)
+    }
+}
+
+impl From<&str> for Line {
+    fn from(content: &str) -> Self {
+        Self::from(Span::raw(content))
+    }
+}
+
+/// Lines of styled text
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyledText(pub Vec<Line>);
+
+impl From<Line> for StyledText {
+    fn from(line: Line) -> Self {
+        Self(vec![line])
+    }
+}
+
+impl From<Vec<Line>> for StyledText {
+    fn from(lines: Vec<Line>) -> Self {
+        Self(lines)
+    }
+}
+
+impl From<&str> for StyledText {
+    /// Unstyled text, with a line for each line of `content`
+    fn from(content: &str) -> Self {
+        Self(content.lines().map(Line::from).collect())
+    }
+}
+
+/// The width of a character on screen, which is 0 for combining characters
+fn char_width(c: char) -> usize {
+    grapheme_column_width(c.encode_utf8(&mut [0; 4]), None)
+}
+
+/// The byte offset of character `x` of `line`, or the line's length if it's shorter
+pub(crate) fn byte_index(line: &str, x: usize) -> usize {
+    line.char_indices().nth(x).map_or(line.len(), |(i, _)| i)
+}
+
+/// Splits a line into rows of at most `width` columns, or a single row cut off at `width` if
+/// `wrap` is false
+fn layout_line(line: &Line, width: usize, wrap: bool) -> Vec<(Vec<Span>, usize)> {
+    let mut rows = vec![(vec![], 0)];
+    for span in &line.0 {
+        let mut content = String::new();
+        for c in span.content.chars() {
+            let (row, used) = rows.last_mut().expect("there is always a row");
+            let char_width = char_width(c);
+            if *used + char_width > width {
+                if !wrap {
+                    break;
+                }
+                if !content.is_empty() {
+                    row.push(Span::new(std::mem::take(&mut content), span.attrs.clone()));
+                }
+                // A character wider than the rect can't be drawn at all
+                if char_width > width {
+                    continue;
+                }
+                rows.push((vec![], 0));
+            }
+            content.push(c);
+            rows.last_mut().expect("there is always a row").1 += char_width;
+        }
+        if !content.is_empty() {
+            let (row, _) = rows.last_mut().expect("there is always a row");
+            row.push(Span::new(content, span.attrs.clone()));
+        }
+    }
+    rows
+}
+
+/// Draws styled text within `rect` on `surface`, cut off at the edges of the rect. Lines that
+/// are too wide for the rect are wrapped onto the next row if `wrap` is true, or cut off
+/// otherwise. Wrapping and alignment go by the width of the text on screen, so wide characters
+/// like CJK and emoji take two columns.
+///
+/// Cells in the rect that aren't covered by text are left as they were, so use [`fill`] first to
+/// give the text a background. The attributes are reset at the end of each line.
+pub fn render_styled(
+    surface: &mut Surface,
+    text: &StyledText,
+    rect: Rect,
+    wrap: bool,
+    align: Alignment,
+) {
+    let Some(rect) = rect.intersection(&Rect::from_size(surface.dimensions())) else {
+        return;
+    };
+    let (x, y) = rect.origin_usize();
+    let (width, height) = rect.size_usize();
+    let rows = text
+        .0
+        .iter()
+        .flat_map(|line| layout_line(line, width, wrap))
+        .take(height);
+
+    let mut changes = vec![];
+    for (row, (spans, used)) in rows.enumerate() {
+        let offset = match align {
+            Alignment::Left => 0,
+            Alignment::Center => (width - used) / 2,
+            Alignment::Right => width - used,
+        };
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(x + offset),
+            y: Position::Absolute(y + row),
+        });
+        for span in spans {
+            changes.push(Change::AllAttributes(span.attrs));
+            changes.push(Change::Text(span.content));
+        }
+        changes.push(Change::AllAttributes(CellAttributes::default()));
+    }
+    surface.add_changes(changes);
+}
+
+/// Fills `rect` on `surface` with spaces drawn with `attrs`, such as to give text a background
+pub fn fill(surface: &mut Surface, rect: Rect, attrs: &CellAttributes) {
+    let Some(rect) = rect.intersection(&Rect::from_size(surface.dimensions())) else {
+        return;
+    };
+    let (x, y) = rect.origin_usize();
+    let (width, height) = rect.size_usize();
+    let mut changes = vec![Change::AllAttributes(attrs.clone())];
+    for row in y..y + height {
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(x),
+            y: Position::Absolute(row),
+        });
+        changes.push(Change::Text(" ".repeat(width)));
+    }
+    changes.push(Change::AllAttributes(CellAttributes::default()));
+    surface.add_changes(changes);
+}
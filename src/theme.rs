@@ -0,0 +1,178 @@
+//! Named styles that widgets look up when they render, so an app's colors can be changed in one
+//! place.
+//!
+//! Built-in widgets use these keys:
+//!
+//! | Key                | Used for                                           |
+//! |--------------------|----------------------------------------------------|
+//! | `border`           | [`Border`](crate::widgets::Border)s                |
+//! | `border.focused`   | The border of the focused window                   |
+//! | `menu.title`       | The title row of a [`Menu`](crate::widgets::Menu)  |
+//! | `menu.item`        | Menu items                                         |
+//! | `menu.selected`    | The active menu item                               |
+//! | `menu.hovered`     | The menu item under the mouse                      |
+//! | `palette.selected` | The active command in a command palette            |
+//! | `tree.selected`    | The selected node of a tree                        |
+//! | `textbox.search`   | [`TextBox`](crate::widgets::TextBox) search query  |
+//! | `textbox.match`    | Search matches in a textbox                        |
+//! | `input`            | The text of an [`Input`](crate::widgets::Input)    |
+//! | `button.focused`   | A [`Button`](crate::widgets::Button) with focus    |
+//! | `form.focused`     | The label of the field with focus in a form        |
+//! | `text.dim`         | Hints and other secondary text                     |
+//! | `error`            | Panels shown in place of widgets that failed       |
+//! | `notify.info`      | The border of an info notification                 |
+//! | `notify.warn`      | The border of a warning notification               |
+//! | `notify.error`     | The border of an error notification                |
+//! | `tooltip`          | Tooltips shown with `UpdateCtx::set_tooltip`       |
+//! | `picker`           | Window labels shown by `App::pick_window`          |
+//! | `metrics`          | The overlay shown with `Config::show_metrics`      |
+//!
+//! Apps can add their own keys, and look them up from [`RenderCtx::theme`].
+//!
+//! ```
+//! use sanguine::{
+//!     style::{AnsiColor, CellAttributes},
+//!     theme::Theme,
+//! };
+//!
+//! let mut accent = CellAttributes::default();
+//! accent.set_foreground(AnsiColor::Fuchsia);
+//! let theme = Theme::light().with("border.focused", accent.clone()).with("accent", accent);
+//!
+//! assert_eq!(theme.style("accent").foreground(), AnsiColor::Fuchsia.into());
+//! // Keys without a style use the default attributes
+//! assert_eq!(theme.style("unknown"), CellAttributes::default());
+//! ```
+//!
+//! [`RenderCtx::theme`]: crate::RenderCtx::theme
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::style::{AnsiColor, CellAttributes, ColorAttribute, Intensity};
+
+/// A map of style keys, such as `"menu.selected"`, to the attributes used to draw them. See the
+/// [module docs](self) for the keys used by built-in widgets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    styles: HashMap<String, CellAttributes>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// Attributes with the given foreground and background colors
+fn colors(fg: impl Into<ColorAttribute>, bg: impl Into<ColorAttribute>) -> CellAttributes {
+    let mut attrs = CellAttributes::default();
+    attrs.set_foreground(fg).set_background(bg);
+    attrs
+}
+
+impl Theme {
+    /// A theme with no styles, where every key uses the default attributes
+    pub fn empty() -> Self {
+        Self {
+            styles: HashMap::new(),
+        }
+    }
+
+    /// The default theme, for terminals with a dark background
+    pub fn dark() -> Self {
+        Self::with_base(
+            colors(AnsiColor::Black, AnsiColor::White),
+            colors(ColorAttribute::Default, AnsiColor::Grey),
+            AnsiColor::Aqua,
+            AnsiColor::Yellow,
+            AnsiColor::Red,
+        )
+    }
+
+    /// A theme for terminals with a light background
+    pub fn light() -> Self {
+        Self::with_base(
+            colors(AnsiColor::White, AnsiColor::Navy),
+            colors(ColorAttribute::Default, AnsiColor::Silver),
+            AnsiColor::Navy,
+            AnsiColor::Olive,
+            AnsiColor::Maroon,
+        )
+    }
+
+    /// Builds a preset from its selection, hover, accent, warning and error colors
+    fn with_base(
+        selected: CellAttributes,
+        hovered: CellAttributes,
+        accent: AnsiColor,
+        warning: AnsiColor,
+        error: AnsiColor,
+    ) -> Self {
+        let mut dim = CellAttributes::default();
+        dim.set_intensity(Intensity::Half);
+        let fg = |color: AnsiColor| {
+            let mut attrs = CellAttributes::default();
+            attrs.set_foreground(color);
+            attrs
+        };
+        Self::empty()
+            .with("border", CellAttributes::default())
+            .with("border.focused", fg(accent))
+            .with("menu.title", selected.clone())
+            .with("menu.item", CellAttributes::default())
+            .with("menu.selected", selected.clone())
+            .with("menu.hovered", hovered.clone())
+            .with("palette.selected", selected.clone())
+            .with("tree.selected", selected.clone())
+            .with("textbox.search", hovered.clone())
+            .with("textbox.match", colors(AnsiColor::Black, warning))
+            .with("input", hovered.clone())
+            .with("button.focused", selected.clone())
+            .with("picker", selected)
+            .with("form.focused", fg(accent))
+            .with("text.dim", dim)
+            .with("error", fg(error))
+            .with("notify.info", fg(accent))
+            .with("notify.warn", fg(warning))
+            .with("notify.error", fg(error))
+            .with("tooltip", hovered.clone())
+            .with("metrics", hovered)
+    }
+
+    /// Set the style of a key, replacing any existing style
+    pub fn with(mut self, key: impl Into<String>, attrs: CellAttributes) -> Self {
+        self.set(key, attrs);
+        self
+    }
+
+    /// Set the style of a key, replacing any existing style
+    pub fn set(&mut self, key: impl Into<String>, attrs: CellAttributes) {
+        self.styles.insert(key.into(), attrs);
+    }
+
+    /// Remove the style of a key, returning it if there was one
+    pub fn remove(&mut self, key: &str) -> Option<CellAttributes> {
+        self.styles.remove(key)
+    }
+
+    /// The style of a key, if it has one
+    pub fn get(&self, key: &str) -> Option<&CellAttributes> {
+        self.styles.get(key)
+    }
+
+    /// The style of a key, or the default attributes if it has none
+    pub fn style(&self, key: &str) -> CellAttributes {
+        self.get(key).cloned().unwrap_or_default()
+    }
+
+    /// Iterate over every key and its style
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CellAttributes)> {
+        self.styles.iter().map(|(key, attrs)| (key.as_str(), attrs))
+    }
+
+    /// The default theme, shared by contexts that aren't created by an [`crate::App`]
+    pub(crate) fn fallback() -> &'static Theme {
+        static FALLBACK: OnceLock<Theme> = OnceLock::new();
+        FALLBACK.get_or_init(Theme::default)
+    }
+}
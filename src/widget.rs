@@ -1,21 +1,108 @@
-use std::{
-    ptr::NonNull,
-    sync::{mpsc::Sender, Arc},
-};
-
 use crate::{
-    event::{Event, UserEvent},
+    app::TooltipRequest,
+    event::{Event, EventSender, TaskHandle},
     layout::*,
-    surface::Surface,
+    surface::{CursorShape, Surface},
+    theme::Theme,
     WidgetStore,
 };
 
+/// Where and how to show the cursor in a focused widget, returned from [`Widget::cursor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorState {
+    /// The position of the cursor, relative to the widget
+    pub position: (usize, usize),
+    /// The index of an inner widget returned from [`Widget::render`] whose cursor is shown
+    /// instead, if any. The app asks that widget for its cursor, which may delegate to one of its
+    /// own inner widgets in turn, and the position and shape are taken from the last one.
+    pub child: Option<usize>,
+    pub shape: CursorShape,
+    pub visible: bool,
+}
+
+impl CursorState {
+    /// A visible cursor at the given position, in the terminal's default shape
+    pub fn at(x: usize, y: usize) -> Self {
+        Self {
+            position: (x, y),
+            child: None,
+            shape: CursorShape::Default,
+            visible: true,
+        }
+    }
+
+    /// Show the cursor of the inner widget at index `child` instead, wherever that widget is
+    /// drawn. Widgets that wrap another, like [`Border`](crate::widgets::Border), return this.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+    ///     widgets::{Border, TextBox},
+    ///     App,
+    /// };
+    ///
+    /// let mut app = App::<(), ()>::test(12, 5).with_layout(|layout, widgets| {
+    ///     let textbox = widgets.register(TextBox::new());
+    ///     let inner = widgets.register(Border::new("In", textbox));
+    ///     let leaf = layout.add_leaf(widgets, Border::new("Out", inner));
+    ///     layout.add_child(layout.root(), leaf).ok()?;
+    ///     Some(leaf)
+    /// });
+    ///
+    /// let backend = app.backend_mut::<TestBackend>().unwrap();
+    /// for c in "hi".chars() {
+    ///     backend.push_input(InputEvent::Key(KeyEvent {
+    ///         key: KeyCode::Char(c),
+    ///         modifiers: Modifiers::NONE,
+    ///     }));
+    /// }
+    /// app.handle_events().unwrap();
+    /// app.render().unwrap();
+    ///
+    /// // After the text, inside both frames
+    /// assert_eq!(app.backend::<TestBackend>().unwrap().cursor(), Some((4, 2)));
+    /// ```
+    pub fn delegate(child: usize) -> Self {
+        Self::at(0, 0).in_child(child)
+    }
+
+    /// Show the cursor of the inner widget at index `child` instead. See [`CursorState::delegate`].
+    pub fn in_child(mut self, child: usize) -> Self {
+        self.child = Some(child);
+        self
+    }
+
+    pub fn with_shape(mut self, shape: CursorShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+}
+
 /// The data passed to [`Widget::render`]
 pub struct RenderCtx<'render, U, S> {
     pub focused: bool,
     pub layout: &'render Layout<U, S>,
     pub state: &'render S,
     widgets: &'render WidgetStore<U, S>,
+    theme: &'render Theme,
+    /// The widget being rendered
+    pub(crate) widget: WidgetId,
+    /// The window being rendered
+    pub(crate) owner: NodeId,
+    /// Whether the window being rendered is zoomed
+    pub(crate) zoomed: bool,
+    /// What the widget drew in the last frame, if it can be reused
+    pub(crate) previous: Option<&'render Surface>,
+    /// The index of the inner widget with focus among the inner widgets this widget returned the
+    /// last time it was rendered, if it returned several. See
+    /// [`App::focused_child`](crate::App::focused_child).
+    pub focused_child: Option<usize>,
 }
 
 /// The data passed to [`Widget::update`]
@@ -23,9 +110,12 @@ pub struct UpdateCtx<'update, U, S> {
     pub owner: NodeId,
     pub bounds: Rect,
     pub layout: &'update mut Layout<U, S>,
-    pub tx: Arc<Sender<UserEvent<U>>>,
+    pub tx: EventSender<U>,
     pub state: &'update mut S,
-    widgets: NonNull<WidgetStore<U, S>>,
+    widgets: &'update mut WidgetStore<U, S>,
+    theme: &'update Theme,
+    /// Where tooltip requests are left for the app, see [`UpdateCtx::set_tooltip`]
+    pub(crate) tooltip: Option<&'update mut Option<TooltipRequest>>,
 }
 
 impl<'render, U, S> RenderCtx<'render, U, S> {
@@ -40,9 +130,74 @@ impl<'render, U, S> RenderCtx<'render, U, S> {
             layout,
             widgets,
             state,
+            theme: Theme::fallback(),
+            widget: WidgetId::default(),
+            owner: NodeId::default(),
+            zoomed: false,
+            previous: None,
+            focused_child: None,
         }
     }
 
+    /// Render as part of the given window, whose data is returned by [`RenderCtx::node_data`]
+    pub fn with_owner(mut self, owner: NodeId) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    /// The window being rendered. Inner widgets are rendered with the window of the widget that
+    /// contains them.
+    pub fn owner(&self) -> NodeId {
+        self.owner
+    }
+
+    /// Render as part of a zoomed window. See [`App::toggle_zoom`](crate::App::toggle_zoom).
+    pub fn with_zoomed(mut self, zoomed: bool) -> Self {
+        self.zoomed = zoomed;
+        self
+    }
+
+    /// Whether the window being rendered is zoomed to fill the terminal
+    pub fn zoomed(&self) -> bool {
+        self.zoomed
+    }
+
+    /// The surface the widget was rendered onto in the last frame, if it's the same size as the
+    /// one it's drawing on now. The surface being drawn on always starts out blank, but widgets
+    /// that scroll can copy the rows that didn't change from here instead of drawing them again,
+    /// see [`crate::render_util::scroll_and_render_delta`].
+    ///
+    /// The app discards it when the theme changes or a full redraw is requested, but anything
+    /// else the widget's output depends on, such as [`RenderCtx::focused`], is up to the widget
+    /// to check.
+    pub fn previous_surface(&self) -> Option<&'render Surface> {
+        self.previous
+    }
+
+    /// The data attached to the window being rendered, if it has data of type `T`. See
+    /// [`Layout::set_data`].
+    pub fn node_data<T: 'static>(&self) -> Option<&'render T> {
+        self.layout.get_data(self.owner)
+    }
+
+    /// Use the given theme instead of the default one
+    pub fn with_theme(mut self, theme: &'render Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The app's theme, which widgets look up their styles from
+    pub fn theme(&self) -> &'render Theme {
+        self.theme
+    }
+
+    /// Render the widget again on the next frame, even if it hasn't been updated. Widgets that
+    /// display external state, such as a buffer shared with other threads, can call this from
+    /// [`Widget::render`] to stay up to date.
+    pub fn request_redraw(&self) {
+        self.widgets.mark_dirty(self.widget);
+    }
+
     pub fn get_widget(&self, id: WidgetId) -> Option<&'render dyn Widget<U, S>> {
         self.widgets.get(id)
     }
@@ -50,61 +205,167 @@ impl<'render, U, S> RenderCtx<'render, U, S> {
     pub fn resolve<T: Widget<U, S> + 'static>(&self, id: WidgetId) -> Option<&T> {
         self.widgets.resolve(id)
     }
+
+    /// The id of the widget registered under `name`. See [`WidgetStore::register_named`].
+    pub fn id_of(&self, name: &str) -> Option<WidgetId> {
+        self.widgets.id_of(name)
+    }
+
+    /// Get a reference to the widget registered under `name` as a concrete type, for example to
+    /// show the state of a collaborating widget
+    pub fn resolve_named<T: Widget<U, S> + 'static>(&self, name: &str) -> Option<&'render T> {
+        self.widgets.resolve_named(name)
+    }
+
+    /// All registered widgets, for looking up or iterating over other widgets
+    pub fn widgets(&self) -> &'render WidgetStore<U, S> {
+        self.widgets
+    }
 }
 
 impl<'update, U, S> UpdateCtx<'update, U, S> {
     pub fn new(
         owner: NodeId,
         bounds: Rect,
-        widgets: *mut WidgetStore<U, S>,
+        widgets: &'update mut WidgetStore<U, S>,
         layout: &'update mut Layout<U, S>,
-        tx: Arc<Sender<UserEvent<U>>>,
+        tx: EventSender<U>,
         state: &'update mut S,
     ) -> Self {
         Self {
             owner,
             bounds,
-            widgets: unsafe { NonNull::new_unchecked(widgets) },
+            widgets,
             layout,
             tx,
             state,
+            theme: Theme::fallback(),
+            tooltip: None,
         }
     }
 
+    /// Use the given theme instead of the default one
+    pub fn with_theme(mut self, theme: &'update Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The app's theme, which widgets look up their styles from
+    pub fn theme(&self) -> &'update Theme {
+        self.theme
+    }
+
+    /// The data attached to the window the event was sent to, if it has data of type `T`. See
+    /// [`Layout::set_data`].
+    pub fn node_data<T: 'static>(&self) -> Option<&T> {
+        self.layout.get_data(self.owner)
+    }
+
+    /// The data attached to the window the event was sent to mutably, if it has data of type
+    /// `T`
+    pub fn node_data_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.layout.get_data_mut(self.owner)
+    }
+
+    /// Attach data to the window the event was sent to, replacing any data it already had
+    pub fn set_node_data(&mut self, data: Box<dyn std::any::Any + Send>) {
+        self.layout.set_data(self.owner, data);
+    }
+
     /// Get a reference to a widget by its ID, as an immutable `dyn Widget` trait object.
-    pub fn get_widget(&self, id: WidgetId) -> Option<&'update dyn Widget<U, S>> {
-        unsafe { self.widgets.as_ref().get(id) }
+    ///
+    /// The widget being updated is taken out of the store until its update returns, so it can't
+    /// be reached through the context.
+    pub fn get_widget(&self, id: WidgetId) -> Option<&dyn Widget<U, S>> {
+        self.widgets.get(id)
     }
 
     /// Get a reference to a widget by its ID, as a mutable `dyn Widget` trait object.
-    pub fn get_widget_mut(&mut self, id: WidgetId) -> Option<&'update mut dyn Widget<U, S>> {
-        unsafe { self.widgets.as_mut().get_mut(id) }
+    pub fn get_widget_mut(&mut self, id: WidgetId) -> Option<&mut dyn Widget<U, S>> {
+        self.widgets.get_mut(id)
+    }
+
+    /// Send an event to another widget, such as an inner widget, with this context's bounds.
+    /// Use [`UpdateCtx::with_rect`] to give the widget different bounds.
+    pub fn update_widget(&mut self, id: WidgetId, event: Event<U>) -> crate::error::Result<()> {
+        let owner = self.owner;
+        let bounds = self.bounds;
+        let tx = self.tx.clone();
+        let (layout, state, theme) = (&mut *self.layout, &mut *self.state, self.theme);
+        let tooltip = self.tooltip.as_deref_mut();
+        self.widgets
+            .with_widget(id, |widget, widgets| {
+                let mut cx =
+                    UpdateCtx::new(owner, bounds, widgets, layout, tx, state).with_theme(theme);
+                cx.tooltip = tooltip;
+                widget.update(&mut cx, event)
+            })
+            .ok_or(crate::error::Error::WidgetNotFound(owner))?
+    }
+
+    /// All registered widgets except the one being updated, for looking up or iterating over
+    /// other widgets
+    pub fn widgets(&self) -> &WidgetStore<U, S> {
+        self.widgets
     }
 
     /// Remove a widget from the widget store.
-    ///
-    /// Note that any references to the widget following this call are invalid.
     pub fn remove_widget(&mut self, id: WidgetId) {
-        unsafe { self.widgets.as_mut().remove(id) };
+        self.widgets.remove(id);
     }
 
     /// Get an immutable reference to a widget by its ID, and attempt to downcast it to a concrete type.
-    pub fn resolve<W: Widget<U, S> + 'static>(&self, id: WidgetId) -> Option<&'update W> {
-        unsafe { self.widgets.as_ref().resolve::<W>(id) }
+    pub fn resolve<W: Widget<U, S> + 'static>(&self, id: WidgetId) -> Option<&W> {
+        self.widgets.resolve::<W>(id)
     }
 
     /// Get a mutable reference to a widget by its ID, and attempt to downcast it to a concrete
     /// type.
-    pub fn resolve_mut<W: Widget<U, S> + 'static>(
-        &mut self,
-        id: WidgetId,
-    ) -> Option<&'update mut W> {
-        unsafe { self.widgets.as_mut().resolve_mut::<W>(id) }
+    pub fn resolve_mut<W: Widget<U, S> + 'static>(&mut self, id: WidgetId) -> Option<&mut W> {
+        self.widgets.resolve_mut::<W>(id)
+    }
+
+    /// The id of the widget registered under `name`. See [`WidgetStore::register_named`].
+    pub fn id_of(&self, name: &str) -> Option<WidgetId> {
+        self.widgets.id_of(name)
+    }
+
+    /// Get an immutable reference to the widget registered under `name`, and attempt to downcast
+    /// it to a concrete type.
+    pub fn resolve_named<W: Widget<U, S> + 'static>(&self, name: &str) -> Option<&W> {
+        self.widgets.resolve_named::<W>(name)
+    }
+
+    /// Get a mutable reference to the widget registered under `name`, and attempt to downcast it
+    /// to a concrete type.
+    pub fn resolve_named_mut<W: Widget<U, S> + 'static>(&mut self, name: &str) -> Option<&mut W> {
+        self.widgets.resolve_named_mut::<W>(name)
+    }
+
+    /// Redraw the given widget on the next render. Widgets are redrawn automatically after they
+    /// are updated, so this is only needed when a widget changes state shown by another widget.
+    pub fn request_redraw(&mut self, id: WidgetId) {
+        self.widgets.mark_dirty(id)
+    }
+
+    /// Run `f` on a worker thread, and send its result to the widget shown in the owner window as
+    /// a user event once it's done. The result is dropped if the window's widget has been removed
+    /// by then.
+    pub fn spawn<R>(
+        &self,
+        f: impl FnOnce() -> R + Send + 'static,
+        map: impl FnOnce(R) -> U + Send + 'static,
+    ) -> TaskHandle
+    where
+        U: Send + 'static,
+    {
+        let widget = self.layout.widget(self.owner);
+        self.tx.spawn_to(widget, f, map)
     }
 
     /// Register a new widget with the widget store.
     pub fn register_widget(&mut self, widget: impl Widget<U, S> + 'static) -> WidgetId {
-        unsafe { self.widgets.as_mut().register(widget) }
+        self.widgets.register(widget)
     }
 
     /// Create a new [`UpdateCtx`] with different bounds, intended for rendering inner widgets.
@@ -112,10 +373,12 @@ impl<'update, U, S> UpdateCtx<'update, U, S> {
         UpdateCtx {
             owner: self.owner,
             bounds: rect,
-            widgets: self.widgets,
-            layout: self.layout,
+            widgets: &mut *self.widgets,
+            layout: &mut *self.layout,
             tx: self.tx.clone(),
-            state: self.state,
+            state: &mut *self.state,
+            theme: self.theme,
+            tooltip: self.tooltip.as_deref_mut(),
         }
     }
 }
@@ -132,7 +395,15 @@ impl<'update, U, S> UpdateCtx<'update, U, S> {
 pub trait Widget<U, S> {
     /// This method is called every render loop, and is responsible for rendering the widget onto
     /// the provided surface.
-    fn render(&self, cx: &RenderCtx<U, S>, surface: &mut Surface) -> Option<Vec<(Rect, WidgetId)>>;
+    ///
+    /// Returns the inner widgets to render on top of this one, with their bounds relative to the
+    /// surface, or an empty vec if there are none. An error stops the frame from being drawn, and
+    /// is returned from [`crate::App::render`] as [`crate::error::Error::WidgetRenderFailed`].
+    fn render(
+        &self,
+        cx: &RenderCtx<U, S>,
+        surface: &mut Surface,
+    ) -> crate::error::Result<Vec<(Rect, WidgetId)>>;
 
     /// This method is called when an input event is received that targets this widget.
     /// It allows the widget to update its internal state in response to an event.
@@ -140,26 +411,124 @@ pub trait Widget<U, S> {
         Ok(())
     }
 
+    /// Called with clicks on the inner widgets this widget returned from render, and when it
+    /// returned several focusable ones, with key presses for the one with focus, before the inner
+    /// widget receives them. `child` is the index of the inner widget, and mouse positions are
+    /// relative to it. Returning `true` consumes the event, so the inner widget doesn't receive it.
+    fn child_event(
+        &mut self,
+        cx: &mut UpdateCtx<U, S>,
+        child: usize,
+        event: &Event<U>,
+    ) -> crate::error::Result<bool> {
+        Ok(false)
+    }
+
     /// This method is called when the widget is focused, to determine where (or if) to display the
-    /// cursor.
-    fn cursor(&self, widgets: &WidgetStore<U, S>) -> Option<(Option<usize>, usize, usize)> {
+    /// cursor, and in what shape. Returning `None` hides the cursor.
+    fn cursor(&self, widgets: &WidgetStore<U, S>) -> Option<CursorState> {
+        None
+    }
+
+    /// Whether <kbd>Tab</kbd> can move focus to this widget when it's one of several inner widgets
+    /// of a window. Widgets that only display information can return `false` to be skipped.
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    /// Whether the cells this widget doesn't draw on show what's beneath it. The surface a
+    /// transparent widget renders onto starts out with the contents already drawn under its
+    /// bounds, such as the widget that returned it as an inner widget or, for the widget of a
+    /// floating window, the windows below the float, instead of being blank.
+    ///
+    /// What's beneath can change without the widget changing, so windows showing a transparent
+    /// widget aren't cached: they're rendered again on every frame that's drawn. For the same
+    /// reason, [`RenderCtx::previous_surface`] includes what was beneath the widget last time.
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     error::Result,
+    ///     layout::{Rect, WidgetId},
+    ///     surface::{Change, Surface},
+    ///     widgets::TextBox,
+    ///     App, RenderCtx, Widget,
+    /// };
+    ///
+    /// /// Marks its top left corner, leaving the rest of the window beneath it visible
+    /// struct Marker;
+    ///
+    /// impl Widget<(), ()> for Marker {
+    ///     fn render(&self, _: &RenderCtx<(), ()>, surface: &mut Surface) -> Result<Vec<(Rect, WidgetId)>> {
+    ///         surface.add_change(Change::Text("*".to_owned()));
+    ///         Ok(vec![])
+    ///     }
+    ///
+    ///     fn transparent(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn as_any(&self) -> &dyn std::any::Any {
+    ///         self
+    ///     }
+    ///
+    ///     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+    ///         self
+    ///     }
+    /// }
+    ///
+    /// let mut app = App::<(), ()>::test(8, 3).with_layout(|layout, widgets| {
+    ///     let text = TextBox::from_str("abcdefgh\nijklmnop\nqrstuvwx");
+    ///     let leaf = layout.add_leaf(widgets, text);
+    ///     layout.add_child(layout.root(), leaf).ok()?;
+    ///     Some(leaf)
+    /// });
+    /// app.open_float(Marker, 4, 1);
+    /// app.render().unwrap();
+    ///
+    /// app.backend::<TestBackend>().unwrap().assert_screen(&[
+    ///     "abcdefgh",
+    ///     "ij*lmnop",
+    ///     "qrstuvwx",
+    /// ]);
+    /// ```
+    fn transparent(&self) -> bool {
+        false
+    }
+
+    /// The value entered into the widget, if it's an input like [`crate::widgets::Input`]. Used to
+    /// collect the values of a [`crate::widgets::Form`].
+    fn value(&self) -> Option<String> {
         None
     }
 
+    /// Replace the value entered into the widget, returning whether it has a value to set. See
+    /// [`Widget::value`].
+    fn set_value(&mut self, value: &str) -> bool {
+        false
+    }
+
     /// This method provides a hint to the layout engine about how much
     /// space the widget should take up.
     fn constraint(&self, widgets: &WidgetStore<U, S>) -> Constraint {
         Constraint::Fill
     }
 
+    /// The inner widgets this widget shows, such as the contents of a
+    /// [`crate::widgets::Border`]. They count as references in [`Layout::widget_refcount`], so
+    /// they're kept while this widget is registered.
+    fn children(&self) -> Vec<WidgetId> {
+        vec![]
+    }
+
     /// Convert the widget into an immutable [`std::any::Any`] trait object, for use when resolving
     /// widgets to concrete types. This should usually return `self`. They are required to be
     /// implemented by each widget because a ref'd concrete type (&Self) implementing widget can be cast to &dyn Any,
     /// but trait objects such as &dyn Widget cannot.
     ///
-    /// ```rust
+    /// ```rust,ignore
     /// fn as_any(&self) -> &dyn std::any::Any {
-    ///		self
+    ///     self
     /// }
     /// ```
     ///
@@ -170,9 +539,9 @@ pub trait Widget<U, S> {
     /// implemented by each widget because a ref'd concrete type (&Self) implementing widget can be cast to &dyn Any,
     /// but trait objects such as &dyn Widget cannot.
     ///
-    /// ```rust
+    /// ```rust,ignore
     /// fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-    ///		self
+    ///     self
     /// }
     /// ```
     ///
@@ -0,0 +1,142 @@
+//! Scrollable view of text with ANSI escape sequences, such as the colored output of a command.
+
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    ansi::{incomplete_escape, parse_ansi},
+    error::{read_or_err, write_or_err, Result},
+    event::{Event, KeyCode, KeyEvent},
+    layout::{Rect, WidgetId},
+    surface::Surface,
+    text::{render_styled, Alignment, StyledText},
+    widget::{RenderCtx, UpdateCtx, Widget},
+};
+
+/// Number of lines scrolled per mouse wheel step
+const WHEEL_STEP: usize = 3;
+
+/// Displays text containing ANSI escape sequences from a shared buffer, like the output of
+/// `git log --color` or any other command that writes colored output.
+///
+/// The buffer is parsed again each frame, so it can be written to from other threads. Output
+/// can be appended in chunks as it's read: an escape sequence cut off at the end of the buffer
+/// isn't shown until the rest of it is appended.
+///
+/// ```
+/// use sanguine::widgets::AnsiView;
+///
+/// let view = AnsiView::new();
+/// view.append("\x1b[32mgreen\x1b").unwrap();
+/// view.append("[0m plain\n").unwrap();
+/// assert_eq!(view.buffer().read().unwrap().as_str(), "\x1b[32mgreen\x1b[0m plain\n");
+/// ```
+pub struct AnsiView {
+    buf: Arc<RwLock<String>>,
+    /// The index of the top visible line
+    top: usize,
+}
+
+impl Default for AnsiView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiView {
+    pub fn new() -> Self {
+        Self::from_buffer(Arc::new(RwLock::new(String::new())))
+    }
+
+    /// Create a view displaying an existing shared buffer
+    pub fn from_buffer(buf: Arc<RwLock<String>>) -> Self {
+        Self { buf, top: 0 }
+    }
+
+    /// Get a shared handle to the buffer
+    pub fn buffer(&self) -> Arc<RwLock<String>> {
+        self.buf.clone()
+    }
+
+    /// Append text to the buffer, which may end in the middle of a line or escape sequence
+    pub fn append(&self, text: &str) -> Result<()> {
+        write_or_err(&self.buf)?.push_str(text);
+        Ok(())
+    }
+
+    /// Clear the buffer and scroll back to the top
+    pub fn clear(&mut self) -> Result<()> {
+        write_or_err(&self.buf)?.clear();
+        self.top = 0;
+        Ok(())
+    }
+
+    /// The index of the top visible line
+    pub fn scroll_offset(&self) -> usize {
+        self.top
+    }
+
+    /// Scroll up by the given number of lines
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.top = self.top.saturating_sub(lines);
+    }
+
+    /// Scroll down by the given number of lines, given the number of visible rows
+    pub fn scroll_down(&mut self, lines: usize, rows: usize) -> Result<()> {
+        let bottom = self.text()?.0.len().saturating_sub(rows);
+        self.top = self.top.saturating_add(lines).min(bottom);
+        Ok(())
+    }
+
+    /// The complete part of the buffer, parsed into styled lines
+    fn text(&self) -> Result<StyledText> {
+        let buf = read_or_err(&self.buf)?;
+        let end = incomplete_escape(&buf).unwrap_or(buf.len());
+        parse_ansi(&buf[..end])
+    }
+}
+
+impl<U, S> Widget<U, S> for AnsiView {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        // The buffer is shared, so it may change without the widget being updated
+        cx.request_redraw();
+        let (width, height) = surface.dimensions();
+        let mut text = self.text()?;
+        // The buffer may have been cleared or shortened by another thread since the last scroll
+        let top = self.top.min(text.0.len().saturating_sub(height));
+        text.0.drain(..top);
+        let rect = Rect::from_size((width, height));
+        render_styled(surface, &text, rect, false, Alignment::Left);
+        Ok(vec![])
+    }
+
+    fn update<'u>(&mut self, cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        let rows = cx.bounds.height_usize();
+        match event {
+            Event::Key(KeyEvent { key, .. }) => match key {
+                KeyCode::UpArrow => self.scroll_up(1),
+                KeyCode::DownArrow => self.scroll_down(1, rows)?,
+                KeyCode::PageUp => self.scroll_up(rows.max(1)),
+                KeyCode::PageDown => self.scroll_down(rows.max(1), rows)?,
+                KeyCode::Home => self.scroll_up(usize::MAX),
+                KeyCode::End => self.scroll_down(usize::MAX, rows)?,
+                _ => {}
+            },
+            Event::Scroll { delta, .. } if delta < 0 => self.scroll_up(WHEEL_STEP),
+            Event::Scroll { .. } => self.scroll_down(WHEEL_STEP, rows)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -1,18 +1,48 @@
 //! Displays a border around a widget, with a title and a `*` when the widget is focused.
 
 use crate::{
-    error::Error,
     event::Event,
-    layout::{Rect, WidgetId},
+    layout::{Constraint, Rect, WidgetId},
+    style::CellAttributes,
     surface::*,
-    widget::{RenderCtx, UpdateCtx},
+    widget::{CursorState, RenderCtx, UpdateCtx},
     Widget, WidgetStore,
 };
 
+/// Creates the title shown by a [`Border`] from its title and the render context
+type TitleFormat<U, S> = dyn Fn(&str, &RenderCtx<U, S>) -> String;
+
 /// Displays a border around a widget, with a title and a `*` when the widget is focused.
+///
+/// The border takes its constraint from the inner widget, with room for the frame added to fixed
+/// sizes, so a bordered single-line widget stays three rows tall:
+///
+/// ```
+/// use sanguine::{
+///     layout::{Axis, Layout, Rect},
+///     widgets::{Border, Input, TextBox},
+///     WidgetStore,
+/// };
+///
+/// let mut layout = Layout::<(), ()>::new();
+/// let mut widgets = WidgetStore::new();
+/// let input = widgets.register(Input::new());
+/// let search = layout.add_leaf(&mut widgets, Border::new("Search", input));
+/// let results = layout.add_leaf(&mut widgets, TextBox::new());
+/// layout.set_direction(layout.root(), Axis::Vertical);
+/// layout.add_child(layout.root(), search).unwrap();
+/// layout.add_child(layout.root(), results).unwrap();
+///
+/// layout.update_constraints(&widgets);
+/// layout.compute(&Rect::from_size((40, 20)));
+/// assert_eq!(layout.layout(search).unwrap().height, 3.);
+/// assert_eq!(layout.layout(results).unwrap().height, 17.);
+/// ```
 pub struct Border<U, S> {
     title: String,
     inner: WidgetId,
+    format_title: Option<Box<TitleFormat<U, S>>>,
+    transparent: bool,
     marker: std::marker::PhantomData<(S, U)>,
 }
 
@@ -20,10 +50,55 @@ impl<U, S> Border<U, S> {
     pub fn new(title: impl Into<String>, inner: WidgetId) -> Self {
         Self {
             title: title.into(),
-            inner: inner,
+            inner,
+            format_title: None,
+            transparent: false,
             marker: std::marker::PhantomData,
         }
     }
+
+    /// Create the title shown when the border is rendered from its title and the render context,
+    /// for example to show data attached to the window with
+    /// [`Layout::set_data`](crate::layout::Layout::set_data).
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use sanguine::{
+    ///     layout::Layout, surface::Surface, widgets::{Border, TextBox}, RenderCtx, Widget,
+    ///     WidgetStore,
+    /// };
+    ///
+    /// let mut layout = Layout::<(), ()>::new();
+    /// let mut widgets = WidgetStore::new();
+    /// let editor = widgets.register(TextBox::new());
+    /// let window = layout.add_leaf_id(editor);
+    /// layout.set_data(window, Box::new(PathBuf::from("notes.txt")));
+    ///
+    /// let border = Border::new("Editor", editor).with_title_format(|title, cx| {
+    ///     match cx.node_data::<PathBuf>() {
+    ///         Some(path) => format!("{title}: {}", path.display()),
+    ///         None => title.to_owned(),
+    ///     }
+    /// });
+    /// let cx = RenderCtx::new(false, &layout, &widgets, &()).with_owner(window);
+    /// let mut surface = Surface::new(24, 3);
+    /// border.render(&cx, &mut surface).unwrap();
+    /// assert!(surface.screen_chars_to_string().starts_with("┌Editor: notes.txt─────┐"));
+    /// ```
+    pub fn with_title_format(
+        mut self,
+        format: impl Fn(&str, &RenderCtx<U, S>) -> String + 'static,
+    ) -> Self {
+        self.format_title = Some(Box::new(format));
+        self
+    }
+
+    /// Let what's beneath the border show through around the frame and title, such as the
+    /// background of the widget that contains it. See [`Widget::transparent`].
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
 }
 
 const HORIZONTAL: char = '─';
@@ -38,17 +113,27 @@ impl<U: 'static, S: 'static> Widget<U, S> for Border<U, S> {
         &self,
         cx: &RenderCtx<'r, U, S>,
         surface: &mut Surface,
-    ) -> Option<Vec<(Rect, WidgetId)>> {
+    ) -> crate::error::Result<Vec<(Rect, WidgetId)>> {
         let (width, height) = surface.dimensions();
-        let mut changes = vec![];
+        let style = if cx.focused {
+            "border.focused"
+        } else {
+            "border"
+        };
+        let mut changes = vec![Change::AllAttributes(cx.theme().style(style))];
         changes.push(Change::Text(TOP_LEFT.to_string()));
-        let title = if cx.focused {
-            self.title.clone() + "*"
+        let title = match &self.format_title {
+            Some(format) => format(&self.title, cx),
+            None => self.title.clone(),
+        };
+        let title = if cx.zoomed() {
+            title + " (zoomed)"
         } else {
-            self.title.clone()
+            title
         };
+        let title = if cx.focused { title + "*" } else { title };
         changes.push(Change::Text(title.to_owned()));
-        for _ in 0..(width - 1 - title.len()) {
+        for _ in 0..width.saturating_sub(1 + title.chars().count()) {
             changes.push(Change::Text(HORIZONTAL.to_string()));
         }
         changes.push(Change::CursorPosition {
@@ -81,6 +166,7 @@ impl<U: 'static, S: 'static> Widget<U, S> for Border<U, S> {
             y: Position::Relative(0),
         });
         changes.push(Change::Text(BOTTOM_RIGHT.to_string()));
+        changes.push(Change::AllAttributes(CellAttributes::default()));
 
         surface.add_changes(changes);
 
@@ -91,18 +177,37 @@ impl<U: 'static, S: 'static> Widget<U, S> for Border<U, S> {
             width: (width - 2) as f32,
             height: (height - 2) as f32,
         };
-        Some(vec![(inner_rect, self.inner.clone())])
+        Ok(vec![(inner_rect, self.inner)])
     }
 
-    fn cursor(&self, widgets: &WidgetStore<U, S>) -> Option<(Option<usize>, usize, usize)> {
-        let w = widgets.get(self.inner)?;
-        let r = w.cursor(widgets);
-        r.map(|(_, x, y)| (Some(0), x, y))
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        Some(CursorState::delegate(0))
+    }
+
+    fn children(&self) -> Vec<WidgetId> {
+        vec![self.inner]
+    }
+
+    fn transparent(&self) -> bool {
+        self.transparent
+    }
+
+    /// The inner widget's constraint, with the frame added to fixed, minimum and maximum sizes
+    fn constraint(&self, widgets: &WidgetStore<U, S>) -> Constraint {
+        let Some(inner) = widgets.get(self.inner) else {
+            return Constraint::Fill;
+        };
+        match inner.constraint(widgets) {
+            Constraint::Fixed(size) => Constraint::Fixed(size + 2),
+            Constraint::Min(size) => Constraint::Min(size + 2),
+            Constraint::Max(size) => Constraint::Max(size + 2),
+            constraint => constraint,
+        }
     }
 
     fn update<'u>(
         &mut self,
-        mut cx: &mut UpdateCtx<'u, U, S>,
+        cx: &mut UpdateCtx<'u, U, S>,
         event: Event<U>,
     ) -> crate::error::Result<()> {
         let rect = Rect {
@@ -111,13 +216,7 @@ impl<U: 'static, S: 'static> Widget<U, S> for Border<U, S> {
             width: cx.bounds.width - 2.,
             height: cx.bounds.height - 2.,
         };
-
-        cx.bounds = rect;
-        let w = cx
-            .get_widget_mut(self.inner)
-            .ok_or(Error::external("could not find widget"))?;
-        w.update(cx, event)?;
-        Ok(())
+        cx.with_rect(rect).update_widget(self.inner, event)
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
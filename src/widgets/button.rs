@@ -0,0 +1,107 @@
+//! A button that runs an action when pressed.
+
+use crate::{
+    error::Result,
+    event::{Event, EventSender, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+    layout::{Constraint, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    widget::{RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// Whether an event presses a button or toggles a checkbox
+pub(super) fn activates(event: &Event<impl Sized>) -> bool {
+    matches!(
+        event,
+        Event::Key(KeyEvent {
+            key: KeyCode::Char(' ') | KeyCode::Enter,
+            modifiers: Modifiers::NONE,
+        }) | Event::Mouse(MouseEvent {
+            mouse_buttons: MouseButtons::LEFT,
+            ..
+        })
+    )
+}
+
+/// An action run when a button is pressed
+pub trait ButtonAction<U>: FnMut(&EventSender<U>) {}
+
+impl<C, U> ButtonAction<U> for C where C: FnMut(&EventSender<U>) {}
+
+/// A button drawn as `[ label ]`, which runs its action when pressed with <kbd>Space</kbd>,
+/// <kbd>Enter</kbd> or a click. It's highlighted while it has focus.
+pub struct Button<U> {
+    label: String,
+    action: Option<Box<dyn ButtonAction<U>>>,
+}
+
+impl<U> Button<U> {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            action: None,
+        }
+    }
+
+    /// Run `action` when the button is pressed
+    pub fn with_action(mut self, action: impl ButtonAction<U> + 'static) -> Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Run the button's action, as if it was pressed
+    pub fn press(&mut self, event_tx: &EventSender<U>) {
+        if let Some(action) = &mut self.action {
+            action(event_tx);
+        }
+    }
+}
+
+impl<U: 'static, S: 'static> Widget<U, S> for Button<U> {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        let attrs = if cx.focused {
+            cx.theme().style("button.focused")
+        } else {
+            Default::default()
+        };
+        let text = format!("[ {} ]", self.label);
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            Change::AllAttributes(attrs),
+            Change::Text(text.chars().take(width).collect()),
+            Change::AllAttributes(Default::default()),
+        ]);
+        Ok(vec![])
+    }
+
+    fn update(&mut self, cx: &mut UpdateCtx<U, S>, event: Event<U>) -> Result<()> {
+        if activates(&event) {
+            self.press(&cx.tx);
+        }
+        Ok(())
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
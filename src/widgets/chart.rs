@@ -0,0 +1,377 @@
+//! Sparkline and bar chart widgets for displaying series of values.
+
+use crate::{
+    error::Result,
+    layout::{Constraint, Rect, WidgetId},
+    style::{AnsiColor, CellAttributes, ColorAttribute},
+    surface::Surface,
+    text::{render_styled, Alignment, Line, Span, StyledText},
+    widget::{RenderCtx, Widget},
+    WidgetStore,
+};
+
+/// Block characters from 1/8th to a full cell tall.
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Clamps a value to be non-negative, treating NaN as zero.
+fn clamp_value(value: f64) -> f64 {
+    if value.is_nan() {
+        0.
+    } else {
+        value.max(0.)
+    }
+}
+
+/// Splits `values` into `buckets` consecutive groups of roughly equal size, and averages each
+/// group. Returns the values unchanged if there are no more of them than buckets.
+fn bucket(values: &[f64], buckets: usize) -> Vec<f64> {
+    if values.len() <= buckets {
+        return values.to_vec();
+    }
+    (0..buckets)
+        .map(|i| {
+            let group = &values[i * values.len() / buckets..(i + 1) * values.len() / buckets];
+            group.iter().sum::<f64>() / group.len() as f64
+        })
+        .collect()
+}
+
+/// Formats a value without decimals if it's a whole number, or with one decimal otherwise.
+fn format_value(value: f64) -> String {
+    if value.fract() == 0. {
+        format!("{value}")
+    } else {
+        format!("{value:.1}")
+    }
+}
+
+/// The largest value, or `None` if every value is zero.
+fn max_value(values: &[f64]) -> Option<f64> {
+    let max = values.iter().copied().fold(0., f64::max);
+    (max > 0.).then_some(max)
+}
+
+/// A single-row chart of a series of values, drawn with block characters scaled to the largest
+/// value.
+///
+/// The newest values are on the right. When there are more values than columns, neighbouring
+/// values are averaged so the whole series fits.
+///
+/// ```
+/// use sanguine::{surface::Surface, widgets::Sparkline, Layout, RenderCtx, Widget, WidgetStore};
+///
+/// let (layout, widgets) = (Layout::<(), ()>::new(), WidgetStore::new());
+/// let cx = RenderCtx::new(false, &layout, &widgets, &());
+/// let sparkline = Sparkline::new().with_data(vec![0., 2., 4., 6., 8., 10., 12., 14.]);
+///
+/// let mut surface = Surface::new(10, 1);
+/// sparkline.render(&cx, &mut surface).unwrap();
+/// assert_eq!(surface.screen_chars_to_string(), "  ▁▂▃▄▅▆▇█\n");
+///
+/// // Pairs of values are averaged to fit in four columns
+/// let mut surface = Surface::new(4, 1);
+/// sparkline.render(&cx, &mut surface).unwrap();
+/// assert_eq!(surface.screen_chars_to_string(), "▂▄▆█\n");
+/// ```
+pub struct Sparkline {
+    data: Vec<f64>,
+    max_width: Option<usize>,
+    style: CellAttributes,
+}
+
+impl Default for Sparkline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sparkline {
+    pub fn new() -> Self {
+        Self {
+            data: vec![],
+            max_width: None,
+            style: CellAttributes::default(),
+        }
+    }
+
+    /// Set the initial data, oldest value first
+    pub fn with_data(mut self, data: Vec<f64>) -> Self {
+        self.set_data(data);
+        self
+    }
+
+    /// Only show the newest `max_width` values
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Set the attributes used to draw the sparkline
+    pub fn with_style(mut self, style: CellAttributes) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the data, oldest value first. Negative and NaN values are shown as zero.
+    pub fn set_data(&mut self, data: Vec<f64>) {
+        self.data = data.into_iter().map(clamp_value).collect();
+    }
+
+    /// Add a value to the end of the data
+    pub fn push(&mut self, value: f64) {
+        self.data.push(clamp_value(value));
+    }
+
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// The characters of the sparkline for a row `width` cells wide, right-aligned
+    fn glyphs(&self, width: usize) -> String {
+        let window = self.max_width.unwrap_or(usize::MAX);
+        let shown = &self.data[self.data.len().saturating_sub(window)..];
+        let values = bucket(shown, width);
+        let max = max_value(&values);
+        let mut glyphs = " ".repeat(width - values.len());
+        glyphs.extend(values.iter().map(|value| match max {
+            Some(max) => LEVELS[((value / max) * 7.).round() as usize],
+            None => LEVELS[0],
+        }));
+        glyphs
+    }
+}
+
+impl<U, S> Widget<U, S> for Sparkline {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        if width == 0 || height == 0 {
+            return Ok(vec![]);
+        }
+        let line = Line(vec![Span::new(self.glyphs(width), self.style.clone())]);
+        let rect = Rect::from_size((width, 1));
+        render_styled(surface, &line.into(), rect, false, Alignment::Left);
+        Ok(vec![])
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// A chart of labeled vertical bars, with each bar's value shown above its label.
+///
+/// Bars are colored by cycling through a palette. When there are more bars than fit in the
+/// widget, neighbouring bars are averaged together and keep the label of the first one.
+///
+/// ```
+/// use sanguine::{surface::Surface, widgets::BarChart, Layout, RenderCtx, Widget, WidgetStore};
+///
+/// let (layout, widgets) = (Layout::<(), ()>::new(), WidgetStore::new());
+/// let cx = RenderCtx::new(false, &layout, &widgets, &());
+/// let chart = BarChart::new().with_data(vec![
+///     ("mon".to_owned(), 2.),
+///     ("tuesday".to_owned(), 4.),
+///     ("wed".to_owned(), f64::NAN),
+/// ]);
+///
+/// let mut surface = Surface::new(11, 4);
+/// chart.render(&cx, &mut surface).unwrap();
+/// assert_eq!(
+///     surface.screen_chars_to_string(),
+///     "    ███    \n███ ███    \n 2   4   0 \nmon tue wed\n"
+/// );
+/// ```
+pub struct BarChart {
+    data: Vec<(String, f64)>,
+    bar_width: usize,
+    gap: usize,
+    palette: Vec<ColorAttribute>,
+}
+
+impl Default for BarChart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BarChart {
+    pub fn new() -> Self {
+        Self {
+            data: vec![],
+            bar_width: 3,
+            gap: 1,
+            palette: vec![
+                AnsiColor::Blue.into(),
+                AnsiColor::Lime.into(),
+                AnsiColor::Yellow.into(),
+                AnsiColor::Fuchsia.into(),
+                AnsiColor::Aqua.into(),
+                AnsiColor::Red.into(),
+            ],
+        }
+    }
+
+    /// Set the initial bars, as pairs of labels and values
+    pub fn with_data(mut self, data: Vec<(String, f64)>) -> Self {
+        self.set_data(data);
+        self
+    }
+
+    /// Set the width of each bar, which is 3 columns by default
+    pub fn with_bar_width(mut self, bar_width: usize) -> Self {
+        self.bar_width = bar_width.max(1);
+        self
+    }
+
+    /// Set the number of columns between bars, which is 1 by default
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Set the colors of the bars, which are used in order and repeat when there are more bars
+    /// than colors. An empty palette draws the bars with the default color.
+    pub fn with_palette(mut self, palette: Vec<ColorAttribute>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Set the bars, as pairs of labels and values. Negative and NaN values are shown as zero.
+    pub fn set_data(&mut self, data: Vec<(String, f64)>) {
+        self.data = data
+            .into_iter()
+            .map(|(label, value)| (label, clamp_value(value)))
+            .collect();
+    }
+
+    pub fn data(&self) -> &[(String, f64)] {
+        &self.data
+    }
+
+    /// The bars that fit in `width` columns, averaging neighbouring bars if there are too many
+    fn bars(&self, width: usize) -> Vec<(&str, f64)> {
+        let fit = ((width + self.gap) / (self.bar_width + self.gap)).max(1);
+        if self.data.len() <= fit {
+            return self.data.iter().map(|(l, v)| (l.as_str(), *v)).collect();
+        }
+        let values = self.data.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+        bucket(&values, fit)
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (self.data[i * self.data.len() / fit].0.as_str(), value))
+            .collect()
+    }
+
+    fn bar_style(&self, index: usize) -> CellAttributes {
+        let mut attrs = CellAttributes::default();
+        if !self.palette.is_empty() {
+            attrs.set_foreground(self.palette[index % self.palette.len()]);
+        }
+        attrs
+    }
+}
+
+impl<U, S> Widget<U, S> for BarChart {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        if width == 0 || height < 3 {
+            return Ok(vec![]);
+        }
+        let bars = self.bars(width);
+        let values = bars.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+        let max = max_value(&values);
+        // The bottom two rows hold the values and labels
+        let rows = height - 2;
+        let eighths = values
+            .iter()
+            .map(|value| match max {
+                Some(max) => ((value / max) * (rows * 8) as f64).round() as usize,
+                None => 0,
+            })
+            .collect::<Vec<_>>();
+
+        let gap = Span::raw(" ".repeat(self.gap));
+        let mut lines = (0..rows)
+            .map(|row| {
+                // Rows are counted up from the bottom of the bars
+                let row = rows - 1 - row;
+                let mut spans = vec![];
+                for (i, eighths) in eighths.iter().enumerate() {
+                    let c = match eighths.saturating_sub(row * 8) {
+                        0 => ' ',
+                        n => LEVELS[n.min(8) - 1],
+                    };
+                    if i > 0 {
+                        spans.push(gap.clone());
+                    }
+                    spans.push(Span::new(
+                        c.to_string().repeat(self.bar_width),
+                        self.bar_style(i),
+                    ));
+                }
+                Line(spans)
+            })
+            .collect::<Vec<_>>();
+        lines.push(Line::default());
+        lines.push(Line::default());
+        render_styled(
+            surface,
+            &StyledText(lines),
+            Rect::from_size((width, height)),
+            false,
+            Alignment::Left,
+        );
+
+        // Values and labels are centered under their bars, and cut off at the bar's edges
+        for (i, (label, value)) in bars.iter().enumerate() {
+            let x = i * (self.bar_width + self.gap);
+            let cell = |y: usize| Rect::new(x as f32, y as f32, self.bar_width as f32, 1.);
+            let value = Line::from(Span::new(format_value(*value), self.bar_style(i)));
+            render_styled(surface, &value.into(), cell(rows), false, Alignment::Center);
+            render_styled(
+                surface,
+                &(*label).into(),
+                cell(rows + 1),
+                false,
+                Alignment::Center,
+            );
+        }
+        Ok(vec![])
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Min(3)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
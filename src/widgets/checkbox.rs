@@ -0,0 +1,93 @@
+//! A checkbox, for boolean fields of forms.
+
+use super::button::activates;
+use crate::{
+    error::Result,
+    event::Event,
+    layout::{Constraint, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    widget::{CursorState, RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// A box that is checked and unchecked with <kbd>Space</kbd>, <kbd>Enter</kbd> or a click,
+/// followed by a label. Its [`Widget::value`] is `"true"` or `"false"`.
+#[derive(Debug, Clone, Default)]
+pub struct Checkbox {
+    label: String,
+    checked: bool,
+}
+
+impl Checkbox {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            checked: false,
+        }
+    }
+
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+
+    pub fn checked(&self) -> bool {
+        self.checked
+    }
+
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+}
+
+impl<U, S> Widget<U, S> for Checkbox {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        let mark = if self.checked { 'x' } else { ' ' };
+        let text = format!("[{mark}] {}", self.label);
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            Change::Text(text.chars().take(width).collect()),
+        ]);
+        Ok(vec![])
+    }
+
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        Some(CursorState::at(1, 0))
+    }
+
+    fn update(&mut self, _cx: &mut UpdateCtx<U, S>, event: Event<U>) -> Result<()> {
+        if activates(&event) {
+            self.checked = !self.checked;
+        }
+        Ok(())
+    }
+
+    fn value(&self) -> Option<String> {
+        Some(self.checked.to_string())
+    }
+
+    fn set_value(&mut self, value: &str) -> bool {
+        self.checked = value == "true";
+        true
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,410 @@
+//! Labeled input fields stacked in rows, with validation and a submit action.
+
+use std::collections::HashMap;
+
+use crate::{
+    error::Result,
+    event::{Event, EventSender, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+    layout::{Constraint, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    widget::{RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// Checks the value of a form field, returning the message shown under the field if it's invalid
+pub type Validator = dyn Fn(&str) -> std::result::Result<(), String>;
+
+/// Called with the value of every field, by label, when a form is submitted
+pub trait FormAction<U>: FnMut(HashMap<String, String>, &EventSender<U>) {}
+
+impl<C, U> FormAction<U> for C where C: FnMut(HashMap<String, String>, &EventSender<U>) {}
+
+struct Field {
+    label: String,
+    widget: WidgetId,
+    validator: Option<Box<Validator>>,
+    /// The message from the validator when the form was last submitted
+    error: Option<String>,
+}
+
+/// Fields stacked in rows as `Label: value`, such as [`Input`](crate::widgets::Input)s and
+/// [`Checkbox`](crate::widgets::Checkbox)es, with an optional submit [`Button`] below them.
+///
+/// Each field is an inner widget, so <kbd>Tab</kbd> and <kbd>Shift</kbd>+<kbd>Tab</kbd> move
+/// between them and key presses go to the field with focus (see
+/// [`App::focused_child`](crate::App::focused_child)). <kbd>Enter</kbd> on the last field or on
+/// the submit button, or clicking the submit button, submits the form: every field is validated,
+/// and if they're all valid their values are passed to the [`Form::on_submit`] action. Invalid
+/// fields show their error under them.
+///
+/// Forms are usually shown in a [`Border`](crate::widgets::Border) in a floating window.
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use sanguine::{
+///     backend::TestBackend,
+///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+///     widgets::{Form, Input},
+///     App,
+/// };
+///
+/// let submitted = Rc::new(RefCell::new(None));
+/// let mut app = App::<(), ()>::test(16, 3).with_layout(|layout, widgets| {
+///     let name = widgets.register(Input::new());
+///     let port = widgets.register(Input::new().with_text("80"));
+///     let form = widgets.register(
+///         Form::new()
+///             .with_field("Name", name)
+///             .with_field("Port", port)
+///             .with_validator("Port", |port| {
+///                 let port = port.parse::<u16>().map_err(|_| "not a port")?;
+///                 (port != 0).then_some(()).ok_or("not a port".to_owned())
+///             })
+///             .on_submit({
+///                 let submitted = Rc::clone(&submitted);
+///                 move |values, _| *submitted.borrow_mut() = Some(values)
+///             }),
+///     );
+///     let leaf = layout.add_leaf_id(form);
+///     layout.add_child(layout.root(), leaf).ok()?;
+///     Some(leaf)
+/// });
+/// app.render().unwrap();
+///
+/// let backend = app.backend_mut::<TestBackend>().unwrap();
+/// let keys = "ferris".chars().map(KeyCode::Char);
+/// for key in keys.chain([KeyCode::Tab, KeyCode::Enter]) {
+///     backend.push_input(InputEvent::Key(KeyEvent {
+///         key,
+///         modifiers: Modifiers::NONE,
+///     }));
+/// }
+/// app.handle_events().unwrap();
+/// app.render().unwrap();
+///
+/// let values = submitted.borrow_mut().take().unwrap();
+/// assert_eq!(values["Name"], "ferris");
+/// assert_eq!(values["Port"], "80");
+/// app.backend::<TestBackend>().unwrap().assert_screen(&[
+///     "Name: ferris    ",
+///     "Port: 80        ",
+///     "                ",
+/// ]);
+/// ```
+/// ```
+pub struct Form<U> {
+    fields: Vec<Field>,
+    /// The submit button, shown below the fields
+    submit: Option<WidgetId>,
+    on_submit: Option<Box<dyn FormAction<U>>>,
+}
+
+impl<U> Default for Form<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<U> Form<U> {
+    pub fn new() -> Self {
+        Self {
+            fields: vec![],
+            submit: None,
+            on_submit: None,
+        }
+    }
+
+    /// Add a field below the existing ones. Labels identify fields, so they should be unique.
+    pub fn with_field(mut self, label: impl Into<String>, widget: WidgetId) -> Self {
+        self.add_field(label, widget);
+        self
+    }
+
+    /// Add a field below the existing ones. See [`Form::with_field`].
+    pub fn add_field(&mut self, label: impl Into<String>, widget: WidgetId) {
+        self.fields.push(Field {
+            label: label.into(),
+            widget,
+            validator: None,
+            error: None,
+        });
+    }
+
+    /// Check the value of the field with the given label when the form is submitted
+    pub fn with_validator(
+        mut self,
+        label: &str,
+        validator: impl Fn(&str) -> std::result::Result<(), String> + 'static,
+    ) -> Self {
+        if let Some(field) = self.fields.iter_mut().find(|f| f.label == label) {
+            field.validator = Some(Box::new(validator));
+        }
+        self
+    }
+
+    /// Show a button below the fields, which submits the form when pressed. The button's own
+    /// action isn't run.
+    pub fn with_submit_button(mut self, button: WidgetId) -> Self {
+        self.submit = Some(button);
+        self
+    }
+
+    /// Run `action` with the value of every field, by label, when the form is submitted and all of
+    /// its fields are valid
+    pub fn on_submit(mut self, action: impl FormAction<U> + 'static) -> Self {
+        self.on_submit = Some(Box::new(action));
+        self
+    }
+
+    /// The labels of the fields, from top to bottom
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|f| f.label.as_str())
+    }
+
+    /// The widget of the field with the given label
+    pub fn field(&self, label: &str) -> Option<WidgetId> {
+        let field = self.fields.iter().find(|f| f.label == label)?;
+        Some(field.widget)
+    }
+
+    /// The validation error shown under a field, if it was invalid when the form was last
+    /// submitted
+    pub fn error(&self, label: &str) -> Option<&str> {
+        let field = self.fields.iter().find(|f| f.label == label)?;
+        field.error.as_deref()
+    }
+
+    /// The value of every field by label, from [`Widget::value`]. Fields without a value are
+    /// left out.
+    pub fn values<S>(&self, widgets: &WidgetStore<U, S>) -> HashMap<String, String> {
+        self.fields
+            .iter()
+            .filter_map(|f| Some((f.label.clone(), widgets.get(f.widget)?.value()?)))
+            .collect()
+    }
+
+    /// Run the validators, updating the errors shown under the fields. Returns whether every
+    /// field is valid.
+    pub fn validate<S>(&mut self, widgets: &WidgetStore<U, S>) -> bool {
+        for field in &mut self.fields {
+            let value = widgets.get(field.widget).and_then(|w| w.value());
+            field.error = match (&field.validator, value) {
+                (Some(validator), Some(value)) => validator(&value).err(),
+                _ => None,
+            };
+        }
+        self.fields.iter().all(|f| f.error.is_none())
+    }
+
+    /// Validate the fields, and run the submit action if they're all valid. Returns whether the
+    /// form was submitted.
+    pub fn submit<S>(&mut self, widgets: &WidgetStore<U, S>, event_tx: &EventSender<U>) -> bool {
+        if !self.validate(widgets) {
+            return false;
+        }
+        let values = self.values(widgets);
+        if let Some(action) = &mut self.on_submit {
+            action(values, event_tx);
+        }
+        true
+    }
+
+    /// Set the value of a field of the form registered as `form`, returning whether the field
+    /// exists and has a value. See [`Widget::set_value`].
+    pub fn set_value<S>(
+        widgets: &mut WidgetStore<U, S>,
+        form: WidgetId,
+        label: &str,
+        value: &str,
+    ) -> bool
+    where
+        U: 'static,
+        S: 'static,
+    {
+        let field = widgets.resolve::<Self>(form).and_then(|f| f.field(label));
+        let widget = field.and_then(|field| widgets.get_mut(field));
+        widget.is_some_and(|w| w.set_value(value))
+    }
+
+    /// Clear every field and validation error of the form registered as `form`
+    pub fn reset<S>(widgets: &mut WidgetStore<U, S>, form: WidgetId)
+    where
+        U: 'static,
+        S: 'static,
+    {
+        let Some(form) = widgets.resolve_mut::<Self>(form) else {
+            return;
+        };
+        let fields = form.fields.iter_mut().map(|field| {
+            field.error = None;
+            field.widget
+        });
+        for field in fields.collect::<Vec<_>>() {
+            if let Some(widget) = widgets.get_mut(field) {
+                widget.set_value("");
+            }
+        }
+    }
+
+    /// The width of the label column, including the `: ` after the labels
+    fn label_width(&self) -> usize {
+        let widest = self.fields.iter().map(|f| f.label.chars().count()).max();
+        widest.unwrap_or(0) + 2
+    }
+
+    /// The bounds of the fields and the submit button, in that order. Fields are as tall as their
+    /// fixed size, or a single row, with a row for their error below them if they have one.
+    fn rows<S>(&self, widgets: &WidgetStore<U, S>, width: usize) -> Vec<(Rect, WidgetId)> {
+        let left = self.label_width();
+        let width = width.saturating_sub(left) as f32;
+        let mut y = 0;
+        let mut rows = vec![];
+        for field in &self.fields {
+            let constraint = widgets.get(field.widget).map(|w| w.constraint(widgets));
+            let height = match constraint {
+                Some(Constraint::Fixed(height)) => height.max(1),
+                _ => 1,
+            };
+            rows.push((
+                Rect::new(left as f32, y as f32, width, height as f32),
+                field.widget,
+            ));
+            y += height + usize::from(field.error.is_some());
+        }
+        if let Some(submit) = self.submit {
+            // Leave a blank row between the fields and the button
+            rows.push((Rect::new(left as f32, (y + 1) as f32, width, 1.), submit));
+        }
+        rows
+    }
+}
+
+impl<U: 'static, S: 'static> Widget<U, S> for Form<U> {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        let theme = cx.theme();
+        let rows = self.rows(cx.widgets(), width);
+        let mut changes = vec![];
+        for (i, (field, (rect, _))) in self.fields.iter().zip(&rows).enumerate() {
+            let attrs = if cx.focused && cx.focused_child == Some(i) {
+                theme.style("form.focused")
+            } else {
+                Default::default()
+            };
+            changes.extend([
+                Change::CursorPosition {
+                    x: Position::Absolute(0),
+                    y: Position::Absolute(rect.y as usize),
+                },
+                Change::AllAttributes(attrs),
+                Change::Text(format!("{}: ", field.label).chars().take(width).collect()),
+                Change::AllAttributes(Default::default()),
+            ]);
+            if let Some(error) = &field.error {
+                changes.extend([
+                    Change::CursorPosition {
+                        x: Position::Absolute(rect.x as usize),
+                        y: Position::Absolute(rect.bottom() as usize),
+                    },
+                    Change::AllAttributes(theme.style("error")),
+                    Change::Text(error.chars().take(rect.width as usize).collect()),
+                    Change::AllAttributes(Default::default()),
+                ]);
+            }
+        }
+        surface.add_changes(changes);
+        Ok(rows)
+    }
+
+    fn child_event(
+        &mut self,
+        cx: &mut UpdateCtx<U, S>,
+        child: usize,
+        event: &Event<U>,
+    ) -> Result<bool> {
+        let last_field = child + 1 == self.fields.len();
+        let on_submit = child == self.fields.len() && self.submit.is_some();
+        let submits = match event {
+            Event::Key(KeyEvent {
+                key: KeyCode::Enter,
+                modifiers: Modifiers::NONE,
+            }) => last_field || on_submit,
+            Event::Mouse(MouseEvent {
+                mouse_buttons: MouseButtons::LEFT,
+                ..
+            }) => on_submit,
+            _ => false,
+        };
+        if submits {
+            let event_tx = cx.tx.clone();
+            self.submit(cx.widgets(), &event_tx);
+        }
+        Ok(submits)
+    }
+
+    /// Mouse events reach the form instead of its fields when it's inside another widget, so
+    /// they're passed on to the field under the mouse
+    fn update(&mut self, cx: &mut UpdateCtx<U, S>, event: Event<U>) -> Result<()> {
+        let Event::Mouse(MouseEvent {
+            x,
+            y,
+            mouse_buttons,
+            modifiers,
+        }) = event
+        else {
+            return Ok(());
+        };
+        let rows = self.rows(cx.widgets(), cx.bounds.width_usize());
+        let Some((child, (rect, widget))) = rows
+            .into_iter()
+            .enumerate()
+            .find(|(_, (rect, _))| rect.contains(x as f32, y as f32))
+        else {
+            return Ok(());
+        };
+        let event = Event::Mouse(MouseEvent {
+            x: x - rect.x as u16,
+            y: y - rect.y as u16,
+            mouse_buttons,
+            modifiers,
+        });
+        if self.child_event(cx, child, &event)? {
+            return Ok(());
+        }
+        let bounds = Rect {
+            x: cx.bounds.x + rect.x,
+            y: cx.bounds.y + rect.y,
+            ..rect
+        };
+        cx.with_rect(bounds).update_widget(widget, event)
+    }
+
+    fn children(&self) -> Vec<WidgetId> {
+        let fields = self.fields.iter().map(|f| f.widget);
+        fields.chain(self.submit).collect()
+    }
+
+    fn constraint(&self, widgets: &WidgetStore<U, S>) -> Constraint {
+        let rows = self.rows(widgets, 0);
+        let fields = self.fields.iter().zip(&rows);
+        let bottom = fields
+            .map(|(field, (rect, _))| rect.bottom() as usize + usize::from(field.error.is_some()));
+        let bottom = bottom.chain(
+            rows.get(self.fields.len())
+                .map(|(r, _)| r.bottom() as usize),
+        );
+        Constraint::Fixed(bottom.max().unwrap_or(0))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,170 @@
+//! Single-line text input, for forms and prompts.
+
+use crate::{
+    error::Result,
+    event::{Event, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+    layout::{Constraint, Rect, WidgetId},
+    surface::{Change, Position, Surface},
+    text::fill,
+    widget::{CursorState, RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// A single line of editable text. The text scrolls horizontally to keep the cursor visible, and
+/// a placeholder can be shown while it's empty.
+///
+/// ```
+/// use sanguine::{widgets::Input, Widget};
+///
+/// let mut input = Input::new().with_text("hello");
+/// assert_eq!(input.text(), "hello");
+/// assert!(Widget::<(), ()>::set_value(&mut input, "world"));
+/// assert_eq!(Widget::<(), ()>::value(&input).as_deref(), Some("world"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    text: Vec<char>,
+    /// The position of the cursor, in characters
+    cursor: usize,
+    /// The first visible character
+    scroll: usize,
+    placeholder: String,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start with the given text, with the cursor at its end
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.set_text(text);
+        self
+    }
+
+    /// Text shown in the input while it's empty
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    /// Replace the text, moving the cursor to its end. Only the first line of `text` is kept.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        self.text = text.lines().next().unwrap_or("").chars().collect();
+        self.cursor = self.text.len();
+        self.scroll = 0;
+    }
+
+    fn insert(&mut self, text: &str) {
+        let chars = text.chars().take_while(|c| *c != '\n' && *c != '\r');
+        for c in chars {
+            self.text.insert(self.cursor, c);
+            self.cursor += 1;
+        }
+    }
+
+    /// Scroll so that the cursor is within the given width
+    fn scroll_to_cursor(&mut self, width: usize) {
+        let width = width.max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + width {
+            self.scroll = self.cursor + 1 - width;
+        }
+    }
+}
+
+impl<U, S> Widget<U, S> for Input {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        let theme = cx.theme();
+        fill(surface, Rect::from_size((width, 1)), &theme.style("input"));
+        let (text, attrs) = if self.text.is_empty() {
+            let mut attrs = theme.style("input");
+            attrs.set_intensity(theme.style("text.dim").intensity());
+            (self.placeholder.chars().take(width).collect(), attrs)
+        } else {
+            let text = self.text.iter().skip(self.scroll).take(width).collect();
+            (text, theme.style("input"))
+        };
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            Change::AllAttributes(attrs),
+            Change::Text(text),
+            Change::AllAttributes(Default::default()),
+        ]);
+        Ok(vec![])
+    }
+
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        Some(CursorState::at(self.cursor - self.scroll, 0))
+    }
+
+    fn update(&mut self, cx: &mut UpdateCtx<U, S>, event: Event<U>) -> Result<()> {
+        match event {
+            Event::Key(KeyEvent { key, modifiers })
+                if modifiers == Modifiers::NONE || modifiers == Modifiers::SHIFT =>
+            {
+                match key {
+                    KeyCode::Char(c) => self.insert(&c.to_string()),
+                    KeyCode::Backspace if self.cursor > 0 => {
+                        self.cursor -= 1;
+                        self.text.remove(self.cursor);
+                    }
+                    KeyCode::Delete if self.cursor < self.text.len() => {
+                        self.text.remove(self.cursor);
+                    }
+                    KeyCode::LeftArrow => self.cursor = self.cursor.saturating_sub(1),
+                    KeyCode::RightArrow => self.cursor = (self.cursor + 1).min(self.text.len()),
+                    KeyCode::Home => self.cursor = 0,
+                    KeyCode::End => self.cursor = self.text.len(),
+                    _ => {}
+                }
+            }
+            Event::Paste(text) => self.insert(&text),
+            Event::Mouse(MouseEvent {
+                x,
+                mouse_buttons: MouseButtons::LEFT,
+                ..
+            }) => {
+                self.cursor = (self.scroll + x as usize).min(self.text.len());
+            }
+            _ => {}
+        }
+        self.scroll_to_cursor(cx.bounds.width_usize());
+        Ok(())
+    }
+
+    fn value(&self) -> Option<String> {
+        Some(self.text())
+    }
+
+    fn set_value(&mut self, value: &str) -> bool {
+        self.set_text(value);
+        true
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
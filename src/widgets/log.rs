@@ -0,0 +1,300 @@
+//! Scrolling log viewer that follows the tail of a shared line buffer.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+use crate::{
+    error::{read_or_err, Error, Result},
+    event::{Event, KeyCode, KeyEvent, MouseButtons, MouseEvent},
+    layout::{Rect, WidgetId},
+    render_util::{changed_lines, redraw_line, scroll_and_render_delta},
+    style::{AnsiColor, CellAttributes},
+    surface::{Change, Position, Surface},
+    widget::{RenderCtx, UpdateCtx, Widget},
+};
+
+/// Number of lines scrolled per mouse wheel step
+const WHEEL_STEP: usize = 3;
+
+/// Displays the lines of a shared buffer with the newest lines at the bottom.
+///
+/// The view follows the tail of the buffer until the user scrolls up, and resumes following once
+/// they scroll back to the bottom (or press <kbd>End</kbd>). Lines beginning with a configured
+/// level prefix (`ERROR`, `WARN`, `INFO` by default) are drawn with that level's attributes.
+pub struct LogView {
+    buf: Arc<RwLock<VecDeque<String>>>,
+    max_lines: usize,
+    /// The index of the top visible line counting dropped lines, so that it stays on the same
+    /// line as older lines are dropped, or `None` when following the tail
+    top: Option<usize>,
+    levels: Vec<(String, CellAttributes)>,
+    /// The number of lines dropped from the front of the buffer by the line cap, so lines can be
+    /// told apart from the ones that were at the same index before
+    dropped: AtomicUsize,
+    /// The line at the top of the last frame, counting dropped lines, or `None` if the last frame
+    /// can't be reused because the levels changed
+    drawn: Mutex<Option<usize>>,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        Self::from_buffer(Arc::new(RwLock::new(VecDeque::new())))
+    }
+
+    /// Create a log view displaying an existing shared buffer
+    pub fn from_buffer(buf: Arc<RwLock<VecDeque<String>>>) -> Self {
+        let level = |color: AnsiColor| {
+            let mut attrs = CellAttributes::default();
+            attrs.set_foreground(color);
+            attrs
+        };
+        Self {
+            buf,
+            max_lines: 10_000,
+            top: None,
+            levels: vec![
+                ("ERROR".to_owned(), level(AnsiColor::Red)),
+                ("WARN".to_owned(), level(AnsiColor::Yellow)),
+                ("INFO".to_owned(), level(AnsiColor::Teal)),
+            ],
+            dropped: AtomicUsize::new(0),
+            drawn: Mutex::new(None),
+        }
+    }
+
+    /// Set the maximum number of lines kept in the buffer. Older lines are dropped first, when
+    /// lines are pushed or, for lines written to the shared buffer directly, when the view next
+    /// receives an event.
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+
+    /// Set the attributes used for lines beginning with the given level prefix, adding the level
+    /// if it isn't already known
+    pub fn with_level(mut self, prefix: impl Into<String>, attrs: CellAttributes) -> Self {
+        self.set_level(prefix, attrs);
+        self
+    }
+
+    /// Set the attributes used for lines beginning with the given level prefix, adding the level
+    /// if it isn't already known
+    pub fn set_level(&mut self, prefix: impl Into<String>, attrs: CellAttributes) {
+        let prefix = prefix.into();
+        match self.levels.iter_mut().find(|(p, _)| *p == prefix) {
+            Some((_, a)) => *a = attrs,
+            None => self.levels.push((prefix, attrs)),
+        }
+        // Lines already drawn may have the old attributes
+        if let Ok(drawn) = self.drawn.get_mut() {
+            *drawn = None;
+        }
+    }
+
+    /// Get a shared handle to the line buffer
+    pub fn buffer(&self) -> Arc<RwLock<VecDeque<String>>> {
+        self.buf.clone()
+    }
+
+    /// Append a line to the buffer, dropping the oldest lines past the maximum line count
+    pub fn push(&self, line: impl Into<String>) {
+        if let Ok(mut buf) = self.buf.write() {
+            buf.push_back(line.into());
+            self.truncate(&mut buf);
+        }
+    }
+
+    /// Clear the buffer and resume following
+    pub fn clear(&mut self) {
+        if let Ok(mut buf) = self.buf.write() {
+            buf.clear();
+        }
+        self.top = None;
+    }
+
+    /// Whether the view is following the tail of the buffer
+    pub fn is_following(&self) -> bool {
+        self.top.is_none()
+    }
+
+    /// Jump to the bottom of the buffer and follow new lines
+    pub fn follow(&mut self) {
+        self.top = None;
+    }
+
+    /// Scroll up by the given number of lines, given the number of visible rows
+    pub fn scroll_up(&mut self, lines: usize, rows: usize) {
+        let len = self.len();
+        if len <= rows {
+            return;
+        }
+        let top = self.top().unwrap_or(len - rows);
+        self.top = Some(top.saturating_sub(lines) + self.dropped.load(Ordering::Relaxed));
+    }
+
+    /// Scroll down by the given number of lines, given the number of visible rows. Scrolling to
+    /// the bottom resumes following.
+    pub fn scroll_down(&mut self, lines: usize, rows: usize) {
+        let Some(top) = self.top() else {
+            return;
+        };
+        let bottom = self.len().saturating_sub(rows);
+        if top + lines >= bottom {
+            self.top = None;
+        } else {
+            self.top = Some(top + lines + self.dropped.load(Ordering::Relaxed));
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.buf.read().map(|b| b.len()).unwrap_or(0)
+    }
+
+    /// The index of the top visible line in the buffer, or `None` when following the tail. Lines
+    /// dropped since scrolling move it up by as many lines, stopping at the oldest line kept.
+    fn top(&self) -> Option<usize> {
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        self.top.map(|top| top.saturating_sub(dropped))
+    }
+
+    /// Drops the oldest lines past the maximum line count
+    fn truncate(&self, buf: &mut VecDeque<String>) {
+        let excess = buf.len().saturating_sub(self.max_lines);
+        buf.drain(..excess);
+        self.dropped.fetch_add(excess, Ordering::Relaxed);
+    }
+
+    fn level_attrs(&self, line: &str) -> Option<&CellAttributes> {
+        let line = line.trim_start().trim_start_matches('[');
+        self.levels
+            .iter()
+            .find(|(prefix, _)| line.starts_with(prefix.as_str()))
+            .map(|(_, attrs)| attrs)
+    }
+}
+
+impl<U, S> Widget<U, S> for LogView {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        // The buffer is shared, so it may change without the widget being updated
+        cx.request_redraw();
+        let (width, height) = surface.dimensions();
+        if height == 0 {
+            return Ok(vec![]);
+        }
+        // The last row is reserved for the follow indicator
+        let rows = height - 1;
+
+        let buf = read_or_err(&self.buf)?;
+        let len = buf.len();
+        let top = self
+            .top()
+            .unwrap_or(usize::MAX)
+            .min(len.saturating_sub(rows));
+
+        // Lines that were on screen in the last frame are moved instead of drawn again, so
+        // following a busy log only draws the new lines at the bottom
+        let absolute = top + self.dropped.load(Ordering::Relaxed);
+        let mut drawn = self.drawn.lock().map_err(|_| Error::LockPoisoned)?;
+        let prev = drawn.and(cx.previous_surface());
+        let scrolled = drawn.map_or(0, |drawn| absolute as isize - drawn as isize);
+        *drawn = Some(absolute);
+        // The last row is left for the indicator, which is drawn every frame
+        let line = |row: usize| buf.get(top + row).filter(|_| row < rows);
+        let text =
+            |row: usize| line(row).map_or(String::new(), |line| line.chars().take(width).collect());
+        let draw_line = |row: usize, surface: &mut Surface| {
+            let Some(line) = line(row) else {
+                return;
+            };
+            let attrs = self.level_attrs(line).cloned().unwrap_or_default();
+            surface.add_changes(vec![
+                Change::AllAttributes(attrs),
+                Change::Text(line.chars().take(width).collect()),
+                Change::AllAttributes(CellAttributes::default()),
+            ]);
+        };
+        scroll_and_render_delta(surface, prev, scrolled, &draw_line);
+        // Lines can be replaced or removed from the shared buffer, so the moved rows are checked
+        // for changes too
+        if prev.is_some() {
+            for row in changed_lines(surface, &text) {
+                redraw_line(surface, row, &draw_line);
+            }
+        }
+
+        let indicator = if self.top.is_none() {
+            " following ".to_owned()
+        } else {
+            format!(" {} more ", len.saturating_sub(top + rows))
+        };
+        let dim = cx.theme().style("text.dim");
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(rows),
+            },
+            Change::AllAttributes(dim),
+            Change::Text(
+                format!("{:─^width$}", indicator, width = width)
+                    .chars()
+                    .take(width)
+                    .collect(),
+            ),
+            Change::AllAttributes(CellAttributes::default()),
+        ]);
+        Ok(vec![])
+    }
+
+    fn update<'u>(&mut self, cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        // Enforce the line cap when the buffer is written to directly instead of with `push`
+        if let Ok(mut buf) = self.buf.write() {
+            self.truncate(&mut buf);
+        }
+        let rows = cx.bounds.height_usize().saturating_sub(1);
+        match event {
+            Event::Key(KeyEvent { key, .. }) => match key {
+                KeyCode::UpArrow => self.scroll_up(1, rows),
+                KeyCode::DownArrow => self.scroll_down(1, rows),
+                KeyCode::PageUp => self.scroll_up(rows.max(1), rows),
+                KeyCode::PageDown => self.scroll_down(rows.max(1), rows),
+                KeyCode::Home => self.scroll_up(usize::MAX, rows),
+                KeyCode::End => self.follow(),
+                _ => {}
+            },
+            Event::Mouse(MouseEvent { mouse_buttons, .. })
+                if mouse_buttons.contains(MouseButtons::VERT_WHEEL) =>
+            {
+                if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                    self.scroll_up(WHEEL_STEP, rows);
+                } else {
+                    self.scroll_down(WHEEL_STEP, rows);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
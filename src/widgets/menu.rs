@@ -1,25 +1,62 @@
-use std::sync::{mpsc::Sender, Arc};
-
 use termwiz::input::{KeyCode, KeyEvent, MouseButtons, MouseEvent};
-use termwiz::surface::{Change, Position, Surface};
+use termwiz::surface::Surface;
 
+use crate::error::Result;
 use crate::event::Event;
 use crate::layout::{Rect, WidgetId};
+use crate::text::{fill, render_styled, Alignment, Line, Span};
 use crate::widget::{RenderCtx, UpdateCtx};
-use crate::{event::UserEvent, Widget};
-use termwiz::{
-    cell::AttributeChange,
-    color::{AnsiColor, ColorAttribute},
-};
+use crate::{event::EventSender, Widget};
+
+/// Called with the item's index when a menu item is selected
+pub trait MenuAction<U>: FnMut(usize, &mut MenuHandle<U>, &EventSender<U>) {}
 
-pub trait MenuAction<U>: Fn(usize, &mut Menu<U>, Arc<Sender<UserEvent<U>>>) {}
+impl<C, U> MenuAction<U> for C where C: FnMut(usize, &mut MenuHandle<U>, &EventSender<U>) {}
 
-impl<C, U> MenuAction<U> for C where C: Fn(usize, &mut Menu<U>, Arc<Sender<UserEvent<U>>>) {}
+/// What is shown in a menu item's row
+enum Row {
+    /// A title with a tag after it
+    Text { title: String, tag: String },
+    /// A widget drawn in the row, such as a [`Checkbox`](crate::widgets::Checkbox)
+    Widget(WidgetId),
+}
 
+/// A menu item's row and action. The action is taken out while it runs.
+struct Item<U> {
+    row: Row,
+    action: Option<Box<dyn MenuAction<U>>>,
+}
+
+/// A menu with a title and a list of items, each with a tag shown after it and an action run
+/// when the item is selected.
+///
+/// Items can also be widgets, added with [`Menu::with_widget_item`], which are drawn in their row
+/// and receive the mouse events over it. Selecting one with <kbd>Enter</kbd> or a click passes
+/// the key or click on to the widget before running the item's action, so a checkbox row is
+/// toggled when it's selected.
+///
+/// ```
+/// use std::sync::{mpsc, Arc};
+/// use sanguine::{event::EventSender, widgets::Menu};
+///
+/// let (tx, _rx) = mpsc::channel();
+/// let tx = EventSender::<()>::from(Arc::new(tx));
+///
+/// let mut count = 0;
+/// let mut menu = Menu::new("Counter").with_item("Count", "0", move |index, menu, _| {
+///     count += 1;
+///     menu.update_tag(index, |_| count.to_string());
+/// });
+/// menu.select(&tx);
+/// menu.select(&tx);
+/// assert_eq!(menu.tag(0), Some("2"));
+/// ```
 pub struct Menu<U> {
     title: String,
-    items: Vec<(String, String, Box<dyn MenuAction<U>>)>,
+    items: Vec<Item<U>>,
     active: usize,
+    /// The item under the mouse, if any
+    hovered: Option<usize>,
 }
 
 impl<U> Menu<U> {
@@ -28,6 +65,7 @@ impl<U> Menu<U> {
             title: title.into(),
             items: vec![],
             active: 0,
+            hovered: None,
         }
     }
 
@@ -42,7 +80,11 @@ impl<U> Menu<U> {
     }
 
     pub fn with_items(mut self, items: Vec<(String, String, Box<dyn MenuAction<U>>)>) -> Self {
-        self.items.extend(items);
+        self.items
+            .extend(items.into_iter().map(|(title, tag, action)| Item {
+                row: Row::Text { title, tag },
+                action: Some(action),
+            }));
         self
     }
 
@@ -52,8 +94,66 @@ impl<U> Menu<U> {
         tag: impl Into<String>,
         action: impl MenuAction<U> + 'static,
     ) {
-        self.items
-            .push((title.into(), tag.into(), Box::new(action)));
+        self.items.push(Item {
+            row: Row::Text {
+                title: title.into(),
+                tag: tag.into(),
+            },
+            action: Some(Box::new(action)),
+        });
+    }
+
+    /// Add an item drawn by a registered widget, one row tall, instead of a title and tag
+    ///
+    /// ```
+    /// use sanguine::{
+    ///     backend::TestBackend,
+    ///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+    ///     widgets::{Checkbox, Menu},
+    ///     App,
+    /// };
+    ///
+    /// let mut app = App::<(), ()>::test(20, 4).with_layout(|layout, widgets| {
+    ///     let wrap = widgets.register(Checkbox::new("Wrap lines"));
+    ///     let menu = Menu::new("View")
+    ///         .with_item("Zoom", "", |_, _, _| {})
+    ///         .with_widget_item(wrap, |_, _, _| {});
+    ///     let leaf = layout.add_leaf(widgets, menu);
+    ///     layout.add_child(layout.root(), leaf).ok()?;
+    ///     Some(leaf)
+    /// });
+    ///
+    /// let backend = app.backend_mut::<TestBackend>().unwrap();
+    /// for key in [KeyCode::DownArrow, KeyCode::Enter] {
+    ///     backend.push_input(InputEvent::Key(KeyEvent {
+    ///         key,
+    ///         modifiers: Modifiers::NONE,
+    ///     }));
+    /// }
+    /// app.handle_events().unwrap();
+    /// app.render().unwrap();
+    /// app.backend::<TestBackend>().unwrap().assert_screen(&[
+    ///     "        View",
+    ///     "",
+    ///     "       Zoom",
+    ///     " [x] Wrap lines",
+    /// ]);
+    /// ```
+    pub fn with_widget_item(
+        mut self,
+        widget: WidgetId,
+        action: impl MenuAction<U> + 'static,
+    ) -> Self {
+        self.add_widget_item(widget, action);
+        self
+    }
+
+    /// Add an item drawn by a registered widget. See [`Menu::with_widget_item`].
+    pub fn add_widget_item(&mut self, widget: WidgetId, action: impl MenuAction<U> + 'static) {
+        self.items.push(Item {
+            row: Row::Widget(widget),
+            action: Some(Box::new(action)),
+        });
     }
 
     pub fn next(&mut self) {
@@ -64,34 +164,72 @@ impl<U> Menu<U> {
         self.active = (self.active + self.items.len() - 1) % self.items.len().max(1);
     }
 
-    pub fn select(&mut self, event_tx: Arc<Sender<UserEvent<U>>>) {
-        if let Some((_, _, action)) = self.items.get(self.active) {
-            let func = action as *const dyn MenuAction<U>;
-            unsafe { (*func)(self.active, self, event_tx.clone()) };
+    /// Run the action of the active item
+    pub fn select(&mut self, event_tx: &EventSender<U>) {
+        let Some(mut action) = self
+            .items
+            .get_mut(self.active)
+            .and_then(|item| item.action.take())
+        else {
+            return;
+        };
+        action(self.active, &mut MenuHandle { menu: self }, event_tx);
+        // The action may have added or removed items, so it's put back in the only empty slot
+        // rather than at the active index. It's dropped if its item was removed.
+        if let Some(item) = self.items.iter_mut().find(|item| item.action.is_none()) {
+            item.action = Some(action);
         }
     }
 
-    pub fn item(&self, index: usize) -> Option<&(String, String, Box<dyn MenuAction<U>>)> {
-        self.items.get(index)
+    /// The title and tag of an item, or `None` if it's drawn by a widget
+    pub fn item(&self, index: usize) -> Option<(&str, &str)> {
+        match &self.items.get(index)?.row {
+            Row::Text { title, tag } => Some((title.as_str(), tag.as_str())),
+            Row::Widget(_) => None,
+        }
+    }
+
+    /// The widget that draws an item, if it was added with [`Menu::with_widget_item`]
+    pub fn widget(&self, index: usize) -> Option<WidgetId> {
+        match self.items.get(index)?.row {
+            Row::Widget(widget) => Some(widget),
+            Row::Text { .. } => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
     }
 
     pub fn tag(&self, index: usize) -> Option<&str> {
-        self.items.get(index).map(|(_, tag, _)| tag.as_str())
+        self.item(index).map(|(_, tag)| tag)
     }
 
     pub fn update_tag(&mut self, index: usize, f: impl Fn(&str) -> String) {
-        if let Some((_, t, _)) = self.items.get_mut(index) {
-            *t = f(t);
+        if let Some(Item {
+            row: Row::Text { tag, .. },
+            ..
+        }) = self.items.get_mut(index)
+        {
+            *tag = f(tag);
         }
     }
 
     pub fn entry(&self, index: usize) -> Option<&str> {
-        self.items.get(index).map(|(title, _, _)| title.as_str())
+        self.item(index).map(|(title, _)| title)
     }
 
     pub fn update_entry(&mut self, index: usize, f: impl Fn(&str) -> String) {
-        if let Some((t, _, _)) = self.items.get_mut(index) {
-            *t = f(t);
+        if let Some(Item {
+            row: Row::Text { title, .. },
+            ..
+        }) = self.items.get_mut(index)
+        {
+            *title = f(title);
         }
     }
 
@@ -103,58 +241,187 @@ impl<U> Menu<U> {
         self.items.remove(index);
     }
 
+    /// The item shown on the given row, if any
+    fn item_at(&self, y: u16) -> Option<usize> {
+        (y as usize)
+            .checked_sub(2)
+            .filter(|i| *i < self.items.len())
+    }
+
+    /// The text of a text item, if it's cut off at the given width
+    fn truncated_text(&self, index: usize, width: usize) -> Option<String> {
+        let Row::Text { title, tag } = &self.items.get(index)?.row else {
+            return None;
+        };
+        let text = format!("{title} {tag}").trim_end().to_owned();
+        (text.chars().count() > width).then_some(text)
+    }
+
+    /// The bounds of a widget item's widget, leaving a column on either side of it so the row's
+    /// highlight shows
+    fn widget_rect(index: usize, width: usize) -> Rect {
+        Rect::new(1., index as f32 + 2., width.saturating_sub(2) as f32, 1.)
+    }
+
+    /// The index of the item drawn by the `child`th widget returned from render
+    fn widget_item(&self, child: usize) -> Option<usize> {
+        let widgets = self.items.iter().enumerate();
+        let (index, _) = widgets
+            .filter(|(_, item)| matches!(item.row, Row::Widget(_)))
+            .nth(child)?;
+        Some(index)
+    }
+
+    /// Send an event to a widget item's widget, with the position of mouse events made relative
+    /// to it
+    fn forward<S>(&self, cx: &mut UpdateCtx<U, S>, index: usize, event: Event<U>) -> Result<()> {
+        let Some(widget) = self.widget(index) else {
+            return Ok(());
+        };
+        let rect = Self::widget_rect(index, cx.bounds.width_usize());
+        let event = match event {
+            Event::Mouse(mouse) => Event::Mouse(MouseEvent {
+                x: mouse.x.saturating_sub(rect.x as u16),
+                y: mouse.y.saturating_sub(rect.y as u16),
+                ..mouse
+            }),
+            event => event,
+        };
+        let bounds = Rect {
+            x: cx.bounds.x + rect.x,
+            y: cx.bounds.y + rect.y,
+            ..rect
+        };
+        cx.with_rect(bounds).update_widget(widget, event)
+    }
+
+    /// Handles a key press, returning whether it was used
+    fn key<S>(&mut self, cx: &mut UpdateCtx<U, S>, key: &KeyEvent) -> Result<bool> {
+        match key.key {
+            KeyCode::UpArrow => self.prev(),
+            KeyCode::DownArrow => self.next(),
+            KeyCode::Enter => {
+                // The key selects a widget item, as well as being passed on to it
+                self.forward(cx, self.active, Event::Key(key.clone()))?;
+                self.select(&cx.tx);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
     pub fn update_menu_title(&mut self, f: impl Fn(&str) -> String) {
         self.title = f(&self.title);
     }
 }
 
+/// The parts of a [`Menu`] that its actions can change while they run
+pub struct MenuHandle<'a, U> {
+    menu: &'a mut Menu<U>,
+}
+
+impl<U> MenuHandle<'_, U> {
+    pub fn add_item(
+        &mut self,
+        title: impl Into<String>,
+        tag: impl Into<String>,
+        action: impl MenuAction<U> + 'static,
+    ) {
+        self.menu.add_item(title, tag, action);
+    }
+
+    pub fn add_widget_item(&mut self, widget: WidgetId, action: impl MenuAction<U> + 'static) {
+        self.menu.add_widget_item(widget, action);
+    }
+
+    pub fn item(&self, index: usize) -> Option<(&str, &str)> {
+        self.menu.item(index)
+    }
+
+    pub fn widget(&self, index: usize) -> Option<WidgetId> {
+        self.menu.widget(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.menu.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.menu.is_empty()
+    }
+
+    pub fn tag(&self, index: usize) -> Option<&str> {
+        self.menu.tag(index)
+    }
+
+    pub fn update_tag(&mut self, index: usize, f: impl Fn(&str) -> String) {
+        self.menu.update_tag(index, f);
+    }
+
+    pub fn entry(&self, index: usize) -> Option<&str> {
+        self.menu.entry(index)
+    }
+
+    pub fn update_entry(&mut self, index: usize, f: impl Fn(&str) -> String) {
+        self.menu.update_entry(index, f);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.menu.remove(index);
+    }
+
+    pub fn clear(&mut self) {
+        self.menu.clear();
+    }
+
+    pub fn update_menu_title(&mut self, f: impl Fn(&str) -> String) {
+        self.menu.update_menu_title(f);
+    }
+}
+
 impl<U: 'static, S: 'static> Widget<U, S> for Menu<U> {
     fn render<'r>(
         &self,
-        _cx: &RenderCtx<'r, U, S>,
+        cx: &RenderCtx<'r, U, S>,
         surface: &mut Surface,
-    ) -> Option<Vec<(Rect, WidgetId)>> {
-        let dims = surface.dimensions();
-        surface.add_changes(vec![Change::CursorPosition {
-            x: Position::Absolute(0),
-            y: Position::Relative(0),
-        }]);
-        let line = format!("{:^width$}", self.title, width = dims.0);
-        surface.add_changes(vec![
-            Change::Attribute(AttributeChange::Foreground(AnsiColor::Black.into())),
-            Change::Attribute(AttributeChange::Background(AnsiColor::White.into())),
-            Change::Text(line),
-            Change::Attribute(AttributeChange::Foreground(Default::default())),
-            Change::Attribute(AttributeChange::Background(Default::default())),
-            Change::CursorPosition {
-                x: Position::Absolute(0),
-                y: Position::Relative(2),
-            },
-        ]);
-        surface.add_changes(vec![]);
-        for (i, (item, tag, _)) in self.items.iter().enumerate() {
-            if i == self.active {
-                surface.add_changes(vec![
-                    Change::Attribute(AttributeChange::Foreground(AnsiColor::Black.into())),
-                    Change::Attribute(AttributeChange::Background(AnsiColor::White.into())),
-                ]);
+    ) -> crate::error::Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        let row = |y: usize| Rect::new(0., y as f32, width as f32, 1.);
+        let theme = cx.theme();
+
+        let title_style = theme.style("menu.title");
+        fill(surface, row(0), &title_style);
+        let title = Line::from(Span::new(self.title.as_str(), title_style));
+        render_styled(surface, &title.into(), row(0), false, Alignment::Center);
+
+        let mut children = vec![];
+        for (i, item) in self.items.iter().enumerate() {
+            let attrs = if i == self.active {
+                theme.style("menu.selected")
+            } else if self.hovered == Some(i) {
+                theme.style("menu.hovered")
+            } else {
+                theme.style("menu.item")
+            };
+            let rect = row(i + 2);
+            fill(surface, rect, &attrs);
+            match &item.row {
+                Row::Text { title, tag } => {
+                    let line = Line(vec![
+                        Span::new(title.as_str(), attrs.clone()),
+                        Span::new(" ", attrs.clone()),
+                        Span::new(tag.as_str(), attrs),
+                    ]);
+                    render_styled(surface, &line.into(), rect, false, Alignment::Center);
+                }
+                // Rows below the surface aren't drawn, so their widgets are left out
+                Row::Widget(widget) if i + 2 < height => {
+                    children.push((Self::widget_rect(i, width), *widget));
+                }
+                Row::Widget(_) => {}
             }
-            let line = format!("{item} {tag}");
-            surface.add_changes(vec![
-                Change::Text(format!("{:^width$}", line, width = dims.0)),
-                Change::CursorPosition {
-                    x: Position::Relative(dims.0 as isize),
-                    y: Position::Relative(0),
-                },
-                Change::Attribute(AttributeChange::Foreground(ColorAttribute::Default)),
-                Change::Attribute(AttributeChange::Background(ColorAttribute::Default)),
-                Change::CursorPosition {
-                    x: Position::Absolute(0),
-                    y: Position::Relative(1),
-                },
-            ]);
         }
-        None
+        Ok(children)
     }
 
     fn update<'u>(
@@ -163,37 +430,83 @@ impl<U: 'static, S: 'static> Widget<U, S> for Menu<U> {
         event: Event<U>,
     ) -> crate::error::Result<()> {
         match event {
-            Event::Key(KeyEvent { key, .. }) => match key {
-                KeyCode::UpArrow => self.prev(),
-                KeyCode::DownArrow => self.next(),
-                KeyCode::Enter => self.select(cx.tx.clone()),
-                _ => {}
-            },
-            Event::Mouse(MouseEvent {
-                y, mouse_buttons, ..
-            }) => {
-                if mouse_buttons == MouseButtons::LEFT {
-                    if (y as usize) <= self.items.len() + 1 && y >= 2 {
-                        self.active = y as usize - 2;
-                        self.select(cx.tx.clone());
+            Event::Key(key) => {
+                self.key(cx, &key)?;
+            }
+            Event::Scroll { delta, .. } if !self.items.is_empty() => {
+                let last = self.items.len() as i64 - 1;
+                self.active = (self.active as i64 + delta as i64).clamp(0, last) as usize;
+            }
+            Event::Mouse(mouse) => {
+                let item = self.item_at(mouse.y);
+                // Widget items receive the mouse events over them, which only reach the menu when
+                // it's inside another widget
+                if let Some(item) = item {
+                    let rect = Self::widget_rect(item, cx.bounds.width_usize());
+                    if rect.contains(mouse.x as f32, mouse.y as f32) {
+                        self.forward(cx, item, Event::Mouse(mouse.clone()))?;
                     }
-                } else if mouse_buttons == MouseButtons::NONE {
-                    if (y as usize) <= self.items.len() + 1 && y >= 2 {
-                        self.active = y as usize - 2;
+                }
+                if mouse.mouse_buttons == MouseButtons::LEFT {
+                    if let Some(item) = item {
+                        self.active = item;
+                        self.select(&cx.tx);
+                    }
+                } else if mouse.mouse_buttons == MouseButtons::NONE {
+                    self.hovered = item;
+                    // Items too long for the menu show their whole text when hovered
+                    let width = cx.bounds.width_usize();
+                    match item.and_then(|item| self.truncated_text(item, width)) {
+                        Some(text) => cx.set_tooltip(text),
+                        None => cx.clear_tooltip(),
                     }
                 }
             }
+            Event::MouseLeave => self.hovered = None,
             _ => {}
         }
 
         Ok(())
     }
 
+    /// Selects widget items that are clicked, and handles the menu's keys while a widget item has
+    /// focus
+    fn child_event(
+        &mut self,
+        cx: &mut UpdateCtx<'_, U, S>,
+        child: usize,
+        event: &Event<U>,
+    ) -> Result<bool> {
+        match event {
+            Event::Key(key) => self.key(cx, key),
+            Event::Mouse(MouseEvent {
+                mouse_buttons: MouseButtons::LEFT,
+                ..
+            }) => {
+                if let Some(item) = self.widget_item(child) {
+                    self.active = item;
+                    self.select(&cx.tx);
+                }
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn children(&self) -> Vec<WidgetId> {
+        let rows = self.items.iter().map(|item| &item.row);
+        rows.filter_map(|row| match row {
+            Row::Widget(widget) => Some(*widget),
+            Row::Text { .. } => None,
+        })
+        .collect()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 
-    fn as_any_mut<'a>(&'a mut self) -> &'a mut dyn std::any::Any {
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
 }
@@ -1,9 +1,41 @@
 //! Built-in widgets
 
+#[cfg(feature = "ansi")]
+mod ansi_view;
 mod border;
+mod button;
+mod chart;
+mod checkbox;
+mod form;
+mod input;
+mod log;
 mod menu;
+mod palette;
+mod progress;
+mod spinner;
+mod status;
+#[cfg(feature = "pty")]
+mod terminal;
 mod textbox;
+mod tree;
 
+#[cfg(feature = "ansi")]
+pub use ansi_view::AnsiView;
 pub use border::Border;
-pub use menu::Menu;
-pub use textbox::TextBox;
+pub use button::{Button, ButtonAction};
+pub use chart::{BarChart, Sparkline};
+pub use checkbox::Checkbox;
+pub use form::{Form, FormAction, Validator};
+pub use input::Input;
+pub use log::LogView;
+pub use menu::{Menu, MenuAction, MenuHandle};
+pub use palette::CommandPalette;
+#[cfg(feature = "pty")]
+pub use portable_pty::CommandBuilder;
+pub use progress::{Gauge, ProgressBar};
+pub use spinner::{Spinner, BRAILLE_FRAMES};
+pub use status::{Segment, StatusLine};
+#[cfg(feature = "pty")]
+pub use terminal::Terminal;
+pub use textbox::{TextBox, TextBuffer};
+pub use tree::{Tree, TreeNodeId};
@@ -0,0 +1,309 @@
+//! Command palette overlay, intended to be shown in a floating window.
+
+use crate::{
+    error::Result,
+    event::{
+        Event, EventSender, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent, UserEvent,
+    },
+    layout::{Rect, WidgetId},
+    style::CellAttributes,
+    surface::{Change, Position, Surface},
+    widget::{CursorState, RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// An action run when a command is selected from the palette.
+pub trait PaletteAction<U>: FnMut(EventSender<U>) {}
+
+impl<C, U> PaletteAction<U> for C where C: FnMut(EventSender<U>) {}
+
+struct Command<U> {
+    name: String,
+    hint: Option<String>,
+    action: Box<dyn PaletteAction<U>>,
+}
+
+/// Scores `candidate` against `query` as a case-insensitive subsequence match, or returns `None`
+/// if the query doesn't match. Consecutive matches and matches at the start of words score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut chars = candidate.chars().enumerate();
+    let mut prev_match: Option<usize> = None;
+    let mut prev_char = ' ';
+    for q in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            let (i, c) = chars.next()?;
+            let word_start = !prev_char.is_alphanumeric();
+            prev_char = c;
+            if c.to_lowercase().eq(std::iter::once(q)) {
+                score += 1;
+                if word_start {
+                    score += 2;
+                }
+                if prev_match.is_some_and(|p| p + 1 == i) {
+                    score += 3;
+                }
+                prev_match = Some(i);
+                break;
+            }
+        }
+    }
+    Some(score)
+}
+
+/// A single-line input combined with a fuzzy-filtered list of commands.
+///
+/// The palette is intended to be opened as a floating window with [`crate::App::open_float`].
+/// Selecting a command with <kbd>Enter</kbd> runs its action and closes the palette, and
+/// <kbd>Escape</kbd> closes it without running anything. Closing is done by sending
+/// [`UserEvent::Close`] with the palette's node, which the app handles by removing the float and
+/// its widget.
+pub struct CommandPalette<U> {
+    title: String,
+    query: String,
+    commands: Vec<Command<U>>,
+    /// Indices into `commands` that match the query, best match first
+    matches: Vec<usize>,
+    active: usize,
+}
+
+impl<U> Default for CommandPalette<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<U> CommandPalette<U> {
+    pub fn new() -> Self {
+        Self {
+            title: "Commands".to_owned(),
+            query: String::new(),
+            commands: vec![],
+            matches: vec![],
+            active: 0,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_command(
+        mut self,
+        name: impl Into<String>,
+        hint: Option<&str>,
+        action: impl PaletteAction<U> + 'static,
+    ) -> Self {
+        self.add_command(name, hint, action);
+        self
+    }
+
+    /// Add a command with an optional keybinding hint, displayed to the right of its name
+    pub fn add_command(
+        &mut self,
+        name: impl Into<String>,
+        hint: Option<&str>,
+        action: impl PaletteAction<U> + 'static,
+    ) {
+        self.commands.push(Command {
+            name: name.into(),
+            hint: hint.map(str::to_owned),
+            action: Box::new(action),
+        });
+        self.filter();
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+        self.filter();
+    }
+
+    /// The names of the commands matching the current query, best match first
+    pub fn matches(&self) -> impl Iterator<Item = &str> {
+        self.matches.iter().map(|i| self.commands[*i].name.as_str())
+    }
+
+    fn filter(&mut self) {
+        let mut scored = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| fuzzy_score(&self.query, &c.name).map(|s| (i, s)))
+            .collect::<Vec<_>>();
+        scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(i, _)| i).collect();
+        self.active = 0;
+    }
+
+    fn next(&mut self) {
+        self.active = (self.active + 1) % self.matches.len().max(1);
+    }
+
+    fn prev(&mut self) {
+        self.active = (self.active + self.matches.len().max(1) - 1) % self.matches.len().max(1);
+    }
+
+    fn run(&mut self, event_tx: EventSender<U>) {
+        if let Some(command) = self
+            .matches
+            .get(self.active)
+            .and_then(|i| self.commands.get_mut(*i))
+        {
+            (command.action)(event_tx);
+        }
+    }
+
+    /// Writes a framed row of the palette
+    fn row(changes: &mut Vec<Change>, y: usize, width: usize, left: char, fill: char, right: char) {
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(y),
+        });
+        let mut line = String::new();
+        line.push(left);
+        (0..width.saturating_sub(2)).for_each(|_| line.push(fill));
+        line.push(right);
+        changes.push(Change::Text(line));
+    }
+}
+
+impl<U: 'static, S: 'static> Widget<U, S> for CommandPalette<U> {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        if width < 4 || height < 4 {
+            return Ok(vec![]);
+        }
+        let theme = cx.theme();
+        let inner = width - 2;
+
+        // Frame, with a separator between the input and the list
+        let mut changes = vec![];
+        Self::row(&mut changes, 0, width, '┌', '─', '┐');
+        Self::row(&mut changes, 1, width, '│', ' ', '│');
+        Self::row(&mut changes, 2, width, '├', '─', '┤');
+        (3..height - 1).for_each(|y| Self::row(&mut changes, y, width, '│', ' ', '│'));
+        Self::row(&mut changes, height - 1, width, '└', '─', '┘');
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(1),
+            y: Position::Absolute(0),
+        });
+        changes.push(Change::Text(self.title.chars().take(inner).collect()));
+
+        // Input line
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(1),
+            y: Position::Absolute(1),
+        });
+        changes.push(Change::Text(
+            format!("> {}", self.query).chars().take(inner).collect(),
+        ));
+
+        // Matching commands, scrolled so that the active one is visible
+        let rows = height - 4;
+        let scroll = (self.active + 1).saturating_sub(rows);
+        for (row, (i, index)) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(rows)
+            .enumerate()
+        {
+            let command = &self.commands[*index];
+            let hint = command.hint.as_deref().unwrap_or("");
+            let name_width = inner.saturating_sub(hint.chars().count() + 1);
+            let name = format!("{:<name_width$}", command.name, name_width = name_width)
+                .chars()
+                .take(name_width)
+                .collect::<String>();
+
+            changes.push(Change::CursorPosition {
+                x: Position::Absolute(1),
+                y: Position::Absolute(row + 3),
+            });
+            let (attrs, hint_attrs) = if i == self.active {
+                let selected = theme.style("palette.selected");
+                (selected.clone(), selected)
+            } else {
+                (CellAttributes::default(), theme.style("text.dim"))
+            };
+            changes.push(Change::AllAttributes(attrs));
+            changes.push(Change::Text(name));
+            changes.push(Change::Text(" ".to_owned()));
+            changes.push(Change::AllAttributes(hint_attrs));
+            changes.push(Change::Text(
+                hint.chars().take(inner.saturating_sub(1)).collect(),
+            ));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+        }
+
+        surface.add_changes(changes);
+        Ok(vec![])
+    }
+
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        Some(CursorState::at(3 + self.query.chars().count(), 1))
+    }
+
+    fn update<'u>(&mut self, cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        match event {
+            Event::Key(KeyEvent { key, modifiers }) => match key {
+                KeyCode::Char(c)
+                    if modifiers == Modifiers::NONE || modifiers == Modifiers::SHIFT =>
+                {
+                    self.query.push(c);
+                    self.filter();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.filter();
+                }
+                KeyCode::UpArrow => self.prev(),
+                KeyCode::DownArrow => self.next(),
+                KeyCode::Enter => {
+                    self.run(cx.tx.clone());
+                    cx.tx.send(UserEvent::Close(cx.owner))?;
+                }
+                KeyCode::Escape => {
+                    cx.tx.send(UserEvent::Close(cx.owner))?;
+                }
+                _ => {}
+            },
+            Event::Mouse(MouseEvent {
+                y, mouse_buttons, ..
+            }) => {
+                let rows = cx.bounds.height_usize().saturating_sub(4);
+                let scroll = (self.active + 1).saturating_sub(rows);
+                let row = (y as usize).checked_sub(3).map(|r| r + scroll);
+                if let Some(row) = row.filter(|r| *r < self.matches.len()) {
+                    if mouse_buttons == MouseButtons::LEFT {
+                        self.active = row;
+                        self.run(cx.tx.clone());
+                        cx.tx.send(UserEvent::Close(cx.owner))?;
+                    } else if mouse_buttons == MouseButtons::NONE {
+                        self.active = row;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,296 @@
+//! Progress bar and gauge widgets for displaying a ratio between 0.0 and 1.0.
+
+use crate::{
+    error::Result,
+    layout::{Constraint, Rect, WidgetId},
+    style::CellAttributes,
+    surface::{Change, Position, Surface},
+    widget::{RenderCtx, Widget},
+    WidgetStore,
+};
+
+/// Partial block characters, from 1/8th to 7/8ths of a cell.
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const FULL_BLOCK: char = '█';
+
+/// Clamps a ratio to `0.0..=1.0`, treating NaN as zero.
+fn clamp_ratio(ratio: f32) -> f32 {
+    if ratio.is_nan() {
+        0.
+    } else {
+        ratio.clamp(0., 1.)
+    }
+}
+
+/// Builds the characters of a bar `width` cells wide filled to `ratio`, using a partial block for
+/// the last cell when the ratio doesn't land on a cell boundary.
+fn bar_cells(width: usize, ratio: f32) -> Vec<char> {
+    let eighths = (ratio * (width * 8) as f32).round() as usize;
+    let full = eighths / 8;
+    let partial = eighths % 8;
+
+    let mut cells = vec![' '; width];
+    cells.iter_mut().take(full).for_each(|c| *c = FULL_BLOCK);
+    if partial > 0 && full < width {
+        cells[full] = PARTIAL_BLOCKS[partial - 1];
+    }
+    cells
+}
+
+/// Writes `label` centered over `cells`, returning the range of cells it covers.
+fn overlay_label(cells: &mut [char], label: &str) -> std::ops::Range<usize> {
+    let len = label.chars().count().min(cells.len());
+    let start = (cells.len() - len) / 2;
+    cells[start..]
+        .iter_mut()
+        .zip(label.chars().take(len))
+        .for_each(|(cell, c)| *cell = c);
+    start..start + len
+}
+
+/// Renders a single row of cells at the given position, switching attributes only when they
+/// change between cells.
+fn render_row(surface: &mut Surface, x: usize, y: usize, cells: &[(char, &CellAttributes)]) {
+    let mut changes = vec![Change::CursorPosition {
+        x: Position::Absolute(x),
+        y: Position::Absolute(y),
+    }];
+    let mut current: Option<&CellAttributes> = None;
+    let mut run = String::new();
+    for (c, attrs) in cells {
+        if current != Some(*attrs) {
+            if let Some(prev) = current {
+                changes.push(Change::AllAttributes(prev.clone()));
+                changes.push(Change::Text(std::mem::take(&mut run)));
+            }
+            current = Some(*attrs);
+        }
+        run.push(*c);
+    }
+    if let Some(prev) = current {
+        changes.push(Change::AllAttributes(prev.clone()));
+        changes.push(Change::Text(run));
+    }
+    changes.push(Change::AllAttributes(CellAttributes::default()));
+    surface.add_changes(changes);
+}
+
+/// A single-row progress bar, with an optional percentage label centered over it.
+pub struct ProgressBar {
+    ratio: f32,
+    show_percentage: bool,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        Self {
+            ratio: 0.,
+            show_percentage: true,
+        }
+    }
+
+    /// Set whether or not to display the percentage label over the bar
+    pub fn with_percentage(mut self, show_percentage: bool) -> Self {
+        self.show_percentage = show_percentage;
+        self
+    }
+
+    /// Set the initial ratio of the bar
+    pub fn with_ratio(mut self, ratio: f32) -> Self {
+        self.set_ratio(ratio);
+        self
+    }
+
+    /// Set the progress ratio, clamped to `0.0..=1.0`
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = clamp_ratio(ratio);
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+}
+
+impl<U, S> Widget<U, S> for ProgressBar {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        if width == 0 || height == 0 {
+            return Ok(vec![]);
+        }
+        let mut cells = bar_cells(width, self.ratio);
+        let label = if self.show_percentage {
+            overlay_label(&mut cells, &format!("{:.0}%", self.ratio * 100.))
+        } else {
+            0..0
+        };
+        // Label characters that sit on top of a full block are drawn reversed so they stay
+        // readable against the filled portion of the bar
+        let normal = CellAttributes::default();
+        let mut reversed = CellAttributes::default();
+        reversed.set_reverse(true);
+        let filled = (self.ratio * width as f32).floor() as usize;
+        let cells = cells
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if label.contains(&i) && i < filled {
+                    (c, &reversed)
+                } else {
+                    (c, &normal)
+                }
+            })
+            .collect::<Vec<_>>();
+        render_row(surface, 0, 0, &cells);
+        Ok(vec![])
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// A three-row gauge with a label and configurable filled/empty attributes.
+///
+/// The bar is drawn on the middle row, inset by one cell on each side.
+pub struct Gauge {
+    ratio: f32,
+    label: Option<String>,
+    filled: CellAttributes,
+    empty: CellAttributes,
+}
+
+impl Default for Gauge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Gauge {
+    pub fn new() -> Self {
+        let mut filled = CellAttributes::default();
+        filled.set_reverse(true);
+        Self {
+            ratio: 0.,
+            label: None,
+            filled,
+            empty: CellAttributes::default(),
+        }
+    }
+
+    /// Set the label displayed in the center of the gauge
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the attributes used for the filled portion of the gauge
+    pub fn with_filled(mut self, filled: CellAttributes) -> Self {
+        self.filled = filled;
+        self
+    }
+
+    /// Set the attributes used for the empty portion of the gauge
+    pub fn with_empty(mut self, empty: CellAttributes) -> Self {
+        self.empty = empty;
+        self
+    }
+
+    /// Set the initial ratio of the gauge
+    pub fn with_ratio(mut self, ratio: f32) -> Self {
+        self.set_ratio(ratio);
+        self
+    }
+
+    /// Set the progress ratio, clamped to `0.0..=1.0`
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = clamp_ratio(ratio);
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+impl<U, S> Widget<U, S> for Gauge {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        if width < 3 || height == 0 {
+            return Ok(vec![]);
+        }
+        let inner = width - 2;
+        let mut cells = bar_cells(inner, self.ratio);
+        let label = match &self.label {
+            Some(label) => overlay_label(&mut cells, label),
+            None => 0..0,
+        };
+        // The partially filled cell keeps its glyph, drawn with the empty attributes so the
+        // glyph itself shows the fractional progress
+        let filled = (self.ratio * inner as f32).floor() as usize;
+        let cells = cells
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i < filled {
+                    // Full blocks are drawn as spaces on the filled background, unless a
+                    // label character is on top
+                    let c = if label.contains(&i) { c } else { ' ' };
+                    (c, &self.filled)
+                } else {
+                    (c, &self.empty)
+                }
+            })
+            .collect::<Vec<_>>();
+        render_row(surface, 1, height / 2, &cells);
+        Ok(vec![])
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(3)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
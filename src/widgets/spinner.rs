@@ -0,0 +1,142 @@
+//! Animated spinner that advances on tick events.
+
+use crate::{
+    error::Result,
+    event::{Event, UserEvent},
+    layout::{Constraint, Rect, WidgetId},
+    surface::{Change, Surface},
+    widget::{RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// The default spinner frames
+pub const BRAILLE_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// A spinner that shows the next frame each time it receives [`UserEvent::Tick`].
+///
+/// Ticks are only produced when the app is created with [`crate::App::with_tick_interval`]. When
+/// the spinner is inactive it stops advancing and shows its "done" glyph instead.
+pub struct Spinner {
+    frames: Vec<String>,
+    frame: usize,
+    done: String,
+    label: String,
+    active: bool,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Spinner {
+    pub fn new() -> Self {
+        Self {
+            frames: BRAILLE_FRAMES.iter().map(|f| (*f).to_owned()).collect(),
+            frame: 0,
+            done: "✓".to_owned(),
+            label: String::new(),
+            active: true,
+        }
+    }
+
+    /// Use a custom set of frames. An empty set leaves the frames unchanged.
+    pub fn with_frames<I, F>(mut self, frames: I) -> Self
+    where
+        I: IntoIterator<Item = F>,
+        F: Into<String>,
+    {
+        let frames = frames.into_iter().map(Into::into).collect::<Vec<_>>();
+        if !frames.is_empty() {
+            self.frames = frames;
+            self.frame = 0;
+        }
+        self
+    }
+
+    /// Set the glyph shown when the spinner is inactive
+    pub fn with_done(mut self, done: impl Into<String>) -> Self {
+        self.done = done.into();
+        self
+    }
+
+    /// Set the text shown after the spinner
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = label.into();
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Start or stop the spinner. While inactive, ticks are ignored and the "done" glyph is shown.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Advance to the next frame
+    pub fn tick(&mut self) {
+        if self.active {
+            self.frame = (self.frame + 1) % self.frames.len();
+        }
+    }
+
+    /// The glyph that would currently be rendered
+    pub fn glyph(&self) -> &str {
+        if self.active {
+            &self.frames[self.frame]
+        } else {
+            &self.done
+        }
+    }
+}
+
+impl<U, S> Widget<U, S> for Spinner {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        let text = if self.label.is_empty() {
+            self.glyph().to_owned()
+        } else {
+            format!("{} {}", self.glyph(), self.label)
+        };
+        surface.add_change(Change::Text(text.chars().take(width).collect()));
+        Ok(vec![])
+    }
+
+    fn update<'u>(&mut self, _cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        if let Event::User(UserEvent::Tick) = event {
+            self.tick();
+        }
+        Ok(())
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,143 @@
+//! Single-row status line with left, center, and right aligned segments.
+
+use crate::{
+    error::Result,
+    layout::{Constraint, Rect, WidgetId},
+    style::CellAttributes,
+    surface::Surface,
+    text::{fill, render_styled, Alignment, Line, Span},
+    widget::{RenderCtx, Widget},
+    WidgetStore,
+};
+
+/// A list of styled text chunks that make up one segment of the status line
+pub type Segment = Vec<(String, CellAttributes)>;
+
+fn segment_line(segment: &Segment) -> Line {
+    Line(
+        segment
+            .iter()
+            .map(|(text, attrs)| Span::new(text.as_str(), attrs.clone()))
+            .collect(),
+    )
+}
+
+/// A single-row bar split into left, center, and right segments.
+///
+/// When the bar is too narrow to fit every segment, the center segment is dropped first, then
+/// the right segment. The left segment is truncated to the width of the bar as a last resort.
+pub struct StatusLine {
+    left: Segment,
+    center: Segment,
+    right: Segment,
+    /// Attributes used for the space between segments
+    style: CellAttributes,
+}
+
+impl Default for StatusLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatusLine {
+    pub fn new() -> Self {
+        Self {
+            left: vec![],
+            center: vec![],
+            right: vec![],
+            style: CellAttributes::default(),
+        }
+    }
+
+    /// Set the attributes used to fill the space between segments
+    pub fn with_style(mut self, style: CellAttributes) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn set_style(&mut self, style: CellAttributes) {
+        self.style = style;
+    }
+
+    pub fn set_left(&mut self, segment: Segment) {
+        self.left = segment;
+    }
+
+    pub fn set_center(&mut self, segment: Segment) {
+        self.center = segment;
+    }
+
+    pub fn set_right(&mut self, segment: Segment) {
+        self.right = segment;
+    }
+
+    pub fn left(&self) -> &Segment {
+        &self.left
+    }
+
+    pub fn center(&self) -> &Segment {
+        &self.center
+    }
+
+    pub fn right(&self) -> &Segment {
+        &self.right
+    }
+
+    /// Writes a segment starting at column `x`, truncated to `max` columns.
+    fn write_segment(surface: &mut Surface, segment: Line, x: usize, max: usize) {
+        let rect = Rect::new(x as f32, 0., max as f32, 1.);
+        render_styled(surface, &segment.into(), rect, false, Alignment::Left);
+    }
+}
+
+impl<U, S> Widget<U, S> for StatusLine {
+    fn render<'r>(
+        &self,
+        _cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, _) = surface.dimensions();
+        let (left_line, center_line, right_line) = (
+            segment_line(&self.left),
+            segment_line(&self.center),
+            segment_line(&self.right),
+        );
+        let left = left_line.width();
+        let center = center_line.width();
+        let right = right_line.width();
+
+        fill(surface, Rect::from_size((width, 1)), &self.style);
+
+        Self::write_segment(surface, left_line, 0, width);
+
+        let show_right = left + right <= width;
+        if show_right {
+            Self::write_segment(surface, right_line, width - right, right);
+        }
+
+        // The center segment is centered on the whole bar, and only shown if it doesn't overlap
+        // the other segments
+        let center_x = width.saturating_sub(center) / 2;
+        if show_right && center > 0 && center_x >= left && center_x + center + right <= width {
+            Self::write_segment(surface, center_line, center_x, center);
+        }
+        Ok(vec![])
+    }
+
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn constraint(&self, _: &WidgetStore<U, S>) -> Constraint {
+        Constraint::Fixed(1)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,802 @@
+#![cfg(feature = "pty")]
+//! Terminal emulator widget, running a command on a pseudo-terminal.
+
+use std::{
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use portable_pty::{
+    native_pty_system, ChildKiller, CommandBuilder, ExitStatus, MasterPty, PtySize,
+};
+use termwiz::{
+    cell::{grapheme_column_width, AttributeChange, CellAttributes},
+    escape::{
+        csi::{
+            Cursor, CursorStyle, DecPrivateMode, DecPrivateModeCode, Device, Edit, EraseInDisplay,
+            EraseInLine, Mode, Sgr,
+        },
+        parser::Parser,
+        Action, ControlCode, Esc, EscCode, OperatingSystemCommand, CSI,
+    },
+    input::{KeyCodeEncodeModes, KeyboardEncoding},
+};
+
+use crate::{
+    error::{Error, Result},
+    event::{Event, EventSender, KeyEvent, MouseButtons, MouseEvent, UserEvent},
+    layout::{Rect, WidgetId},
+    surface::{Change, CursorShape, Position, Surface},
+    widget::{CursorState, RenderCtx, UpdateCtx, Widget},
+    WidgetStore,
+};
+
+/// The size of the pseudo-terminal until the widget is first rendered
+const INITIAL_SIZE: (usize, usize) = (80, 24);
+
+/// Runs a command on a pseudo-terminal, and shows its screen.
+///
+/// Keys, pastes and mouse events sent to the widget are written to the command's input, and the
+/// pseudo-terminal is resized to fit the widget whenever it's rendered at a new size. When the
+/// command exits, [`UserEvent::ProcessExited`] is sent with its process id and exit code.
+///
+/// The emulator covers what shells and most full-screen programs need, including colors, scroll
+/// regions, the alternate screen and mouse reporting, but keeps no scrollback.
+///
+/// ```no_run
+/// use sanguine::{
+///     event::{Event, UserEvent},
+///     widgets::{CommandBuilder, Terminal},
+///     App, Config,
+/// };
+///
+/// let mut app = App::<(), ()>::new(Config::default())?;
+/// let shell = Terminal::spawn(CommandBuilder::new("bash"), &app.event_sender())?;
+/// let pid = shell.pid();
+/// app = app
+///     .with_layout(|layout, widgets| {
+///         let node = layout.add_leaf(widgets, shell);
+///         layout.add_child(layout.root(), node).ok()?;
+///         Some(node)
+///     })
+///     // Exit along with the shell
+///     .with_handler(move |_, event, tx| match event {
+///         Event::User(UserEvent::ProcessExited(exited, _)) if *exited == pid => {
+///             tx.quit()?;
+///             Ok(true)
+///         }
+///         _ => Ok(false),
+///     });
+/// app.exec()?;
+/// # Ok::<(), sanguine::error::Error>(())
+/// ```
+pub struct Terminal {
+    /// The controlling side of the pseudo-terminal, which the command's output is read from and
+    /// its input written to
+    master: Box<dyn MasterPty + Send>,
+    /// Writes to the command's input, shared with the thread answering the command's queries
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    pid: u32,
+    /// The emulated screen, updated by the thread reading the command's output
+    shared: Arc<Mutex<Shared>>,
+    /// The mouse buttons held during the last mouse event sent to the command
+    buttons: MouseButtons,
+}
+
+/// The state shared with the thread reading the command's output
+struct Shared {
+    screen: Screen,
+    exit_status: Option<ExitStatus>,
+}
+
+impl Terminal {
+    /// Run `command` on a new pseudo-terminal. [`UserEvent::ProcessExited`] is sent with `tx`
+    /// once it exits.
+    ///
+    /// The command's standard input, output and error are connected to the pseudo-terminal, and
+    /// `TERM` is set to `xterm-256color` unless the command sets it to something other than this
+    /// process's `TERM`.
+    pub fn spawn<U: Send + 'static>(
+        mut command: CommandBuilder,
+        tx: &EventSender<U>,
+    ) -> Result<Self> {
+        let pty = native_pty_system()
+            .openpty(pty_size(INITIAL_SIZE))
+            .map_err(Error::external)?;
+        // The inherited `TERM` describes the terminal the app runs in, not this one
+        if command.get_env("TERM") == std::env::var_os("TERM").as_deref() {
+            command.env("TERM", "xterm-256color");
+        }
+        let mut child = pty.slave.spawn_command(command).map_err(Error::external)?;
+        // Close the parent's copy of the other side, so that reads fail once the command exits
+        drop(pty.slave);
+
+        let shared = Arc::new(Mutex::new(Shared {
+            screen: Screen::new(INITIAL_SIZE),
+            exit_status: None,
+        }));
+        let mut reader = pty.master.try_clone_reader().map_err(Error::external)?;
+        let writer = Arc::new(Mutex::new(
+            pty.master.take_writer().map_err(Error::external)?,
+        ));
+        let killer = child.clone_killer();
+        let pid = child.process_id().unwrap_or_default();
+        let reader = std::thread::spawn({
+            let (shared, writer) = (shared.clone(), writer.clone());
+            move || {
+                let mut parser = Parser::new();
+                let mut buf = [0; 4096];
+                while let Ok(read @ 1..) = reader.read(&mut buf) {
+                    let Ok(mut shared) = shared.lock() else {
+                        break;
+                    };
+                    parser.parse(&buf[..read], |action| shared.screen.apply(action));
+                    let replies = std::mem::take(&mut shared.screen.replies);
+                    drop(shared);
+                    if replies.is_empty() {
+                        continue;
+                    }
+                    let Ok(mut writer) = writer.lock() else {
+                        break;
+                    };
+                    if writer.write_all(&replies).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        // Wait on the command separately from reading its output, so that it's reaped as soon as
+        // it exits even if something it started keeps the pseudo-terminal open
+        let tx = tx.clone();
+        std::thread::spawn({
+            let shared = shared.clone();
+            move || {
+                let status = child.wait().ok();
+                let code = status.as_ref().map(|status| status.exit_code() as i32);
+                if let Ok(mut shared) = shared.lock() {
+                    shared.exit_status = status;
+                }
+                // Let the last of the output reach the screen before reporting the exit
+                reader.join().ok();
+                tx.send(UserEvent::ProcessExited(pid, code)).ok();
+            }
+        });
+
+        Ok(Self {
+            master: pty.master,
+            writer,
+            killer,
+            pid,
+            shared,
+            buttons: MouseButtons::NONE,
+        })
+    }
+
+    /// The process id of the command
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// How the command exited, or `None` if it's still running
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.shared.lock().ok()?.exit_status.clone()
+    }
+
+    /// The title the command last set, if any
+    pub fn title(&self) -> Option<String> {
+        self.shared.lock().ok()?.screen.title.clone()
+    }
+
+    /// Write bytes to the command's input, as if they were typed
+    pub fn write(&self, bytes: &[u8]) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| Error::LockPoisoned)?;
+        writer.write_all(bytes).map_err(Error::external)
+    }
+
+    /// Encodes a mouse event in the format requested by the command, if it asked for mouse
+    /// events of that kind
+    fn encode_mouse(&mut self, mouse: &MouseEvent, screen: &Screen) -> Option<String> {
+        let tracking = screen.mouse_tracking?;
+        let held =
+            mouse.mouse_buttons & (MouseButtons::LEFT | MouseButtons::MIDDLE | MouseButtons::RIGHT);
+        let previous = std::mem::replace(&mut self.buttons, held);
+        let button = |buttons: MouseButtons| {
+            if buttons.contains(MouseButtons::LEFT) {
+                0
+            } else if buttons.contains(MouseButtons::MIDDLE) {
+                1
+            } else {
+                2
+            }
+        };
+        let (code, release) = if held.is_empty() && previous.is_empty() {
+            // Motion with no buttons held
+            if tracking != MouseTracking::Motion {
+                return None;
+            }
+            (35, false)
+        } else if held.is_empty() {
+            (button(previous), true)
+        } else if held == previous {
+            if tracking == MouseTracking::Click {
+                return None;
+            }
+            (button(held) + 32, false)
+        } else {
+            (button(held), false)
+        };
+        Some(encode_mouse(
+            code,
+            mouse.x,
+            mouse.y,
+            release,
+            screen.sgr_mouse,
+        ))
+    }
+}
+
+/// Encodes a mouse report for a button code at a widget-relative position
+fn encode_mouse(code: u16, x: u16, y: u16, release: bool, sgr: bool) -> String {
+    let (x, y) = (x + 1, y + 1);
+    if sgr {
+        let end = if release { 'm' } else { 'M' };
+        format!("\x1b[<{code};{x};{y}{end}")
+    } else {
+        // The legacy encoding has no release button, and can't report past column 223
+        let code = if release { 3 } else { code };
+        let encode = |n: u16| char::from((n.min(223) + 32) as u8);
+        format!("\x1b[M{}{}{}", encode(code), encode(x), encode(y))
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        // Hang up, like closing a terminal window does. The thread waiting on the command reaps it.
+        if self.exit_status().is_none() {
+            self.killer.kill().ok();
+        }
+    }
+}
+
+fn pty_size((cols, rows): (usize, usize)) -> PtySize {
+    PtySize {
+        rows: rows as u16,
+        cols: cols as u16,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+impl<U, S> Widget<U, S> for Terminal {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        // The command's output arrives on another thread, without the widget being updated
+        cx.request_redraw();
+        let Ok(mut shared) = self.shared.lock() else {
+            return Err(Error::LockPoisoned);
+        };
+        let (cols, rows) = surface.dimensions();
+        if shared.screen.surface.dimensions() != (cols, rows) && cols > 0 && rows > 0 {
+            shared.screen.resize(cols, rows);
+            // The command is told about the new size, with SIGWINCH on Unix
+            self.master.resize(pty_size((cols, rows))).ok();
+        }
+        surface.draw_from_screen(&shared.screen.surface, 0, 0);
+        Ok(vec![])
+    }
+
+    fn update<'u>(&mut self, _cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        let shared = self.shared.clone();
+        let shared = shared.lock().map_err(|_| Error::LockPoisoned)?;
+        // The command may have exited and closed the pseudo-terminal
+        if shared.exit_status.is_some() {
+            return Ok(());
+        }
+        let screen = &shared.screen;
+        let input = match event {
+            Event::Key(KeyEvent { key, modifiers }) => {
+                let modes = KeyCodeEncodeModes {
+                    encoding: KeyboardEncoding::Xterm,
+                    application_cursor_keys: screen.application_cursor_keys,
+                    newline_mode: false,
+                    modify_other_keys: None,
+                };
+                key.encode(modifiers, modes, true).ok()
+            }
+            Event::Paste(text) if screen.bracketed_paste => {
+                Some(format!("\x1b[200~{text}\x1b[201~"))
+            }
+            Event::Paste(text) => Some(text),
+            Event::Mouse(mouse) => self.encode_mouse(&mouse, screen),
+            Event::Scroll { delta, x, y } => screen.mouse_tracking.map(|_| {
+                let code = if delta < 0 { 64 } else { 65 };
+                encode_mouse(code, x, y, false, screen.sgr_mouse)
+            }),
+            _ => None,
+        };
+        drop(shared);
+        match input {
+            Some(input) => self.write(input.as_bytes()),
+            None => Ok(()),
+        }
+    }
+
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        let shared = self.shared.lock().ok()?;
+        let screen = &shared.screen;
+        if !screen.cursor_visible || shared.exit_status.is_some() {
+            return None;
+        }
+        let (cols, _) = screen.surface.dimensions();
+        let x = screen.x.min(cols.saturating_sub(1));
+        Some(CursorState::at(x, screen.y).with_shape(screen.cursor_shape))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// The mouse events a command asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MouseTracking {
+    /// Presses and releases
+    Click,
+    /// Presses, releases, and motion while a button is held
+    Drag,
+    /// All mouse events
+    Motion,
+}
+
+/// The screen of an emulated terminal, updated by applying the actions parsed from a command's
+/// output
+struct Screen {
+    surface: Surface,
+    /// The cursor position. `x` is one past the last column after printing in it, and the next
+    /// character printed wraps onto the next row.
+    x: usize,
+    y: usize,
+    attrs: CellAttributes,
+    /// The cursor position and attributes saved with DECSC
+    saved_cursor: (usize, usize, CellAttributes),
+    /// The first and last rows of the scroll region
+    top: usize,
+    bottom: usize,
+    cursor_visible: bool,
+    cursor_shape: CursorShape,
+    application_cursor_keys: bool,
+    bracketed_paste: bool,
+    /// The kind of mouse events the command asked for, if any
+    mouse_tracking: Option<MouseTracking>,
+    sgr_mouse: bool,
+    /// The normal screen, kept while the alternate screen is shown
+    normal_screen: Option<Surface>,
+    title: Option<String>,
+    /// Replies to queries like cursor position reports, to be written to the command's input
+    replies: Vec<u8>,
+}
+
+impl Screen {
+    fn new((cols, rows): (usize, usize)) -> Self {
+        Self {
+            surface: Surface::new(cols, rows),
+            x: 0,
+            y: 0,
+            attrs: CellAttributes::default(),
+            saved_cursor: (0, 0, CellAttributes::default()),
+            top: 0,
+            bottom: rows - 1,
+            cursor_visible: true,
+            cursor_shape: CursorShape::Default,
+            application_cursor_keys: false,
+            bracketed_paste: false,
+            mouse_tracking: None,
+            sgr_mouse: false,
+            normal_screen: None,
+            title: None,
+            replies: vec![],
+        }
+    }
+
+    fn cols(&self) -> usize {
+        self.surface.dimensions().0
+    }
+
+    fn rows(&self) -> usize {
+        self.surface.dimensions().1
+    }
+
+    fn resize(&mut self, cols: usize, rows: usize) {
+        self.surface.resize(cols, rows);
+        if let Some(normal) = &mut self.normal_screen {
+            normal.resize(cols, rows);
+        }
+        self.x = self.x.min(cols);
+        self.y = self.y.min(rows - 1);
+        self.top = 0;
+        self.bottom = rows - 1;
+    }
+
+    /// Applies changes at the cursor position, with the current attributes
+    fn change(&mut self, changes: impl IntoIterator<Item = Change>) {
+        let x = self.x.min(self.cols() - 1);
+        self.surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(x),
+                y: Position::Absolute(self.y),
+            },
+            Change::AllAttributes(self.attrs.clone()),
+        ]);
+        self.surface.add_changes(changes.into_iter().collect());
+        // The surface's change log isn't needed, and would otherwise grow forever
+        let seq = self.surface.current_seqno();
+        self.surface.flush_changes_older_than(seq);
+    }
+
+    fn background(&self) -> termwiz::color::ColorAttribute {
+        self.attrs.background()
+    }
+
+    fn print(&mut self, c: char) {
+        let width = grapheme_column_width(c.encode_utf8(&mut [0; 4]), None);
+        // Combining characters aren't supported, and would take up a cell of their own
+        if width == 0 {
+            return;
+        }
+        if self.x + width > self.cols() {
+            self.x = 0;
+            self.line_feed();
+        }
+        self.change([Change::Text(c.to_string())]);
+        self.x += width;
+    }
+
+    fn line_feed(&mut self) {
+        if self.y == self.bottom {
+            self.scroll_up(1);
+        } else if self.y + 1 < self.rows() {
+            self.y += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.y == self.top {
+            self.scroll_down(1);
+        } else {
+            self.y = self.y.saturating_sub(1);
+        }
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        self.scroll_region_up(self.top, count);
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        self.scroll_region_down(self.top, count);
+    }
+
+    /// Scrolls the rows from `first_row` to the bottom of the scroll region up
+    fn scroll_region_up(&mut self, first_row: usize, count: usize) {
+        let region_size = self.bottom + 1 - first_row;
+        self.change([Change::ScrollRegionUp {
+            first_row,
+            region_size,
+            scroll_count: count.min(region_size),
+        }]);
+    }
+
+    /// Scrolls the rows from `first_row` to the bottom of the scroll region down
+    fn scroll_region_down(&mut self, first_row: usize, count: usize) {
+        let region_size = self.bottom + 1 - first_row;
+        self.change([Change::ScrollRegionDown {
+            first_row,
+            region_size,
+            scroll_count: count.min(region_size),
+        }]);
+    }
+
+    fn move_to(&mut self, x: usize, y: usize) {
+        self.x = x.min(self.cols() - 1);
+        self.y = y.min(self.rows() - 1);
+    }
+
+    /// Erases `count` cells from the cursor onwards, without moving the cursor
+    fn erase_chars(&mut self, count: usize) {
+        let count = count.min(self.cols() - self.x.min(self.cols()));
+        self.change([Change::Text(" ".repeat(count))]);
+    }
+
+    /// Shifts the cells from the cursor to the end of the row right by `count`, or left if
+    /// `insert` is false, filling the space left behind with blanks
+    fn shift_cells(&mut self, count: usize, insert: bool) {
+        let (x, y) = (self.x.min(self.cols() - 1), self.y);
+        let mut blank = CellAttributes::default();
+        blank.set_background(self.background());
+        if let Some(row) = self.surface.screen_cells().into_iter().nth(y) {
+            let cells = &mut row[x..];
+            let count = count.min(cells.len());
+            if insert {
+                cells.rotate_right(count);
+                cells[..count].fill(termwiz::cell::Cell::new(' ', blank));
+            } else {
+                cells.rotate_left(count);
+                let len = cells.len();
+                cells[len - count..].fill(termwiz::cell::Cell::new(' ', blank));
+            }
+        }
+    }
+
+    fn swap_screen(&mut self, alternate: bool) {
+        if alternate == self.normal_screen.is_some() {
+            return;
+        }
+        let (cols, rows) = self.surface.dimensions();
+        match self.normal_screen.take() {
+            Some(normal) => self.surface = normal,
+            None => {
+                let normal = std::mem::replace(&mut self.surface, Surface::new(cols, rows));
+                self.normal_screen = Some(normal);
+            }
+        }
+    }
+
+    fn set_mode(&mut self, mode: &DecPrivateMode, enable: bool) {
+        let DecPrivateMode::Code(code) = mode else {
+            return;
+        };
+        use DecPrivateModeCode as Code;
+        match code {
+            Code::ApplicationCursorKeys => self.application_cursor_keys = enable,
+            Code::ShowCursor => self.cursor_visible = enable,
+            Code::BracketedPaste => self.bracketed_paste = enable,
+            Code::MouseTracking => self.mouse_tracking = enable.then_some(MouseTracking::Click),
+            Code::ButtonEventMouse => self.mouse_tracking = enable.then_some(MouseTracking::Drag),
+            Code::AnyEventMouse => self.mouse_tracking = enable.then_some(MouseTracking::Motion),
+            Code::SGRMouse => self.sgr_mouse = enable,
+            Code::SaveCursor if enable => self.save_cursor(),
+            Code::SaveCursor => self.restore_cursor(),
+            Code::EnableAlternateScreen | Code::OptEnableAlternateScreen => {
+                self.swap_screen(enable)
+            }
+            Code::ClearAndEnableAlternateScreen => {
+                if enable {
+                    self.save_cursor();
+                    self.swap_screen(true);
+                } else {
+                    self.swap_screen(false);
+                    self.restore_cursor();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved_cursor = (self.x, self.y, self.attrs.clone());
+    }
+
+    fn restore_cursor(&mut self) {
+        let (x, y, attrs) = self.saved_cursor.clone();
+        self.move_to(x, y);
+        self.attrs = attrs;
+    }
+
+    fn apply(&mut self, action: Action) {
+        match action {
+            Action::Print(c) => self.print(c),
+            Action::PrintString(s) => s.chars().for_each(|c| self.print(c)),
+            Action::Control(code) => self.control(code),
+            Action::CSI(csi) => self.csi(csi),
+            Action::Esc(Esc::Code(code)) => match code {
+                EscCode::Index => self.line_feed(),
+                EscCode::NextLine => {
+                    self.x = 0;
+                    self.line_feed();
+                }
+                EscCode::ReverseIndex => self.reverse_index(),
+                EscCode::DecSaveCursorPosition => self.save_cursor(),
+                EscCode::DecRestoreCursorPosition => self.restore_cursor(),
+                EscCode::FullReset => {
+                    let title = self.title.take();
+                    *self = Self::new(self.surface.dimensions());
+                    self.title = title;
+                }
+                _ => {}
+            },
+            Action::OperatingSystemCommand(osc) => match *osc {
+                OperatingSystemCommand::SetIconNameAndWindowTitle(title)
+                | OperatingSystemCommand::SetWindowTitle(title) => self.title = Some(title),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn control(&mut self, code: ControlCode) {
+        match code {
+            ControlCode::LineFeed | ControlCode::VerticalTab | ControlCode::FormFeed => {
+                self.line_feed()
+            }
+            ControlCode::CarriageReturn => self.x = 0,
+            ControlCode::Backspace => self.x = self.x.min(self.cols() - 1).saturating_sub(1),
+            ControlCode::HorizontalTab => self.x = ((self.x / 8 + 1) * 8).min(self.cols() - 1),
+            ControlCode::NEL => {
+                self.x = 0;
+                self.line_feed();
+            }
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, csi: CSI) {
+        match csi {
+            CSI::Sgr(sgr) => self.sgr(sgr),
+            CSI::Cursor(cursor) => self.cursor(cursor),
+            CSI::Edit(edit) => self.edit(edit),
+            CSI::Mode(Mode::SetDecPrivateMode(mode)) => self.set_mode(&mode, true),
+            CSI::Mode(Mode::ResetDecPrivateMode(mode)) => self.set_mode(&mode, false),
+            CSI::Device(device) => match *device {
+                Device::RequestPrimaryDeviceAttributes => {
+                    // A VT102
+                    self.replies.extend_from_slice(b"\x1b[?6c");
+                }
+                Device::RequestSecondaryDeviceAttributes => {
+                    self.replies.extend_from_slice(b"\x1b[>0;0;0c");
+                }
+                Device::StatusReport => self.replies.extend_from_slice(b"\x1b[0n"),
+                Device::SoftReset => {
+                    self.attrs = CellAttributes::default();
+                    self.cursor_visible = true;
+                    self.top = 0;
+                    self.bottom = self.rows() - 1;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn cursor(&mut self, cursor: Cursor) {
+        let (x, y) = (self.x.min(self.cols() - 1), self.y);
+        match cursor {
+            Cursor::Up(n) | Cursor::LinePositionBackward(n) => {
+                // The cursor stops at the top of the scroll region if it's inside of it
+                let top = if y >= self.top { self.top } else { 0 };
+                self.move_to(x, y.saturating_sub(n as usize).max(top));
+            }
+            Cursor::Down(n) | Cursor::LinePositionForward(n) => {
+                let bottom = if y <= self.bottom {
+                    self.bottom
+                } else {
+                    self.rows() - 1
+                };
+                self.move_to(x, (y + n as usize).min(bottom));
+            }
+            Cursor::Left(n) | Cursor::CharacterPositionBackward(n) => {
+                self.move_to(x.saturating_sub(n as usize), y)
+            }
+            Cursor::Right(n) | Cursor::CharacterPositionForward(n) => {
+                self.move_to(x + n as usize, y)
+            }
+            Cursor::NextLine(n) => self.move_to(0, y + n as usize),
+            Cursor::PrecedingLine(n) => self.move_to(0, y.saturating_sub(n as usize)),
+            Cursor::Position { line, col } | Cursor::CharacterAndLinePosition { line, col } => {
+                self.move_to(col.as_zero_based() as usize, line.as_zero_based() as usize)
+            }
+            Cursor::CharacterAbsolute(col) | Cursor::CharacterPositionAbsolute(col) => {
+                self.move_to(col.as_zero_based() as usize, y)
+            }
+            Cursor::LinePositionAbsolute(line) => {
+                self.move_to(x, (line as usize).saturating_sub(1))
+            }
+            Cursor::SaveCursor => self.save_cursor(),
+            Cursor::RestoreCursor => self.restore_cursor(),
+            Cursor::RequestActivePositionReport => {
+                let report = format!("\x1b[{};{}R", y + 1, x + 1);
+                self.replies.extend_from_slice(report.as_bytes());
+            }
+            Cursor::SetTopAndBottomMargins { top, bottom } => {
+                let rows = self.rows();
+                let top = (top.as_zero_based() as usize).min(rows - 1);
+                let bottom = (bottom.as_zero_based() as usize).min(rows - 1);
+                if top < bottom {
+                    self.top = top;
+                    self.bottom = bottom;
+                    self.move_to(0, 0);
+                }
+            }
+            Cursor::CursorStyle(style) => {
+                self.cursor_shape = match style {
+                    CursorStyle::Default => CursorShape::Default,
+                    CursorStyle::BlinkingBlock => CursorShape::BlinkingBlock,
+                    CursorStyle::SteadyBlock => CursorShape::SteadyBlock,
+                    CursorStyle::BlinkingUnderline => CursorShape::BlinkingUnderline,
+                    CursorStyle::SteadyUnderline => CursorShape::SteadyUnderline,
+                    CursorStyle::BlinkingBar => CursorShape::BlinkingBar,
+                    CursorStyle::SteadyBar => CursorShape::SteadyBar,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn edit(&mut self, edit: Edit) {
+        let (x, y) = (self.x.min(self.cols() - 1), self.y);
+        let background = self.background();
+        match edit {
+            Edit::EraseInLine(erase) => match erase {
+                EraseInLine::EraseToEndOfLine => {
+                    self.change([Change::ClearToEndOfLine(background)])
+                }
+                EraseInLine::EraseToStartOfLine => {
+                    self.x = 0;
+                    self.erase_chars(x + 1);
+                    self.x = x;
+                }
+                EraseInLine::EraseLine => {
+                    self.x = 0;
+                    self.change([Change::ClearToEndOfLine(background)]);
+                    self.x = x;
+                }
+            },
+            Edit::EraseInDisplay(erase) => match erase {
+                EraseInDisplay::EraseToEndOfDisplay => {
+                    self.change([Change::ClearToEndOfScreen(background)])
+                }
+                EraseInDisplay::EraseToStartOfDisplay => {
+                    for row in 0..y {
+                        self.move_to(0, row);
+                        self.change([Change::ClearToEndOfLine(background)]);
+                    }
+                    self.move_to(0, y);
+                    self.erase_chars(x + 1);
+                    self.move_to(x, y);
+                }
+                EraseInDisplay::EraseDisplay => {
+                    self.change([Change::ClearScreen(background)]);
+                }
+                EraseInDisplay::EraseScrollback => {}
+            },
+            Edit::EraseCharacter(n) => self.erase_chars(n as usize),
+            Edit::InsertCharacter(n) => self.shift_cells(n as usize, true),
+            Edit::DeleteCharacter(n) => self.shift_cells(n as usize, false),
+            Edit::InsertLine(n) if (self.top..=self.bottom).contains(&y) => {
+                self.scroll_region_down(y, n as usize);
+            }
+            Edit::DeleteLine(n) if (self.top..=self.bottom).contains(&y) => {
+                self.scroll_region_up(y, n as usize);
+            }
+            Edit::ScrollUp(n) => self.scroll_up(n as usize),
+            Edit::ScrollDown(n) => self.scroll_down(n as usize),
+            _ => {}
+        }
+    }
+
+    fn sgr(&mut self, sgr: Sgr) {
+        let change = match sgr {
+            Sgr::Reset => {
+                self.attrs = CellAttributes::default();
+                return;
+            }
+            Sgr::Intensity(intensity) => AttributeChange::Intensity(intensity),
+            Sgr::Underline(underline) => AttributeChange::Underline(underline),
+            Sgr::Blink(blink) => AttributeChange::Blink(blink),
+            Sgr::Italic(italic) => AttributeChange::Italic(italic),
+            Sgr::Inverse(reverse) => AttributeChange::Reverse(reverse),
+            Sgr::Invisible(invisible) => AttributeChange::Invisible(invisible),
+            Sgr::StrikeThrough(strike) => AttributeChange::StrikeThrough(strike),
+            Sgr::Foreground(color) => AttributeChange::Foreground(color.into()),
+            Sgr::Background(color) => AttributeChange::Background(color.into()),
+            _ => return,
+        };
+        self.attrs.apply_change(&change);
+    }
+}
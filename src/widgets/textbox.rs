@@ -1,12 +1,19 @@
-use std::sync::{Arc, RwLock};
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex, RwLock},
+};
 
+#[cfg(feature = "rope")]
+use crate::rope::Rope;
 use crate::{
-    error::Error,
-    error::Result,
-    event::{Event, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+    error::{read_or_err, write_or_err, Error, Result},
+    event::{Event, Gesture, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
     layout::{Rect, WidgetId},
-    surface::{Change, Position, Surface},
-    widget::{RenderCtx, UpdateCtx, Widget},
+    render_util::{changed_lines, redraw_line, scroll_and_render_delta},
+    style::CellAttributes,
+    surface::{Change, CursorShape, Position, Surface},
+    text::{byte_index, fill},
+    widget::{CursorState, RenderCtx, UpdateCtx, Widget},
     WidgetStore,
 };
 
@@ -16,10 +23,205 @@ pub struct Cursor {
     y: usize,
 }
 
-/// A simple editable textbox widget
-pub struct TextBox {
-    buf: Arc<RwLock<Vec<String>>>,
+/// A match of the search query, as its line and the character it starts at
+type Match = (usize, usize);
+
+/// A search started with <kbd>ctrl</kbd>+<kbd>f</kbd>
+struct Search {
+    query: String,
+    /// Whether the query is being typed. Once it's confirmed with <kbd>enter</kbd>, <kbd>n</kbd>
+    /// and <kbd>shift</kbd>+<kbd>n</kbd> jump between matches.
+    typing: bool,
+    /// The cursor and scroll positions before the search, restored if it's cancelled
+    origin: (Cursor, usize, usize),
+    /// The matches of the prefixes of the query searched for so far, with their length in
+    /// characters, so that typing filters the last matches instead of scanning the buffer again
+    /// and backspace goes back to earlier ones
+    matches: Vec<(usize, Vec<Match>)>,
+}
+
+impl Search {
+    /// The matches of the whole query, in order
+    fn matches(&self) -> &[Match] {
+        let len = self.query.chars().count();
+        match self.matches.last() {
+            Some((searched, matches)) if *searched == len => matches,
+            _ => &[],
+        }
+    }
+}
+
+/// Whether `text` starts with `query`, ignoring case unless `case_sensitive` is set
+fn starts_with(text: &str, query: &str, case_sensitive: bool) -> bool {
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let mut text = text.chars();
+    query.chars().all(|q| {
+        text.next()
+            .is_some_and(|c| c == q || (!case_sensitive && fold(c) == fold(q)))
+    })
+}
+
+/// The text edited by a [`TextBox`], as lines without their line breaks. Positions within a
+/// line are in characters.
+pub trait TextBuffer {
+    /// The number of lines, which is never zero
+    fn len_lines(&self) -> usize;
+
+    /// The line at index `y`
+    fn line(&self, y: usize) -> Option<&str>;
+
+    /// The number of characters in line `y`
+    fn line_len(&self, y: usize) -> usize {
+        self.line(y).map_or(0, |line| line.chars().count())
+    }
+
+    /// Insert a character before character `x` of line `y`
+    fn insert_char(&mut self, y: usize, x: usize, c: char);
+
+    /// Remove character `x` of line `y`
+    fn remove_char(&mut self, y: usize, x: usize);
+
+    /// Split line `y` before character `x`, moving the rest of it to a new line below
+    fn split_line(&mut self, y: usize, x: usize);
+
+    /// Append line `y + 1` to line `y`, removing it
+    fn join_lines(&mut self, y: usize);
+}
+
+/// The simple backend, which moves every line below an inserted or removed line
+impl TextBuffer for Vec<String> {
+    fn len_lines(&self) -> usize {
+        self.len().max(1)
+    }
+
+    fn line(&self, y: usize) -> Option<&str> {
+        match self.get(y) {
+            None if y == 0 => Some(""),
+            line => line.map(String::as_str),
+        }
+    }
+
+    fn insert_char(&mut self, y: usize, x: usize, c: char) {
+        if self.is_empty() {
+            self.push(String::new());
+        }
+        if let Some(line) = self.get_mut(y) {
+            line.insert(byte_index(line, x), c);
+        }
+    }
+
+    fn remove_char(&mut self, y: usize, x: usize) {
+        if let Some(line) = self.get_mut(y) {
+            let at = byte_index(line, x);
+            if at < line.len() {
+                line.remove(at);
+            }
+        }
+    }
+
+    fn split_line(&mut self, y: usize, x: usize) {
+        if self.is_empty() {
+            self.push(String::new());
+        }
+        if let Some(line) = self.get_mut(y) {
+            let rest = line.split_off(byte_index(line, x));
+            self.insert(y + 1, rest);
+        }
+    }
+
+    fn join_lines(&mut self, y: usize) {
+        if y + 1 < self.len() {
+            let next = self.remove(y + 1);
+            self[y].push_str(&next);
+        }
+    }
+}
+
+#[cfg(feature = "rope")]
+impl TextBuffer for Rope {
+    fn len_lines(&self) -> usize {
+        self.len_lines()
+    }
+
+    fn line(&self, y: usize) -> Option<&str> {
+        self.line(y)
+    }
+
+    fn insert_char(&mut self, y: usize, x: usize, c: char) {
+        self.insert_char(y, x, c);
+    }
+
+    fn remove_char(&mut self, y: usize, x: usize) {
+        self.remove_char(y, x);
+    }
+
+    fn split_line(&mut self, y: usize, x: usize) {
+        self.split_line(y, x);
+    }
+
+    fn join_lines(&mut self, y: usize) {
+        self.join_lines(y);
+    }
+}
+
+/// A simple editable textbox widget. The text is kept in a `Vec<String>` by default, or in a
+/// [`Rope`](crate::rope::Rope) for large files with the `rope` feature.
+///
+/// <kbd>ctrl</kbd>+<kbd>f</kbd> starts a search, typed on the bottom row. The cursor moves to the
+/// first match after it as the query is typed, and every match on screen is drawn with the theme's
+/// `textbox.match` style. <kbd>enter</kbd> confirms the query, after which <kbd>enter</kbd> or
+/// <kbd>n</kbd> jumps to the next match and <kbd>shift</kbd>+<kbd>n</kbd> to the previous one.
+/// <kbd>escape</kbd> ends the search, and returns the cursor to where it was if the query was
+/// still being typed. <kbd>alt</kbd>+<kbd>c</kbd> toggles whether case is ignored, which it is by
+/// default.
+///
+/// ```
+/// use sanguine::{
+///     backend::TestBackend,
+///     event::{InputEvent, KeyCode, KeyEvent, Modifiers},
+///     widgets::TextBox,
+///     App,
+/// };
+///
+/// let mut app = App::<(), ()>::test(12, 3).with_layout(|layout, widgets| {
+///     let leaf = layout.add_leaf(widgets, TextBox::from_str("one two\nthree TWO"));
+///     layout.add_child(layout.root(), leaf).ok()?;
+///     Some(leaf)
+/// });
+///
+/// let backend = app.backend_mut::<TestBackend>().unwrap();
+/// let key = |key, modifiers| InputEvent::Key(KeyEvent { key, modifiers });
+/// backend.push_input(key(KeyCode::Char('f'), Modifiers::CTRL));
+/// for c in "two".chars() {
+///     backend.push_input(key(KeyCode::Char(c), Modifiers::NONE));
+/// }
+/// app.handle_events().unwrap();
+/// app.render().unwrap();
+///
+/// app.backend::<TestBackend>().unwrap().assert_screen(&[
+///     "one two     ",
+///     "three TWO   ",
+///     "/two    1/2 ",
+/// ]);
+/// ```
+pub struct TextBox<B = Vec<String>> {
+    buf: Arc<RwLock<B>>,
     cursor: Cursor,
+    /// The first visible line
+    scroll: usize,
+    /// The first visible column
+    hscroll: usize,
+    /// The selected text as a line and a range of characters
+    selection: Option<(usize, Range<usize>)>,
+    cursor_shape: CursorShape,
+    search: Option<Search>,
+    case_sensitive: bool,
+    /// The height of the textbox when it was last updated, for placing the cursor on the search
+    /// row
+    height: usize,
+    /// The vertical and horizontal scroll positions of the last frame, or `None` if it had
+    /// highlights and can't be reused
+    drawn: Mutex<Option<(usize, usize)>>,
 }
 
 impl Default for TextBox {
@@ -30,222 +232,567 @@ impl Default for TextBox {
 
 impl TextBox {
     pub fn new() -> Self {
-        Self {
-            buf: Arc::new(RwLock::new(vec![String::new()])),
-            cursor: Cursor { x: 0, y: 0 },
-        }
+        Self::with_buffer(vec![String::new()])
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: impl Into<String>) -> Self {
+        Self::with_buffer(s.into().lines().map(|s| s.to_owned()).collect())
+    }
+
+    pub fn buffer(&self) -> Arc<RwLock<Vec<String>>> {
+        self.buf.clone()
+    }
+}
+
+#[cfg(feature = "rope")]
+impl TextBox<Rope> {
+    /// Read text into a textbox backed by a [`Rope`], which keeps editing fast in files of tens
+    /// of megabytes. Only the visible lines are drawn.
+    ///
+    /// ```
+    /// use sanguine::widgets::TextBox;
+    ///
+    /// let textbox = TextBox::from_reader("one\ntwo\n".as_bytes()).unwrap();
+    /// assert_eq!(textbox.rope().read().unwrap().line(1), Some("two"));
+    /// ```
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self> {
+        Ok(Self::with_buffer(Rope::from_reader(reader)?))
+    }
+
+    pub fn rope(&self) -> Arc<RwLock<Rope>> {
+        self.buf.clone()
+    }
+}
+
+impl<B: TextBuffer> TextBox<B> {
+    fn with_buffer(buf: B) -> Self {
         Self {
-            buf: Arc::new(RwLock::new(
-                s.into().lines().map(|s| s.to_owned()).collect(),
-            )),
+            buf: Arc::new(RwLock::new(buf)),
             cursor: Cursor { x: 0, y: 0 },
+            scroll: 0,
+            hscroll: 0,
+            selection: None,
+            cursor_shape: CursorShape::Default,
+            search: None,
+            case_sensitive: false,
+            height: 0,
+            drawn: Mutex::new(None),
         }
     }
 
-    pub fn buffer(&self) -> Arc<RwLock<Vec<String>>> {
-        self.buf.clone()
+    /// Show the cursor in the given shape while the textbox is focused, such as a bar while
+    /// inserting and a block otherwise
+    pub fn with_cursor_shape(mut self, shape: CursorShape) -> Self {
+        self.cursor_shape = shape;
+        self
     }
 
-    fn write_char(&mut self, c: char) -> Result<()> {
-        let mut writer = self.buf.write().unwrap();
-        let line = writer
-            .get(self.cursor.y)
-            .ok_or(crate::error::Error::TerminalError)?;
-        if self.cursor.x >= line.len() {
-            writer
-                .get_mut(self.cursor.y)
-                .ok_or(crate::error::Error::TerminalError)?
-                .push(c);
-        } else {
-            let mut new_line = String::new();
-            new_line.push_str(&line[0..self.cursor.x]);
-            new_line.push(c);
-            new_line.push_str(&line[self.cursor.x..]);
-            *writer
-                .get_mut(self.cursor.y)
-                .ok_or(crate::error::Error::TerminalError)? = new_line;
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.cursor_shape = shape;
+    }
+
+    /// Set whether searches match case `default: false`. Toggled with <kbd>alt</kbd>+<kbd>c</kbd>
+    /// while searching.
+    pub fn with_case_sensitive_search(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// The query of the search in progress, if any
+    pub fn search_query(&self) -> Option<&str> {
+        self.search.as_ref().map(|search| search.query.as_str())
+    }
+
+    /// The selected text, if any
+    pub fn selection(&self) -> Option<String> {
+        let (line, range) = self.selection.as_ref()?;
+        let buf = read_or_err(&self.buf).ok()?;
+        let line = buf.line(*line)?;
+        Some(line.chars().skip(range.start).take(range.len()).collect())
+    }
+
+    /// Selects the word at the given position, moving the cursor to its end
+    fn select_word(&mut self, x: usize, y: usize) -> Result<()> {
+        let buf = read_or_err(&self.buf)?;
+        let Some(line) = buf.line(y) else {
+            return Ok(());
+        };
+        let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+        let chars = line.chars().collect::<Vec<_>>();
+        if !chars.get(x).is_some_and(is_word) {
+            return Ok(());
         }
+        let start = chars[..x]
+            .iter()
+            .rposition(|c| !is_word(c))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let end = chars[x..]
+            .iter()
+            .position(|c| !is_word(c))
+            .map(|i| x + i)
+            .unwrap_or(chars.len());
+        drop(buf);
+        self.selection = Some((y, start..end));
+        self.set_cursor(end, y)
+    }
+
+    fn write_char(&mut self, c: char) -> Result<()> {
+        write_or_err(&self.buf)?.insert_char(self.cursor.y, self.cursor.x, c);
         self.cursor.x += 1;
         Ok(())
     }
 
+    /// Inserts pasted text at the cursor, moving the cursor to its end
+    fn write_str(&mut self, text: &str) -> Result<()> {
+        let mut writer = write_or_err(&self.buf)?;
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    writer.split_line(self.cursor.y, self.cursor.x);
+                    self.cursor = Cursor {
+                        x: 0,
+                        y: self.cursor.y + 1,
+                    };
+                }
+                '\r' => {}
+                c => {
+                    writer.insert_char(self.cursor.y, self.cursor.x, c);
+                    self.cursor.x += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn delete(&mut self) -> Result<()> {
         // backspace
         if self.cursor.x == 0 && self.cursor.y == 0 {
             return Ok(());
         }
 
+        let mut writer = write_or_err(&self.buf)?;
         if self.cursor.x == 0 {
-            let mut writer = self.buf.write().unwrap();
-            let line = writer.remove(self.cursor.y);
-            let prev_line = writer
-                .get_mut(self.cursor.y - 1)
-                .ok_or(crate::error::Error::TerminalError)?;
-            let old_len = prev_line.len();
-            prev_line.push_str(&line);
+            let prev_len = writer.line_len(self.cursor.y - 1);
+            writer.join_lines(self.cursor.y - 1);
             self.cursor.y -= 1;
-            self.cursor.x = old_len;
+            self.cursor.x = prev_len;
         } else {
-            let mut writer = self.buf.write().unwrap();
-            let line = writer
-                .get_mut(self.cursor.y)
-                .ok_or(crate::error::Error::TerminalError)?;
-            let mut new_line = String::new();
-            new_line.push_str(&line[0..self.cursor.x - 1]);
-            new_line.push_str(&line[self.cursor.x..]);
-            *writer
-                .get_mut(self.cursor.y)
-                .ok_or(crate::error::Error::TerminalError)? = new_line;
+            writer.remove_char(self.cursor.y, self.cursor.x - 1);
             self.cursor.x -= 1;
         }
         Ok(())
     }
 
-    fn set_cursor_x(&mut self, x: usize) {
-        let line = self
-            .buf
-            .read()
-            .unwrap()
-            .get(self.cursor.y)
-            .map(|l| l.len())
-            .unwrap_or(0);
-        if x >= line {
-            self.cursor.x = line;
-        } else {
-            self.cursor.x = x;
+    fn set_cursor_x(&mut self, x: usize) -> Result<()> {
+        let line = read_or_err(&self.buf)?.line_len(self.cursor.y);
+        self.cursor.x = x.min(line);
+        Ok(())
+    }
+
+    fn set_cursor_y(&mut self, y: usize) -> Result<()> {
+        let buf = read_or_err(&self.buf)?;
+        self.cursor.y = y.min(buf.len_lines() - 1);
+        self.cursor.x = self.cursor.x.min(buf.line_len(self.cursor.y));
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, x: usize, y: usize) -> Result<()> {
+        self.set_cursor_y(y)?;
+        self.set_cursor_x(x)
+    }
+
+    /// Scrolls the view by `delta` lines, moving the cursor if needed to keep it in view
+    fn scroll_by(&mut self, delta: i32, height: usize) -> Result<()> {
+        let last = read_or_err(&self.buf)?.len_lines() - 1;
+        self.scroll = (self.scroll as i64 + delta as i64).clamp(0, last as i64) as usize;
+        if self.cursor.y < self.scroll {
+            self.set_cursor_y(self.scroll)?;
+        } else if height > 0 && self.cursor.y >= self.scroll + height {
+            self.set_cursor_y(self.scroll + height - 1)?;
+        }
+        Ok(())
+    }
+
+    /// Scrolls the view so that the cursor is visible
+    fn scroll_to_cursor(&mut self, width: usize, height: usize) {
+        if self.cursor.y < self.scroll {
+            self.scroll = self.cursor.y;
+        } else if height > 0 && self.cursor.y >= self.scroll + height {
+            self.scroll = self.cursor.y + 1 - height;
+        }
+        if self.cursor.x < self.hscroll {
+            self.hscroll = self.cursor.x;
+        } else if width > 0 && self.cursor.x >= self.hscroll + width {
+            self.hscroll = self.cursor.x + 1 - width;
+        }
+    }
+
+    /// The number of rows the text is drawn on, leaving the bottom row for the search query
+    fn text_height(&self, height: usize) -> usize {
+        match self.search {
+            Some(_) => height.saturating_sub(1),
+            None => height,
+        }
+    }
+
+    /// Brings the matches of the search up to date with its query, filtering the matches of the
+    /// query before the last character was typed rather than scanning the whole buffer
+    fn update_matches(&mut self) -> Result<()> {
+        let Some(search) = &mut self.search else {
+            return Ok(());
+        };
+        let len = search.query.chars().count();
+        search.matches.retain(|(searched, _)| *searched <= len);
+        if len == 0
+            || search
+                .matches
+                .last()
+                .is_some_and(|(searched, _)| *searched == len)
+        {
+            return Ok(());
         }
+        let buf = read_or_err(&self.buf)?;
+        let (query, case_sensitive) = (search.query.as_str(), self.case_sensitive);
+        let found = match search.matches.last() {
+            Some((_, matches)) => matches
+                .iter()
+                .copied()
+                .filter(|(y, x)| {
+                    let line = buf.line(*y).unwrap_or_default();
+                    starts_with(&line[byte_index(line, *x)..], query, case_sensitive)
+                })
+                .collect(),
+            None => (0..buf.len_lines())
+                .flat_map(|y| {
+                    let line = buf.line(y).unwrap_or_default();
+                    let starts = line.char_indices().enumerate();
+                    starts
+                        .filter(|(_, (i, _))| starts_with(&line[*i..], query, case_sensitive))
+                        .map(move |(x, _)| (y, x))
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        };
+        search.matches.push((len, found));
+        Ok(())
     }
 
-    fn set_cursor_y(&mut self, y: usize) {
-        let nlines = self.buf.read().unwrap().len();
-        if y >= nlines {
-            self.cursor.y = nlines - 1;
+    /// Moves the cursor to the next match after `from`, or the previous one before it, wrapping
+    /// around the buffer. With `inclusive`, a match at `from` counts too.
+    fn jump(&mut self, from: Match, forward: bool, inclusive: bool) -> Result<()> {
+        let Some(search) = &self.search else {
+            return Ok(());
+        };
+        let matches = search.matches();
+        let found = if forward {
+            let next = matches
+                .iter()
+                .find(|m| **m > from || inclusive && **m == from);
+            next.or(matches.first())
         } else {
-            self.cursor.y = y;
-        }
-        let len = self
-            .buf
-            .read()
-            .unwrap()
-            .get(self.cursor.y)
-            .map(|l| l.len())
-            .unwrap_or(0);
-        if self.cursor.x > len {
-            self.cursor.x = len;
+            let previous = matches
+                .iter()
+                .rev()
+                .find(|m| **m < from || inclusive && **m == from);
+            previous.or(matches.last())
+        };
+        match found.copied() {
+            Some((y, x)) => self.set_cursor(x, y),
+            None => Ok(()),
         }
     }
 
-    fn set_cursor(&mut self, x: usize, y: usize) {
-        self.set_cursor_y(y);
-        self.set_cursor_x(x);
+    /// Handles a key while searching, or <kbd>ctrl</kbd>+<kbd>f</kbd> to start a search.
+    /// Returns whether the key was consumed.
+    fn search_key(&mut self, key: &KeyEvent) -> Result<bool> {
+        let KeyEvent { key, modifiers } = *key;
+        let at = (self.cursor.y, self.cursor.x);
+        if (key, modifiers) == (KeyCode::Char('f'), Modifiers::CTRL) {
+            let origin = (self.cursor, self.scroll, self.hscroll);
+            let search = self.search.get_or_insert_with(|| Search {
+                query: String::new(),
+                typing: true,
+                origin,
+                matches: vec![],
+            });
+            // The buffer may have changed since the last search, so it's searched again
+            search.typing = true;
+            search.matches.clear();
+            self.update_matches()?;
+            return Ok(true);
+        }
+        let Some(search) = &mut self.search else {
+            return Ok(false);
+        };
+        let origin = search.origin;
+        if !search.typing {
+            match (key, modifiers) {
+                (KeyCode::Enter | KeyCode::Char('n'), Modifiers::NONE) => {
+                    self.jump(at, true, false)?
+                }
+                (KeyCode::Char('N' | 'n'), Modifiers::SHIFT)
+                | (KeyCode::Char('N'), Modifiers::NONE) => self.jump(at, false, false)?,
+                (KeyCode::Escape, _) => self.search = None,
+                (KeyCode::Char('c'), Modifiers::ALT) => self.toggle_case()?,
+                // Any other key ends the search, and is handled like it usually is
+                _ => {
+                    self.search = None;
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+        match (key, modifiers) {
+            (KeyCode::Escape, _) => {
+                let (cursor, scroll, hscroll) = origin;
+                self.search = None;
+                (self.cursor, self.scroll, self.hscroll) = (cursor, scroll, hscroll);
+                return self.validate_cursor().map(|_| true);
+            }
+            (KeyCode::Enter, _) => {
+                search.typing = false;
+                return Ok(true);
+            }
+            (KeyCode::Backspace, _) => {
+                search.query.pop();
+            }
+            (KeyCode::Char('c'), Modifiers::ALT) => return self.toggle_case().map(|_| true),
+            (KeyCode::Char(c), Modifiers::NONE | Modifiers::SHIFT) => search.query.push(c),
+            _ => return Ok(true),
+        }
+        self.update_matches()?;
+        self.preview()?;
+        Ok(true)
     }
 
-    fn validate_cursor(&mut self) {
-        let nlines = self.buf.read().unwrap().len();
-        if self.cursor.y >= nlines {
-            self.cursor.y = nlines - 1;
+    /// Moves the cursor to the first match after where the search started, or back to where it
+    /// started if there are none
+    fn preview(&mut self) -> Result<()> {
+        let Some(search) = &self.search else {
+            return Ok(());
+        };
+        let (origin, ..) = search.origin;
+        if search.matches().is_empty() {
+            self.set_cursor(origin.x, origin.y)
+        } else {
+            self.jump((origin.y, origin.x), true, true)
         }
-        let len = self
-            .buf
-            .read()
-            .unwrap()
-            .get(self.cursor.y)
-            .map(|l| l.len())
-            .unwrap_or(0);
-        if self.cursor.x > len {
-            self.cursor.x = len;
+    }
+
+    /// Toggles whether the search matches case, and searches again
+    fn toggle_case(&mut self) -> Result<()> {
+        self.case_sensitive = !self.case_sensitive;
+        if let Some(search) = &mut self.search {
+            search.matches.clear();
+        }
+        self.update_matches()?;
+        if self.search.as_ref().is_some_and(|search| search.typing) {
+            self.preview()?;
+        }
+        Ok(())
+    }
+
+    /// Draws characters `range` of line `y` over the text with the given attributes, clipped to
+    /// the columns shown
+    fn highlight(
+        &self,
+        surface: &mut Surface,
+        y: usize,
+        line: &str,
+        range: Range<usize>,
+        attrs: CellAttributes,
+    ) {
+        let width = surface.dimensions().0;
+        let start = range.start.max(self.hscroll);
+        let end = range.end.min(self.hscroll + width);
+        if start >= end {
+            return;
         }
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(start - self.hscroll),
+                y: Position::Absolute(y - self.scroll),
+            },
+            Change::AllAttributes(attrs),
+            Change::Text(line.chars().skip(start).take(end - start).collect()),
+            Change::AllAttributes(CellAttributes::default()),
+        ]);
+    }
+
+    /// Draws the search query on the bottom row, with the number of the match at the cursor and
+    /// how many there are
+    fn draw_search(&self, surface: &mut Surface, search: &Search, attrs: &CellAttributes) {
+        let (width, height) = surface.dimensions();
+        let Some(row) = height.checked_sub(1) else {
+            return;
+        };
+        fill(surface, Rect::new(0., row as f32, width as f32, 1.), attrs);
+        let matches = search.matches();
+        let current = matches
+            .iter()
+            .position(|m| *m == (self.cursor.y, self.cursor.x))
+            .map_or(0, |i| i + 1);
+        let case = if self.case_sensitive { " Aa" } else { "" };
+        let status = format!("{current}/{}{case} ", matches.len());
+        let query = format!("/{}", search.query);
+        let status_width = status.chars().count();
+        let query = query.chars().take(width.saturating_sub(status_width + 1));
+        surface.add_changes(vec![
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(row),
+            },
+            Change::AllAttributes(attrs.clone()),
+            Change::Text(query.collect()),
+            Change::CursorPosition {
+                x: Position::Absolute(width.saturating_sub(status_width)),
+                y: Position::Absolute(row),
+            },
+            Change::Text(status),
+            Change::AllAttributes(CellAttributes::default()),
+        ]);
+    }
+
+    fn validate_cursor(&mut self) -> Result<()> {
+        self.set_cursor_y(self.cursor.y)
     }
 }
 
-impl<U, S> Widget<U, S> for TextBox {
+impl<U, S, B: TextBuffer + 'static> Widget<U, S> for TextBox<B> {
     fn render<'r>(
         &self,
-        _cx: &RenderCtx<'r, U, S>,
+        cx: &RenderCtx<'r, U, S>,
         surface: &mut Surface,
-    ) -> Option<Vec<(Rect, WidgetId)>> {
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        // The buffer is shared, so it may change without the widget being updated
+        cx.request_redraw();
         let (width, height) = surface.dimensions();
-        self.buf
-            .read()
-            .unwrap()
-            .iter()
-            .map(|l| &l[0..width.min(l.len())])
-            .enumerate()
-            .take(height)
-            .for_each(|(i, l)| {
-                if i > 0 {
-                    surface.add_change(Change::CursorPosition {
-                        x: Position::Absolute(0),
-                        y: Position::Relative(1),
-                    });
+        let height = self.text_height(height);
+        // Only the visible lines are looked up, so large buffers draw as fast as small ones
+        let buf = read_or_err(&self.buf)?;
+        let visible = self.scroll..(self.scroll + height).min(buf.len_lines());
+
+        // Lines still on screen from the last frame are moved rather than drawn again, and only
+        // the ones that were edited since are drawn. Highlights are drawn over the text, so
+        // frames with highlights aren't reused.
+        let mut drawn = self.drawn.lock().map_err(|_| Error::LockPoisoned)?;
+        let prev = drawn
+            .filter(|(_, hscroll)| *hscroll == self.hscroll)
+            .and(cx.previous_surface());
+        let scrolled = drawn.map_or(0, |(scroll, _)| self.scroll as isize - scroll as isize);
+        let plain = self.search.is_none() && self.selection.is_none();
+        *drawn = plain.then_some((self.scroll, self.hscroll));
+        let text = |row: usize| {
+            let line = buf.line(self.scroll + row).filter(|_| row < height);
+            line.map_or(String::new(), |line| {
+                line.chars().skip(self.hscroll).take(width).collect()
+            })
+        };
+        let draw_line = |row: usize, surface: &mut Surface| {
+            surface.add_change(Change::Text(text(row)));
+        };
+        scroll_and_render_delta(surface, prev, scrolled, &draw_line);
+        if prev.is_some() {
+            for row in changed_lines(surface, &text) {
+                redraw_line(surface, row, &draw_line);
+            }
+        }
+        if let Some(search) = &self.search {
+            // The matches are checked again, in case the buffer was changed by something else
+            let attrs = cx.theme().style("textbox.match");
+            let matches = search.matches();
+            let first = matches.partition_point(|(y, _)| *y < visible.start);
+            let len = search.query.chars().count();
+            for &(y, x) in matches[first..]
+                .iter()
+                .take_while(|(y, _)| visible.contains(y))
+            {
+                let line = buf.line(y).unwrap_or_default();
+                if starts_with(
+                    &line[byte_index(line, x)..],
+                    &search.query,
+                    self.case_sensitive,
+                ) {
+                    self.highlight(surface, y, line, x..x + len, attrs.clone());
                 }
-                surface.add_change(Change::Text(format!("{}", l)));
-            });
-        None
+            }
+            self.draw_search(surface, search, &cx.theme().style("textbox.search"));
+        }
+        // Draw the selection over the text in reverse video
+        if let Some((y, range)) = self.selection.clone().filter(|(y, _)| visible.contains(y)) {
+            let mut reverse = CellAttributes::default();
+            reverse.set_reverse(true);
+            self.highlight(surface, y, buf.line(y).unwrap_or_default(), range, reverse);
+        }
+        Ok(vec![])
     }
 
-    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<(Option<usize>, usize, usize)> {
-        Some((None, self.cursor.x, self.cursor.y))
+    fn cursor(&self, _: &WidgetStore<U, S>) -> Option<CursorState> {
+        if let Some(search) = self.search.as_ref().filter(|search| search.typing) {
+            let x = search.query.chars().count() + 1;
+            return Some(CursorState::at(x, self.height.saturating_sub(1)));
+        }
+        Some(
+            CursorState::at(
+                self.cursor.x.saturating_sub(self.hscroll),
+                self.cursor.y.saturating_sub(self.scroll),
+            )
+            .with_shape(self.cursor_shape),
+        )
     }
 
     fn update<'u>(
         &mut self,
-        _cx: &mut UpdateCtx<'u, U, S>,
+        cx: &mut UpdateCtx<'u, U, S>,
         event: Event<U>,
     ) -> crate::error::Result<()> {
-        self.validate_cursor();
+        self.validate_cursor()?;
+        self.height = cx.bounds.height_usize();
+        let (width, height) = (cx.bounds.width_usize(), self.text_height(self.height));
+        if let Event::Key(key) = &event {
+            if self.search_key(key)? {
+                // The search row may have been opened or closed
+                let height = self.text_height(self.height);
+                self.scroll_to_cursor(width, height);
+                return Ok(());
+            }
+        }
+        match event {
+            Event::Key(_) | Event::Paste(_) => self.selection = None,
+            Event::Mouse(MouseEvent { mouse_buttons, .. })
+                if mouse_buttons != MouseButtons::NONE =>
+            {
+                self.selection = None
+            }
+            _ => {}
+        }
         match event {
             Event::Key(KeyEvent { key, modifiers }) => {
                 if modifiers == Modifiers::NONE || modifiers == Modifiers::SHIFT {
                     match key {
                         KeyCode::Char(c) => self.write_char(c)?,
                         KeyCode::Enter => {
-                            if self.cursor.x
-                                == self
-                                    .buf
-                                    .write()
-                                    .unwrap()
-                                    .get(self.cursor.y)
-                                    .ok_or(Error::TerminalError)?
-                                    .len()
-                            {
-                                self.buf
-                                    .write()
-                                    .unwrap()
-                                    .insert(self.cursor.y + 1, String::new());
-                            } else {
-                                let mut writer = self.buf.write().unwrap();
-                                let line =
-                                    writer.get_mut(self.cursor.y).ok_or(Error::TerminalError)?;
-                                let new_line = line.drain(self.cursor.x..).collect::<String>();
-
-                                if self.cursor.y == writer.len() {
-                                    writer.push(new_line);
-                                } else {
-                                    writer.insert(self.cursor.y + 1, new_line);
-                                }
-                            }
-                            self.set_cursor(0, self.cursor.y + 1);
+                            write_or_err(&self.buf)?.split_line(self.cursor.y, self.cursor.x);
+                            self.set_cursor(0, self.cursor.y + 1)?;
                         }
                         KeyCode::Tab => {
                             self.write_char(' ')?;
                             self.write_char(' ')?;
                         }
                         KeyCode::UpArrow => {
-                            self.set_cursor_y(self.cursor.y.saturating_sub(1));
+                            self.set_cursor_y(self.cursor.y.saturating_sub(1))?;
                         }
                         KeyCode::DownArrow => {
-                            let lines = self.buf.read().unwrap().len();
-                            self.set_cursor_y(self.cursor.y.saturating_add(1).min(lines));
+                            let lines = read_or_err(&self.buf)?.len_lines();
+                            self.set_cursor_y(self.cursor.y.saturating_add(1).min(lines))?;
                         }
                         KeyCode::LeftArrow => {
-                            self.set_cursor_x(self.cursor.x.saturating_sub(1));
+                            self.set_cursor_x(self.cursor.x.saturating_sub(1))?;
                         }
                         KeyCode::RightArrow => {
-                            self.set_cursor_x(self.cursor.x.saturating_add(1));
+                            self.set_cursor_x(self.cursor.x.saturating_add(1))?;
                         }
                         KeyCode::Backspace => {
                             self.delete()?;
@@ -253,6 +800,24 @@ impl<U, S> Widget<U, S> for TextBox {
                         _ => {}
                     }
                 }
+                self.scroll_to_cursor(width, height);
+                Ok(())
+            }
+            Event::Paste(text) => {
+                self.write_str(&text)?;
+                self.scroll_to_cursor(width, height);
+                Ok(())
+            }
+            Event::Scroll { delta, .. } => {
+                self.scroll_by(delta, height)?;
+                Ok(())
+            }
+            Event::MouseGesture(Gesture::DoubleClick {
+                x,
+                y,
+                button: MouseButtons::LEFT,
+            }) => {
+                self.select_word(x as usize + self.hscroll, y as usize + self.scroll)?;
                 Ok(())
             }
             Event::Mouse(MouseEvent {
@@ -262,7 +827,7 @@ impl<U, S> Widget<U, S> for TextBox {
                 modifiers: _,
             }) => {
                 if mouse_buttons == MouseButtons::LEFT {
-                    self.set_cursor(x as usize, y as usize);
+                    self.set_cursor(x as usize + self.hscroll, y as usize + self.scroll)?;
                 }
                 Ok(())
             }
@@ -0,0 +1,393 @@
+//! Collapsible tree view for hierarchical data, such as a file browser.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    error::Result,
+    event::{Event, EventSender, KeyCode, KeyEvent, MouseButtons, MouseEvent},
+    layout::{Rect, WidgetId},
+    style::CellAttributes,
+    surface::{Change, Position, Surface},
+    widget::{RenderCtx, UpdateCtx, Widget},
+};
+use slotmap::{new_key_type, SlotMap};
+
+new_key_type! {
+    pub struct TreeNodeId;
+}
+
+/// Called with the labels from the root to the activated node when <kbd>Enter</kbd> is pressed.
+pub trait TreeActivate<U>: FnMut(Vec<String>, EventSender<U>) {}
+
+impl<C, U> TreeActivate<U> for C where C: FnMut(Vec<String>, EventSender<U>) {}
+
+/// Called the first time a node is expanded, so its children can be populated lazily.
+pub trait TreeExpand<U>: FnMut(&mut Tree<U>, TreeNodeId) {}
+
+impl<C, U> TreeExpand<U> for C where C: FnMut(&mut Tree<U>, TreeNodeId) {}
+
+struct TreeNode {
+    label: String,
+    parent: Option<TreeNodeId>,
+    children: Vec<TreeNodeId>,
+    expanded: bool,
+    /// Whether the node can be expanded even if it has no children yet
+    expandable: bool,
+    /// Whether the `on_expand` hook has been called for this node
+    loaded: bool,
+}
+
+/// A tree view widget that displays nodes with indentation and `▸`/`▾` expansion markers.
+pub struct Tree<U> {
+    nodes: SlotMap<TreeNodeId, TreeNode>,
+    roots: Vec<TreeNodeId>,
+    selected: Option<TreeNodeId>,
+    /// Index of the first visible row
+    scroll: usize,
+    /// Index of the first row drawn in the last frame, which can differ from `scroll` when the
+    /// surface shrank since the last update. Clicks are mapped to rows with it.
+    drawn: AtomicUsize,
+    on_activate: Option<Box<dyn TreeActivate<U>>>,
+    on_expand: Option<Box<dyn TreeExpand<U>>>,
+}
+
+impl<U> Default for Tree<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<U> Tree<U> {
+    pub fn new() -> Self {
+        Self {
+            nodes: SlotMap::with_key(),
+            roots: vec![],
+            selected: None,
+            scroll: 0,
+            drawn: AtomicUsize::new(0),
+            on_activate: None,
+            on_expand: None,
+        }
+    }
+
+    /// Set the callback fired when a node is activated with <kbd>Enter</kbd>
+    pub fn with_on_activate(mut self, f: impl TreeActivate<U> + 'static) -> Self {
+        self.on_activate = Some(Box::new(f));
+        self
+    }
+
+    /// Set the hook used to lazily populate a node's children on its first expansion
+    pub fn with_on_expand(mut self, f: impl TreeExpand<U> + 'static) -> Self {
+        self.on_expand = Some(Box::new(f));
+        self
+    }
+
+    /// Add a node under the given parent, or at the top level if `parent` is `None`.
+    pub fn add_node(&mut self, parent: Option<TreeNodeId>, label: impl Into<String>) -> TreeNodeId {
+        let id = self.nodes.insert(TreeNode {
+            label: label.into(),
+            parent,
+            children: vec![],
+            expanded: false,
+            expandable: false,
+            loaded: false,
+        });
+        match parent.and_then(|p| self.nodes.get_mut(p)) {
+            Some(parent) => parent.children.push(id),
+            None => self.roots.push(id),
+        }
+        if self.selected.is_none() {
+            self.selected = Some(id);
+        }
+        id
+    }
+
+    /// Remove a node and all of its descendants.
+    pub fn remove_node(&mut self, id: TreeNodeId) {
+        let Some(node) = self.nodes.remove(id) else {
+            return;
+        };
+        match node.parent.and_then(|p| self.nodes.get_mut(p)) {
+            Some(parent) => parent.children.retain(|c| *c != id),
+            None => self.roots.retain(|c| *c != id),
+        }
+        node.children.into_iter().for_each(|c| self.remove_node(c));
+        // Move the selection off of the removed subtree
+        if self.selected.is_some_and(|s| !self.nodes.contains_key(s)) {
+            self.selected = node.parent.or_else(|| self.roots.first().copied());
+        }
+    }
+
+    /// Remove all nodes from the tree.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.roots.clear();
+        self.selected = None;
+        self.scroll = 0;
+    }
+
+    /// Mark a node as expandable even if it has no children, so that its children can be
+    /// populated by the `on_expand` hook.
+    pub fn set_expandable(&mut self, id: TreeNodeId, expandable: bool) {
+        if let Some(node) = self.nodes.get_mut(id) {
+            node.expandable = expandable;
+        }
+    }
+
+    pub fn is_expandable(&self, id: TreeNodeId) -> bool {
+        self.nodes
+            .get(id)
+            .map(|n| n.expandable || !n.children.is_empty())
+            .unwrap_or(false)
+    }
+
+    pub fn is_expanded(&self, id: TreeNodeId) -> bool {
+        self.nodes.get(id).map(|n| n.expanded).unwrap_or(false)
+    }
+
+    /// Expand a node, calling the `on_expand` hook if this is the first time it is expanded.
+    pub fn expand(&mut self, id: TreeNodeId) {
+        let Some(node) = self.nodes.get_mut(id) else {
+            return;
+        };
+        node.expanded = true;
+        if !node.loaded {
+            node.loaded = true;
+            // The hook is taken out while it runs so that it can mutate the tree
+            if let Some(mut hook) = self.on_expand.take() {
+                hook(self, id);
+                self.on_expand.get_or_insert(hook);
+            }
+        }
+    }
+
+    pub fn collapse(&mut self, id: TreeNodeId) {
+        if let Some(node) = self.nodes.get_mut(id) {
+            node.expanded = false;
+        }
+    }
+
+    pub fn toggle(&mut self, id: TreeNodeId) {
+        if self.is_expanded(id) {
+            self.collapse(id);
+        } else {
+            self.expand(id);
+        }
+    }
+
+    pub fn label(&self, id: TreeNodeId) -> Option<&str> {
+        self.nodes.get(id).map(|n| n.label.as_str())
+    }
+
+    pub fn set_label(&mut self, id: TreeNodeId, label: impl Into<String>) {
+        if let Some(node) = self.nodes.get_mut(id) {
+            node.label = label.into();
+        }
+    }
+
+    pub fn parent(&self, id: TreeNodeId) -> Option<TreeNodeId> {
+        self.nodes.get(id).and_then(|n| n.parent)
+    }
+
+    pub fn children(&self, id: TreeNodeId) -> Option<&[TreeNodeId]> {
+        self.nodes.get(id).map(|n| n.children.as_slice())
+    }
+
+    pub fn roots(&self) -> &[TreeNodeId] {
+        &self.roots
+    }
+
+    pub fn selected(&self) -> Option<TreeNodeId> {
+        self.selected
+    }
+
+    pub fn select(&mut self, id: TreeNodeId) {
+        if self.nodes.contains_key(id) {
+            self.selected = Some(id);
+        }
+    }
+
+    /// Get the labels from the top level down to the given node
+    pub fn path(&self, id: TreeNodeId) -> Vec<String> {
+        let mut path = vec![];
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|c| self.nodes.get(c)) {
+            path.push(node.label.clone());
+            current = node.parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Get the currently visible nodes in display order, along with their depth
+    pub fn visible(&self) -> Vec<(TreeNodeId, usize)> {
+        let mut visible = vec![];
+        self.roots
+            .iter()
+            .for_each(|id| self.visible_inner(*id, 0, &mut visible));
+        visible
+    }
+
+    fn visible_inner(&self, id: TreeNodeId, depth: usize, visible: &mut Vec<(TreeNodeId, usize)>) {
+        let Some(node) = self.nodes.get(id) else {
+            return;
+        };
+        visible.push((id, depth));
+        if node.expanded {
+            node.children
+                .iter()
+                .for_each(|c| self.visible_inner(*c, depth + 1, visible));
+        }
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let visible = self.visible();
+        let Some(current) = self
+            .selected
+            .and_then(|s| visible.iter().position(|(id, _)| *id == s))
+        else {
+            self.selected = visible.first().map(|(id, _)| *id);
+            return;
+        };
+        let next = (current as isize + offset).clamp(0, visible.len() as isize - 1);
+        self.selected = Some(visible[next as usize].0);
+    }
+
+    fn activate(&mut self, event_tx: EventSender<U>) {
+        let Some(selected) = self.selected else {
+            return;
+        };
+        let path = self.path(selected);
+        if let Some(on_activate) = self.on_activate.as_deref_mut() {
+            on_activate(path, event_tx);
+        }
+    }
+
+    /// Adjust the scroll offset so that the selected row is visible
+    fn scroll_to_selected(&mut self, height: usize) {
+        let Some(row) = self
+            .selected
+            .and_then(|s| self.visible().iter().position(|(id, _)| *id == s))
+        else {
+            return;
+        };
+        if row < self.scroll {
+            self.scroll = row;
+        } else if height > 0 && row >= self.scroll + height {
+            self.scroll = row + 1 - height;
+        }
+    }
+}
+
+impl<U: 'static, S: 'static> Widget<U, S> for Tree<U> {
+    fn render<'r>(
+        &self,
+        cx: &RenderCtx<'r, U, S>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        let (width, height) = surface.dimensions();
+        let visible = self.visible();
+
+        // Keep the selection on screen even if the surface shrank since the last update
+        let mut scroll = self.scroll.min(visible.len().saturating_sub(height));
+        if let Some(row) = self
+            .selected
+            .and_then(|s| visible.iter().position(|(id, _)| *id == s))
+        {
+            if row < scroll {
+                scroll = row;
+            } else if height > 0 && row >= scroll + height {
+                scroll = row + 1 - height;
+            }
+        }
+        self.drawn.store(scroll, Ordering::Relaxed);
+
+        for (row, (id, depth)) in visible.iter().skip(scroll).take(height).enumerate() {
+            let node = &self.nodes[*id];
+            let marker = if !self.is_expandable(*id) {
+                ' '
+            } else if node.expanded {
+                '▾'
+            } else {
+                '▸'
+            };
+            let line = format!("{}{} {}", "  ".repeat(*depth), marker, node.label);
+            let line = format!("{:<width$}", line, width = width)
+                .chars()
+                .take(width)
+                .collect::<String>();
+
+            surface.add_change(Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(row),
+            });
+            if self.selected == Some(*id) {
+                surface.add_change(Change::AllAttributes(cx.theme().style("tree.selected")));
+            }
+            surface.add_changes(vec![
+                Change::Text(line),
+                Change::AllAttributes(CellAttributes::default()),
+            ]);
+        }
+        Ok(vec![])
+    }
+
+    fn update<'u>(&mut self, cx: &mut UpdateCtx<'u, U, S>, event: Event<U>) -> Result<()> {
+        match event {
+            Event::Key(KeyEvent { key, .. }) => match key {
+                KeyCode::UpArrow => self.move_selection(-1),
+                KeyCode::DownArrow => self.move_selection(1),
+                KeyCode::RightArrow => {
+                    if let Some(selected) = self.selected {
+                        if !self.is_expanded(selected) && self.is_expandable(selected) {
+                            self.expand(selected);
+                        } else if let Some(first) =
+                            self.children(selected).and_then(|c| c.first()).copied()
+                        {
+                            self.selected = Some(first);
+                        }
+                    }
+                }
+                KeyCode::LeftArrow => {
+                    if let Some(selected) = self.selected {
+                        if self.is_expanded(selected) && self.is_expandable(selected) {
+                            self.collapse(selected);
+                        } else if let Some(parent) = self.parent(selected) {
+                            self.selected = Some(parent);
+                        }
+                    }
+                }
+                KeyCode::Enter => self.activate(cx.tx.clone()),
+                _ => {}
+            },
+            Event::Mouse(MouseEvent {
+                x,
+                y,
+                mouse_buttons,
+                ..
+            }) if mouse_buttons == MouseButtons::LEFT => {
+                // Rows are counted from the top of what's on screen
+                self.scroll = self.drawn.load(Ordering::Relaxed);
+                let visible = self.visible();
+                if let Some((id, depth)) = visible.get(self.scroll + y as usize).copied() {
+                    self.selected = Some(id);
+                    // Clicking the marker toggles expansion
+                    if x as usize == depth * 2 && self.is_expandable(id) {
+                        self.toggle(id);
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.scroll_to_selected(cx.bounds.height_usize());
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
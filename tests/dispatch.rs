@@ -0,0 +1,168 @@
+//! Input dispatched through the app to global handlers and widgets. This runs without a terminal,
+//! so it can also be run under Miri with `cargo miri test --test dispatch`.
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use sanguine::{
+    backend::TestBackend,
+    error::Result,
+    event::{Event, InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent},
+    layout::{Axis, Rect, WidgetId},
+    surface::Surface,
+    widgets::{Border, TextBox},
+    App, RenderCtx, UpdateCtx, Widget,
+};
+
+/// Counts the key presses it's sent
+struct Counter {
+    keys: Rc<Cell<usize>>,
+}
+
+impl Widget<(), ()> for Counter {
+    fn render(&self, _: &RenderCtx<(), ()>, _: &mut Surface) -> Result<Vec<(Rect, WidgetId)>> {
+        Ok(vec![])
+    }
+
+    fn update(&mut self, _: &mut UpdateCtx<(), ()>, event: Event<()>) -> Result<()> {
+        if let Event::Key(_) = event {
+            self.keys.set(self.keys.get() + 1);
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+fn key(c: char) -> InputEvent {
+    InputEvent::Key(KeyEvent {
+        key: KeyCode::Char(c),
+        modifiers: Modifiers::NONE,
+    })
+}
+
+/// A mouse event at a zero-based position, which terminals report one-based
+fn mouse(x: u16, y: u16, mouse_buttons: MouseButtons) -> InputEvent {
+    InputEvent::Mouse(MouseEvent {
+        x: x + 1,
+        y: y + 1,
+        mouse_buttons,
+        modifiers: Modifiers::NONE,
+    })
+}
+
+#[test]
+fn events_reach_handlers_and_widgets() {
+    let keys = Rc::new(Cell::new(0));
+    let counter_keys = Rc::clone(&keys);
+    let mut right = None;
+    let mut app = App::<(), ()>::test(20, 3).with_layout(|layout, widgets| {
+        let textbox = widgets.register(TextBox::new());
+        let left = layout.add_leaf(widgets, Border::new("A", textbox));
+        right = Some(layout.add_leaf(widgets, Counter { keys: counter_keys }));
+        layout.set_direction(layout.root(), Axis::Horizontal);
+        layout.add_child(layout.root(), left).ok()?;
+        layout.add_child(layout.root(), right?).ok()?;
+        Some(left)
+    });
+    let right = right.unwrap();
+    // Slow interpreters shouldn't leave input for a later call
+    app.config_mut().input_budget = Duration::from_secs(60);
+
+    // The first key press replaces the handler that sees it with one that counts key presses,
+    // which only sees the key presses after it
+    let handled = Rc::new(Cell::new(0));
+    let first = Rc::new(Cell::new(None));
+    let id = {
+        let (first, handled) = (Rc::clone(&first), Rc::clone(&handled));
+        app.add_handler(0, move |app, event, _| {
+            let Event::Key(_) = event else {
+                return Ok(false);
+            };
+            if let Some(id) = first.take() {
+                app.remove_handler(id);
+                let handled = Rc::clone(&handled);
+                app.add_handler(0, move |_, event, _| {
+                    if let Event::Key(_) = event {
+                        handled.set(handled.get() + 1);
+                    }
+                    Ok(false)
+                });
+            }
+            Ok(false)
+        })
+    };
+    first.set(Some(id));
+    app.render().unwrap();
+
+    let backend = app.backend_mut::<TestBackend>().unwrap();
+    backend.push_input(key('h'));
+    backend.push_input(key('i'));
+    app.handle_events().unwrap();
+    app.render().unwrap();
+    let screen = app.backend::<TestBackend>().unwrap().screen_text();
+    assert!(screen[1].starts_with("│hi"), "{screen:?}");
+
+    // Clicking the other window focuses it, and typing then goes to it
+    let backend = app.backend_mut::<TestBackend>().unwrap();
+    backend.push_input(mouse(15, 1, MouseButtons::LEFT));
+    backend.push_input(mouse(15, 1, MouseButtons::NONE));
+    backend.push_input(key('x'));
+    app.handle_events().unwrap();
+
+    assert_eq!(app.get_focus(), Some(right));
+    assert_eq!(keys.get(), 1);
+    assert_eq!(handled.get(), 2);
+}
+
+#[test]
+fn split_edges_drag_only_after_the_mouse_moves() {
+    let mut left = None;
+    let mut app = App::<(), ()>::test(20, 3).with_layout(|layout, widgets| {
+        let keys = Rc::new(Cell::new(0));
+        let a = widgets.register(Counter {
+            keys: Rc::clone(&keys),
+        });
+        let b = widgets.register(Counter { keys });
+        left = Some(layout.add_leaf(widgets, Border::new("A", a)));
+        let right = layout.add_leaf(widgets, Border::new("B", b));
+        layout.set_direction(layout.root(), Axis::Horizontal);
+        layout.add_child(layout.root(), left?).ok()?;
+        layout.add_child(layout.root(), right).ok()?;
+        Some(right)
+    });
+    app.config_mut().input_budget = Duration::from_secs(60);
+    // The columns of the window borders, since the windows draw nothing inside them
+    let borders = |app: &mut App| {
+        app.render().unwrap();
+        let screen = app.backend::<TestBackend>().unwrap().screen_text();
+        screen[1]
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| *c != ' ')
+            .map(|(column, _)| column)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(borders(&mut app), [0, 9, 10, 19]);
+
+    // Clicking the edge without moving is a click on the window it belongs to
+    let backend = app.backend_mut::<TestBackend>().unwrap();
+    backend.push_input(mouse(9, 1, MouseButtons::LEFT));
+    backend.push_input(mouse(9, 1, MouseButtons::NONE));
+    app.handle_events().unwrap();
+    assert_eq!(app.get_focus(), left);
+    assert_eq!(borders(&mut app), [0, 9, 10, 19]);
+
+    // Moving with the button held drags it
+    let backend = app.backend_mut::<TestBackend>().unwrap();
+    backend.push_input(mouse(9, 1, MouseButtons::LEFT));
+    backend.push_input(mouse(12, 1, MouseButtons::LEFT));
+    backend.push_input(mouse(12, 1, MouseButtons::NONE));
+    app.handle_events().unwrap();
+    assert_eq!(borders(&mut app), [0, 12, 13, 19]);
+}
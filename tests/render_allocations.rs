@@ -0,0 +1,96 @@
+//! Frames where nothing changed reuse the surfaces and buffers of the last frame, so drawing them
+//! doesn't touch the heap.
+
+use std::{
+    alloc::{GlobalAlloc, Layout as AllocLayout, System},
+    cell::Cell,
+};
+
+use sanguine::{
+    backend::TestBackend,
+    error::Result,
+    layout::{Axis, Rect, WidgetId},
+    surface::{Change, Surface},
+    widgets::Border,
+    App, Config, RenderCtx, Widget,
+};
+
+/// Counts allocations made through the system allocator
+struct Counting;
+
+thread_local! {
+    // Counted per thread, so that the test harness's own threads don't add to the count
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: AllocLayout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: AllocLayout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: AllocLayout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// A line of text that never changes, so it's only drawn again when asked to
+struct Label(String);
+
+impl Widget<(), ()> for Label {
+    fn render(
+        &self,
+        _: &RenderCtx<(), ()>,
+        surface: &mut Surface,
+    ) -> Result<Vec<(Rect, WidgetId)>> {
+        surface.add_change(Change::Text(self.0.clone()));
+        Ok(vec![])
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[test]
+fn unchanged_frame_does_not_allocate() {
+    let backend = TestBackend::new(80, 24);
+    let mut app = App::with_backend(Config::default(), (), backend)
+        .unwrap()
+        .with_layout(|layout, widgets| {
+            let root = layout.root();
+            layout.set_direction(root, Axis::Horizontal);
+            let mut first = None;
+            for column in 0..2 {
+                let container = layout.add_container(Axis::Vertical, None);
+                layout.add_child(root, container).ok()?;
+                for row in 0..2 {
+                    let title = format!("Window {}", column * 2 + row);
+                    let text = widgets.register(Label(format!("The text of {title}")));
+                    let leaf = layout.add_leaf(widgets, Border::new(title, text));
+                    layout.add_child(container, leaf).ok()?;
+                    first.get_or_insert(leaf);
+                }
+            }
+            let status = widgets.register(Label("Status".to_owned()));
+            layout.dock_bottom(status);
+            first
+        });
+    app.render().unwrap();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    app.render().unwrap();
+    assert_eq!(ALLOCATIONS.with(Cell::get) - before, 0);
+}